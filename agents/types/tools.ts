@@ -3,22 +3,38 @@
  */
 export type ToolName =
   | 'add_message'
+  | 'apply_suggestions'
   | 'ask_user'
+  | 'cargo_diagnostics'
   | 'code_search'
+  | 'edit_symbol'
   | 'end_turn'
+  | 'find_affected_tests'
+  | 'find_definition'
   | 'find_files'
+  | 'find_references'
+  | 'find_trait_impls'
   | 'glob'
   | 'list_directory'
   | 'lookup_agent_info'
+  | 'lsp_definition'
+  | 'lsp_diagnostics'
+  | 'lsp_hover'
+  | 'lsp_references'
+  | 'lsp_rename'
+  | 'lsp_workspace_symbols'
   | 'propose_str_replace'
   | 'propose_write_file'
+  | 'read_background_process'
   | 'read_docs'
   | 'read_files'
   | 'read_subtree'
+  | 'read_terminal_session'
   | 'run_file_change_hooks'
   | 'run_terminal_command'
   | 'set_messages'
   | 'set_output'
+  | 'signal_background_process'
   | 'skill'
   | 'spawn_agents'
   | 'str_replace'
@@ -34,22 +50,38 @@ export type ToolName =
  */
 export interface ToolParamsMap {
   add_message: AddMessageParams
+  apply_suggestions: ApplySuggestionsParams
   ask_user: AskUserParams
+  cargo_diagnostics: CargoDiagnosticsParams
   code_search: CodeSearchParams
+  edit_symbol: EditSymbolParams
   end_turn: EndTurnParams
+  find_affected_tests: FindAffectedTestsParams
+  find_definition: FindDefinitionParams
   find_files: FindFilesParams
+  find_references: FindReferencesParams
+  find_trait_impls: FindTraitImplsParams
   glob: GlobParams
   list_directory: ListDirectoryParams
   lookup_agent_info: LookupAgentInfoParams
+  lsp_definition: LspDefinitionParams
+  lsp_diagnostics: LspDiagnosticsParams
+  lsp_hover: LspHoverParams
+  lsp_references: LspReferencesParams
+  lsp_rename: LspRenameParams
+  lsp_workspace_symbols: LspWorkspaceSymbolsParams
   propose_str_replace: ProposeStrReplaceParams
   propose_write_file: ProposeWriteFileParams
+  read_background_process: ReadBackgroundProcessParams
   read_docs: ReadDocsParams
   read_files: ReadFilesParams
   read_subtree: ReadSubtreeParams
+  read_terminal_session: ReadTerminalSessionParams
   run_file_change_hooks: RunFileChangeHooksParams
   run_terminal_command: RunTerminalCommandParams
   set_messages: SetMessagesParams
   set_output: SetOutputParams
+  signal_background_process: SignalBackgroundProcessParams
   skill: SkillParams
   spawn_agents: SpawnAgentsParams
   str_replace: StrReplaceParams
@@ -69,6 +101,27 @@ export interface AddMessageParams {
   content: string
 }
 
+/**
+ * Apply rustc and clippy suggested fixes from cargo_diagnostics to a file, all at once.
+ */
+export interface ApplySuggestionsParams {
+  /** The path to the file the suggestions are for. */
+  path: string
+  /** Suggestions to apply together. */
+  suggestions: {
+    /** File the suggestion was reported for. Must match path if set. */
+    file?: string
+    startLine: number
+    startColumn: number
+    endLine: number
+    endColumn: number
+    /** Text replacing the span; empty to delete it. */
+    replacement: string
+    message?: string
+    applicability?: string
+  }[]
+}
+
 /**
  * Ask the user multiple choice questions and pause execution until they respond.
  */
@@ -102,6 +155,22 @@ export interface AskUserParams {
   }[]
 }
 
+/**
+ * Run cargo check, clippy or build and return the compiler's errors and warnings as structured diagnostics grouped by file.
+ */
+export interface CargoDiagnosticsParams {
+  /** Cargo subcommand to run. Defaults to "check". */
+  command?: 'check' | 'clippy' | 'build'
+  /** Optional workspace package to check (cargo's -p). Defaults to the whole workspace. */
+  package?: string
+  /** Extra arguments for cargo, e.g. ["--all-targets"] or ["--features", "serde"]. */
+  args?: string[]
+  /** Optional directory to run cargo in, relative to the project root. */
+  cwd?: string
+  /** Most diagnostics to return, errors first. Defaults to 20. */
+  max_diagnostics?: number
+}
+
 /**
  * Search for string patterns in the project's files. This tool uses ripgrep (rg), a fast line-oriented search tool. Use this tool only when read_files is not sufficient to find the files you need.
  */
@@ -116,11 +185,48 @@ export interface CodeSearchParams {
   maxResults?: number
 }
 
+/**
+ * Edit Rust items addressed by their qualified path instead of by matching text.
+ */
+export interface EditSymbolParams {
+  /** The path to the Rust file to edit. */
+  path: string
+  /** Edits to apply in order. */
+  edits: {
+    /** Path of the item to edit, as listed by read_files in outline mode, e.g. "Greeting::new", "<Greeting as Greeter>::greet", "Color::Red" or "inner::helper". A suffix of the path is enough if it is unambiguous. */
+    symbol: string
+    /** "replace" replaces the item (keeping its attributes and doc comments), "insert_before"/"insert_after" add a sibling item, "append" adds a field, variant or item at the end of the item's body, and "delete" removes the item with its attributes and doc comments. */
+    action: 'replace' | 'insert_before' | 'insert_after' | 'append' | 'delete'
+    /** The new source code. Indentation is adjusted to the item's position. Not needed for "delete". */
+    content?: string
+  }[]
+}
+
 /**
  * End your turn, regardless of any new tool results that might be coming. This will allow the user to type another prompt.
  */
 export interface EndTurnParams {}
 
+/**
+ * Find the Rust tests affected by changes to the given files, and the cargo test commands running only those tests.
+ */
+export interface FindAffectedTestsParams {
+  /** Changed files, relative to the project root (e.g. ["src/config.rs"]). */
+  paths: string[]
+}
+
+/**
+ * Go to the definition of the symbol at a position in a file: a function, type, method or other item being called or referenced there.
+ */
+export interface FindDefinitionParams {
+  /** Path of the file, relative to the project root. */
+  path: string
+  /** 1-based line of the symbol. */
+  line: number
+  /** 1-based column of any character of the symbol. */
+  column: number
+}
+
 /**
  * Find several files related to a brief natural language description of the files or the name of a function or class you are looking for.
  */
@@ -129,6 +235,28 @@ export interface FindFilesParams {
   prompt: string
 }
 
+/**
+ * Find where a symbol is defined and every place it is called or referenced, with file, line and column.
+ */
+export interface FindReferencesParams {
+  /** Name of the symbol, e.g. "load_config", or a path ending in it, e.g. "Greeting::new" or "config::load_config". Rust items of impls and traits are named "Type::item". */
+  symbol: string
+  /** Optional file defining the symbol, relative to the project root, to pick one of several same-named definitions. */
+  path?: string
+}
+
+/**
+ * Find Rust trait implementations: which types implement a trait, or which traits a type implements. Covers explicit impl blocks, #[derive(..)] attributes and macro-generated impls.
+ */
+export interface FindTraitImplsParams {
+  /** Name of a Rust trait, without its module path (e.g. "Greeter", "Display"). Returns every type implementing it. */
+  trait?: string
+  /** Name of a Rust type, without generics or module path (e.g. "Greeting"). Returns every trait implemented for it. */
+  type?: string
+  /** Optional directory to index, relative to the project root, e.g. a single crate. Defaults to the entire project. */
+  cwd?: string
+}
+
 /**
  * Search for files matching a glob pattern. Returns matching file paths sorted by modification time.
  */
@@ -155,6 +283,78 @@ export interface LookupAgentInfoParams {
   agentId: string
 }
 
+/**
+ * Ask the language server where the symbol at a position in a file is defined, where its type is defined, or what implements it.
+ */
+export interface LspDefinitionParams {
+  /** Path of the file, relative to the project root. */
+  path: string
+  /** 1-based line of the symbol. */
+  line: number
+  /** 1-based column of any character of the symbol. */
+  column: number
+  /** What to go to: the symbol's "definition" (default), the definition of its type ("type_definition"), or the implementations of a trait, interface or trait method ("implementation"). */
+  kind?: 'definition' | 'type_definition' | 'implementation'
+}
+
+/**
+ * Get the errors and warnings the language server reports for the given files.
+ */
+export interface LspDiagnosticsParams {
+  /** Files to check, relative to the project root (e.g. ["src/lib.rs"]). */
+  paths: string[]
+}
+
+/**
+ * Ask the language server for the type and documentation of the symbol or expression at a position in a file.
+ */
+export interface LspHoverParams {
+  /** Path of the file, relative to the project root. */
+  path: string
+  /** 1-based line of the expression. */
+  line: number
+  /** 1-based column of any character of the expression. */
+  column: number
+}
+
+/**
+ * Ask the language server for every reference to the symbol at a position in a file.
+ */
+export interface LspReferencesParams {
+  /** Path of the file, relative to the project root. */
+  path: string
+  /** 1-based line of the symbol. */
+  line: number
+  /** 1-based column of any character of the symbol. */
+  column: number
+  /** Whether to include the symbol's declaration. Defaults to true. */
+  include_declaration?: boolean
+}
+
+/**
+ * Rename the symbol at a position in a file, and every reference to it across the project, using the language server.
+ */
+export interface LspRenameParams {
+  /** Path of the file, relative to the project root. */
+  path: string
+  /** 1-based line of the symbol. */
+  line: number
+  /** 1-based column of any character of the symbol. */
+  column: number
+  /** The symbol's new name. */
+  new_name: string
+}
+
+/**
+ * Search the project's symbols (types, functions, methods, constants, ...) by name using the language server.
+ */
+export interface LspWorkspaceSymbolsParams {
+  /** Name or fuzzy pattern of the symbols, e.g. "Config" or "parse_args". */
+  query: string
+  /** Optional file, relative to the project root, whose language server to ask. Defaults to the servers of the languages used at the project root, e.g. rust-analyzer when there is a Cargo.toml. */
+  path?: string
+}
+
 /**
  * Propose string replacements in a file without actually applying them.
  */
@@ -184,11 +384,25 @@ export interface ProposeWriteFileParams {
   content: string
 }
 
+/**
+ * Read the output of a background process started with run_terminal_command, optionally waiting until a pattern appears.
+ */
+export interface ReadBackgroundProcessParams {
+  /** processId returned by run_terminal_command. */
+  process_id: number
+  /** Offset in the output to read from, e.g. 0 to read everything again. Default: where the previous read ended. */
+  offset?: number
+  /** Regular expression to wait for in the output after the offset, e.g. "Listening on|ready in" for a dev server. */
+  wait_for?: string
+  /** How long to wait for wait_for, or for the process to exit without it. Set to -1 to wait without a limit. Default 30 with wait_for, otherwise 0 */
+  wait_seconds?: number
+}
+
 /**
  * Fetch up-to-date documentation for libraries and frameworks using Context7 API.
  */
 export interface ReadDocsParams {
-  /** The library or framework name (e.g., "Next.js", "MongoDB", "React"). Use the official name as it appears in documentation if possible. Only public libraries available in Context7's database are supported, so small or private libraries may not be available. */
+  /** The library or framework name (e.g., "Next.js", "MongoDB", "React"). Use the official name as it appears in documentation if possible. Only public libraries available in Context7's database are supported, so small or private libraries may not be available. For Rust crates, use the crate name (e.g., "serde_json"). */
   libraryTitle: string
   /** Specific topic to focus on (e.g., "routing", "hooks", "authentication") */
   topic: string
@@ -202,6 +416,10 @@ export interface ReadDocsParams {
 export interface ReadFilesParams {
   /** List of file paths to read. */
   paths: string[]
+  /** For Rust files, return an outline of item signatures (functions, struct fields, enum variants, trait items, impl headers) with line ranges instead of the full contents. Function bodies are elided. Other files are returned in full. */
+  outline?: boolean
+  /** For Rust files, return only the source of these items instead of the full contents, by qualified path, e.g. "Greeting::new", "<Greeting as Display>::fmt" or "config::load". */
+  items?: string[]
 }
 
 /**
@@ -214,6 +432,20 @@ export interface ReadSubtreeParams {
   maxTokens?: number
 }
 
+/**
+ * Read the output of a terminal session, send input to the command running in it, or close it.
+ */
+export interface ReadTerminalSessionParams {
+  /** Name of the session passed to run_terminal_command. */
+  session: string
+  /** Text to write to the running command's stdin, e.g. "y\n" to answer a prompt. End it with a newline to press Enter. */
+  input?: string
+  /** How long to wait for the running command to finish before returning the output so far. Set to -1 to wait until it finishes. Default 10 */
+  wait_seconds?: number
+  /** Kill the session's shell and everything running in it, after returning its unread output. */
+  close?: boolean
+}
+
 /**
  * Parameters for run_file_change_hooks tool
  */
@@ -228,12 +460,14 @@ export interface RunFileChangeHooksParams {
 export interface RunTerminalCommandParams {
   /** CLI command valid for user's OS. */
   command: string
-  /** Either SYNC (waits, returns output) or BACKGROUND (runs in background). Default SYNC */
+  /** Either SYNC (waits, returns output) or BACKGROUND (runs in background and returns a processId). Default SYNC */
   process_type?: 'SYNC' | 'BACKGROUND'
   /** The working directory to run the command in. Default is the project root. */
   cwd?: string
   /** Set to -1 for no timeout. Does not apply for BACKGROUND commands. Default 30 */
   timeout_seconds?: number
+  /** Name of a persistent terminal session to run the command in, e.g. "dev". The session keeps its working directory, environment variables and sourced scripts between commands. It starts in cwd on first use; later, cwd is ignored and `cd` moves the session. */
+  session?: string
 }
 
 /**
@@ -248,6 +482,18 @@ export interface SetMessagesParams {
  */
 export interface SetOutputParams {}
 
+/**
+ * Send a signal to a background process started with run_terminal_command, e.g. to stop it.
+ */
+export interface SignalBackgroundProcessParams {
+  /** processId returned by run_terminal_command. */
+  process_id: number
+  /** Signal to send to the process and everything it started. Default SIGTERM */
+  signal?: 'SIGINT' | 'SIGTERM' | 'SIGKILL' | 'SIGHUP'
+  /** How long to wait for the process to exit. Default 5 */
+  wait_seconds?: number
+}
+
 /**
  * Load a skill's full instructions when relevant to the current task. Skills are loaded on-demand - only load them when you need their specific guidance.
  */
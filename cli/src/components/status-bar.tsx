@@ -1,10 +1,16 @@
 import React, { useEffect, useState } from 'react'
+import { useShallow } from 'zustand/react/shallow'
 
 import { ScrollToBottomButton } from './scroll-to-bottom-button'
 import { ShimmerText } from './shimmer-text'
 import { useTheme } from '../hooks/use-theme'
+import {
+  selectRunningProcesses,
+  useBackgroundProcessStore,
+} from '../state/background-process-store'
 import { useProviderStore } from '../state/provider-store'
 import { useTeamStore } from '../state/team-store'
+import { formatBackgroundProcesses } from '../utils/format-background-processes'
 import { formatElapsedTime } from '../utils/format-elapsed-time'
 
 import type { StatusIndicatorState } from '../utils/status-indicator-state'
@@ -150,13 +156,24 @@ export const StatusBar = ({
     )
   }
 
+  const runningProcesses = useBackgroundProcessStore(
+    useShallow(selectRunningProcesses),
+  )
+
+  const renderBackgroundProcessIndicator = () => {
+    const summary = formatBackgroundProcesses(runningProcesses)
+    if (!summary) return null
+    return <span fg={theme.success}>{summary}</span>
+  }
+
   const statusIndicatorContent = renderStatusIndicator()
   const elapsedTimeContent = renderElapsedTime()
   const teamIndicatorContent = renderTeamIndicator()
   const providerIndicatorContent = renderProviderIndicator()
+  const backgroundProcessIndicatorContent = renderBackgroundProcessIndicator()
 
   // Only show gray background when there's status indicator or timer
-  const hasContent = statusIndicatorContent || elapsedTimeContent || teamIndicatorContent || providerIndicatorContent
+  const hasContent = statusIndicatorContent || elapsedTimeContent || teamIndicatorContent || providerIndicatorContent || backgroundProcessIndicatorContent
 
   return (
     <box
@@ -196,6 +213,14 @@ export const StatusBar = ({
         </box>
       )}
 
+      {backgroundProcessIndicatorContent && (
+        <box style={{ flexShrink: 0 }}>
+          <text style={{ wrapMode: 'none' }}>
+            {backgroundProcessIndicatorContent}
+          </text>
+        </box>
+      )}
+
       <box
         style={{
           flexGrow: 1,
@@ -229,5 +229,22 @@ describe('RunTerminalCommandComponent', () => {
       expect(output).toBe('/project')
       expect(startingCwd).toBe('/project')
     })
+
+    test('describes background processes', () => {
+      const backgroundPayload = JSON.stringify([
+        {
+          type: 'json',
+          value: {
+            command: 'bun dev',
+            processId: 4242,
+            backgroundProcessStatus: 'running',
+          },
+        },
+      ])
+
+      const { output } = parseTerminalOutput(backgroundPayload)
+
+      expect(output).toBe('Running in the background (process 4242)')
+    })
   })
 })
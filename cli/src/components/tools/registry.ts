@@ -41,6 +41,10 @@ const toolComponentRegistry = new Map<ToolName, ToolComponent>([
   // Propose tools reuse the same rendering as their base counterparts
   ['propose_str_replace', StrReplaceComponent],
   ['propose_write_file', WriteFileComponent],
+  // Symbol edits and suggestions are applied as patches, shown like
+  // str_replace edits
+  ['edit_symbol', StrReplaceComponent],
+  ['apply_suggestions', StrReplaceComponent],
   [SkillComponent.toolName, SkillComponent],
 ])
 
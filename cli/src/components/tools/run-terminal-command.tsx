@@ -27,6 +27,13 @@ export const parseTerminalOutput = (rawOutput: string | undefined): ParsedTermin
       if (value.errorMessage) {
         return { output: `Error: ${value.errorMessage}`, startingCwd }
       }
+      // Handle commands started with process_type BACKGROUND
+      if (typeof value.processId === 'number') {
+        return {
+          output: `Running in the background (process ${value.processId})`,
+          startingCwd,
+        }
+      }
       // Combine stdout and stderr for display
       // Use trimEnd() to preserve leading spaces (used for UI elements like trees/tables)
       const stdout = value.stdout || ''
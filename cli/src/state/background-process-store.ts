@@ -0,0 +1,40 @@
+import { create } from 'zustand'
+import { immer } from 'zustand/middleware/immer'
+
+import type { BackgroundProcessInfo } from '@levelcode/sdk'
+
+interface BackgroundProcessState {
+  /** Processes started by the agent, as last reported by the SDK client */
+  processes: BackgroundProcessInfo[]
+}
+
+interface BackgroundProcessActions {
+  setProcesses: (processes: BackgroundProcessInfo[]) => void
+  reset: () => void
+}
+
+type BackgroundProcessStore = BackgroundProcessState & BackgroundProcessActions
+
+const initialState: BackgroundProcessState = {
+  processes: [],
+}
+
+export const useBackgroundProcessStore = create<BackgroundProcessStore>()(
+  immer((set) => ({
+    ...initialState,
+
+    setProcesses: (processes) =>
+      set((state) => {
+        state.processes = processes
+      }),
+
+    reset: () =>
+      set(() => ({
+        ...initialState,
+        processes: [],
+      })),
+  })),
+)
+
+export const selectRunningProcesses = (state: BackgroundProcessStore) =>
+  state.processes.filter(({ status }) => status === 'running')
@@ -0,0 +1,32 @@
+import { describe, test, expect } from 'bun:test'
+
+import { formatBackgroundProcesses } from '../format-background-processes'
+
+describe('formatBackgroundProcesses', () => {
+  test('formats nothing when no process is running', () => {
+    expect(formatBackgroundProcesses([])).toBe('')
+  })
+
+  test('shows the command of a single process', () => {
+    expect(formatBackgroundProcesses([{ command: 'bun dev' }])).toBe(
+      'bg: bun dev',
+    )
+  })
+
+  test('shortens long and multiline commands', () => {
+    expect(
+      formatBackgroundProcesses([
+        { command: 'cargo watch -x check\n  -x "test --workspace --all"' },
+      ]),
+    ).toBe('bg: cargo watch -x check -x "test…')
+  })
+
+  test('counts several processes', () => {
+    expect(
+      formatBackgroundProcesses([
+        { command: 'bun dev' },
+        { command: 'cargo watch' },
+      ]),
+    ).toBe('bg: 2 processes')
+  })
+})
@@ -295,6 +295,33 @@ describe('getFileStatsFromBlocks', () => {
     expect(stats).toHaveLength(2)
   })
 
+  test('includes symbol edits and applied suggestions', () => {
+    const blocks: ContentBlock[] = [
+      {
+        type: 'tool',
+        toolCallId: 'test-1',
+        toolName: 'edit_symbol',
+        input: { path: 'src/lib.rs', edits: [] },
+        outputRaw: [{ type: 'json', value: { patch: '+added\n-removed' } }],
+      },
+      {
+        type: 'tool',
+        toolCallId: 'test-2',
+        toolName: 'apply_suggestions',
+        input: { path: 'src/lib.rs', suggestions: [] },
+        outputRaw: [{ type: 'json', value: { patch: '+fixed' } }],
+      },
+    ]
+    const stats = getFileStatsFromBlocks(blocks)
+    expect(stats).toEqual([
+      {
+        path: 'src/lib.rs',
+        changeType: 'M',
+        stats: { linesAdded: 2, linesRemoved: 1, hunks: 2 },
+      },
+    ])
+  })
+
   test('ignores non-edit tools', () => {
     const blocks: ContentBlock[] = [
       {
@@ -0,0 +1,31 @@
+import type { BackgroundProcessInfo } from '@levelcode/sdk'
+
+const MAX_COMMAND_LENGTH = 30
+
+/**
+ * Format running background processes for the status bar.
+ *
+ * @param processes - Background processes that are still running
+ * @returns Short summary, or an empty string when nothing is running
+ *
+ * @example
+ * formatBackgroundProcesses([{ command: 'bun dev', ... }])
+ * // "bg: bun dev"
+ * formatBackgroundProcesses([{ command: 'bun dev', ... }, { command: 'cargo watch', ... }])
+ * // "bg: 2 processes"
+ */
+export const formatBackgroundProcesses = (
+  processes: Pick<BackgroundProcessInfo, 'command'>[],
+): string => {
+  if (processes.length === 0) {
+    return ''
+  }
+  if (processes.length > 1) {
+    return `bg: ${processes.length} processes`
+  }
+
+  const command = processes[0].command.replace(/\s+/g, ' ').trim()
+  return command.length > MAX_COMMAND_LENGTH
+    ? `bg: ${command.slice(0, MAX_COMMAND_LENGTH - 1)}…`
+    : `bg: ${command}`
+}
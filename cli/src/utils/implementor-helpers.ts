@@ -15,6 +15,8 @@ export const IMPLEMENTOR_AGENT_IDS = [
 const ALL_EDIT_TOOL_NAMES = [
   'str_replace',
   'write_file',
+  'edit_symbol',
+  'apply_suggestions',
   'propose_str_replace',
   'propose_write_file',
 ] as const
@@ -387,8 +389,12 @@ export function getFileChangeType(toolBlock: ToolContentBlock): FileChangeType {
     return isCreate ? 'A' : 'M'
   }
 
-  // str_replace is always a modification
-  if (baseToolName === 'str_replace') {
+  // str_replace, edit_symbol and apply_suggestions only modify existing files
+  if (
+    baseToolName === 'str_replace' ||
+    baseToolName === 'edit_symbol' ||
+    baseToolName === 'apply_suggestions'
+  ) {
     return 'M'
   }
 
@@ -439,7 +445,8 @@ export function getFileStatsFromBlocks(blocks: ContentBlock[] | undefined): File
 /**
  * Build an activity timeline from agent blocks.
  * Interleaves commentary (text blocks) and edits (tool calls).
- * Includes both executed tools (str_replace, write_file) and proposed tools.
+ * Includes both executed tools (str_replace, write_file, edit_symbol,
+ * apply_suggestions) and proposed tools.
  */
 export function buildActivityTimeline(
   blocks: ContentBlock[] | undefined,
@@ -8,6 +8,7 @@ import { loadAgentDefinitions } from './local-agent-registry'
 import { logger } from './logger'
 import { getRgPath } from '../native/ripgrep'
 import { getProjectRoot } from '../project-files'
+import { useBackgroundProcessStore } from '../state/background-process-store'
 
 import type { ClientToolCall } from '@levelcode/common/tools/list'
 
@@ -41,6 +42,10 @@ function removeUndefinedValues<T>(obj: T): T {
  * This should be called after login to ensure the client is re-initialized with new credentials.
  */
 export function resetLevelCodeClient(): void {
+  // Processes the agent started belong to the old client's session
+  clientInstance?.shutdownBackgroundProcesses().catch((error) => {
+    logger.warn({ error }, 'Failed to stop background processes')
+  })
   clientInstance = null
 }
 
@@ -98,6 +103,9 @@ export async function getLevelCodeClient(): Promise<LevelCodeClient | null> {
           },
         },
       })
+      clientInstance.onBackgroundProcessesChange((processes) => {
+        useBackgroundProcessStore.getState().setProcesses(processes)
+      })
     } catch (error) {
       logger.error(error, 'Failed to initialize LevelCodeClient')
       return null
@@ -137,6 +137,12 @@ export interface AgentDefinition {
    */
   spawnableAgents?: string[]
 
+  /** Whether to reject file edits that introduce syntax errors.
+   *
+   * Defaults to false, in which case such edits are applied and the errors, with their line numbers, are listed in the tool result.
+   */
+  strictSyntax?: boolean
+
   // ============================================================================
   // Input and Output
   // ============================================================================
@@ -330,7 +336,12 @@ export type ToolCall<T extends ToolName = ToolName> = {
 /**
  * File operation tools
  */
-export type FileEditingTools = 'read_files' | 'write_file' | 'str_replace'
+export type FileEditingTools =
+  | 'read_files'
+  | 'write_file'
+  | 'str_replace'
+  | 'edit_symbol'
+  | 'apply_suggestions'
 
 /**
  * Code analysis tools
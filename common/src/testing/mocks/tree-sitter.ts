@@ -38,7 +38,11 @@ export interface CreateMockQueryOptions {
 export function createMockCapture(name: string, text: string): MockCapture {
   return {
     name,
-    node: { text },
+    node: {
+      text,
+      startPosition: { row: 0, column: 0 },
+      endPosition: { row: 0, column: text.length },
+    },
   }
 }
 
@@ -22,25 +22,41 @@ export const TOOLS_WHICH_WONT_FORCE_NEXT_STEP = [
 export const toolNames = [
   'add_subgoal',
   'add_message',
+  'apply_suggestions',
   'ask_user',
   'browser_logs',
+  'cargo_diagnostics',
   'code_search',
   'create_plan',
+  'edit_symbol',
   'end_turn',
+  'find_affected_tests',
+  'find_definition',
   'find_files',
+  'find_references',
+  'find_trait_impls',
   'glob',
   'list_directory',
   'lookup_agent_info',
+  'lsp_definition',
+  'lsp_diagnostics',
+  'lsp_hover',
+  'lsp_references',
+  'lsp_rename',
+  'lsp_workspace_symbols',
   'propose_str_replace',
   'propose_write_file',
+  'read_background_process',
   'read_docs',
   'read_files',
   'read_subtree',
+  'read_terminal_session',
   'run_file_change_hooks',
   'run_terminal_command',
   'send_message',
   'set_messages',
   'set_output',
+  'signal_background_process',
   'skill',
   'spawn_agents',
   'spawn_agent_inline',
@@ -62,22 +78,38 @@ export const toolNames = [
 
 export const publishedTools = [
   'add_message',
+  'apply_suggestions',
   'ask_user',
+  'cargo_diagnostics',
   'code_search',
+  'edit_symbol',
   'end_turn',
+  'find_affected_tests',
+  'find_definition',
   'find_files',
+  'find_references',
+  'find_trait_impls',
   'glob',
   'list_directory',
   'lookup_agent_info',
+  'lsp_definition',
+  'lsp_diagnostics',
+  'lsp_hover',
+  'lsp_references',
+  'lsp_rename',
+  'lsp_workspace_symbols',
   'propose_str_replace',
   'propose_write_file',
+  'read_background_process',
   'read_docs',
   'read_files',
   'read_subtree',
+  'read_terminal_session',
   'run_file_change_hooks',
   'run_terminal_command',
   'set_messages',
   'set_output',
+  'signal_background_process',
   'skill',
   'spawn_agents',
   'str_replace',
@@ -3,25 +3,41 @@ import z from 'zod/v4'
 import { FileChangeSchema } from '../actions'
 import { addMessageParams } from './params/tool/add-message'
 import { addSubgoalParams } from './params/tool/add-subgoal'
+import { applySuggestionsParams } from './params/tool/apply-suggestions'
 import { askUserParams } from './params/tool/ask-user'
 import { browserLogsParams } from './params/tool/browser-logs'
+import { cargoDiagnosticsParams } from './params/tool/cargo-diagnostics'
 import { codeSearchParams } from './params/tool/code-search'
 import { createPlanParams } from './params/tool/create-plan'
+import { editSymbolParams } from './params/tool/edit-symbol'
 import { endTurnParams } from './params/tool/end-turn'
+import { findAffectedTestsParams } from './params/tool/find-affected-tests'
+import { findDefinitionParams } from './params/tool/find-definition'
 import { findFilesParams } from './params/tool/find-files'
+import { findReferencesParams } from './params/tool/find-references'
+import { findTraitImplsParams } from './params/tool/find-trait-impls'
 import { globParams } from './params/tool/glob'
 import { listDirectoryParams } from './params/tool/list-directory'
 import { lookupAgentInfoParams } from './params/tool/lookup-agent-info'
+import { lspDefinitionParams } from './params/tool/lsp-definition'
+import { lspDiagnosticsParams } from './params/tool/lsp-diagnostics'
+import { lspHoverParams } from './params/tool/lsp-hover'
+import { lspReferencesParams } from './params/tool/lsp-references'
+import { lspRenameParams } from './params/tool/lsp-rename'
+import { lspWorkspaceSymbolsParams } from './params/tool/lsp-workspace-symbols'
 import { proposeStrReplaceParams } from './params/tool/propose-str-replace'
 import { proposeWriteFileParams } from './params/tool/propose-write-file'
+import { readBackgroundProcessParams } from './params/tool/read-background-process'
 import { readDocsParams } from './params/tool/read-docs'
 import { readFilesParams } from './params/tool/read-files'
 import { readSubtreeParams } from './params/tool/read-subtree'
+import { readTerminalSessionParams } from './params/tool/read-terminal-session'
 import { runFileChangeHooksParams } from './params/tool/run-file-change-hooks'
 import { runTerminalCommandParams } from './params/tool/run-terminal-command'
 import { sendMessageParams } from './params/tool/send-message'
 import { setMessagesParams } from './params/tool/set-messages'
 import { setOutputParams } from './params/tool/set-output'
+import { signalBackgroundProcessParams } from './params/tool/signal-background-process'
 import { skillParams } from './params/tool/skill'
 import { spawnAgentInlineParams } from './params/tool/spawn-agent-inline'
 import { spawnAgentsParams } from './params/tool/spawn-agents'
@@ -47,25 +63,41 @@ import type { ToolCallPart } from '../types/messages/content-part'
 export const toolParams = {
   add_message: addMessageParams,
   add_subgoal: addSubgoalParams,
+  apply_suggestions: applySuggestionsParams,
   ask_user: askUserParams,
   browser_logs: browserLogsParams,
+  cargo_diagnostics: cargoDiagnosticsParams,
   code_search: codeSearchParams,
   create_plan: createPlanParams,
+  edit_symbol: editSymbolParams,
   end_turn: endTurnParams,
+  find_affected_tests: findAffectedTestsParams,
+  find_definition: findDefinitionParams,
   find_files: findFilesParams,
+  find_references: findReferencesParams,
+  find_trait_impls: findTraitImplsParams,
   glob: globParams,
   list_directory: listDirectoryParams,
   lookup_agent_info: lookupAgentInfoParams,
+  lsp_definition: lspDefinitionParams,
+  lsp_diagnostics: lspDiagnosticsParams,
+  lsp_hover: lspHoverParams,
+  lsp_references: lspReferencesParams,
+  lsp_rename: lspRenameParams,
+  lsp_workspace_symbols: lspWorkspaceSymbolsParams,
   propose_str_replace: proposeStrReplaceParams,
   propose_write_file: proposeWriteFileParams,
+  read_background_process: readBackgroundProcessParams,
   read_docs: readDocsParams,
   read_files: readFilesParams,
   read_subtree: readSubtreeParams,
+  read_terminal_session: readTerminalSessionParams,
   run_file_change_hooks: runFileChangeHooksParams,
   run_terminal_command: runTerminalCommandParams,
   send_message: sendMessageParams,
   set_messages: setMessagesParams,
   set_output: setOutputParams,
+  signal_background_process: signalBackgroundProcessParams,
   skill: skillParams,
   spawn_agents: spawnAgentsParams,
   spawn_agent_inline: spawnAgentInlineParams,
@@ -107,6 +139,10 @@ export type LevelCodeToolMessage<T extends ToolName = ToolName> = ToolMessage &
 
 // Tool call to send to client
 export const clientToolCallSchema = z.discriminatedUnion('toolName', [
+  z.object({
+    toolName: z.literal('apply_suggestions'),
+    input: FileChangeSchema,
+  }),
   z.object({
     toolName: z.literal('ask_user'),
     input: toolParams.ask_user.inputSchema,
@@ -115,6 +151,10 @@ export const clientToolCallSchema = z.discriminatedUnion('toolName', [
     toolName: z.literal('browser_logs'),
     input: toolParams.browser_logs.inputSchema,
   }),
+  z.object({
+    toolName: z.literal('cargo_diagnostics'),
+    input: toolParams.cargo_diagnostics.inputSchema,
+  }),
   z.object({
     toolName: z.literal('code_search'),
     input: toolParams.code_search.inputSchema,
@@ -123,6 +163,26 @@ export const clientToolCallSchema = z.discriminatedUnion('toolName', [
     toolName: z.literal('create_plan'),
     input: FileChangeSchema,
   }),
+  z.object({
+    toolName: z.literal('edit_symbol'),
+    input: FileChangeSchema,
+  }),
+  z.object({
+    toolName: z.literal('find_affected_tests'),
+    input: toolParams.find_affected_tests.inputSchema,
+  }),
+  z.object({
+    toolName: z.literal('find_definition'),
+    input: toolParams.find_definition.inputSchema,
+  }),
+  z.object({
+    toolName: z.literal('find_references'),
+    input: toolParams.find_references.inputSchema,
+  }),
+  z.object({
+    toolName: z.literal('find_trait_impls'),
+    input: toolParams.find_trait_impls.inputSchema,
+  }),
   z.object({
     toolName: z.literal('glob'),
     input: toolParams.glob.inputSchema,
@@ -131,6 +191,42 @@ export const clientToolCallSchema = z.discriminatedUnion('toolName', [
     toolName: z.literal('list_directory'),
     input: toolParams.list_directory.inputSchema,
   }),
+  z.object({
+    toolName: z.literal('lsp_definition'),
+    input: toolParams.lsp_definition.inputSchema,
+  }),
+  z.object({
+    toolName: z.literal('lsp_diagnostics'),
+    input: toolParams.lsp_diagnostics.inputSchema,
+  }),
+  z.object({
+    toolName: z.literal('lsp_hover'),
+    input: toolParams.lsp_hover.inputSchema,
+  }),
+  z.object({
+    toolName: z.literal('lsp_references'),
+    input: toolParams.lsp_references.inputSchema,
+  }),
+  z.object({
+    toolName: z.literal('lsp_rename'),
+    input: toolParams.lsp_rename.inputSchema,
+  }),
+  z.object({
+    toolName: z.literal('lsp_workspace_symbols'),
+    input: toolParams.lsp_workspace_symbols.inputSchema,
+  }),
+  z.object({
+    toolName: z.literal('read_background_process'),
+    input: toolParams.read_background_process.inputSchema,
+  }),
+  z.object({
+    toolName: z.literal('read_docs'),
+    input: toolParams.read_docs.inputSchema,
+  }),
+  z.object({
+    toolName: z.literal('read_terminal_session'),
+    input: toolParams.read_terminal_session.inputSchema,
+  }),
   z.object({
     toolName: z.literal('run_file_change_hooks'),
     input: toolParams.run_file_change_hooks.inputSchema,
@@ -141,6 +237,10 @@ export const clientToolCallSchema = z.discriminatedUnion('toolName', [
       z.object({ mode: z.enum(['assistant', 'user']) }),
     ),
   }),
+  z.object({
+    toolName: z.literal('signal_background_process'),
+    input: toolParams.signal_background_process.inputSchema,
+  }),
   z.object({
     toolName: z.literal('str_replace'),
     input: FileChangeSchema,
@@ -0,0 +1,80 @@
+import z from 'zod/v4'
+
+import { $getNativeToolCallExampleString, jsonToolResultSchema } from '../utils'
+import { updateFileResultSchema } from './str-replace'
+
+import type { $ToolParams } from '../../constants'
+
+const toolName = 'apply_suggestions'
+const endsAgentStep = false
+const inputSchema = z
+  .object({
+    path: z
+      .string()
+      .min(1, 'Path cannot be empty')
+      .describe(`The path to the file the suggestions are for.`),
+    suggestions: z
+      .array(
+        z
+          .object({
+            file: z
+              .string()
+              .optional()
+              .describe(
+                `File the suggestion was reported for. Must match path if set.`,
+              ),
+            startLine: z.number().int().min(1),
+            startColumn: z.number().int().min(1),
+            endLine: z.number().int().min(1),
+            endColumn: z.number().int().min(1),
+            replacement: z
+              .string()
+              .describe(`Text replacing the span; empty to delete it.`),
+            message: z.string().optional(),
+            applicability: z.string().optional(),
+          })
+          .describe(
+            `A suggested replacement as returned by cargo_diagnostics. Lines and columns are 1-based and endColumn is exclusive.`,
+          ),
+      )
+      .min(1, 'Suggestions cannot be empty')
+      .describe(`Suggestions to apply together.`),
+  })
+  .describe(
+    `Apply rustc and clippy suggested fixes from cargo_diagnostics to a file, all at once.`,
+  )
+const description = `
+Use this tool to apply the fixes rustc and clippy suggest instead of retyping them with str_replace. Pass the suggestions of one file exactly as cargo_diagnostics returned them.
+
+The suggestions are applied all together or not at all: the edit fails if two spans overlap, if a span is outside the file, or if a suggestion contains placeholders ("HasPlaceholders"). Suggestions marked "MachineApplicable" are safe to apply; review "MaybeIncorrect" ones first. Spans refer to the file as it was when cargo ran, so re-run cargo_diagnostics after other edits to the file.
+
+Example:
+${$getNativeToolCallExampleString({
+  toolName,
+  inputSchema,
+  input: {
+    path: 'src/config.rs',
+    suggestions: [
+      {
+        file: 'src/config.rs',
+        startLine: 12,
+        startColumn: 20,
+        endLine: 12,
+        endColumn: 20,
+        replacement: '&',
+        message: 'consider borrowing here',
+        applicability: 'MachineApplicable',
+      },
+    ],
+  },
+  endsAgentStep,
+})}
+`.trim()
+
+export const applySuggestionsParams = {
+  toolName,
+  endsAgentStep,
+  description,
+  inputSchema,
+  outputSchema: jsonToolResultSchema(updateFileResultSchema),
+} satisfies $ToolParams
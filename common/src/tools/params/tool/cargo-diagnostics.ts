@@ -0,0 +1,124 @@
+import z from 'zod/v4'
+
+import { $getNativeToolCallExampleString, jsonToolResultSchema } from '../utils'
+import { sandboxViolationSchema } from './run-terminal-command'
+
+import type { $ToolParams } from '../../constants'
+
+export const DEFAULT_MAX_CARGO_DIAGNOSTICS = 20
+
+const diagnosticSpanSchema = z.object({
+  file: z.string(),
+  startLine: z.number(),
+  startColumn: z.number(),
+  endLine: z.number(),
+  endColumn: z.number(),
+  label: z.string().optional(),
+})
+
+export const cargoDiagnosticSchema = z.object({
+  level: z.string(),
+  code: z.string().optional(),
+  message: z.string(),
+  packageId: z.string().optional(),
+  primarySpan: diagnosticSpanSchema.optional(),
+  suggestions: z.array(
+    diagnosticSpanSchema.extend({
+      message: z.string(),
+      replacement: z.string(),
+      applicability: z.string().optional(),
+    }),
+  ),
+  rendered: z.string().optional(),
+})
+
+const toolName = 'cargo_diagnostics'
+const endsAgentStep = true
+const inputSchema = z
+  .object({
+    command: z
+      .enum(['check', 'clippy', 'build'])
+      .default('check')
+      .describe(`Cargo subcommand to run. Defaults to "check".`),
+    package: z
+      .string()
+      .optional()
+      .describe(
+        `Optional workspace package to check (cargo's -p). Defaults to the whole workspace.`,
+      ),
+    args: z
+      .array(z.string())
+      .optional()
+      .describe(
+        `Extra arguments for cargo, e.g. ["--all-targets"] or ["--features", "serde"].`,
+      ),
+    cwd: z
+      .string()
+      .optional()
+      .describe(
+        `Optional directory to run cargo in, relative to the project root.`,
+      ),
+    max_diagnostics: z
+      .number()
+      .int()
+      .min(1)
+      .default(DEFAULT_MAX_CARGO_DIAGNOSTICS)
+      .describe(
+        `Most diagnostics to return, errors first. Defaults to ${DEFAULT_MAX_CARGO_DIAGNOSTICS}.`,
+      ),
+  })
+  .describe(
+    `Run cargo check, clippy or build and return the compiler's errors and warnings as structured diagnostics grouped by file.`,
+  )
+const description = `
+Purpose: Compile a Rust project and get its errors and warnings without the noise and truncation of raw terminal output.
+
+Runs \`cargo <command> --message-format=json\` and returns each diagnostic once, with its level, error or lint code, message, primary span, suggested replacements and the text rustc would print. Diagnostics are grouped by file, errors first, up to max_diagnostics; the counts of all errors and warnings are always included.
+
+Prefer this over run_terminal_command for cargo check/clippy/build. Suggestions marked "MachineApplicable" can be applied as-is.
+
+Examples:
+${$getNativeToolCallExampleString({
+  toolName,
+  inputSchema,
+  input: { command: 'check' },
+  endsAgentStep,
+})}
+${$getNativeToolCallExampleString({
+  toolName,
+  inputSchema,
+  input: { command: 'clippy', package: 'my-crate', args: ['--all-targets'] },
+  endsAgentStep,
+})}
+`.trim()
+
+export const cargoDiagnosticsParams = {
+  toolName,
+  endsAgentStep,
+  description,
+  inputSchema,
+  outputSchema: jsonToolResultSchema(
+    z.union([
+      z.object({
+        command: z.string(),
+        success: z.boolean(),
+        exitCode: z.number().optional(),
+        errorCount: z.number(),
+        warningCount: z.number(),
+        omitted: z.number(),
+        files: z.array(
+          z.object({
+            file: z.string(),
+            diagnostics: z.array(cargoDiagnosticSchema),
+          }),
+        ),
+        stderr: z.string().optional(),
+        message: z.string(),
+        sandboxViolations: z.array(sandboxViolationSchema).optional(),
+      }),
+      z.object({
+        errorMessage: z.string(),
+      }),
+    ]),
+  ),
+} satisfies $ToolParams
@@ -0,0 +1,87 @@
+import z from 'zod/v4'
+
+import { $getNativeToolCallExampleString, jsonToolResultSchema } from '../utils'
+import { updateFileResultSchema } from './str-replace'
+
+import type { $ToolParams } from '../../constants'
+
+const toolName = 'edit_symbol'
+const endsAgentStep = false
+const symbolSchema = z
+  .string()
+  .min(1, 'Symbol cannot be empty')
+  .describe(
+    `Path of the item to edit, as listed by read_files in outline mode, e.g. "Greeting::new", "<Greeting as Greeter>::greet", "Color::Red" or "inner::helper". A suffix of the path is enough if it is unambiguous.`,
+  )
+const inputSchema = z
+  .object({
+    path: z
+      .string()
+      .min(1, 'Path cannot be empty')
+      .describe(`The path to the Rust file to edit.`),
+    edits: z
+      .array(
+        z.discriminatedUnion('action', [
+          z
+            .object({
+              symbol: symbolSchema,
+              action: z
+                .enum(['replace', 'insert_before', 'insert_after', 'append'])
+                .describe(
+                  `"replace" replaces the item (keeping its attributes and doc comments), "insert_before"/"insert_after" add a sibling item, and "append" adds a field, variant or item at the end of the item's body.`,
+                ),
+              content: z
+                .string()
+                .describe(
+                  `The new source code. Indentation is adjusted to the item's position.`,
+                ),
+            })
+            .describe('A structural edit of one item.'),
+          z
+            .object({
+              symbol: symbolSchema,
+              action: z
+                .literal('delete')
+                .describe(
+                  `"delete" removes the item with its attributes and doc comments.`,
+                ),
+            })
+            .describe('Removes one item.'),
+        ]),
+      )
+      .min(1, 'Edits cannot be empty')
+      .describe('Edits to apply in order.'),
+  })
+  .describe(
+    `Edit Rust items addressed by their qualified path instead of by matching text.`,
+  )
+const description = `
+Use this tool to edit Rust files item by item. Items are located by their syntax tree rather than by matching text, so it works where str_replace is ambiguous, e.g. on repeated \`impl Display\` blocks. If a path matches more than one item, the edit fails and lists the candidates.
+
+Example:
+${$getNativeToolCallExampleString({
+  toolName,
+  inputSchema,
+  input: {
+    path: 'src/greeting.rs',
+    edits: [
+      {
+        symbol: '<Greeting as Greeter>::greet',
+        action: 'replace',
+        content:
+          'fn greet(&self, name: &str) -> String {\n    format!("{} {}!", self.prefix, name)\n}',
+      },
+      { symbol: 'Color', action: 'append', content: 'Purple' },
+    ],
+  },
+  endsAgentStep,
+})}
+    `.trim()
+
+export const editSymbolParams = {
+  toolName,
+  endsAgentStep,
+  description,
+  inputSchema,
+  outputSchema: jsonToolResultSchema(updateFileResultSchema),
+} satisfies $ToolParams
@@ -0,0 +1,69 @@
+import z from 'zod/v4'
+
+import { $getNativeToolCallExampleString, jsonToolResultSchema } from '../utils'
+
+import type { $ToolParams } from '../../constants'
+
+const rustTestSchema = z.object({
+  name: z.string(),
+  path: z.string(),
+  kind: z.enum(['unit', 'integration', 'doc']),
+  file: z.string(),
+  line: z.number(),
+  package: z.string().optional(),
+  target: z
+    .object({
+      kind: z.enum(['lib', 'bin', 'test', 'example', 'bench']),
+      name: z.string().optional(),
+    })
+    .optional(),
+})
+
+const toolName = 'find_affected_tests'
+const endsAgentStep = true
+const inputSchema = z
+  .object({
+    paths: z
+      .array(z.string())
+      .min(1)
+      .describe(
+        `Changed files, relative to the project root (e.g. ["src/config.rs"]).`,
+      ),
+  })
+  .describe(
+    `Find the Rust tests affected by changes to the given files, and the cargo test commands running only those tests.`,
+  )
+const description = `
+Purpose: Select the Rust tests to run after an edit instead of running the whole workspace's test suite.
+
+A test is affected when it is defined in one of the changed files, or when its body references an item defined in one of them. Unit tests (\`#[test]\`, \`#[tokio::test]\`, ...), integration tests under \`tests/\` and doc-tests are covered. References are resolved through module paths and imports, so calls through traits or macros may be missed: run the wider suite before finishing larger changes.
+
+The result lists each affected test with its file and line, and one \`cargo test -p <package> <target> <filters>\` command per package and target. Run those commands with run_terminal_command.
+
+Example:
+${$getNativeToolCallExampleString({
+  toolName,
+  inputSchema,
+  input: { paths: ['crates/core/src/config.rs'] },
+  endsAgentStep,
+})}
+`.trim()
+
+export const findAffectedTestsParams = {
+  toolName,
+  endsAgentStep,
+  description,
+  inputSchema,
+  outputSchema: jsonToolResultSchema(
+    z.union([
+      z.object({
+        tests: z.array(rustTestSchema),
+        commands: z.array(z.string()),
+        message: z.string(),
+      }),
+      z.object({
+        errorMessage: z.string(),
+      }),
+    ]),
+  ),
+} satisfies $ToolParams
@@ -0,0 +1,87 @@
+import z from 'zod/v4'
+
+import { $getNativeToolCallExampleString, jsonToolResultSchema } from '../utils'
+
+import type { $ToolParams } from '../../constants'
+
+export const sourceRangeSchema = z.object({
+  startLine: z.number(),
+  startColumn: z.number(),
+  endLine: z.number(),
+  endColumn: z.number(),
+})
+
+export const symbolMatchSchema = z.object({
+  file: z.string(),
+  symbol: z.string(),
+  range: sourceRangeSchema,
+  resolved: z.boolean(),
+})
+
+const toolName = 'find_definition'
+const endsAgentStep = true
+const inputSchema = z
+  .object({
+    path: z
+      .string()
+      .min(1, 'Path cannot be empty')
+      .describe(`Path of the file, relative to the project root.`),
+    line: z.number().int().min(1).describe(`1-based line of the symbol.`),
+    column: z
+      .number()
+      .int()
+      .min(1)
+      .describe(`1-based column of any character of the symbol.`),
+  })
+  .describe(
+    `Go to the definition of the symbol at a position in a file: a function, type, method or other item being called or referenced there.`,
+  )
+const description = `
+Purpose: Jump from a use of a symbol to where it is defined, using the project's syntax trees rather than text search.
+
+Rust paths are resolved through modules, \`use\` imports and workspace dependencies, so same-named items in different modules are told apart; such results have \`resolved: true\`. Other languages, and Rust method calls whose receiver type is ambiguous, fall back to definitions with the same name (\`resolved: false\`).
+
+When no resolved definition is found in a Rust file, matching items from rustdoc JSON in \`target/doc\` are listed as \`rustdocItems\` with their signatures, which covers items of dependencies.
+
+Lines and columns are 1-based, as shown by read_files. The result includes the symbol found at the position.
+
+Example:
+${$getNativeToolCallExampleString({
+  toolName,
+  inputSchema,
+  input: { path: 'src/main.rs', line: 38, column: 30 },
+  endsAgentStep,
+})}
+`.trim()
+
+export const findDefinitionParams = {
+  toolName,
+  endsAgentStep,
+  description,
+  inputSchema,
+  outputSchema: jsonToolResultSchema(
+    z.union([
+      z.object({
+        symbol: z.string(),
+        kind: z.enum(['definition', 'reference']),
+        definitions: z.array(symbolMatchSchema),
+        rustdocItems: z
+          .array(
+            z.object({
+              path: z.string(),
+              kind: z.string(),
+              signature: z.string(),
+              deprecation: z.string().optional(),
+              file: z.string(),
+              line: z.number(),
+            }),
+          )
+          .optional(),
+        message: z.string(),
+      }),
+      z.object({
+        errorMessage: z.string(),
+      }),
+    ]),
+  ),
+} satisfies $ToolParams
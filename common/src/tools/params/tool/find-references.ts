@@ -0,0 +1,73 @@
+import z from 'zod/v4'
+
+import { $getNativeToolCallExampleString, jsonToolResultSchema } from '../utils'
+import { sourceRangeSchema, symbolMatchSchema } from './find-definition'
+
+import type { $ToolParams } from '../../constants'
+
+const toolName = 'find_references'
+const endsAgentStep = true
+const inputSchema = z
+  .object({
+    symbol: z
+      .string()
+      .min(1, 'Symbol cannot be empty')
+      .describe(
+        `Name of the symbol, e.g. "load_config", or a path ending in it, e.g. "Greeting::new" or "config::load_config". Rust items of impls and traits are named "Type::item".`,
+      ),
+    path: z
+      .string()
+      .optional()
+      .describe(
+        `Optional file defining the symbol, relative to the project root, to pick one of several same-named definitions.`,
+      ),
+  })
+  .describe(
+    `Find where a symbol is defined and every place it is called or referenced, with file, line and column.`,
+  )
+const description = `
+Purpose: List the call sites and other references of a function, type or method before changing it, using the project's syntax trees rather than text search.
+
+Rust references are resolved through modules, \`use\` imports and workspace dependencies, so only references to the matching definitions are returned (\`resolved: true\`). Other languages, and Rust method calls whose receiver type is ambiguous, are matched by name (\`resolved: false\`), so check those before relying on them.
+
+Lines and columns are 1-based, as shown by read_files.
+
+Examples:
+${$getNativeToolCallExampleString({
+  toolName,
+  inputSchema,
+  input: { symbol: 'Greeting::new' },
+  endsAgentStep,
+})}
+${$getNativeToolCallExampleString({
+  toolName,
+  inputSchema,
+  input: { symbol: 'load', path: 'crates/core/src/config.rs' },
+  endsAgentStep,
+})}
+`.trim()
+
+export const findReferencesParams = {
+  toolName,
+  endsAgentStep,
+  description,
+  inputSchema,
+  outputSchema: jsonToolResultSchema(
+    z.union([
+      z.object({
+        definitions: z.array(
+          z.object({
+            file: z.string(),
+            symbol: z.string(),
+            range: sourceRangeSchema,
+          }),
+        ),
+        references: z.array(symbolMatchSchema),
+        message: z.string(),
+      }),
+      z.object({
+        errorMessage: z.string(),
+      }),
+    ]),
+  ),
+} satisfies $ToolParams
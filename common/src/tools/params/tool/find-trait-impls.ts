@@ -0,0 +1,90 @@
+import z from 'zod/v4'
+
+import { $getNativeToolCallExampleString, jsonToolResultSchema } from '../utils'
+
+import type { $ToolParams } from '../../constants'
+
+const traitImplSchema = z.object({
+  traitName: z.string(),
+  typeName: z.string(),
+  file: z.string(),
+  line: z.number(),
+  header: z.string().optional(),
+  isBlanket: z.boolean(),
+  origin: z.enum(['impl', 'derive', 'macro']),
+  crateName: z.string().optional(),
+})
+
+const toolName = 'find_trait_impls'
+const endsAgentStep = true
+const inputSchema = z
+  .object({
+    trait: z
+      .string()
+      .optional()
+      .describe(
+        `Name of a Rust trait, without its module path (e.g. "Greeter", "Display"). Returns every type implementing it.`,
+      ),
+    type: z
+      .string()
+      .optional()
+      .describe(
+        `Name of a Rust type, without generics or module path (e.g. "Greeting"). Returns every trait implemented for it.`,
+      ),
+    cwd: z
+      .string()
+      .optional()
+      .describe(
+        `Optional directory to index, relative to the project root, e.g. a single crate. Defaults to the entire project.`,
+      ),
+  })
+  .describe(
+    `Find Rust trait implementations: which types implement a trait, or which traits a type implements. Covers explicit impl blocks, #[derive(..)] attributes and macro-generated impls.`,
+  )
+const description = `
+Purpose: Look up the relationship between Rust traits and the types implementing them, using the project's syntax trees rather than text search.
+
+Use cases:
+1. Before adding or changing a trait method, find every implementor that has to be updated
+2. Finding which traits a type implements, including derived ones
+3. Spotting generic (\`impl<T> Trait for Wrapper<T>\`) and blanket (\`impl<T: Bound> Trait for T\`) impls that may apply to many types
+
+Provide a trait, a type, or both. Results include the file and line of each impl and its header. Impls in dependencies are included when their rustdoc JSON is in \`target/doc\` (\`cargo +nightly rustdoc -p <crate> -- -Z unstable-options --output-format json\`); those have a \`crateName\`, as do impls in the members of a Cargo workspace. Blanket impls are always listed when querying a trait, and are listed separately when querying a type, since they may apply to it depending on their bounds.
+
+Examples:
+${$getNativeToolCallExampleString({
+  toolName,
+  inputSchema,
+  input: { trait: 'Greeter' },
+  endsAgentStep,
+})}
+${$getNativeToolCallExampleString({
+  toolName,
+  inputSchema,
+  input: { type: 'Greeting', cwd: 'crates/greet' },
+  endsAgentStep,
+})}
+`.trim()
+
+export const findTraitImplsParams = {
+  toolName,
+  endsAgentStep,
+  description,
+  inputSchema,
+  outputSchema: jsonToolResultSchema(
+    z.union([
+      z.object({
+        traitDefinitions: z
+          .array(z.object({ file: z.string(), line: z.number() }))
+          .optional(),
+        implementors: z.array(traitImplSchema).optional(),
+        implementedTraits: z.array(traitImplSchema).optional(),
+        blanketImpls: z.array(traitImplSchema).optional(),
+        message: z.string(),
+      }),
+      z.object({
+        errorMessage: z.string(),
+      }),
+    ]),
+  ),
+} satisfies $ToolParams
@@ -0,0 +1,82 @@
+import z from 'zod/v4'
+
+import { $getNativeToolCallExampleString, jsonToolResultSchema } from '../utils'
+import { sourceRangeSchema } from './find-definition'
+
+import type { $ToolParams } from '../../constants'
+
+export const lspLocationSchema = z.object({
+  file: z.string(),
+  range: sourceRangeSchema,
+  /** The trimmed source line at the start of the range */
+  text: z.string().optional(),
+})
+
+const toolName = 'lsp_definition'
+const endsAgentStep = true
+const inputSchema = z
+  .object({
+    path: z
+      .string()
+      .min(1, 'Path cannot be empty')
+      .describe(`Path of the file, relative to the project root.`),
+    line: z.number().int().min(1).describe(`1-based line of the symbol.`),
+    column: z
+      .number()
+      .int()
+      .min(1)
+      .describe(`1-based column of any character of the symbol.`),
+    kind: z
+      .enum(['definition', 'type_definition', 'implementation'])
+      .default('definition')
+      .describe(
+        `What to go to: the symbol's "definition" (default), the definition of its type ("type_definition"), or the implementations of a trait, interface or trait method ("implementation").`,
+      ),
+  })
+  .describe(
+    `Ask the language server where the symbol at a position in a file is defined, where its type is defined, or what implements it.`,
+  )
+const description = `
+Purpose: Go to definitions with full type information, e.g. the impl a trait method call dispatches to, or the type of a variable.
+
+Uses the project's language server (rust-analyzer, typescript-language-server or pyright), so method calls are resolved through the receiver's type, generics and trait bounds. Prefer find_definition for quick lookups that do not need type information: it does not have to wait for the server to index the project.
+
+Lines and columns are 1-based, as shown by read_files.
+
+Examples:
+${$getNativeToolCallExampleString({
+  toolName,
+  inputSchema,
+  input: { path: 'src/main.rs', line: 38, column: 30 },
+  endsAgentStep,
+})}
+${$getNativeToolCallExampleString({
+  toolName,
+  inputSchema,
+  input: {
+    path: 'src/storage.rs',
+    line: 5,
+    column: 11,
+    kind: 'implementation',
+  },
+  endsAgentStep,
+})}
+`.trim()
+
+export const lspDefinitionParams = {
+  toolName,
+  endsAgentStep,
+  description,
+  inputSchema,
+  outputSchema: jsonToolResultSchema(
+    z.union([
+      z.object({
+        locations: z.array(lspLocationSchema),
+        message: z.string(),
+      }),
+      z.object({
+        errorMessage: z.string(),
+      }),
+    ]),
+  ),
+} satisfies $ToolParams
@@ -0,0 +1,68 @@
+import z from 'zod/v4'
+
+import { $getNativeToolCallExampleString, jsonToolResultSchema } from '../utils'
+import { sourceRangeSchema } from './find-definition'
+
+import type { $ToolParams } from '../../constants'
+
+const toolName = 'lsp_diagnostics'
+const endsAgentStep = true
+const inputSchema = z
+  .object({
+    paths: z
+      .array(z.string())
+      .min(1)
+      .describe(
+        `Files to check, relative to the project root (e.g. ["src/lib.rs"]).`,
+      ),
+  })
+  .describe(
+    `Get the errors and warnings the language server reports for the given files.`,
+  )
+const description = `
+Purpose: Check edited files for type errors and other problems without running a full build.
+
+Returns the diagnostics of each file with their severity, code, source (e.g. "rustc", "ts" or "Pyright"), message and range. Files are read from disk, so edits made by other tools are checked. For Rust, cargo_diagnostics gives the complete output of cargo check or clippy, including other files.
+
+Example:
+${$getNativeToolCallExampleString({
+  toolName,
+  inputSchema,
+  input: { paths: ['src/config.rs', 'src/main.rs'] },
+  endsAgentStep,
+})}
+`.trim()
+
+export const lspDiagnosticsParams = {
+  toolName,
+  endsAgentStep,
+  description,
+  inputSchema,
+  outputSchema: jsonToolResultSchema(
+    z.union([
+      z.object({
+        files: z.array(
+          z.object({
+            file: z.string(),
+            diagnostics: z.array(
+              z.object({
+                severity: z.enum(['error', 'warning', 'information', 'hint']),
+                code: z.string().optional(),
+                source: z.string().optional(),
+                message: z.string(),
+                range: sourceRangeSchema,
+              }),
+            ),
+            errorMessage: z.string().optional(),
+          }),
+        ),
+        errorCount: z.number(),
+        warningCount: z.number(),
+        message: z.string(),
+      }),
+      z.object({
+        errorMessage: z.string(),
+      }),
+    ]),
+  ),
+} satisfies $ToolParams
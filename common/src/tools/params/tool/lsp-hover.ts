@@ -0,0 +1,59 @@
+import z from 'zod/v4'
+
+import { $getNativeToolCallExampleString, jsonToolResultSchema } from '../utils'
+import { sourceRangeSchema } from './find-definition'
+
+import type { $ToolParams } from '../../constants'
+
+const toolName = 'lsp_hover'
+const endsAgentStep = true
+const inputSchema = z
+  .object({
+    path: z
+      .string()
+      .min(1, 'Path cannot be empty')
+      .describe(`Path of the file, relative to the project root.`),
+    line: z.number().int().min(1).describe(`1-based line of the expression.`),
+    column: z
+      .number()
+      .int()
+      .min(1)
+      .describe(`1-based column of any character of the expression.`),
+  })
+  .describe(
+    `Ask the language server for the type and documentation of the symbol or expression at a position in a file.`,
+  )
+const description = `
+Purpose: Get the type of an expression, the signature of a function or method, or the documentation of an item, as the compiler's language server sees it.
+
+Unlike the syntax-based tools, this resolves generics, trait methods and inferred types, e.g. the concrete type of a \`let\` binding in Rust or of a variable in TypeScript. Uses rust-analyzer for Rust, typescript-language-server for JavaScript and TypeScript, and pyright for Python; the server starts on first use, so the first request on a large project can take a while.
+
+Lines and columns are 1-based, as shown by read_files.
+
+Example:
+${$getNativeToolCallExampleString({
+  toolName,
+  inputSchema,
+  input: { path: 'src/main.rs', line: 12, column: 9 },
+  endsAgentStep,
+})}
+`.trim()
+
+export const lspHoverParams = {
+  toolName,
+  endsAgentStep,
+  description,
+  inputSchema,
+  outputSchema: jsonToolResultSchema(
+    z.union([
+      z.object({
+        contents: z.string(),
+        range: sourceRangeSchema.optional(),
+        message: z.string(),
+      }),
+      z.object({
+        errorMessage: z.string(),
+      }),
+    ]),
+  ),
+} satisfies $ToolParams
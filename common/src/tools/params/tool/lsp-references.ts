@@ -0,0 +1,64 @@
+import z from 'zod/v4'
+
+import { $getNativeToolCallExampleString, jsonToolResultSchema } from '../utils'
+import { lspLocationSchema } from './lsp-definition'
+
+import type { $ToolParams } from '../../constants'
+
+const toolName = 'lsp_references'
+const endsAgentStep = true
+const inputSchema = z
+  .object({
+    path: z
+      .string()
+      .min(1, 'Path cannot be empty')
+      .describe(`Path of the file, relative to the project root.`),
+    line: z.number().int().min(1).describe(`1-based line of the symbol.`),
+    column: z
+      .number()
+      .int()
+      .min(1)
+      .describe(`1-based column of any character of the symbol.`),
+    include_declaration: z
+      .boolean()
+      .default(true)
+      .describe(
+        `Whether to include the symbol's declaration. Defaults to true.`,
+      ),
+  })
+  .describe(
+    `Ask the language server for every reference to the symbol at a position in a file.`,
+  )
+const description = `
+Purpose: Find the uses of a function, type, field or method before changing it, resolved by the project's language server.
+
+Unlike find_references, this tells apart same-named methods of different types and finds uses through trait dispatch, generics and re-exports. Point it at the symbol's definition or at any use of it.
+
+Lines and columns are 1-based, as shown by read_files.
+
+Example:
+${$getNativeToolCallExampleString({
+  toolName,
+  inputSchema,
+  input: { path: 'src/config.rs', line: 14, column: 12 },
+  endsAgentStep,
+})}
+`.trim()
+
+export const lspReferencesParams = {
+  toolName,
+  endsAgentStep,
+  description,
+  inputSchema,
+  outputSchema: jsonToolResultSchema(
+    z.union([
+      z.object({
+        references: z.array(lspLocationSchema),
+        message: z.string(),
+      }),
+      z.object({
+        errorMessage: z.string(),
+      }),
+    ]),
+  ),
+} satisfies $ToolParams
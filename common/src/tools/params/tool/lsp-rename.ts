@@ -0,0 +1,72 @@
+import z from 'zod/v4'
+
+import { $getNativeToolCallExampleString, jsonToolResultSchema } from '../utils'
+
+import type { $ToolParams } from '../../constants'
+
+const toolName = 'lsp_rename'
+const endsAgentStep = true
+const inputSchema = z
+  .object({
+    path: z
+      .string()
+      .min(1, 'Path cannot be empty')
+      .describe(`Path of the file, relative to the project root.`),
+    line: z.number().int().min(1).describe(`1-based line of the symbol.`),
+    column: z
+      .number()
+      .int()
+      .min(1)
+      .describe(`1-based column of any character of the symbol.`),
+    new_name: z
+      .string()
+      .min(1, 'New name cannot be empty')
+      .describe(`The symbol's new name.`),
+  })
+  .describe(
+    `Rename the symbol at a position in a file, and every reference to it across the project, using the language server.`,
+  )
+const description = `
+Purpose: Rename a function, type, field, method, variable or module safely across the project.
+
+The language server computes the edits, so only references to this symbol are changed, including uses through imports, re-exports and trait methods, and the edits are written to the files. The result lists the changed files with their diffs.
+
+Lines and columns are 1-based, as shown by read_files.
+
+Example:
+${$getNativeToolCallExampleString({
+  toolName,
+  inputSchema,
+  input: {
+    path: 'src/config.rs',
+    line: 14,
+    column: 12,
+    new_name: 'load_settings',
+  },
+  endsAgentStep,
+})}
+`.trim()
+
+export const lspRenameParams = {
+  toolName,
+  endsAgentStep,
+  description,
+  inputSchema,
+  outputSchema: jsonToolResultSchema(
+    z.union([
+      z.object({
+        files: z.array(
+          z.object({
+            file: z.string(),
+            edits: z.number(),
+            unifiedDiff: z.string(),
+          }),
+        ),
+        message: z.string(),
+      }),
+      z.object({
+        errorMessage: z.string(),
+      }),
+    ]),
+  ),
+} satisfies $ToolParams
@@ -0,0 +1,66 @@
+import z from 'zod/v4'
+
+import { $getNativeToolCallExampleString, jsonToolResultSchema } from '../utils'
+import { sourceRangeSchema } from './find-definition'
+
+import type { $ToolParams } from '../../constants'
+
+const toolName = 'lsp_workspace_symbols'
+const endsAgentStep = true
+const inputSchema = z
+  .object({
+    query: z
+      .string()
+      .min(1, 'Query cannot be empty')
+      .describe(
+        `Name or fuzzy pattern of the symbols, e.g. "Config" or "parse_args".`,
+      ),
+    path: z
+      .string()
+      .optional()
+      .describe(
+        `Optional file, relative to the project root, whose language server to ask. Defaults to the servers of the languages used at the project root, e.g. rust-analyzer when there is a Cargo.toml.`,
+      ),
+  })
+  .describe(
+    `Search the project's symbols (types, functions, methods, constants, ...) by name using the language server.`,
+  )
+const description = `
+Purpose: Find where a type, function or other item is defined when you know (part of) its name but not its file.
+
+The language server matches names fuzzily and includes items of dependencies only when nothing in the project matches. Results are capped at 100.
+
+Example:
+${$getNativeToolCallExampleString({
+  toolName,
+  inputSchema,
+  input: { query: 'RequestHandler' },
+  endsAgentStep,
+})}
+`.trim()
+
+export const lspWorkspaceSymbolsParams = {
+  toolName,
+  endsAgentStep,
+  description,
+  inputSchema,
+  outputSchema: jsonToolResultSchema(
+    z.union([
+      z.object({
+        symbols: z.array(
+          z.object({
+            name: z.string(),
+            kind: z.string(),
+            container: z.string().optional(),
+            file: z.string(),
+            range: sourceRangeSchema.optional(),
+          }),
+        ),
+        message: z.string(),
+      }),
+      z.object({
+        errorMessage: z.string(),
+      }),
+    ]),
+  ),
+} satisfies $ToolParams
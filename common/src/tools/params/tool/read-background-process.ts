@@ -0,0 +1,87 @@
+import z from 'zod/v4'
+
+import { $getNativeToolCallExampleString, jsonToolResultSchema } from '../utils'
+import { sandboxViolationSchema } from './run-terminal-command'
+
+import type { $ToolParams } from '../../constants'
+
+export const backgroundProcessStatusSchema = z.enum([
+  'running',
+  'completed',
+  'error',
+])
+
+const toolName = 'read_background_process'
+const endsAgentStep = true
+const inputSchema = z
+  .object({
+    process_id: z
+      .number()
+      .int()
+      .describe(`processId returned by run_terminal_command.`),
+    offset: z
+      .number()
+      .int()
+      .min(0)
+      .optional()
+      .describe(
+        `Offset in the output to read from, e.g. 0 to read everything again. Default: where the previous read ended.`,
+      ),
+    wait_for: z
+      .string()
+      .optional()
+      .describe(
+        `Regular expression to wait for in the output after the offset, e.g. "Listening on|ready in" for a dev server.`,
+      ),
+    wait_seconds: z
+      .number()
+      .optional()
+      .describe(
+        `How long to wait for wait_for, or for the process to exit without it. Set to -1 to wait without a limit. Default 30 with wait_for, otherwise 0`,
+      ),
+  })
+  .describe(
+    `Read the output of a background process started with run_terminal_command, optionally waiting until a pattern appears.`,
+  )
+const description = `
+Purpose: Check on a process started with run_terminal_command and process_type BACKGROUND, e.g. whether a dev server is up or what a watcher printed after an edit.
+
+stdout and stderr are combined in one log. Each read returns the output after the previous read and its nextOffset, so polling again returns only new output. The result includes the process status ("running", "completed" or "error") and its exit code once it exited.
+
+To wait until a server is ready, pass wait_for with a pattern from its startup output; matched tells whether it appeared.
+
+Example:
+${$getNativeToolCallExampleString({
+  toolName,
+  inputSchema,
+  input: { process_id: 48213, wait_for: 'Listening on', wait_seconds: 60 },
+  endsAgentStep,
+})}
+`.trim()
+
+export const readBackgroundProcessParams = {
+  toolName,
+  endsAgentStep,
+  description,
+  inputSchema,
+  outputSchema: jsonToolResultSchema(
+    z.union([
+      z.object({
+        processId: z.number(),
+        command: z.string(),
+        status: backgroundProcessStatusSchema,
+        exitCode: z.number().optional(),
+        signal: z.string().optional(),
+        output: z.string(),
+        offset: z.number(),
+        nextOffset: z.number(),
+        matched: z.boolean().optional(),
+        message: z.string().optional(),
+        sandboxViolations: z.array(sandboxViolationSchema).optional(),
+      }),
+      z.object({
+        errorMessage: z.string(),
+      }),
+    ]),
+  ),
+} satisfies $ToolParams
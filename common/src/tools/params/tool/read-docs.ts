@@ -12,7 +12,7 @@ const inputSchema = z
       .string()
       .min(1, 'Library title cannot be empty')
       .describe(
-        `The library or framework name (e.g., "Next.js", "MongoDB", "React"). Use the official name as it appears in documentation if possible. Only public libraries available in Context7's database are supported, so small or private libraries may not be available.`,
+        `The library or framework name (e.g., "Next.js", "MongoDB", "React"). Use the official name as it appears in documentation if possible. Only public libraries available in Context7's database are supported, so small or private libraries may not be available. For Rust crates, use the crate name (e.g., "serde_json").`,
       ),
     topic: z
       .string()
@@ -49,6 +49,8 @@ Use cases:
 
 The tool will search for the library and return the most relevant documentation content. If a topic is specified, it will focus the results on that specific area.
 
+Rust crates the project depends on are documented offline from the local cargo registry, at the exact version in Cargo.lock: the crate docs, public items with their signatures and doc comments, and examples. For these, the topic may also be an item path such as "Value::as_str". When \`target/doc\` holds rustdoc JSON for the crate (\`cargo +nightly rustdoc -p <crate> -- -Z unstable-options --output-format json\`), its compiler-resolved paths, signatures and deprecations are used instead, which also covers the project's own crates.
+
 Example:
 ${$getNativeToolCallExampleString({
   toolName,
@@ -80,6 +82,7 @@ export const readDocsParams = {
   outputSchema: jsonToolResultSchema(
     z.object({
       documentation: z.string(),
+      errorMessage: z.string().optional(),
     }),
   ),
 } satisfies $ToolParams
@@ -9,6 +9,13 @@ export const fileContentsSchema = z.union([
     path: z.string(),
     content: z.string(),
     referencedBy: z.record(z.string(), z.string().array()).optional(),
+    crate: z.string().optional(),
+    view: z
+      .enum(['outline', 'items'])
+      .optional()
+      .describe(
+        'Set when content is an outline of item signatures, or only the requested items, instead of the whole file',
+      ),
   }),
   z.object({
     path: z.string(),
@@ -30,6 +37,18 @@ const inputSchema = z
           ),
       )
       .describe('List of file paths to read.'),
+    outline: z
+      .boolean()
+      .optional()
+      .describe(
+        `For Rust files, return an outline of item signatures (functions, struct fields, enum variants, trait items, impl headers) with line ranges instead of the full contents. Function bodies are elided. Other files are returned in full.`,
+      ),
+    items: z
+      .array(z.string())
+      .optional()
+      .describe(
+        `For Rust files, return only the source of these items instead of the full contents, by qualified path, e.g. "Greeting::new", "<Greeting as Display>::fmt" or "config::load".`,
+      ),
   })
   .describe(
     `Read multiple files from disk and return their contents. Use this tool to read as many files as would be helpful to answer the user's request.`,
@@ -44,6 +63,26 @@ ${$getNativeToolCallExampleString({
   },
   endsAgentStep,
 })}
+
+Large Rust files can be skimmed with an outline first, and then read item by item:
+${$getNativeToolCallExampleString({
+  toolName,
+  inputSchema,
+  input: {
+    paths: ['src/greeting.rs'],
+    outline: true,
+  },
+  endsAgentStep,
+})}
+${$getNativeToolCallExampleString({
+  toolName,
+  inputSchema,
+  input: {
+    paths: ['src/greeting.rs'],
+    items: ['Greeting::new'],
+  },
+  endsAgentStep,
+})}
 `.trim()
 export const readFilesParams = {
   toolName,
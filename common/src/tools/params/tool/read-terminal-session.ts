@@ -0,0 +1,73 @@
+import z from 'zod/v4'
+
+import { $getNativeToolCallExampleString, jsonToolResultSchema } from '../utils'
+import { sandboxViolationSchema } from './run-terminal-command'
+
+import type { $ToolParams } from '../../constants'
+
+const toolName = 'read_terminal_session'
+const endsAgentStep = true
+const inputSchema = z
+  .object({
+    session: z
+      .string()
+      .min(1, 'Session name cannot be empty')
+      .describe(`Name of the session passed to run_terminal_command.`),
+    input: z
+      .string()
+      .optional()
+      .describe(
+        `Text to write to the running command's stdin, e.g. "y\\n" to answer a prompt. End it with a newline to press Enter.`,
+      ),
+    wait_seconds: z
+      .number()
+      .optional()
+      .describe(
+        `How long to wait for the running command to finish before returning the output so far. Set to -1 to wait until it finishes. Default 10`,
+      ),
+    close: z
+      .boolean()
+      .optional()
+      .describe(
+        `Kill the session's shell and everything running in it, after returning its unread output.`,
+      ),
+  })
+  .describe(
+    `Read the output of a terminal session, send input to the command running in it, or close it.`,
+  )
+const description = `
+Purpose: Follow up on a command run with run_terminal_command in a named session: read output it printed after the tool call returned, wait for it to finish, answer prompts, or close the session.
+
+Output is returned once: each read returns what was printed since the previous one. The result has the session's status: "running" while a command runs, "idle" when it finished (with its exitCode), or "exited" when the shell is gone. Close sessions that run servers or watchers once you no longer need them.
+
+Example:
+${$getNativeToolCallExampleString({
+  toolName,
+  inputSchema,
+  input: { session: 'backend', wait_seconds: 30 },
+  endsAgentStep,
+})}
+`.trim()
+
+export const readTerminalSessionParams = {
+  toolName,
+  endsAgentStep,
+  description,
+  inputSchema,
+  outputSchema: jsonToolResultSchema(
+    z.union([
+      z.object({
+        session: z.string(),
+        status: z.enum(['idle', 'running', 'exited']),
+        stdout: z.string(),
+        stderr: z.string().optional(),
+        exitCode: z.number().optional(),
+        message: z.string().optional(),
+        sandboxViolations: z.array(sandboxViolationSchema).optional(),
+      }),
+      z.object({
+        errorMessage: z.string(),
+      }),
+    ]),
+  ),
+} satisfies $ToolParams
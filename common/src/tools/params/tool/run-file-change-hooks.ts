@@ -5,6 +5,15 @@ import { $getNativeToolCallExampleString, jsonToolResultSchema } from '../utils'
 
 import type { $ToolParams } from '../../constants'
 
+export const fileChangeHookResultSchema = terminalCommandOutputSchema.and(
+  z.object({
+    hookName: z.string(),
+    /** Whether a failure of the hook fails the edit that triggered it */
+    required: z.boolean().optional(),
+    passed: z.boolean().optional(),
+  }),
+)
+
 const toolName = 'run_file_change_hooks'
 const endsAgentStep = true
 const inputSchema = z.object({
@@ -22,7 +31,7 @@ Use cases:
 - Ensure code quality by running configured linters and type checkers
 - Validate that changes don't break the build
 
-The client will run only the hooks whose filePattern matches the provided files.
+The client will run only the hooks whose filePattern matches the provided files. Each result says whether the hook passed and whether it is required.
 
 Example:
 ${$getNativeToolCallExampleString({
@@ -43,11 +52,7 @@ export const runFileChangeHooksParams = {
   outputSchema: jsonToolResultSchema(
     z
       .union([
-        terminalCommandOutputSchema.and(
-          z.object({
-            hookName: z.string(),
-          }),
-        ),
+        fileChangeHookResultSchema,
         z.object({
           errorMessage: z.string(),
         }),
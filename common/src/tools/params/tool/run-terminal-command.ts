@@ -4,14 +4,25 @@ import { $getNativeToolCallExampleString, jsonToolResultSchema } from '../utils'
 
 import type { $ToolParams } from '../../constants'
 
+/** An error in command output caused by the optional command sandbox */
+export const sandboxViolationSchema = z.object({
+  kind: z.enum(['write', 'network']),
+  path: z.string().optional(),
+  line: z.string(),
+  message: z.string(),
+})
+export type SandboxViolation = z.infer<typeof sandboxViolationSchema>
+
 export const terminalCommandOutputSchema = z.union([
   z.object({
     command: z.string(),
+    session: z.string().optional(),
     startingCwd: z.string().optional(),
     message: z.string().optional(),
     stderr: z.string().optional(),
     stdout: z.string().optional(),
     exitCode: z.number().optional(),
+    sandboxViolations: z.array(sandboxViolationSchema).optional(),
   }),
   z.object({
     command: z.string(),
@@ -20,11 +31,13 @@ export const terminalCommandOutputSchema = z.union([
     stderr: z.string().optional(),
     stdoutOmittedForLength: z.literal(true),
     exitCode: z.number().optional(),
+    sandboxViolations: z.array(sandboxViolationSchema).optional(),
   }),
   z.object({
     command: z.string(),
     processId: z.number(),
     backgroundProcessStatus: z.enum(['running', 'completed', 'error']),
+    message: z.string().optional(),
   }),
   z.object({
     command: z.string(),
@@ -108,7 +121,7 @@ const inputSchema = z
       .enum(['SYNC', 'BACKGROUND'])
       .default('SYNC')
       .describe(
-        `Either SYNC (waits, returns output) or BACKGROUND (runs in background). Default SYNC`,
+        `Either SYNC (waits, returns output) or BACKGROUND (runs in background and returns a processId). Default SYNC`,
       ),
     cwd: z
       .string()
@@ -123,6 +136,13 @@ const inputSchema = z
       .describe(
         `Set to -1 for no timeout. Does not apply for BACKGROUND commands. Default 30`,
       ),
+    session: z
+      .string()
+      .min(1, 'Session name cannot be empty')
+      .optional()
+      .describe(
+        `Name of a persistent terminal session to run the command in, e.g. "dev". The session keeps its working directory, environment variables and sourced scripts between commands. It starts in cwd on first use; later, cwd is ignored and \`cd\` moves the session.`,
+      ),
   })
   .describe(
     `Execute a CLI command from the **project root** (different from the user's cwd).`,
@@ -152,6 +172,9 @@ Do:
 Notes:
 - If the user references a specific file, it could be either from their cwd or from the project root. You **must** determine which they are referring to (either infer or ask). Then, you must specify the path relative to the project root (or use the cwd parameter)
 - Commands can succeed without giving any output, e.g. if no type errors were found.
+- Use process_type BACKGROUND for commands that keep running, like dev servers, watchers (\`cargo watch\`) or local databases. It returns a processId right away; read_background_process then polls the output or waits for a line showing the process is ready, and signal_background_process stops it. Background processes keep running across turns and are stopped when the session ends.
+- Each command runs in a fresh shell unless you pass \`session\`. Use a session when commands build on each other, e.g. \`cd\` into a subproject, \`source .envrc\` or activate a toolchain once and then run several commands there. In a session, a command still running at the timeout is not killed: its output so far is returned, and read_terminal_session reads the rest, sends it input or closes the session. Sessions are closed when the run ends.
+- The user may run commands in a sandbox that only lets them write to the project (except .git), the cargo target directory and caches like the cargo registry, and may have no network access. Secrets such as API tokens are removed from their environment. When the sandbox blocked something, the result lists it in sandboxViolations: work within the limits, e.g. build offline, instead of retrying the same command.
 
 ${gitCommitGuidePrompt}
 
@@ -165,6 +188,16 @@ ${$getNativeToolCallExampleString({
   endsAgentStep,
 })}
 
+${$getNativeToolCallExampleString({
+  toolName,
+  inputSchema,
+  input: {
+    command: 'source .venv/bin/activate && cd backend',
+    session: 'backend',
+  },
+  endsAgentStep,
+})}
+
 ${$getNativeToolCallExampleString({
   toolName,
   inputSchema,
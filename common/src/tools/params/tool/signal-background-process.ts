@@ -0,0 +1,64 @@
+import z from 'zod/v4'
+
+import { $getNativeToolCallExampleString, jsonToolResultSchema } from '../utils'
+import { backgroundProcessStatusSchema } from './read-background-process'
+
+import type { $ToolParams } from '../../constants'
+
+const toolName = 'signal_background_process'
+const endsAgentStep = true
+const inputSchema = z
+  .object({
+    process_id: z
+      .number()
+      .int()
+      .describe(`processId returned by run_terminal_command.`),
+    signal: z
+      .enum(['SIGINT', 'SIGTERM', 'SIGKILL', 'SIGHUP'])
+      .optional()
+      .describe(
+        `Signal to send to the process and everything it started. Default SIGTERM`,
+      ),
+    wait_seconds: z
+      .number()
+      .optional()
+      .describe(`How long to wait for the process to exit. Default 5`),
+  })
+  .describe(
+    `Send a signal to a background process started with run_terminal_command, e.g. to stop it.`,
+  )
+const description = `
+Purpose: Stop a process started with run_terminal_command and process_type BACKGROUND, or send it another signal, e.g. SIGHUP to make it reload its configuration.
+
+The signal goes to the whole process group, so servers started by a script or a package manager stop too. SIGTERM asks the process to stop, SIGINT is like pressing Ctrl-C, and SIGKILL stops a process that ignores them. Stop background processes you no longer need; they are also stopped when the session ends.
+
+Example:
+${$getNativeToolCallExampleString({
+  toolName,
+  inputSchema,
+  input: { process_id: 48213 },
+  endsAgentStep,
+})}
+`.trim()
+
+export const signalBackgroundProcessParams = {
+  toolName,
+  endsAgentStep,
+  description,
+  inputSchema,
+  outputSchema: jsonToolResultSchema(
+    z.union([
+      z.object({
+        processId: z.number(),
+        command: z.string(),
+        status: backgroundProcessStatusSchema,
+        exitCode: z.number().optional(),
+        signal: z.string().optional(),
+        message: z.string(),
+      }),
+      z.object({
+        errorMessage: z.string(),
+      }),
+    ]),
+  ),
+} satisfies $ToolParams
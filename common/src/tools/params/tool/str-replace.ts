@@ -1,6 +1,7 @@
 import z from 'zod/v4'
 
 import { $getNativeToolCallExampleString, jsonToolResultSchema } from '../utils'
+import { fileChangeHookResultSchema } from './run-file-change-hooks'
 
 import type { $ToolParams } from '../../constants'
 
@@ -9,11 +10,17 @@ export const updateFileResultSchema = z.union([
     file: z.string(),
     message: z.string(),
     unifiedDiff: z.string(),
+    /** Results of the file change hooks run after the edit */
+    hooks: z.array(fileChangeHookResultSchema).optional(),
+    /** Whether the hooks changed the file again, e.g. a formatter */
+    changedByHooks: z.boolean().optional(),
   }),
   z.object({
     file: z.string(),
     errorMessage: z.string(),
     patch: z.string().optional(),
+    hooks: z.array(fileChangeHookResultSchema).optional(),
+    changedByHooks: z.boolean().optional(),
   }),
 ])
 
@@ -119,6 +119,7 @@ export type AgentTemplate<
   mcpServers: Record<string, MCPConfig>
   toolNames: (ToolName | (string & {}))[]
   spawnableAgents: AgentTemplateType[]
+  strictSyntax?: boolean
 
   spawnerPrompt?: string
   systemPrompt: string
@@ -0,0 +1,41 @@
+import { z } from 'zod/v4'
+
+/**
+ * Zod schema for the Cargo workspace model attached to the project file
+ * context. Mirrors `CargoWorkspace` from the code-map package.
+ */
+export const CargoTargetSchema = z.object({
+  kind: z.enum(['lib', 'bin', 'example', 'test', 'bench', 'build-script']),
+  name: z.string(),
+  path: z.string(),
+})
+
+export const CargoDependencySchema = z.object({
+  name: z.string(),
+  package: z.string(),
+  kind: z.enum(['normal', 'dev', 'build']),
+  optional: z.boolean(),
+  versionReq: z.string().optional(),
+  lockedVersion: z.string().optional(),
+  workspaceMember: z.string().optional(),
+})
+
+export const CargoCrateSchema = z.object({
+  name: z.string(),
+  version: z.string().optional(),
+  manifestPath: z.string(),
+  rootDir: z.string(),
+  targets: z.array(CargoTargetSchema),
+  features: z.record(z.string(), z.array(z.string())),
+  dependencies: z.array(CargoDependencySchema),
+})
+
+export const CargoWorkspaceSchema = z.object({
+  rootManifestPath: z.string(),
+  members: z.array(CargoCrateSchema),
+})
+
+export type CargoTarget = z.infer<typeof CargoTargetSchema>
+export type CargoDependency = z.infer<typeof CargoDependencySchema>
+export type CargoCrate = z.infer<typeof CargoCrateSchema>
+export type CargoWorkspace = z.infer<typeof CargoWorkspaceSchema>
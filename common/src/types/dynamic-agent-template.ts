@@ -181,6 +181,7 @@ export const DynamicAgentDefinitionSchema = z.object({
     .array(z.string())
     .optional()
     .default(() => []),
+  strictSyntax: z.boolean().optional(),
 
   // Input and output
   inputSchema: InputSchemaObjectSchema,
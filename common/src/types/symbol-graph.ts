@@ -0,0 +1,24 @@
+import { z } from 'zod/v4'
+
+/**
+ * Zod schema for the definition/reference graph attached to the project file
+ * context. Mirrors `SymbolGraph` from the code-map package.
+ */
+export const SymbolDefinitionSchema = z.object({
+  line: z.number(),
+  signature: z.string(),
+})
+
+export const SymbolGraphSchema = z.object({
+  definitions: z.record(
+    z.string(),
+    z.record(z.string(), SymbolDefinitionSchema),
+  ),
+  references: z.record(
+    z.string(),
+    z.record(z.string(), z.record(z.string(), z.number())),
+  ),
+})
+
+export type SymbolDefinition = z.infer<typeof SymbolDefinitionSchema>
+export type SymbolGraph = z.infer<typeof SymbolGraphSchema>
@@ -3,8 +3,13 @@ import * as path from 'path'
 
 import { z } from 'zod/v4'
 
+import { CargoWorkspaceSchema } from '../types/cargo-workspace'
+import { SymbolGraphSchema } from '../types/symbol-graph'
+
+import type { CargoWorkspace } from '../types/cargo-workspace'
 import type { LevelCodeFileSystem } from '../types/filesystem'
 import type { SkillsMap } from '../types/skill'
+import type { SymbolGraph } from '../types/symbol-graph'
 
 export const FileTreeNodeSchema: z.ZodType<FileTreeNode> = z.object({
   name: z.string(),
@@ -64,6 +69,8 @@ export const ProjectFileContextSchema = z.object({
   tokenCallers: z
     .record(z.string(), z.record(z.string(), z.array(z.string())))
     .optional(),
+  cargoWorkspace: CargoWorkspaceSchema.optional(),
+  symbolGraph: SymbolGraphSchema.optional(),
   knowledgeFiles: z.record(z.string(), z.string()),
   userKnowledgeFiles: z.record(z.string(), z.string()).optional(),
   agentTemplates: z.record(z.string(), z.any()).default(() => ({})),
@@ -93,6 +100,8 @@ export type ProjectFileContext = {
   fileTree: FileTreeNode[]
   fileTokenScores: Record<string, Record<string, number>>
   tokenCallers?: Record<string, Record<string, string[]>>
+  cargoWorkspace?: CargoWorkspace
+  symbolGraph?: SymbolGraph
   knowledgeFiles: Record<string, string>
   userKnowledgeFiles?: Record<string, string>
   agentTemplates: Record<string, any>
@@ -0,0 +1,109 @@
+import { findSyntaxIssues } from '@levelcode/code-map/syntax'
+import { beforeAll, describe, expect, it } from 'bun:test'
+
+import { checkEditSyntax } from '../check-edit-syntax'
+
+import type { Logger } from '@levelcode/common/types/contracts/logger'
+
+const logger: Logger = {
+  debug: () => {},
+  info: () => {},
+  warn: () => {},
+  error: () => {},
+}
+
+const valid = 'fn main() {\n    println!("hi");\n}\n'
+const extraBrace = 'fn main() {\n    println!("hi");\n}\n}\n'
+
+function edited(content: string) {
+  return {
+    tool: 'str_replace' as const,
+    path: 'src/main.rs',
+    content,
+    messages: [],
+  }
+}
+
+describe('checkEditSyntax', () => {
+  let hasRustGrammar = false
+
+  beforeAll(async () => {
+    hasRustGrammar = (await findSyntaxIssues('main.rs', valid)) !== undefined
+  })
+
+  it('should reject edits that introduce syntax errors in strict mode', async () => {
+    if (!hasRustGrammar) {
+      console.log('⚠️  Skipping test - Rust grammar not available')
+      return
+    }
+
+    const result = await checkEditSyntax({
+      result: edited(extraBrace),
+      initialContentPromise: Promise.resolve(valid),
+      strict: true,
+      logger,
+    })
+
+    expect(result).toEqual({
+      tool: 'str_replace',
+      path: 'src/main.rs',
+      error: expect.stringContaining(
+        'The edit was rejected because it introduces syntax errors in src/main.rs:\nLine 4',
+      ),
+    })
+  })
+
+  it('should report syntax errors with the result otherwise', async () => {
+    if (!hasRustGrammar) {
+      console.log('⚠️  Skipping test - Rust grammar not available')
+      return
+    }
+
+    const result = await checkEditSyntax({
+      result: edited(extraBrace),
+      initialContentPromise: Promise.resolve(valid),
+      strict: false,
+      logger,
+    })
+
+    expect(result).toEqual({
+      ...edited(extraBrace),
+      syntaxErrors: expect.stringContaining(
+        'The edit introduced syntax errors in src/main.rs',
+      ),
+    })
+  })
+
+  it('should ignore syntax errors the file already had', async () => {
+    if (!hasRustGrammar) {
+      console.log('⚠️  Skipping test - Rust grammar not available')
+      return
+    }
+    const result = edited(`// Entry point\n${extraBrace}`)
+
+    expect(
+      await checkEditSyntax({
+        result,
+        initialContentPromise: Promise.resolve(extraBrace),
+        strict: true,
+        logger,
+      }),
+    ).toBe(result)
+  })
+
+  it('should pass through failed edits and files without a grammar', async () => {
+    const failed = { tool: 'str_replace' as const, path: 'a.rs', error: 'x' }
+    const text = { ...edited('}}}'), path: 'notes.txt' }
+
+    for (const result of [failed, text]) {
+      expect(
+        await checkEditSyntax({
+          result,
+          initialContentPromise: Promise.resolve(''),
+          strict: true,
+          logger,
+        }),
+      ).toBe(result)
+    }
+  })
+})
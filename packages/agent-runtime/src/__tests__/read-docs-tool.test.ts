@@ -385,4 +385,57 @@ describe('read_docs tool with researcher agent (via web API facade)', () => {
       mockCreditsUsed,
     )
   }, 10000)
+
+  test('should use local crate docs from the client without the web API', async () => {
+    const localDocumentation = '# serde_json 1.0.117\n\nJSON for serde'
+    const spy = spyOn(webApi, 'callDocsSearchAPI').mockResolvedValue({
+      documentation: 'Web documentation',
+      creditsUsed: 2,
+    })
+    const requestedTools: string[] = []
+    runAgentStepBaseParams.requestToolCall = async ({ toolName }) => {
+      requestedTools.push(toolName)
+      return {
+        output: [
+          { type: 'json', value: { documentation: localDocumentation } },
+        ],
+      }
+    }
+
+    mockAgentStream([
+      createToolCallChunk('read_docs', {
+        libraryTitle: 'serde_json',
+        topic: 'Value',
+      }),
+      createToolCallChunk('end_turn', {}),
+    ])
+
+    const sessionState = getInitialSessionState(mockFileContextWithAgents)
+    const agentState = {
+      ...sessionState.mainAgentState,
+      agentType: 'researcher' as const,
+    }
+    const { agentTemplates } = assembleLocalAgentTemplates({
+      ...agentRuntimeImpl,
+      fileContext: mockFileContextWithAgents,
+    })
+
+    const { agentState: newAgentState } = await runAgentStep({
+      ...runAgentStepBaseParams,
+      fileContext: mockFileContextWithAgents,
+      localAgentTemplates: agentTemplates,
+      agentTemplate: agentTemplates['researcher'],
+      agentState,
+      prompt: 'Get serde_json documentation',
+    })
+
+    expect(requestedTools).toContain('read_docs')
+    expect(spy).not.toHaveBeenCalled()
+    const toolMsgs = newAgentState.messageHistory.filter(
+      (m) => m.role === 'tool' && m.toolName === 'read_docs',
+    )
+    expect(JSON.stringify(toolMsgs[toolMsgs.length - 1].content)).toContain(
+      'JSON for serde',
+    )
+  }, 10000)
 })
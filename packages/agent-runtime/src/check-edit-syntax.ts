@@ -0,0 +1,77 @@
+import {
+  findNewSyntaxIssues,
+  formatSyntaxIssue,
+} from '@levelcode/code-map/syntax'
+import { getErrorObject } from '@levelcode/common/util/error'
+
+import type { Logger } from '@levelcode/common/types/contracts/logger'
+
+const MAX_REPORTED_ISSUES = 10
+
+type EditResult<T extends string> = { tool: T; path: string } & (
+  | {
+      content: string
+      patch?: string
+      messages: string[]
+      syntaxErrors?: string
+    }
+  | { error: string }
+)
+
+/**
+ * Re-parses an edited file and records the syntax errors the edit introduced
+ * in `syntaxErrors`, which are reported with the tool result. In strict mode,
+ * such an edit is rejected instead. Files without a known grammar are not
+ * checked.
+ */
+export async function checkEditSyntax<T extends string>(params: {
+  result: EditResult<T>
+  initialContentPromise: Promise<string | null>
+  strict: boolean
+  logger: Logger
+}): Promise<EditResult<T>> {
+  const { result, initialContentPromise, strict, logger } = params
+  if ('error' in result) {
+    return result
+  }
+
+  let issues: Awaited<ReturnType<typeof findNewSyntaxIssues>>
+  try {
+    issues = await findNewSyntaxIssues(
+      result.path,
+      await initialContentPromise,
+      result.content,
+    )
+  } catch (error) {
+    logger.warn(
+      { path: result.path, error: getErrorObject(error) },
+      'checkEditSyntax: Failed to parse the edited file',
+    )
+    return result
+  }
+  if (issues.length === 0) {
+    return result
+  }
+
+  const omitted = issues.length - MAX_REPORTED_ISSUES
+  const issueList = [
+    ...issues.slice(0, MAX_REPORTED_ISSUES).map(formatSyntaxIssue),
+    ...(omitted > 0 ? [`and ${omitted} more`] : []),
+  ].join('\n')
+  logger.debug(
+    { path: result.path, issues, strict },
+    `checkEditSyntax: Edit introduced ${issues.length} syntax error(s)`,
+  )
+
+  if (strict) {
+    return {
+      tool: result.tool,
+      path: result.path,
+      error: `The edit was rejected because it introduces syntax errors in ${result.path}:\n${issueList}\nThe file was not changed. Fix the edit and try again.`,
+    }
+  }
+  return {
+    ...result,
+    syntaxErrors: `The edit introduced syntax errors in ${result.path}. Fix them before continuing:\n${issueList}`,
+  }
+}
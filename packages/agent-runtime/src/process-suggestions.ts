@@ -0,0 +1,89 @@
+import { applyCargoSuggestions } from '@levelcode/code-map/rust/diagnostics'
+import { createPatch } from 'diff'
+import { posix } from 'path'
+
+import type { SpanReplacement } from '@levelcode/code-map/rust/diagnostics'
+import type { Logger } from '@levelcode/common/types/contracts/logger'
+
+export async function processSuggestions(params: {
+  path: string
+  /** `file` is the file a suggestion was reported for, checked against `path` */
+  suggestions: (SpanReplacement & { file?: string })[]
+  initialContentPromise: Promise<string | null>
+  logger: Logger
+}): Promise<
+  | {
+      tool: 'apply_suggestions'
+      path: string
+      content: string
+      patch: string
+      messages: string[]
+    }
+  | { tool: 'apply_suggestions'; path: string; error: string }
+> {
+  const { path, suggestions, initialContentPromise, logger } = params
+  const initialContent = await initialContentPromise
+  if (initialContent === null) {
+    return {
+      tool: 'apply_suggestions',
+      path,
+      error:
+        'The file does not exist, skipping. Run cargo_diagnostics again for up-to-date suggestions.',
+    }
+  }
+
+  const otherFile = suggestions.find(
+    ({ file }) => file && posix.normalize(file) !== posix.normalize(path),
+  )?.file
+  if (otherFile) {
+    return {
+      tool: 'apply_suggestions',
+      path,
+      error: `A suggestion is for ${otherFile}, not ${path}. Apply the suggestions of each file in a separate call.`,
+    }
+  }
+
+  const lineEnding = initialContent.includes('\r\n') ? '\r\n' : '\n'
+  // rustc's spans are unaffected, as columns end before the line ending
+  const result = applyCargoSuggestions(
+    initialContent.replace(/\r\n/g, '\n'),
+    suggestions,
+  )
+  if ('error' in result) {
+    return { tool: 'apply_suggestions', path, error: result.error }
+  }
+
+  const { messages } = result
+  const currentContent = result.content.replaceAll('\n', lineEnding)
+  if (initialContent === currentContent) {
+    logger.debug(
+      { path, initialContent },
+      `processSuggestions: No change to ${path}`,
+    )
+    return {
+      tool: 'apply_suggestions',
+      path,
+      error: [...messages, 'No change to the file'].join('\n\n'),
+    }
+  }
+
+  let patch = createPatch(path, initialContent, currentContent)
+  const lines = patch.split('\n')
+  const hunkStartIndex = lines.findIndex((line) => line.startsWith('@@'))
+  if (hunkStartIndex !== -1) {
+    patch = lines.slice(hunkStartIndex).join('\n')
+  }
+
+  logger.debug(
+    { path, newContent: currentContent, patch, messages },
+    `processSuggestions: Updated file ${path}`,
+  )
+
+  return {
+    tool: 'apply_suggestions',
+    path,
+    content: currentContent,
+    patch,
+    messages,
+  }
+}
@@ -0,0 +1,76 @@
+import { editRustSymbols } from '@levelcode/code-map/rust/edit'
+import { createPatch } from 'diff'
+
+import type { RustSymbolEdit } from '@levelcode/code-map/rust/edit'
+import type { Logger } from '@levelcode/common/types/contracts/logger'
+
+export async function processSymbolEdit(params: {
+  path: string
+  edits: RustSymbolEdit[]
+  initialContentPromise: Promise<string | null>
+  logger: Logger
+}): Promise<
+  | {
+      tool: 'edit_symbol'
+      path: string
+      content: string
+      patch: string
+      messages: string[]
+    }
+  | { tool: 'edit_symbol'; path: string; error: string }
+> {
+  const { path, edits, initialContentPromise, logger } = params
+  const initialContent = await initialContentPromise
+  if (initialContent === null) {
+    return {
+      tool: 'edit_symbol',
+      path,
+      error:
+        'The file does not exist, skipping. Please use the write_file tool to create the file.',
+    }
+  }
+
+  const lineEnding = initialContent.includes('\r\n') ? '\r\n' : '\n'
+  const result = await editRustSymbols(
+    path,
+    initialContent.replace(/\r\n/g, '\n'),
+    edits,
+  )
+  if ('error' in result) {
+    return { tool: 'edit_symbol', path, error: result.error }
+  }
+
+  const { messages } = result
+  const currentContent = result.content.replaceAll('\n', lineEnding)
+  if (initialContent === currentContent) {
+    logger.debug(
+      { path, initialContent },
+      `processSymbolEdit: No change to ${path}`,
+    )
+    return {
+      tool: 'edit_symbol',
+      path,
+      error: [...messages, 'No change to the file'].join('\n\n'),
+    }
+  }
+
+  let patch = createPatch(path, initialContent, currentContent)
+  const lines = patch.split('\n')
+  const hunkStartIndex = lines.findIndex((line) => line.startsWith('@@'))
+  if (hunkStartIndex !== -1) {
+    patch = lines.slice(hunkStartIndex).join('\n')
+  }
+
+  logger.debug(
+    { path, newContent: currentContent, patch, messages },
+    `processSymbolEdit: Updated file ${path}`,
+  )
+
+  return {
+    tool: 'edit_symbol',
+    path,
+    content: currentContent,
+    patch,
+    messages,
+  }
+}
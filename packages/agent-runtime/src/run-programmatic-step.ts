@@ -191,6 +191,7 @@ export async function runProgrammaticStep(
     fileChangeErrors: [],
     fileChanges: [],
     firstFileProcessed: false,
+    pathsChangedByHooks: new Set(),
   }
   const agentContext = cloneDeep(agentState.agentContext)
   const _sendSubagentChunk = (data: {
@@ -1,3 +1,8 @@
+import {
+  findRepoMapSeeds,
+  rankSymbols,
+  renderRepoMap,
+} from '@levelcode/code-map/repo-map'
 import {
   flattenTree,
   getLastReadFilePaths,
@@ -7,6 +12,7 @@ import { truncateString } from '@levelcode/common/util/string'
 import { closeXml } from '@levelcode/common/util/xml'
 
 import { truncateFileTreeBasedOnTokenBudget } from './truncate-file-tree'
+import { countTokens } from '../util/token-counter'
 
 import type { Logger } from '@levelcode/common/types/contracts/logger'
 import type { ProjectFileContext } from '@levelcode/common/util/file'
@@ -109,17 +115,43 @@ export const additionalSystemPrompts = {
   compact: compactPrompt,
 } as const
 
+/** Share of the file tree budget spent on the repository map */
+const REPO_MAP_BUDGET_SHARE = 0.2
+const MAX_REPO_MAP_TOKENS = 8_000
+
 export const getProjectFileTreePrompt = (params: {
   fileContext: ProjectFileContext
   fileTreeTokenBudget: number
   mode: 'search' | 'agent'
+  /** Latest user message; the repository map is ranked towards it */
+  userInput?: string
   logger: Logger
 }) => {
-  const { fileContext, fileTreeTokenBudget, mode, logger } = params
+  const { fileContext, fileTreeTokenBudget, mode, userInput, logger } = params
   const { projectRoot } = fileContext
+  const fullCargoWorkspacePrompt = getCargoWorkspacePrompt(fileContext)
+  const cargoWorkspaceTokens = fullCargoWorkspacePrompt
+    ? countTokens(fullCargoWorkspacePrompt)
+    : 0
+  // Workspaces too large for the whole budget are left out
+  const cargoWorkspacePrompt =
+    cargoWorkspaceTokens <= fileTreeTokenBudget ? fullCargoWorkspacePrompt : ''
+  const remainingBudget =
+    fileTreeTokenBudget - (cargoWorkspacePrompt ? cargoWorkspaceTokens : 0)
+  const repoMapPrompt = getRepoMapPrompt({
+    fileContext,
+    userInput,
+    tokenBudget: Math.min(
+      MAX_REPO_MAP_TOKENS,
+      Math.floor(remainingBudget * REPO_MAP_BUDGET_SHARE),
+    ),
+  })
   const { printedTree, truncationLevel } = truncateFileTreeBasedOnTokenBudget({
     fileContext,
-    tokenBudget: Math.max(0, fileTreeTokenBudget),
+    tokenBudget: Math.max(
+      0,
+      remainingBudget - (repoMapPrompt ? countTokens(repoMapPrompt) : 0),
+    ),
     logger,
   })
 
@@ -155,9 +187,78 @@ ${
 ${printedTree}
 ${closeXml('project_file_tree')}
 ${truncationNote}
+${repoMapPrompt}${cargoWorkspacePrompt}
 `.trim()
 }
 
+/**
+ * Renders the most important definitions of the project, ranked with
+ * PageRank over the definition/reference graph and personalized towards the
+ * files and identifiers the user's message mentions.
+ */
+export const getRepoMapPrompt = (params: {
+  fileContext: ProjectFileContext
+  userInput?: string
+  tokenBudget: number
+}) => {
+  const { fileContext, userInput, tokenBudget } = params
+  const { symbolGraph } = fileContext
+  if (!symbolGraph || tokenBudget <= 0) {
+    return ''
+  }
+  const seeds = userInput ? findRepoMapSeeds(userInput, symbolGraph) : undefined
+  const repoMap = renderRepoMap(
+    rankSymbols(symbolGraph, seeds),
+    tokenBudget,
+    countTokens,
+  )
+  if (!repoMap) {
+    return ''
+  }
+
+  return `
+Repository map: the most important definitions in the project${userInput ? ' for the latest user message' : ''}, ranked by how often other code references them. Each file lists \`line: signature\` for its top definitions:
+<repo_map>
+${repoMap}
+${closeXml('repo_map')}
+`
+}
+
+export const getCargoWorkspacePrompt = (fileContext: ProjectFileContext) => {
+  const { cargoWorkspace } = fileContext
+  if (!cargoWorkspace || cargoWorkspace.members.length === 0) {
+    return ''
+  }
+  const crates = cargoWorkspace.members.map((crate) => {
+    const targets = crate.targets
+      .map((target) =>
+        target.kind === 'lib' ? 'lib' : `${target.kind}:${target.name}`,
+      )
+      .join(', ')
+    const workspaceDeps = [
+      ...new Set(
+        crate.dependencies.flatMap((dep) =>
+          dep.workspaceMember ? [dep.workspaceMember] : [],
+        ),
+      ),
+    ]
+    return [
+      `- ${crate.name} (${crate.rootDir || '.'})`,
+      targets && ` targets: ${targets}`,
+      workspaceDeps.length > 0 && ` depends on: ${workspaceDeps.join(', ')}`,
+    ]
+      .filter(Boolean)
+      .join(';')
+  })
+
+  return `
+Cargo workspace (${cargoWorkspace.rootManifestPath}). Each file belongs to the member crate with the deepest directory containing it; edit the crate that owns the code, and remember that changes to a crate affect the crates that depend on it:
+<cargo_workspace>
+${crates.join('\n')}
+${closeXml('cargo_workspace')}
+`
+}
+
 const windowsNote = `
 Note: many commands in the terminal are different on Windows.
 For example, the mkdir command is \`mkdir\` instead of \`mkdir -p\`. Instead of grep, use \`findstr\`. Instead of \`ls\` use \`dir\` to list files. Instead of \`mv\` use \`move\`. Instead of \`rm\` use \`del\`. Instead of \`cp\` use \`copy\`. Unless the user is in Powershell, in which case you should use the Powershell commands instead.
@@ -90,6 +90,7 @@ export async function formatPrompt(
         fileContext,
         fileTreeTokenBudget: 2_500,
         mode: 'agent',
+        userInput: lastUserInput,
         logger,
       }),
     [PLACEHOLDER.FILE_TREE_PROMPT]: () =>
@@ -97,6 +98,7 @@ export async function formatPrompt(
         fileContext,
         fileTreeTokenBudget: 10_000,
         mode: 'agent',
+        userInput: lastUserInput,
         logger,
       }),
     [PLACEHOLDER.FILE_TREE_PROMPT_LARGE]: () =>
@@ -104,6 +106,7 @@ export async function formatPrompt(
         fileContext,
         fileTreeTokenBudget: 190_000,
         mode: 'search',
+        userInput: lastUserInput,
         logger,
       }),
     [PLACEHOLDER.GIT_CHANGES_PROMPT]: () => getGitChangesPrompt(fileContext),
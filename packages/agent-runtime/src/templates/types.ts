@@ -41,6 +41,7 @@ export const baseAgentToolNames: ToolName[] = [
   'create_plan',
   'run_terminal_command',
   'str_replace',
+  'edit_symbol',
   'write_file',
   'spawn_agents',
   'add_subgoal',
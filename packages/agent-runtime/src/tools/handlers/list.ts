@@ -1,23 +1,39 @@
 import { handleAddMessage } from './tool/add-message'
 import { handleAddSubgoal } from './tool/add-subgoal'
+import { handleApplySuggestions } from './tool/apply-suggestions'
 import { handleAskUser } from './tool/ask-user'
 import { handleBrowserLogs } from './tool/browser-logs'
+import { handleCargoDiagnostics } from './tool/cargo-diagnostics'
 import { handleCodeSearch } from './tool/code-search'
 import { handleCreatePlan } from './tool/create-plan'
+import { handleEditSymbol } from './tool/edit-symbol'
 import { handleEndTurn } from './tool/end-turn'
+import { handleFindAffectedTests } from './tool/find-affected-tests'
+import { handleFindDefinition } from './tool/find-definition'
 import { handleFindFiles } from './tool/find-files'
+import { handleFindReferences } from './tool/find-references'
+import { handleFindTraitImpls } from './tool/find-trait-impls'
 import { handleGlob } from './tool/glob'
 import { handleListDirectory } from './tool/list-directory'
 import { handleLookupAgentInfo } from './tool/lookup-agent-info'
+import { handleLspDefinition } from './tool/lsp-definition'
+import { handleLspDiagnostics } from './tool/lsp-diagnostics'
+import { handleLspHover } from './tool/lsp-hover'
+import { handleLspReferences } from './tool/lsp-references'
+import { handleLspRename } from './tool/lsp-rename'
+import { handleLspWorkspaceSymbols } from './tool/lsp-workspace-symbols'
 import { handleProposeStrReplace } from './tool/propose-str-replace'
 import { handleProposeWriteFile } from './tool/propose-write-file'
+import { handleReadBackgroundProcess } from './tool/read-background-process'
 import { handleReadDocs } from './tool/read-docs'
 import { handleReadFiles } from './tool/read-files'
 import { handleReadSubtree } from './tool/read-subtree'
+import { handleReadTerminalSession } from './tool/read-terminal-session'
 import { handleRunFileChangeHooks } from './tool/run-file-change-hooks'
 import { handleRunTerminalCommand } from './tool/run-terminal-command'
 import { handleSetMessages } from './tool/set-messages'
 import { handleSetOutput } from './tool/set-output'
+import { handleSignalBackgroundProcess } from './tool/signal-background-process'
 import { handleSkill } from './tool/skill'
 import { handleSpawnAgentInline } from './tool/spawn-agent-inline'
 import { handleSpawnAgents } from './tool/spawn-agents'
@@ -52,24 +68,40 @@ import type { ToolName } from '@levelcode/common/tools/constants'
 export const levelcodeToolHandlers = {
   add_message: handleAddMessage,
   add_subgoal: handleAddSubgoal,
+  apply_suggestions: handleApplySuggestions,
   ask_user: handleAskUser,
   browser_logs: handleBrowserLogs,
+  cargo_diagnostics: handleCargoDiagnostics,
   code_search: handleCodeSearch,
   create_plan: handleCreatePlan,
+  edit_symbol: handleEditSymbol,
   end_turn: handleEndTurn,
+  find_affected_tests: handleFindAffectedTests,
+  find_definition: handleFindDefinition,
   find_files: handleFindFiles,
+  find_references: handleFindReferences,
+  find_trait_impls: handleFindTraitImpls,
   glob: handleGlob,
   list_directory: handleListDirectory,
   lookup_agent_info: handleLookupAgentInfo,
+  lsp_definition: handleLspDefinition,
+  lsp_diagnostics: handleLspDiagnostics,
+  lsp_hover: handleLspHover,
+  lsp_references: handleLspReferences,
+  lsp_rename: handleLspRename,
+  lsp_workspace_symbols: handleLspWorkspaceSymbols,
   propose_str_replace: handleProposeStrReplace,
   propose_write_file: handleProposeWriteFile,
+  read_background_process: handleReadBackgroundProcess,
   read_docs: handleReadDocs,
   read_files: handleReadFiles,
   read_subtree: handleReadSubtree,
+  read_terminal_session: handleReadTerminalSession,
   run_file_change_hooks: handleRunFileChangeHooks,
   run_terminal_command: handleRunTerminalCommand,
   set_messages: handleSetMessages,
   set_output: handleSetOutput,
+  signal_background_process: handleSignalBackgroundProcess,
   skill: handleSkill,
   spawn_agents: handleSpawnAgents,
   spawn_agent_inline: handleSpawnAgentInline,
@@ -0,0 +1,85 @@
+import { describe, expect, it, mock } from 'bun:test'
+
+import { handleStrReplace } from '../str-replace'
+
+import type { FileProcessingState } from '../write-file'
+import type { LevelCodeToolOutput } from '@levelcode/common/tools/list'
+import type { Logger } from '@levelcode/common/types/contracts/logger'
+
+const logger: Logger = {
+  debug: () => {},
+  info: () => {},
+  warn: () => {},
+  error: () => {},
+}
+
+function strReplaceCall(toolCallId: string, old: string, replacement: string) {
+  return {
+    toolCallId,
+    toolName: 'str_replace' as const,
+    input: {
+      path: 'notes.txt',
+      replacements: [{ old, new: replacement, allowMultiple: false }],
+    },
+  }
+}
+
+describe('handleStrReplace', () => {
+  it('should apply edits after a hook changed the file to its new content', async () => {
+    let content = 'a\nb\n'
+    const fileProcessingState: FileProcessingState = {
+      promisesByPath: {},
+      allPromises: [],
+      fileChangeErrors: [],
+      fileChanges: [],
+      firstFileProcessed: false,
+      pathsChangedByHooks: new Set(),
+    }
+    const params = {
+      previousToolCallFinished: Promise.resolve(),
+      agentTemplate: {},
+      fileProcessingState,
+      logger,
+      requestOptionalFile: mock(async () => content),
+      requestClientToolCall: mock(
+        async ({ toolCallId }: { toolCallId: string }) => {
+          if (toolCallId === 'first') {
+            // A formatter hook adds a blank line after the edit
+            content = 'A\n\nb\n'
+          }
+          return [
+            {
+              type: 'json',
+              value: {
+                file: 'notes.txt',
+                message: 'Updated file',
+                unifiedDiff: '',
+                ...(toolCallId === 'first' && { changedByHooks: true }),
+              },
+            },
+          ] as LevelCodeToolOutput<'str_replace'>
+        },
+      ),
+      writeToClient: () => {},
+    }
+
+    const first = handleStrReplace({
+      ...params,
+      toolCall: strReplaceCall('first', 'a', 'A'),
+    })
+    const second = handleStrReplace({
+      ...params,
+      previousToolCallFinished: first.then(() => {}),
+      toolCall: strReplaceCall('second', 'b', 'B'),
+    })
+    await Promise.all([first, second])
+
+    const secondChange = fileProcessingState.fileChanges.find(
+      ({ toolCallId }) => toolCallId === 'second',
+    )
+    expect(secondChange?.content).toBe('A\n\nB\n')
+    expect(fileProcessingState.pathsChangedByHooks).toEqual(
+      new Set(['notes.txt']),
+    )
+  })
+})
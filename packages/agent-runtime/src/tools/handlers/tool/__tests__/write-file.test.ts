@@ -33,6 +33,7 @@ describe('handleWriteFile', () => {
       fileChangeErrors: [],
       fileChanges: [],
       firstFileProcessed: false,
+      pathsChangedByHooks: new Set(),
     }
 
     mockAgentState = getInitialAgentState()
@@ -62,6 +63,7 @@ describe('handleWriteFile', () => {
         previousToolCallFinished: Promise.resolve(),
         toolCall,
         agentState: mockAgentState,
+        agentTemplate: {},
         clientSessionId: 'test-client-session',
         fileProcessingState: mockFileProcessingState,
         fingerprintId: 'test-fingerprint',
@@ -103,6 +105,7 @@ describe('handleWriteFile', () => {
         previousToolCallFinished: Promise.resolve(),
         toolCall,
         agentState: mockAgentState,
+        agentTemplate: {},
         clientSessionId: 'test-client-session',
         fileProcessingState: mockFileProcessingState,
         fingerprintId: 'test-fingerprint',
@@ -153,6 +156,7 @@ describe('handleWriteFile', () => {
         previousToolCallFinished: Promise.resolve(),
         toolCall,
         agentState: mockAgentState,
+        agentTemplate: {},
         clientSessionId: 'test-client-session',
         fileProcessingState: mockFileProcessingState,
         fingerprintId: 'test-fingerprint',
@@ -0,0 +1,19 @@
+import { handleFileEdit } from './file-edit-utils'
+import { processSuggestions } from '../../../process-suggestions'
+
+import type { LevelCodeToolHandlerFunction } from '../handler-function-type'
+import type { FileEditHandlerParams } from './file-edit-utils'
+
+export const handleApplySuggestions = (async (
+  params: FileEditHandlerParams<'apply_suggestions'>,
+) => {
+  const { path, suggestions } = params.toolCall.input
+  return handleFileEdit(params, (initialContentPromise) =>
+    processSuggestions({
+      path,
+      suggestions,
+      initialContentPromise,
+      logger: params.logger,
+    }),
+  )
+}) satisfies LevelCodeToolHandlerFunction<'apply_suggestions'>
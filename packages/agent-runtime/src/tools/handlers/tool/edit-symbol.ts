@@ -0,0 +1,19 @@
+import { handleFileEdit } from './file-edit-utils'
+import { processSymbolEdit } from '../../../process-symbol-edit'
+
+import type { LevelCodeToolHandlerFunction } from '../handler-function-type'
+import type { FileEditHandlerParams } from './file-edit-utils'
+
+export const handleEditSymbol = (async (
+  params: FileEditHandlerParams<'edit_symbol'>,
+) => {
+  const { path, edits } = params.toolCall.input
+  return handleFileEdit(params, (initialContentPromise) =>
+    processSymbolEdit({
+      path,
+      edits,
+      initialContentPromise,
+      logger: params.logger,
+    }),
+  )
+}) satisfies LevelCodeToolHandlerFunction<'edit_symbol'>
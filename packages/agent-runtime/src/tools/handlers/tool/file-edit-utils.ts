@@ -0,0 +1,136 @@
+import { postStreamProcessing } from './write-file'
+import { checkEditSyntax } from '../../../check-edit-syntax'
+
+import type { FileProcessingState } from './write-file'
+import type {
+  ClientToolCall,
+  LevelCodeToolCall,
+  LevelCodeToolOutput,
+} from '@levelcode/common/tools/list'
+import type { AgentTemplate } from '@levelcode/common/types/agent-template'
+import type { RequestOptionalFileFn } from '@levelcode/common/types/contracts/client'
+import type { Logger } from '@levelcode/common/types/contracts/logger'
+import type { ParamsExcluding } from '@levelcode/common/types/function-params'
+
+/** Tools that edit a file by changing parts of its current content */
+type FileEditTool = 'str_replace' | 'edit_symbol' | 'apply_suggestions'
+
+type FileEditResult<T extends FileEditTool> = { tool: T; path: string } & (
+  | { content: string; patch?: string; messages: string[] }
+  | { error: string }
+)
+
+export type FileEditHandlerParams<T extends FileEditTool> = {
+  previousToolCallFinished: Promise<void>
+  toolCall: LevelCodeToolCall<T>
+
+  agentTemplate: Pick<AgentTemplate, 'strictSyntax'>
+  fileProcessingState: FileProcessingState
+  logger: Logger
+
+  requestClientToolCall: (
+    toolCall: ClientToolCall<T>,
+  ) => Promise<LevelCodeToolOutput<T>>
+  writeToClient: (chunk: string) => void
+
+  requestOptionalFile: RequestOptionalFileFn
+} & ParamsExcluding<RequestOptionalFileFn, 'filePath'>
+
+/**
+ * Handles a tool call that edits a file: applies `processEdit` to the content
+ * left by the previous edit of the file in this step, checks the result for
+ * new syntax errors and sends the change to the client. Once file change
+ * hooks changed the file, edits are applied to its content on disk instead.
+ */
+export async function handleFileEdit<T extends FileEditTool>(
+  params: FileEditHandlerParams<T>,
+  processEdit: (
+    initialContentPromise: Promise<string | null>,
+  ) => Promise<FileEditResult<T>>,
+): Promise<{ output: LevelCodeToolOutput<T> }> {
+  const {
+    previousToolCallFinished,
+    toolCall,
+
+    agentTemplate,
+    fileProcessingState,
+    logger,
+
+    requestClientToolCall,
+    requestOptionalFile,
+    writeToClient,
+  } = params
+  const { path } = toolCall.input
+  const toolName = toolCall.toolName as T
+
+  if (!fileProcessingState.promisesByPath[path]) {
+    fileProcessingState.promisesByPath[path] = []
+  }
+
+  const previousPromises = fileProcessingState.promisesByPath[path]
+  const previousEdit = previousPromises[previousPromises.length - 1]
+
+  const latestContentPromise = previousEdit
+    ? previousEdit.then((maybeResult) =>
+        maybeResult && 'content' in maybeResult
+          ? maybeResult.content
+          : requestOptionalFile({ ...params, filePath: path }),
+      )
+    : requestOptionalFile({ ...params, filePath: path })
+
+  const processFrom = (initialContentPromise: Promise<string | null>) =>
+    processEdit(initialContentPromise)
+      .then((result) =>
+        checkEditSyntax({
+          result,
+          initialContentPromise,
+          strict: agentTemplate.strictSyntax ?? false,
+          logger,
+        }),
+      )
+      .catch((error: any) => {
+        logger.error(error, `Error processing ${toolName} block`)
+        return {
+          tool: toolName,
+          path,
+          error: `Unknown error: Failed to process the ${toolName} block.`,
+        }
+      })
+      .then((fileProcessingResult) => ({
+        ...fileProcessingResult,
+        toolCallId: toolCall.toolCallId,
+      }))
+
+  let newPromise = processFrom(latestContentPromise)
+  fileProcessingState.promisesByPath[path].push(newPromise)
+  fileProcessingState.allPromises.push(newPromise)
+
+  await previousToolCallFinished
+
+  if (fileProcessingState.pathsChangedByHooks.has(path)) {
+    const stalePromise = newPromise
+    newPromise = processFrom(requestOptionalFile({ ...params, filePath: path }))
+    for (const promises of [
+      fileProcessingState.promisesByPath[path],
+      fileProcessingState.allPromises,
+    ]) {
+      promises[promises.indexOf(stalePromise)] = newPromise
+    }
+  }
+
+  const editResult = await newPromise
+  const clientToolResult = await postStreamProcessing<T>(
+    editResult,
+    fileProcessingState,
+    writeToClient,
+    requestClientToolCall,
+  )
+
+  // Every file edit tool returns the same result as str_replace
+  const [{ value }] = clientToolResult as LevelCodeToolOutput<'str_replace'>
+  if ('messages' in editResult && 'message' in value) {
+    value.message = [...editResult.messages, value.message].join('\n\n')
+  }
+
+  return { output: clientToolResult }
+}
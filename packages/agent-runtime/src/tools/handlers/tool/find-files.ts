@@ -112,7 +112,11 @@ export const handleFindFiles = (async (
     if (addedFiles.length > 0) {
       return {
         output: jsonToolResult(
-          renderReadFilesResult(addedFiles, fileContext.tokenCallers ?? {}),
+          renderReadFilesResult(
+            addedFiles,
+            fileContext.tokenCallers ?? {},
+            fileContext.cargoWorkspace,
+          ),
         ),
       }
     }
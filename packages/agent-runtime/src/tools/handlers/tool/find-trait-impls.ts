@@ -0,0 +1,22 @@
+import type { LevelCodeToolHandlerFunction } from '../handler-function-type'
+import type {
+  ClientToolCall,
+  LevelCodeToolCall,
+  LevelCodeToolOutput,
+} from '@levelcode/common/tools/list'
+
+type ToolName = 'find_trait_impls'
+export const handleFindTraitImpls = (async (params: {
+  previousToolCallFinished: Promise<void>
+  toolCall: LevelCodeToolCall<ToolName>
+  requestClientToolCall: (
+    toolCall: ClientToolCall<ToolName>,
+  ) => Promise<LevelCodeToolOutput<ToolName>>
+}): Promise<{
+  output: LevelCodeToolOutput<ToolName>
+}> => {
+  const { previousToolCallFinished, toolCall, requestClientToolCall } = params
+
+  await previousToolCallFinished
+  return { output: await requestClientToolCall(toolCall) }
+}) satisfies LevelCodeToolHandlerFunction<ToolName>
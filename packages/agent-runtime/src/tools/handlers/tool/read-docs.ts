@@ -5,6 +5,7 @@ import { callDocsSearchAPI } from '../../../llm-api/levelcode-web-api'
 import type { fetchContext7LibraryDocumentation } from '../../../llm-api/context7-api'
 import type { LevelCodeToolHandlerFunction } from '../handler-function-type'
 import type {
+  ClientToolCall,
   LevelCodeToolCall,
   LevelCodeToolOutput,
 } from '@levelcode/common/tools/list'
@@ -16,6 +17,9 @@ export const handleReadDocs = (async (
   params: {
     previousToolCallFinished: Promise<void>
     toolCall: LevelCodeToolCall<'read_docs'>
+    requestClientToolCall: (
+      toolCall: ClientToolCall<'read_docs'>,
+    ) => Promise<LevelCodeToolOutput<'read_docs'>>
 
     agentStepId: string
     clientSessionId: string
@@ -37,6 +41,7 @@ export const handleReadDocs = (async (
   const {
     previousToolCallFinished,
     toolCall,
+    requestClientToolCall,
 
     agentStepId,
     clientSessionId,
@@ -68,6 +73,34 @@ export const handleReadDocs = (async (
 
   await previousToolCallFinished
 
+  // The client documents the project's Rust crates from local sources, which
+  // works offline and matches the versions in Cargo.lock
+  const localDocs = await requestClientToolCall(toolCall).catch(() => null)
+  const localResult =
+    localDocs?.[0]?.type === 'json' ? localDocs[0].value : null
+  if (
+    localResult &&
+    typeof localResult === 'object' &&
+    !('errorMessage' in localResult) &&
+    typeof localResult.documentation === 'string' &&
+    localResult.documentation.trim()
+  ) {
+    logger.info(
+      {
+        ...docsContext,
+        docsDuration: Date.now() - docsStartTime,
+        resultLength: localResult.documentation.length,
+        usedLocalDocs: true,
+        success: true,
+      },
+      'Documentation request completed from local sources',
+    )
+    return {
+      output: jsonToolResult({ documentation: localResult.documentation }),
+      creditsUsed: 0,
+    }
+  }
+
   let creditsUsed = 0
   try {
     const viaWebApi = await callDocsSearchAPI({
@@ -1,3 +1,7 @@
+import {
+  extractRustItems,
+  outlineRustSource,
+} from '@levelcode/code-map/rust/outline'
 import { jsonToolResult } from '@levelcode/common/util/messages'
 
 import { getFileReadingUpdates } from '../../../get-file-reading-updates'
@@ -12,6 +16,34 @@ import type { ParamsExcluding } from '@levelcode/common/types/function-params'
 import type { ProjectFileContext } from '@levelcode/common/util/file'
 
 type ToolName = 'read_files'
+
+/**
+ * Replaces a Rust file's contents with its outline or the requested items.
+ * Other files, and files that fail to parse, are returned in full.
+ */
+async function applyRustView(
+  file: { path: string; content: string },
+  outline: boolean | undefined,
+  items: string[] | undefined,
+): Promise<{ path: string; content: string; view?: 'outline' | 'items' }> {
+  try {
+    if (items && items.length > 0) {
+      const content = await extractRustItems(file.path, file.content, items)
+      if (content !== undefined) {
+        return { ...file, content, view: 'items' }
+      }
+    } else if (outline) {
+      const content = await outlineRustSource(file.path, file.content)
+      if (content !== undefined) {
+        return { ...file, content, view: 'outline' }
+      }
+    }
+  } catch {
+    // Fall back to the full contents
+  }
+  return file
+}
+
 export const handleReadFiles = (async (
   params: {
     previousToolCallFinished: Promise<void>
@@ -26,18 +58,26 @@ export const handleReadFiles = (async (
 
     fileContext,
   } = params
-  const { paths } = toolCall.input
+  const { paths, outline, items } = toolCall.input
 
   await previousToolCallFinished
 
-  const addedFiles = await getFileReadingUpdates({
-    ...params,
-    requestedFiles: paths,
-  })
+  const addedFiles = await Promise.all(
+    (
+      await getFileReadingUpdates({
+        ...params,
+        requestedFiles: paths,
+      })
+    ).map((file) => applyRustView(file, outline, items)),
+  )
 
   return {
     output: jsonToolResult(
-      renderReadFilesResult(addedFiles, fileContext.tokenCallers ?? {}),
+      renderReadFilesResult(
+        addedFiles,
+        fileContext.tokenCallers ?? {},
+        fileContext.cargoWorkspace,
+      ),
     ),
   }
 }) satisfies LevelCodeToolHandlerFunction<ToolName>
@@ -1,97 +1,19 @@
-import { postStreamProcessing } from './write-file'
+import { handleFileEdit } from './file-edit-utils'
 import { processStrReplace } from '../../../process-str-replace'
 
 import type { LevelCodeToolHandlerFunction } from '../handler-function-type'
-import type { FileProcessingState } from './write-file'
-import type {
-  ClientToolCall,
-  LevelCodeToolCall,
-  LevelCodeToolOutput,
-} from '@levelcode/common/tools/list'
-import type { RequestOptionalFileFn } from '@levelcode/common/types/contracts/client'
-import type { Logger } from '@levelcode/common/types/contracts/logger'
-import type { ParamsExcluding } from '@levelcode/common/types/function-params'
+import type { FileEditHandlerParams } from './file-edit-utils'
 
 export const handleStrReplace = (async (
-  params: {
-    previousToolCallFinished: Promise<void>
-    toolCall: LevelCodeToolCall<'str_replace'>
-
-    fileProcessingState: FileProcessingState
-    logger: Logger
-
-    requestClientToolCall: (
-      toolCall: ClientToolCall<'str_replace'>,
-    ) => Promise<LevelCodeToolOutput<'str_replace'>>
-    writeToClient: (chunk: string) => void
-
-    requestOptionalFile: RequestOptionalFileFn
-  } & ParamsExcluding<RequestOptionalFileFn, 'filePath'>,
-): Promise<{ output: LevelCodeToolOutput<'str_replace'> }> => {
-  const {
-    previousToolCallFinished,
-    toolCall,
-
-    fileProcessingState,
-    logger,
-
-    requestClientToolCall,
-    requestOptionalFile,
-    writeToClient,
-  } = params
-  const { path, replacements } = toolCall.input
-
-  if (!fileProcessingState.promisesByPath[path]) {
-    fileProcessingState.promisesByPath[path] = []
-  }
-
-  const previousPromises = fileProcessingState.promisesByPath[path]
-  const previousEdit = previousPromises[previousPromises.length - 1]
-
-  const latestContentPromise = previousEdit
-    ? previousEdit.then((maybeResult) =>
-        maybeResult && 'content' in maybeResult
-          ? maybeResult.content
-          : requestOptionalFile({ ...params, filePath: path }),
-      )
-    : requestOptionalFile({ ...params, filePath: path })
-
-  const newPromise = processStrReplace({
-    path,
-    replacements,
-    initialContentPromise: latestContentPromise,
-    logger,
-  })
-    .catch((error: any) => {
-      logger.error(error, 'Error processing str_replace block')
-      return {
-        tool: 'str_replace' as const,
-        path,
-        error: 'Unknown error: Failed to process the str_replace block.',
-      }
-    })
-    .then((fileProcessingResult) => ({
-      ...fileProcessingResult,
-      toolCallId: toolCall.toolCallId,
-    }))
-
-  fileProcessingState.promisesByPath[path].push(newPromise)
-  fileProcessingState.allPromises.push(newPromise)
-
-  await previousToolCallFinished
-
-  const strReplaceResult = await newPromise
-  const clientToolResult = await postStreamProcessing<'str_replace'>(
-    strReplaceResult,
-    fileProcessingState,
-    writeToClient,
-    requestClientToolCall,
+  params: FileEditHandlerParams<'str_replace'>,
+) => {
+  const { path, replacements } = params.toolCall.input
+  return handleFileEdit(params, (initialContentPromise) =>
+    processStrReplace({
+      path,
+      replacements,
+      initialContentPromise,
+      logger: params.logger,
+    }),
   )
-
-  const value = clientToolResult[0].value
-  if ('messages' in strReplaceResult && 'message' in value) {
-    value.message = [...strReplaceResult.messages, value.message].join('\n\n')
-  }
-
-  return { output: clientToolResult }
 }) satisfies LevelCodeToolHandlerFunction<'str_replace'>
@@ -1,6 +1,7 @@
 import { AbortError } from '@levelcode/common/util/error'
 import { partition } from 'lodash'
 
+import { checkEditSyntax } from '../../../check-edit-syntax'
 import { processFileBlock } from '../../../process-file-block'
 
 import type { LevelCodeToolHandlerFunction } from '../handler-function-type'
@@ -9,12 +10,18 @@ import type {
   LevelCodeToolCall,
   LevelCodeToolOutput,
 } from '@levelcode/common/tools/list'
+import type { AgentTemplate } from '@levelcode/common/types/agent-template'
 import type { RequestOptionalFileFn } from '@levelcode/common/types/contracts/client'
 import type { Logger } from '@levelcode/common/types/contracts/logger'
 import type { ParamsExcluding } from '@levelcode/common/types/function-params'
 import type { AgentState } from '@levelcode/common/types/session-state'
 
-type FileProcessingTools = 'write_file' | 'str_replace' | 'create_plan'
+type FileProcessingTools =
+  | 'write_file'
+  | 'str_replace'
+  | 'edit_symbol'
+  | 'apply_suggestions'
+  | 'create_plan'
 export type FileProcessing<
   T extends FileProcessingTools = FileProcessingTools,
 > = {
@@ -26,6 +33,8 @@ export type FileProcessing<
       content: string
       patch?: string
       messages: string[]
+      /** Syntax errors introduced by the edit, appended to the tool result */
+      syntaxErrors?: string
     }
   | {
       error: string
@@ -38,6 +47,11 @@ export type FileProcessingState = {
   fileChangeErrors: Extract<FileProcessing, { error: string }>[]
   fileChanges: Exclude<FileProcessing, { error: string }>[]
   firstFileProcessed: boolean
+  /**
+   * Files that file change hooks, e.g. formatters, changed after an edit, so
+   * the content left by the edit is out of date
+   */
+  pathsChangedByHooks: Set<string>
 }
 
 export function getFileProcessingValues(
@@ -49,6 +63,7 @@ export function getFileProcessingValues(
     fileChangeErrors: [],
     fileChanges: [],
     firstFileProcessed: false,
+    pathsChangedByHooks: new Set(),
   }
   for (const [key, value] of Object.entries(state)) {
     const typedKey = key as keyof typeof fileProcessingValues
@@ -65,6 +80,7 @@ export const handleWriteFile = (async (
     toolCall: LevelCodeToolCall<'write_file'>
 
     agentState: AgentState
+    agentTemplate: Pick<AgentTemplate, 'strictSyntax'>
     clientSessionId: string
     fileProcessingState: FileProcessingState
     fingerprintId: string
@@ -95,6 +111,7 @@ export const handleWriteFile = (async (
     toolCall,
 
     agentState,
+    agentTemplate,
     clientSessionId,
     fileProcessingState,
     fingerprintId,
@@ -152,6 +169,14 @@ export const handleWriteFile = (async (
       }
       return result.value
     })
+    .then((result) =>
+      checkEditSyntax({
+        result,
+        initialContentPromise: latestContentPromise,
+        strict: agentTemplate.strictSyntax ?? false,
+        logger,
+      }),
+    )
     .catch((error) => {
       // AbortError propagates up - don't convert to tool error
       if (error instanceof AbortError) {
@@ -257,7 +282,7 @@ export async function postStreamProcessing<T extends FileProcessingTools>(
     )
   }
 
-  const { patch, content, path } = changes[0]
+  const { patch, content, path, syntaxErrors } = changes[0]
   const clientToolCall: ClientToolCall<T> = {
     toolCallId: toolCall.toolCallId,
     toolName: toolCall.tool,
@@ -265,5 +290,15 @@ export async function postStreamProcessing<T extends FileProcessingTools>(
       ? { type: 'patch' as const, path, content: patch }
       : { type: 'file' as const, path, content },
   } as ClientToolCall<T>
-  return await requestClientToolCall(clientToolCall)
+  const clientToolResult = await requestClientToolCall(clientToolCall)
+
+  // Every file processing tool returns the same result as str_replace
+  const [output] = clientToolResult as LevelCodeToolOutput<'str_replace'>
+  if (syntaxErrors && output && 'message' in output.value) {
+    output.value.message = `${output.value.message}\n\n${syntaxErrors}`
+  }
+  if (output?.value.changedByHooks) {
+    fileProcessingState.pathsChangedByHooks.add(path)
+  }
+  return clientToolResult
 }
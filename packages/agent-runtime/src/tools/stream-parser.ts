@@ -102,6 +102,7 @@ export async function processStream(
     fileChangeErrors: [],
     fileChanges: [],
     firstFileProcessed: false,
+    pathsChangedByHooks: new Set(),
   }
 
   // === RESPONSE HANDLER ===
@@ -369,12 +369,18 @@ export function getEditedFiles(params: {
         (
           m,
         ): m is LevelCodeToolMessage<
-          'create_plan' | 'str_replace' | 'write_file'
+          | 'create_plan'
+          | 'str_replace'
+          | 'edit_symbol'
+          | 'apply_suggestions'
+          | 'write_file'
         > => {
           return (
             m.role === 'tool' &&
             (m.toolName === 'create_plan' ||
               m.toolName === 'str_replace' ||
+              m.toolName === 'edit_symbol' ||
+              m.toolName === 'apply_suggestions' ||
               m.toolName === 'write_file')
           )
         },
@@ -1,18 +1,40 @@
+import type { CargoWorkspace } from '@levelcode/common/types/cargo-workspace'
+
 export interface TokenCallerMap {
   [filePath: string]: {
     [token: string]: string[] // Array of files that call this token
   }
 }
 
+/** Name of the workspace member whose directory most closely contains the file */
+function findOwningCrate(
+  cargoWorkspace: CargoWorkspace | undefined,
+  filePath: string,
+): string | undefined {
+  let owner: CargoWorkspace['members'][number] | undefined
+  for (const crate of cargoWorkspace?.members ?? []) {
+    const contains =
+      crate.rootDir === '' || filePath.startsWith(`${crate.rootDir}/`)
+    if (contains && (!owner || crate.rootDir.length > owner.rootDir.length)) {
+      owner = crate
+    }
+  }
+  return owner?.name
+}
+
 export function renderReadFilesResult(
-  files: { path: string; content: string }[],
+  files: { path: string; content: string; view?: 'outline' | 'items' }[],
   tokenCallers: TokenCallerMap,
+  cargoWorkspace?: CargoWorkspace,
 ) {
   return files.map((file) => {
+    const crate = findOwningCrate(cargoWorkspace, file.path)
     return {
       path: file.path,
       content: file.content,
       referencedBy: tokenCallers[file.path] ?? {},
+      ...(crate && { crate }),
+      ...(file.view && { view: file.view }),
     }
   })
 }
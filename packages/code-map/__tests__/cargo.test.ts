@@ -0,0 +1,261 @@
+import { describe, it, expect } from 'bun:test'
+
+import {
+  findCrateForFile,
+  findLockedRegistryPackage,
+  getDependentCrates,
+  loadCargoWorkspace,
+} from '../src/rust/cargo'
+
+const files: Record<string, string> = {
+  'Cargo.toml': `
+[workspace]
+members = ["crates/*", "tools/cli"]
+exclude = ["crates/experimental"]
+
+[workspace.package]
+version = "1.2.0"
+
+[workspace.dependencies]
+serde = { version = "1.0", features = ["derive"] }
+core = { path = "crates/core-types", package = "core-types" }
+`,
+  'Cargo.lock': `
+version = 3
+
+[[package]]
+name = "serde"
+version = "1.0.200"
+
+[[package]]
+name = "rand"
+version = "0.7.3"
+
+[[package]]
+name = "rand"
+version = "0.8.5"
+
+[[package]]
+name = "net"
+version = "1.2.0"
+dependencies = ["core-types", "rand 0.8.5", "serde"]
+`,
+  'crates/core-types/Cargo.toml': `
+[package]
+name = "core-types"
+version.workspace = true
+
+[features]
+default = ["std"]
+std = []
+
+[dependencies]
+serde.workspace = true
+`,
+  'crates/core-types/src/lib.rs': '',
+  'crates/net/Cargo.toml': `
+[package]
+name = "net"
+version.workspace = true
+
+[dependencies]
+core.workspace = true
+rand = "0.8"
+serde = { workspace = true, optional = true }
+
+[dev-dependencies]
+tokio-test = "0.4"
+`,
+  'crates/net/src/lib.rs': '',
+  'crates/net/src/tcp.rs': '',
+  'crates/net/tests/roundtrip.rs': '',
+  'crates/net/examples/echo/main.rs': '',
+  'crates/net/build.rs': '',
+  'crates/experimental/Cargo.toml': `
+[package]
+name = "experimental"
+`,
+  'tools/cli/Cargo.toml': `
+[package]
+name = "cli"
+version = "0.1.0"
+
+[[bin]]
+name = "levelcli"
+path = "src/main.rs"
+
+[dependencies]
+net = { path = "../../crates/net" }
+`,
+  'tools/cli/src/main.rs': '',
+  'tools/cli/src/bin/helper.rs': '',
+}
+
+describe('loadCargoWorkspace', () => {
+  const workspace = loadCargoWorkspace(files)!
+
+  it('should return undefined without manifests', () => {
+    expect(loadCargoWorkspace({ 'src/main.rs': '' })).toBeUndefined()
+  })
+
+  it('should expand member globs and honour excludes', () => {
+    expect(workspace.rootManifestPath).toBe('Cargo.toml')
+    expect(workspace.members.map((crate) => crate.name).sort()).toEqual([
+      'cli',
+      'core-types',
+      'net',
+    ])
+  })
+
+  it('should inherit workspace fields and discover targets', () => {
+    const net = workspace.members.find((crate) => crate.name === 'net')!
+    expect(net.version).toBe('1.2.0')
+    expect(net.rootDir).toBe('crates/net')
+    expect(net.targets).toEqual([
+      { kind: 'lib', name: 'net', path: 'crates/net/src/lib.rs' },
+      {
+        kind: 'example',
+        name: 'echo',
+        path: 'crates/net/examples/echo/main.rs',
+      },
+      {
+        kind: 'test',
+        name: 'roundtrip',
+        path: 'crates/net/tests/roundtrip.rs',
+      },
+      {
+        kind: 'build-script',
+        name: 'build-script-build',
+        path: 'crates/net/build.rs',
+      },
+    ])
+
+    const cli = workspace.members.find((crate) => crate.name === 'cli')!
+    expect(cli.targets.map((t) => `${t.kind}:${t.name}`)).toEqual([
+      'bin:levelcli',
+      'bin:helper',
+    ])
+
+    const core = workspace.members.find((crate) => crate.name === 'core-types')!
+    expect(core.features).toEqual({ default: ['std'], std: [] })
+  })
+
+  it('should resolve dependency renames, workspace edges and locked versions', () => {
+    const net = workspace.members.find((crate) => crate.name === 'net')!
+    expect(net.dependencies).toEqual([
+      {
+        name: 'core',
+        package: 'core-types',
+        kind: 'normal',
+        optional: false,
+        versionReq: undefined,
+        workspaceMember: 'core-types',
+      },
+      {
+        name: 'rand',
+        package: 'rand',
+        kind: 'normal',
+        optional: false,
+        versionReq: '0.8',
+        lockedVersion: '0.8.5',
+        workspaceMember: undefined,
+      },
+      {
+        name: 'serde',
+        package: 'serde',
+        kind: 'normal',
+        optional: true,
+        versionReq: '1.0',
+        lockedVersion: '1.0.200',
+        workspaceMember: undefined,
+      },
+      {
+        name: 'tokio_test',
+        package: 'tokio-test',
+        kind: 'dev',
+        optional: false,
+        versionReq: '0.4',
+        lockedVersion: undefined,
+        workspaceMember: undefined,
+      },
+    ])
+  })
+
+  it('should find the owning crate and the crates a change affects', () => {
+    expect(findCrateForFile(workspace, 'crates/net/src/tcp.rs')?.name).toBe(
+      'net',
+    )
+    expect(findCrateForFile(workspace, 'README.md')).toBeUndefined()
+    expect(getDependentCrates(workspace, ['core-types']).sort()).toEqual([
+      'cli',
+      'core-types',
+      'net',
+    ])
+    expect(getDependentCrates(workspace, ['cli'])).toEqual(['cli'])
+  })
+})
+
+describe('findLockedRegistryPackage', () => {
+  const registry = 'registry+https://github.com/rust-lang/crates.io-index'
+  const appFiles = {
+    'Cargo.toml': `
+[package]
+name = "app"
+version = "0.1.0"
+
+[dependencies]
+json = { package = "serde_json", version = "1" }
+rand = "0.7"
+`,
+    'Cargo.lock': `
+version = 3
+
+[[package]]
+name = "app"
+version = "0.1.0"
+dependencies = ["rand 0.7.3", "serde_json"]
+
+[[package]]
+name = "rand"
+version = "0.7.3"
+source = "${registry}"
+
+[[package]]
+name = "rand"
+version = "0.8.5"
+source = "${registry}"
+
+[[package]]
+name = "rand_core"
+version = "0.6.4"
+source = "sparse+https://index.crates.io/"
+
+[[package]]
+name = "serde_json"
+version = "1.0.117"
+source = "${registry}"
+`,
+  }
+
+  it('should prefer the version the project depends on directly', () => {
+    expect(findLockedRegistryPackage(appFiles, 'rand')).toEqual({
+      name: 'rand',
+      version: '0.7.3',
+      source: registry,
+    })
+  })
+
+  it('should match import names and differently spelled package names', () => {
+    expect(findLockedRegistryPackage(appFiles, 'json')?.name).toBe(
+      'serde_json',
+    )
+    expect(findLockedRegistryPackage(appFiles, 'Rand-Core')?.version).toBe(
+      '0.6.4',
+    )
+  })
+
+  it('should skip packages that are not from a registry', () => {
+    expect(findLockedRegistryPackage(appFiles, 'app')).toBeUndefined()
+    expect(findLockedRegistryPackage(appFiles, 'tokio')).toBeUndefined()
+  })
+})
@@ -0,0 +1,20 @@
+import type { RustFileSymbols } from '../src/rust/file-symbols'
+
+/** Rust file symbols with every list empty except the given ones */
+export function symbols(partial: Partial<RustFileSymbols>): RustFileSymbols {
+  return {
+    definitions: [],
+    imports: [],
+    references: [],
+    modules: [],
+    macros: [],
+    macroInvocations: [],
+    impliedImpls: [],
+    traits: [],
+    impls: [],
+    tests: [],
+    docTests: [],
+    testModules: [],
+    ...partial,
+  }
+}
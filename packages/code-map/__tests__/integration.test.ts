@@ -1,20 +1,48 @@
+import * as fs from 'fs'
+import * as path from 'path'
+
 import { describe, it, expect, beforeAll, afterAll } from 'bun:test'
 import { Parser } from 'web-tree-sitter'
 
 import { getLanguageConfig, setWasmDir } from '../src/languages'
+import { getSymbolIndex } from '../src/navigation'
 import { parseTokens, getFileTokenScores } from '../src/parse'
+import { buildRustCrateDocs } from '../src/rust/crate-docs'
+import { editRustSymbols } from '../src/rust/edit'
+import { extractRustItems, outlineRustSource } from '../src/rust/outline'
+import { findNewSyntaxIssues } from '../src/syntax'
 
 import type { LanguageConfig} from '../src/languages';
+import type { RustSymbolEdit } from '../src/rust/edit'
 import type { Language, Query } from 'web-tree-sitter';
 
 
 // Test timeout for async operations
 const TEST_TIMEOUT = 15000
 
+/** A grammar's config, or undefined when its WASM file could not be loaded */
+async function loadLanguageConfig(
+  filePath: string,
+): Promise<LanguageConfig | undefined> {
+  const config = await getLanguageConfig(filePath)
+  return config?.parser && config.query ? config : undefined
+}
+
 describe('Real Tree-Sitter Integration Tests', () => {
+  // Tests of languages whose grammar is unavailable are skipped; everything
+  // else, including failed assertions, fails the test
+  let cConfig: LanguageConfig | undefined
+  let cppConfig: LanguageConfig | undefined
+  let phpConfig: LanguageConfig | undefined
+  let rustConfig: LanguageConfig | undefined
+
   beforeAll(async () => {
     // Initialize tree-sitter parser
     await Parser.init()
+    cConfig = await loadLanguageConfig('test.c')
+    cppConfig = await loadLanguageConfig('test.h')
+    phpConfig = await loadLanguageConfig('test.php')
+    rustConfig = await loadLanguageConfig('test.rs')
   })
 
   afterAll(() => {
@@ -153,6 +181,587 @@ service.addUser({ id: 1, name: 'John', email: 'john@example.com' });
     TEST_TIMEOUT,
   )
 
+  it(
+    'should parse C sources and headers with real tree-sitter (may skip if WASM unavailable)',
+    async () => {
+      const readFixture = (filePath: string) =>
+        fs.readFileSync(path.join(__dirname, 'test-langs', filePath), 'utf8')
+
+      if (!cConfig || !cppConfig) {
+        console.log('⚠️  Skipping C test - WASM files not available')
+        return
+      }
+
+      const source = parseTokens('test.c', cConfig, readFixture)
+      expect(source.identifiers).toContain('Greeter')
+      expect(source.identifiers).toContain('create_greeter')
+      expect(source.identifiers).toContain('greet')
+      expect(source.identifiers).toContain('print_greeting')
+      expect(source.identifiers).toContain('main')
+      expect(source.calls).toContain('malloc')
+      expect(source.calls).toContain('greet')
+      expect(source.calls).toContain('create_greeter')
+
+      const header = parseTokens('test.h', cppConfig, readFixture)
+      expect(header.identifiers).toContain('GreeterVTable')
+      expect(header.identifiers).toContain('Greeting')
+      expect(header.identifiers).toContain('GreetingStyle')
+      expect(header.identifiers).toContain('greeting_new')
+      expect(header.identifiers).toContain('greeting_free')
+    },
+    TEST_TIMEOUT,
+  )
+
+  it(
+    'should parse PHP code with real tree-sitter (may skip if WASM unavailable)',
+    async () => {
+      const phpCode = fs.readFileSync(
+        path.join(__dirname, 'test-langs', 'test.php'),
+        'utf8',
+      )
+
+      if (!phpConfig) {
+        console.log('⚠️  Skipping PHP test - WASM files not available')
+        return
+      }
+
+      const result = parseTokens('test.php', phpConfig, () => phpCode)
+
+      expect(result.identifiers).toContain('Greeter')
+      expect(result.identifiers).toContain('Greeting')
+      expect(result.identifiers).toContain('greet')
+      expect(result.identifiers).toContain('printGreeting')
+      expect(result.identifiers).toContain('createGreeter')
+
+      expect(result.calls).toContain('Greeting')
+      expect(result.calls).toContain('createGreeter')
+      expect(result.calls).toContain('printGreeting')
+      expect(result.calls).toContain('greet')
+    },
+    TEST_TIMEOUT,
+  )
+
+  it(
+    'should qualify Rust impl and trait items (may skip if WASM unavailable)',
+    async () => {
+      const rustCode = fs.readFileSync(
+        path.join(__dirname, 'test-langs', 'test.rs'),
+        'utf8',
+      )
+
+      if (!rustConfig) {
+        console.log('⚠️  Skipping Rust test - WASM files not available')
+        return
+      }
+
+      const result = parseTokens('test.rs', rustConfig, () => rustCode)
+
+      // Free items keep their bare names
+      expect(result.identifiers).toContain('Greeting')
+      expect(result.identifiers).toContain('Greeter')
+      expect(result.identifiers).toContain('main')
+
+      // Associated items are qualified with their impl or trait
+      expect(result.identifiers).toContain('Greeting::new')
+      expect(result.identifiers).toContain('Greeting::DEFAULT_PREFIX')
+      expect(result.identifiers).toContain('Greeter::greet')
+      expect(result.identifiers).toContain('Greeter::Output')
+      expect(result.identifiers).toContain('Greeter::PUNCTUATION')
+      expect(result.identifiers).toContain('<Greeting as Greeter>::greet')
+      expect(result.identifiers).toContain('<Greeting as Greeter>::Output')
+      expect(result.identifiers).not.toContain('new')
+      expect(result.identifiers).not.toContain('greet')
+
+      // Path calls are qualified with their type, including `Self`
+      expect(result.calls).toContain('Greeting::new')
+      expect(result.calls).toContain('Greeting::DEFAULT_PREFIX')
+      expect(result.calls).toContain('print_greeting')
+      expect(result.calls).not.toContain('new')
+    },
+    TEST_TIMEOUT,
+  )
+
+  it(
+    'should link Rust method calls to qualified members (may skip if WASM unavailable)',
+    async () => {
+      const files: Record<string, string> = {
+        'src/greeter.rs': `
+pub trait Greeter {
+    const PUNCTUATION: &'static str;
+
+    fn greet(&self, name: &str) -> String;
+}
+
+pub struct Greeting {
+    pub prefix: String,
+}
+
+impl Greeter for Greeting {
+    const PUNCTUATION: &'static str = "!";
+
+    fn greet(&self, name: &str) -> String {
+        let punctuation = Self::PUNCTUATION;
+        format!("{}, {}{}", self.prefix, name, punctuation)
+    }
+}
+`.trimStart(),
+        'src/main.rs': `
+mod greeter;
+
+use greeter::{Greeter, Greeting};
+
+fn main() {
+    let greeting = Greeting { prefix: String::from("Hello") };
+    let message = greeting.greet("World");
+    println!("{}", message);
+}
+`.trimStart(),
+      }
+      const readFile = (filePath: string) => files[filePath] ?? null
+
+      if (!rustConfig) {
+        console.log('⚠️  Skipping Rust test - WASM files not available')
+        return
+      }
+
+      const greeter = parseTokens('src/greeter.rs', rustConfig, readFile)
+      expect(greeter.calls).toContain('<Greeting as Greeter>::PUNCTUATION')
+
+      const { tokenCallers } = await getFileTokenScores(
+        '/tmp/greeter',
+        Object.keys(files),
+        readFile,
+      )
+      const greetCallers = Object.entries(tokenCallers['src/greeter.rs'] ?? {})
+        .filter(([token]) => token.endsWith('::greet'))
+        .flatMap(([, callers]) => callers)
+      expect(greetCallers).toContain('src/main.rs')
+    },
+    TEST_TIMEOUT,
+  )
+
+  it(
+    'should find Rust definitions and references by position (may skip if WASM unavailable)',
+    async () => {
+      const rustCode = fs.readFileSync(
+        path.join(__dirname, 'test-langs', 'test.rs'),
+        'utf8',
+      )
+
+      if (!rustConfig) {
+        console.log('⚠️  Skipping Rust test - WASM files not available')
+        return
+      }
+
+      const index = await getSymbolIndex(
+        path.join(__dirname, 'test-langs'),
+        ['test.rs'],
+        () => rustCode,
+      )
+
+      // `new` in `Greeting::new("Hello")` inside main
+      const result = index.findDefinition('test.rs', 38, 31)
+      expect(result?.symbol).toMatchObject({
+        token: 'Greeting::new',
+        kind: 'reference',
+        range: { startLine: 38, startColumn: 30, endColumn: 33 },
+      })
+      expect(result?.definitions).toHaveLength(1)
+      expect(result?.definitions[0]).toMatchObject({
+        file: 'test.rs',
+        token: 'Greeting::new',
+        range: { startLine: 17, startColumn: 8 },
+      })
+
+      const { definitions, references } =
+        index.findReferences('Greeting::new')
+      expect(definitions).toHaveLength(1)
+      expect(
+        references.map((reference) => reference.range.startLine),
+      ).toContain(38)
+    },
+    TEST_TIMEOUT,
+  )
+
+  it(
+    'should outline Rust items and extract them by path (may skip if WASM unavailable)',
+    async () => {
+      const rustCode = fs.readFileSync(
+        path.join(__dirname, 'test-langs', 'test.rs'),
+        'utf8',
+      )
+
+      if (!rustConfig) {
+        console.log('⚠️  Skipping Rust test - WASM files not available')
+        return
+      }
+
+      const outline = await outlineRustSource('test.rs', rustCode)
+
+      expect(outline?.split('\n')).toEqual([
+        '2-7: trait Greeter',
+        '  3: type Output;',
+        "  4: const PUNCTUATION: &'static str;",
+        '  6: fn greet(&self, name: &str) -> String;',
+        '10-12: struct Greeting',
+        '  11: prefix: String',
+        '14-26: impl Greeting',
+        `  15: const DEFAULT_PREFIX: &'static str = "Hello";`,
+        '  17-21: fn new(prefix: &str) -> Self { ... }',
+        '  23-25: fn default_greeting() -> Self { ... }',
+        '28-35: impl Greeter for Greeting',
+        '  29: type Output = String;',
+        `  30: const PUNCTUATION: &'static str = "!";`,
+        '  32-34: fn greet(&self, name: &str) -> String { ... }',
+        '37-40: fn main() { ... }',
+      ])
+
+      // Trait impl items are also found under `Type::item`
+      const greet = await extractRustItems('test.rs', rustCode, [
+        'Greeting::greet',
+        'Greeting::missing',
+      ])
+      expect(greet).toBe(
+        [
+          '// <Greeting as Greeter>::greet (lines 32-34)',
+          '    fn greet(&self, name: &str) -> String {',
+          '        format!("{}, {}{}", self.prefix, name, Self::PUNCTUATION)',
+          '    }',
+          '',
+          '// No item found at path "Greeting::missing"',
+        ].join('\n'),
+      )
+    },
+    TEST_TIMEOUT,
+  )
+
+  it(
+    'should edit Rust items by symbol path (may skip if WASM unavailable)',
+    async () => {
+      const rustCode = `
+enum Color {
+    Red,
+    Green
+}
+
+struct Meters(f64);
+struct Feet(f64);
+
+impl fmt::Display for Meters {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+impl fmt::Display for Feet {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+`.trimStart()
+
+      if (!rustConfig) {
+        console.log('⚠️  Skipping Rust test - WASM files not available')
+        return
+      }
+
+      const result = await editRustSymbols('units.rs', rustCode, [
+        {
+          symbol: '<Feet as Display>::fmt',
+          action: 'replace',
+          content: [
+            'fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {',
+            '    write!(f, "{} ft", self.0)',
+            '}',
+          ].join('\n'),
+        },
+        { symbol: 'Color', action: 'append', content: 'Blue' },
+        // Ambiguous between the two impls
+        { symbol: 'fmt', action: 'delete' },
+        // Unvalidated input without content
+        { symbol: 'Meters', action: 'insert_after' } as RustSymbolEdit,
+      ])
+
+      expect(result).toEqual({
+        content: `
+enum Color {
+    Red,
+    Green,
+    Blue,
+}
+
+struct Meters(f64);
+struct Feet(f64);
+
+impl fmt::Display for Meters {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+impl fmt::Display for Feet {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{} ft", self.0)
+    }
+}
+`.trimStart(),
+        messages: [
+          expect.stringContaining('"fmt" matches 2 items'),
+          'insert_after Meters: "content" is required for "insert_after".',
+        ],
+      })
+    },
+    TEST_TIMEOUT,
+  )
+
+  it(
+    'should index Rust macro-generated items and derives (may skip if WASM unavailable)',
+    async () => {
+      const rustCode = `
+macro_rules! define_ids {
+    ($($name:ident),* $(,)?) => {
+        $(
+            #[derive(Clone, Copy)]
+            pub struct $name(u64);
+        )*
+    };
+}
+
+macro_rules! impl_named {
+    ($ty:ty, $label:expr) => {
+        impl Named for $ty {
+            fn name(&self) -> &'static str { $label }
+        }
+    };
+}
+
+define_ids!(UserId, OrderId);
+impl_named!(UserId, "user");
+
+#[derive(Debug, Clone, serde::Serialize)]
+struct Order {
+    id: OrderId,
+}
+`.trim()
+
+      if (!rustConfig) {
+        console.log('⚠️  Skipping Rust test - WASM files not available')
+        return
+      }
+
+      const result = parseTokens('macros.rs', rustConfig, () => rustCode)
+
+      expect(result.identifiers).toContain('UserId')
+      expect(result.identifiers).toContain('OrderId')
+      expect(result.identifiers).toContain('<UserId as Named>::name')
+
+      // Derived traits are recorded as implied impls
+      expect(result.calls).toContain('Serialize')
+      expect(result.calls).toContain('Debug')
+      expect(result.rust?.impliedImpls).toContainEqual({
+        typeName: 'Order',
+        traitName: 'Serialize',
+        traitPath: ['serde', 'Serialize'],
+        line: 22,
+        origin: 'derive',
+      })
+      expect(result.rust?.impliedImpls).toContainEqual(
+        expect.objectContaining({
+          typeName: 'UserId',
+          traitName: 'Named',
+          origin: 'macro',
+        }),
+      )
+    },
+    TEST_TIMEOUT,
+  )
+
+  it(
+    'should collect Rust tests and doc-tests (may skip if WASM unavailable)',
+    async () => {
+      const rustCode = `
+/// Adds two numbers.
+///
+/// \`\`\`
+/// assert_eq!(calc::add(1, 2), 3);
+/// \`\`\`
+pub fn add(a: i32, b: i32) -> i32 {
+    a + b
+}
+
+/// \`\`\`text
+/// not a test
+/// \`\`\`
+pub fn sub(a: i32, b: i32) -> i32 {
+    a - b
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn adds() {
+        assert_eq!(add(1, 2), 3);
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn subtracts() {
+        assert_eq!(sub(2, 1), 1);
+    }
+}
+`.trim()
+
+      if (!rustConfig) {
+        console.log('⚠️  Skipping Rust test - WASM files not available')
+        return
+      }
+
+      const result = parseTokens('calc.rs', rustConfig, () => rustCode)
+
+      expect(result.rust?.docTests).toEqual([
+        { token: 'add', modulePath: [], line: 6 },
+      ])
+      expect(result.rust?.testModules).toEqual([['tests']])
+      expect(result.rust?.tests).toEqual([
+        {
+          name: 'adds',
+          modulePath: ['tests'],
+          startLine: 22,
+          endLine: 24,
+          attribute: '#[test]',
+        },
+        {
+          name: 'subtracts',
+          modulePath: ['tests'],
+          startLine: 27,
+          endLine: 29,
+          attribute: '#[tokio::test(flavor = "multi_thread")]',
+        },
+      ])
+    },
+    TEST_TIMEOUT,
+  )
+
+  it(
+    'should report syntax errors introduced by an edit (may skip if WASM unavailable)',
+    async () => {
+      const before = 'fn main() {\n    let x = 1;\n}\n'
+      const extraBrace = 'fn main() {\n    let x = 1;\n}\n}\n'
+
+      if (!rustConfig) {
+        console.log('⚠️  Skipping Rust test - WASM files not available')
+        return
+      }
+
+      expect(await findNewSyntaxIssues('main.rs', before, before)).toEqual(
+        [],
+      )
+      expect(
+        await findNewSyntaxIssues('main.rs', before, extraBrace),
+      ).toEqual([
+        expect.objectContaining({ kind: 'error', startLine: 4, text: '}' }),
+      ])
+      // Errors the file already had are not reported again
+      expect(
+        await findNewSyntaxIssues(
+          'main.rs',
+          extraBrace,
+          `// Entry point\n${extraBrace}`,
+        ),
+      ).toEqual([])
+      expect(
+        await findNewSyntaxIssues('main.rs', null, 'fn main( {}\n'),
+      ).not.toEqual([])
+    },
+    TEST_TIMEOUT,
+  )
+
+  it(
+    'should extract the docs of a crate from its sources (may skip if WASM unavailable)',
+    async () => {
+      const files = {
+        'Cargo.toml': '[package]\nname = "tinyjson"\nversion = "0.3.1"\n',
+        'README.md': 'A tiny JSON library.\n',
+        'src/lib.rs': `
+#![doc = include_str!("../README.md")]
+#![deny(missing_docs)]
+
+mod value;
+
+pub use value::Value;
+
+/// Parses a JSON document.
+pub fn parse(input: &str) -> Value {
+    Value::Str(input.to_string())
+}
+
+fn helper() {}
+`.trimStart(),
+        'src/value.rs': `
+//! JSON values.
+
+/// A parsed JSON value.
+#[derive(Debug)]
+pub enum Value {
+    Null,
+    Str(String),
+}
+
+impl Value {
+    /// Returns the string if the value is a JSON string.
+    pub fn as_str(&self) -> Option<&str> {
+        match self {
+            Value::Str(s) => Some(s),
+            _ => None,
+        }
+    }
+
+    fn internal(&self) {}
+}
+`.trimStart(),
+        'examples/demo.rs': 'fn main() {}\n',
+      }
+
+      if (!rustConfig) {
+        console.log('⚠️  Skipping Rust test - WASM files not available')
+        return
+      }
+
+      const docs = await buildRustCrateDocs(
+        '/registry/tinyjson-0.3.1',
+        files,
+      )
+
+      expect(docs.crateName).toBe('tinyjson')
+      expect(docs.docs).toBe('A tiny JSON library.')
+      expect(docs.examples.map(({ file }) => file)).toEqual([
+        'examples/demo.rs',
+      ])
+      const byPath = Object.fromEntries(
+        docs.items.map((item) => [item.path, item]),
+      )
+      expect(Object.keys(byPath).sort()).toEqual([
+        'tinyjson::Value',
+        'tinyjson::Value::as_str',
+        'tinyjson::parse',
+      ])
+      expect(byPath['tinyjson::Value']).toEqual({
+        path: 'tinyjson::Value',
+        kind: 'enum',
+        signature: 'pub enum Value {\n    Null,\n    Str(String),\n}',
+        docs: 'A parsed JSON value.',
+        file: 'src/value.rs',
+        line: 5,
+      })
+      expect(byPath['tinyjson::Value::as_str']).toMatchObject({
+        signature: 'pub fn as_str(&self) -> Option<&str> { ... }',
+        docs: 'Returns the string if the value is a JSON string.',
+      })
+    },
+    TEST_TIMEOUT,
+  )
+
   it(
     'should process multiple files with getFileTokenScores',
     async () => {
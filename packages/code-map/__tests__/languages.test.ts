@@ -9,6 +9,9 @@ import {
   getWasmDir,
   findLanguageConfigByExtension,
   createLanguageConfig,
+  registerLanguage,
+  unregisterLanguage,
+  getRegisteredLanguageNames,
   type LanguageConfig,
   type RuntimeLanguageLoader,
 } from '../src/languages'
@@ -21,7 +24,7 @@ describe('languages module', () => {
     it('should contain all expected language configurations', () => {
       expect(languageTable).toBeDefined()
       expect(Array.isArray(languageTable)).toBe(true)
-      expect(languageTable.length).toBe(10) // Current number of supported languages
+      expect(languageTable.length).toBe(12) // Current number of supported languages
     })
 
     it('should have proper structure for each language config', () => {
@@ -73,9 +76,18 @@ describe('languages module', () => {
         { ext: '.cs', wasm: 'tree-sitter-c-sharp.wasm' },
         { ext: '.cpp', wasm: 'tree-sitter-cpp.wasm' },
         { ext: '.hpp', wasm: 'tree-sitter-cpp.wasm' },
+        { ext: '.cc', wasm: 'tree-sitter-cpp.wasm' },
+        { ext: '.cxx', wasm: 'tree-sitter-cpp.wasm' },
+        { ext: '.h', wasm: 'tree-sitter-cpp.wasm' },
+        { ext: '.c', wasm: 'tree-sitter-cpp.wasm' },
+        { ext: '.mjs', wasm: 'tree-sitter-javascript.wasm' },
+        { ext: '.cjs', wasm: 'tree-sitter-javascript.wasm' },
+        { ext: '.pyi', wasm: 'tree-sitter-python.wasm' },
         { ext: '.rs', wasm: 'tree-sitter-rust.wasm' },
+        { ext: '.rs.in', wasm: 'tree-sitter-rust.wasm' },
         { ext: '.rb', wasm: 'tree-sitter-ruby.wasm' },
         { ext: '.go', wasm: 'tree-sitter-go.wasm' },
+        { ext: '.php', wasm: 'tree-sitter-php.wasm' },
       ]
 
       expectedLanguages.forEach(({ ext, wasm }) => {
@@ -94,6 +106,7 @@ describe('languages module', () => {
         'tree-sitter-go.wasm',
         'tree-sitter-java.wasm',
         'tree-sitter-javascript.wasm',
+        'tree-sitter-php.wasm',
         'tree-sitter-python.wasm',
         'tree-sitter-ruby.wasm',
         'tree-sitter-rust.wasm',
@@ -184,12 +197,130 @@ describe('languages module', () => {
       expect(config?.extensions).toContain('.ts')
     })
 
+    it('should match extensions spanning several dots', () => {
+      const config = findLanguageConfigByExtension('src/bindings.rs.in')
+      expect(config?.wasmFile).toBe('tree-sitter-rust.wasm')
+      expect(findLanguageConfigByExtension('config.in')).toBeUndefined()
+    })
+
+    it('should use the C query for C sources and headers', () => {
+      const cConfig = findLanguageConfigByExtension('vendor/zlib/deflate.c')
+      const headerConfig = findLanguageConfigByExtension('vendor/zlib/zlib.h')
+      const cppConfig = findLanguageConfigByExtension('src/lib.cc')
+      expect(cConfig?.extensions).toEqual(['.c', '.h'])
+      expect(headerConfig).toBe(cConfig)
+      expect(cppConfig?.wasmFile).toBe(cConfig?.wasmFile)
+      expect(cppConfig).not.toBe(cConfig)
+    })
+
     it('should be case sensitive', () => {
       const config = findLanguageConfigByExtension('test.TS')
       expect(config).toBeUndefined()
     })
   })
 
+  describe('registerLanguage', () => {
+    it('should find registered languages by extension', () => {
+      registerLanguage({
+        name: 'protobuf',
+        extensions: ['.proto'],
+        wasmPath: '/grammars/tree-sitter-proto.wasm',
+        query: '(message (message_name) @identifier)',
+      })
+
+      const config = findLanguageConfigByExtension('api/service.proto')
+      expect(config?.wasmFile).toBe('/grammars/tree-sitter-proto.wasm')
+      expect(config?.queryPathOrContent).toBe(
+        '(message (message_name) @identifier)',
+      )
+      expect(getRegisteredLanguageNames()).toContain('protobuf')
+
+      unregisterLanguage('protobuf')
+      expect(findLanguageConfigByExtension('api/service.proto')).toBeUndefined()
+    })
+
+    it('should match file names and resolve query files', () => {
+      registerLanguage({
+        name: 'starlark',
+        matchFileName: (fileName) => fileName === 'BUILD',
+        wasmPath: '/grammars/tree-sitter-starlark.wasm',
+        query: '/grammars/starlark-tags.scm',
+      })
+
+      const config = findLanguageConfigByExtension('lib/BUILD')
+      expect(config?.queryPathOrContent).toBe('/grammars/starlark-tags.scm')
+      expect(findLanguageConfigByExtension('lib/BUILD.md')).toBeUndefined()
+
+      unregisterLanguage('starlark')
+    })
+
+    it('should take precedence over built-in languages', () => {
+      registerLanguage({
+        name: 'python-next',
+        extensions: ['.py'],
+        wasmPath: '/grammars/tree-sitter-python-next.wasm',
+        query: '(function_definition name: (identifier) @identifier)',
+      })
+
+      expect(findLanguageConfigByExtension('main.py')?.wasmFile).toBe(
+        '/grammars/tree-sitter-python-next.wasm',
+      )
+
+      unregisterLanguage('python-next')
+      expect(findLanguageConfigByExtension('main.py')?.wasmFile).toBe(
+        'tree-sitter-python.wasm',
+      )
+    })
+
+    it('should only apply languages of a project to its files', () => {
+      registerLanguage({
+        name: 'wgsl',
+        projectRoot: '/projects/renderer',
+        extensions: ['.wgsl'],
+        wasmPath: '/projects/renderer/tree-sitter-wgsl.wasm',
+        query: '(function_declaration (identifier) @identifier)',
+      })
+      registerLanguage({
+        name: 'wgsl',
+        extensions: ['.wgsl'],
+        wasmPath: '/grammars/tree-sitter-wgsl.wasm',
+        query: '(function_declaration (identifier) @identifier)',
+      })
+
+      expect(
+        findLanguageConfigByExtension('/projects/renderer/src/light.wgsl')
+          ?.wasmFile,
+      ).toBe('/projects/renderer/tree-sitter-wgsl.wasm')
+      expect(
+        findLanguageConfigByExtension('/projects/renderer-next/light.wgsl')
+          ?.wasmFile,
+      ).toBe('/grammars/tree-sitter-wgsl.wasm')
+      expect(findLanguageConfigByExtension('src/light.wgsl')?.wasmFile).toBe(
+        '/grammars/tree-sitter-wgsl.wasm',
+      )
+      expect(getRegisteredLanguageNames('/projects/renderer')).toEqual([
+        'wgsl',
+      ])
+
+      unregisterLanguage('wgsl')
+      expect(
+        findLanguageConfigByExtension('/projects/other/light.wgsl'),
+      ).toBeUndefined()
+      unregisterLanguage('wgsl', '/projects/renderer')
+      expect(getRegisteredLanguageNames('/projects/renderer')).toEqual([])
+    })
+
+    it('should reject languages that match no files', () => {
+      expect(() =>
+        registerLanguage({
+          name: 'nothing',
+          wasmPath: '/grammars/tree-sitter-nothing.wasm',
+          query: '',
+        }),
+      ).toThrow()
+    })
+  })
+
   describe('createLanguageConfig', () => {
     it('should return undefined for unsupported file extensions', async () => {
       const mockLoader: RuntimeLanguageLoader = {
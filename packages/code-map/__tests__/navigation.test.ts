@@ -0,0 +1,193 @@
+import { describe, it, expect } from 'bun:test'
+
+import { buildSymbolIndex } from '../src/navigation'
+import { symbols } from './helpers'
+
+import type { ParsedTokens, TokenLocation } from '../src/parse'
+import type { RustFileSymbols, RustReference } from '../src/rust/file-symbols'
+import type { SourceRange } from '../src/source-range'
+
+function range(line: number, startColumn: number, endColumn: number) {
+  return { startLine: line, startColumn, endLine: line, endColumn }
+}
+
+function parsed(
+  locations: TokenLocation[],
+  rust?: Partial<RustFileSymbols>,
+): ParsedTokens {
+  return {
+    numLines: 10,
+    identifiers: [],
+    calls: [],
+    locations,
+    ...(rust && { rust: symbols(rust) }),
+  }
+}
+
+function definition(token: string, at: SourceRange): TokenLocation {
+  return { token, kind: 'definition', range: at }
+}
+
+function reference(token: string, at: SourceRange): TokenLocation {
+  return { token, kind: 'reference', range: at }
+}
+
+function rustReference(
+  path: string[],
+  at: SourceRange,
+  kind: RustReference['kind'] = 'path',
+): RustReference {
+  const token = kind === 'path' ? path.slice(-2).join('::') : path[0]
+  return { token, path, modulePath: [], kind, range: at }
+}
+
+// pub struct Config;
+// impl Config {
+//     pub fn new() -> Self { .. }
+// }
+const configFile = () =>
+  parsed(
+    [
+      definition('Config', range(1, 12, 18)),
+      definition('Config::new', range(3, 12, 15)),
+    ],
+    {
+      definitions: [
+        { token: 'Config', modulePath: [], visibility: 'public' },
+        { token: 'Config::new', modulePath: [], visibility: 'public' },
+      ],
+    },
+  )
+
+describe('SymbolIndex', () => {
+  // use crate::b::Config;
+  //
+  // let first = Config::new();
+  // let second = crate::a::Config::new();
+  // first.reload();
+  const mainLoop = parsed(
+    [
+      reference('Config::new', range(3, 25, 28)),
+      reference('Config::new', range(4, 34, 37)),
+      reference('reload', range(5, 11, 17)),
+    ],
+    {
+      imports: [
+        {
+          modulePath: [],
+          alias: 'Config',
+          path: ['crate', 'b', 'Config'],
+          isGlob: false,
+          visibility: 'private',
+        },
+      ],
+      references: [
+        rustReference(['Config', 'new'], range(3, 25, 28)),
+        rustReference(['crate', 'a', 'Config', 'new'], range(4, 34, 37)),
+        rustReference(['reload'], range(5, 11, 17), 'method'),
+      ],
+    },
+  )
+
+  const index = buildSymbolIndex(
+    new Map([
+      ['src/lib.rs', parsed([], { modules: [['a'], ['b']] })],
+      ['src/a.rs', configFile()],
+      ['src/b.rs', configFile()],
+      ['src/main_loop.rs', mainLoop],
+      ['web/settings.ts', parsed([reference('Config', range(2, 7, 13))])],
+    ]),
+    'app',
+  )
+
+  it('should resolve Rust references through imports and paths', () => {
+    expect(index.findDefinition('src/main_loop.rs', 3, 26)).toEqual({
+      symbol: {
+        file: 'src/main_loop.rs',
+        token: 'Config::new',
+        kind: 'reference',
+        range: range(3, 25, 28),
+      },
+      definitions: [
+        {
+          file: 'src/b.rs',
+          token: 'Config::new',
+          range: range(3, 12, 15),
+          resolved: true,
+        },
+      ],
+    })
+    expect(
+      index.findDefinition('src/main_loop.rs', 4, 34)?.definitions,
+    ).toEqual([
+      {
+        file: 'src/a.rs',
+        token: 'Config::new',
+        range: range(3, 12, 15),
+        resolved: true,
+      },
+    ])
+  })
+
+  it('should return definitions themselves', () => {
+    expect(index.findDefinition('src/a.rs', 1, 18)?.definitions).toEqual([
+      {
+        file: 'src/a.rs',
+        token: 'Config',
+        range: range(1, 12, 18),
+        resolved: true,
+      },
+    ])
+  })
+
+  it('should match unresolved references by name', () => {
+    const result = index.findDefinition('web/settings.ts', 2, 8)
+
+    expect(result?.definitions.map((d) => [d.file, d.resolved])).toEqual([
+      ['src/a.rs', false],
+      ['src/b.rs', false],
+    ])
+    expect(
+      index.findDefinition('src/main_loop.rs', 5, 12)?.definitions,
+    ).toEqual([])
+  })
+
+  it('should return nothing between symbols', () => {
+    expect(index.findDefinition('src/main_loop.rs', 3, 2)).toBeUndefined()
+    expect(index.findDefinition('src/missing.rs', 1, 1)).toBeUndefined()
+  })
+
+  it('should only list references resolving to the definition', () => {
+    const { definitions, references } = index.findReferences(
+      'Config::new',
+      'src/b.rs',
+    )
+
+    expect(definitions).toEqual([
+      { file: 'src/b.rs', token: 'Config::new', range: range(3, 12, 15) },
+    ])
+    expect(references).toEqual([
+      {
+        file: 'src/main_loop.rs',
+        token: 'Config::new',
+        range: range(3, 25, 28),
+        resolved: true,
+      },
+    ])
+    expect(index.findReferences('new').references).toHaveLength(2)
+  })
+
+  it('should include references matching by name only', () => {
+    const { definitions, references } = index.findReferences('Config')
+
+    expect(definitions.map((d) => d.file)).toEqual(['src/a.rs', 'src/b.rs'])
+    expect(references).toEqual([
+      {
+        file: 'web/settings.ts',
+        token: 'Config',
+        range: range(2, 7, 13),
+        resolved: false,
+      },
+    ])
+  })
+})
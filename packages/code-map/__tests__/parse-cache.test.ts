@@ -0,0 +1,220 @@
+import {
+  createMockTreeSitterCaptures,
+  createMockTreeSitterParser,
+  createMockTreeSitterQuery,
+} from '@levelcode/common/testing/mocks/tree-sitter'
+import { describe, it, expect, mock } from 'bun:test'
+
+import { parseTokens } from '../src/parse'
+import {
+  ParseCache,
+  PARSE_CACHE_VERSION,
+  getSourceEdit,
+} from '../src/parse-cache'
+
+import type { LanguageConfig } from '../src/languages-common'
+import type { Mock } from 'bun:test'
+
+interface MockTree {
+  rootNode: { text: string }
+  edit: any
+  delete: any
+  copy: any
+}
+
+function createLanguageConfig() {
+  const trees: MockTree[] = []
+  const copies: MockTree[] = []
+  const createTree = (created: MockTree[]): MockTree => {
+    const tree = {
+      rootNode: { text: 'mock tree' },
+      edit: mock(() => {}),
+      delete: mock(() => {}),
+      copy: mock(() => createTree(copies)),
+    }
+    created.push(tree)
+    return tree
+  }
+  const parser = createMockTreeSitterParser({
+    parseImpl: () => createTree(trees),
+  })
+  const languageConfig: LanguageConfig = {
+    extensions: ['.ts'],
+    wasmFile: 'tree-sitter-typescript.wasm',
+    queryText: 'mock query',
+    parser,
+    query: createMockTreeSitterQuery({
+      captures: createMockTreeSitterCaptures([
+        { name: 'identifier', text: 'hello' },
+        { name: 'call.identifier', text: 'console' },
+      ]),
+    }),
+  }
+  return { languageConfig, parser, trees, copies }
+}
+
+describe('getSourceEdit', () => {
+  it('should span the text between the common prefix and suffix', () => {
+    const edit = getSourceEdit(
+      'fn a() {}\nfn b() {}\n',
+      'fn a() {}\nfn bc() {}\n',
+    )
+
+    expect(edit).toEqual({
+      startIndex: 14,
+      oldEndIndex: 14,
+      newEndIndex: 15,
+      startPosition: { row: 1, column: 4 },
+      oldEndPosition: { row: 1, column: 4 },
+      newEndPosition: { row: 1, column: 5 },
+    })
+  })
+
+  it('should handle inserted lines', () => {
+    const edit = getSourceEdit('a\nc\n', 'a\nb\nc\n')
+
+    expect(edit.startIndex).toBe(2)
+    expect(edit.oldEndIndex).toBe(2)
+    expect(edit.newEndIndex).toBe(4)
+    expect(edit.newEndPosition).toEqual({ row: 2, column: 0 })
+  })
+})
+
+describe('ParseCache', () => {
+  it('should not re-parse unchanged files', () => {
+    const { languageConfig, parser } = createLanguageConfig()
+    const cache = new ParseCache()
+    const source = 'function hello() {}'
+
+    const first = parseTokens('test.ts', languageConfig, () => source, cache)
+    const second = parseTokens('test.ts', languageConfig, () => source, cache)
+
+    expect(second).toEqual(first)
+    expect(parser.parse).toHaveBeenCalledTimes(1)
+    expect(cache.isChanged).toBe(true)
+  })
+
+  it('should re-parse changed files incrementally from their previous tree', () => {
+    const { languageConfig, parser, trees, copies } = createLanguageConfig()
+    const cache = new ParseCache()
+
+    parseTokens('test.ts', languageConfig, () => 'let a = 1', cache)
+    parseTokens('test.ts', languageConfig, () => 'let ab = 1', cache)
+
+    expect(parser.parse).toHaveBeenCalledTimes(2)
+    expect(parser.parse).toHaveBeenLastCalledWith('let ab = 1', copies[0])
+    expect(copies[0].edit).toHaveBeenCalledWith(
+      getSourceEdit('let a = 1', 'let ab = 1'),
+    )
+    expect(copies[0].delete).toHaveBeenCalled()
+    expect(trees[0].edit).not.toHaveBeenCalled()
+    expect(trees[0].delete).toHaveBeenCalled()
+    expect(cache.getTree('test.ts')?.tree).toBe(trees[1] as any)
+  })
+
+  it('should keep the previous tree intact when re-parsing fails', () => {
+    const { languageConfig, parser, trees, copies } = createLanguageConfig()
+    const cache = new ParseCache()
+
+    parseTokens('test.ts', languageConfig, () => 'let a = 1', cache)
+    ;(parser.parse as Mock<typeof parser.parse>).mockImplementationOnce(() => {
+      throw new Error('parse failed')
+    })
+    parseTokens('test.ts', languageConfig, () => 'let ab = 1', cache)
+
+    expect(copies[0].delete).toHaveBeenCalled()
+    expect(trees[0].edit).not.toHaveBeenCalled()
+    expect(trees[0].delete).not.toHaveBeenCalled()
+    expect(cache.getTree('test.ts')).toEqual({
+      sourceCode: 'let a = 1',
+      tree: trees[0] as any,
+    })
+  })
+
+  it('should free trees when there is no cache to keep them', () => {
+    const { languageConfig, trees } = createLanguageConfig()
+
+    parseTokens('test.ts', languageConfig, () => 'let a = 1')
+
+    expect(trees[0].delete).toHaveBeenCalled()
+  })
+
+  it('should re-parse files when the grammar or query changed', () => {
+    const { languageConfig, parser } = createLanguageConfig()
+    const cache = new ParseCache()
+    const source = 'function hello() {}'
+
+    parseTokens(
+      'test.ts',
+      { ...languageConfig, cacheKey: 'v1' },
+      () => source,
+      cache,
+    )
+    parseTokens(
+      'test.ts',
+      { ...languageConfig, cacheKey: 'v2' },
+      () => source,
+      cache,
+    )
+
+    expect(parser.parse).toHaveBeenCalledTimes(2)
+    // Trees of the old grammar are not reused for incremental parsing
+    expect(parser.parse).toHaveBeenLastCalledWith(source)
+    expect(cache.get('test.ts', source, 'v1')).toBeUndefined()
+    expect(cache.get('test.ts', source, 'v2')?.identifiers).toEqual(['hello'])
+  })
+
+  it('should return copies of cached results', () => {
+    const { languageConfig } = createLanguageConfig()
+    const cache = new ParseCache()
+    const source = 'function hello() {}'
+
+    const parsed = parseTokens('test.ts', languageConfig, () => source, cache)
+    parsed.identifiers.push('mutated')
+
+    expect(cache.get('test.ts', source)?.identifiers).toEqual(['hello'])
+  })
+
+  it('should survive serialization', () => {
+    const { languageConfig, parser } = createLanguageConfig()
+    const cache = new ParseCache()
+    const source = 'function hello() {}'
+    const parsed = parseTokens('test.ts', languageConfig, () => source, cache)
+
+    const restored = ParseCache.fromJSON(JSON.parse(JSON.stringify(cache)))
+
+    expect(restored.isChanged).toBe(false)
+    expect(
+      parseTokens('test.ts', languageConfig, () => source, restored),
+    ).toEqual(parsed)
+    expect(parser.parse).toHaveBeenCalledTimes(1)
+  })
+
+  it('should discard caches written by another version', () => {
+    const cache = ParseCache.fromJSON({
+      version: PARSE_CACHE_VERSION + 1,
+      entries: {
+        'test.ts': {
+          hash: 'abc',
+          parsed: { numLines: 1, identifiers: [], calls: [] },
+        },
+      },
+    })
+
+    expect(cache.size).toBe(0)
+  })
+
+  it('should drop entries of removed files', () => {
+    const { languageConfig } = createLanguageConfig()
+    const cache = new ParseCache()
+    parseTokens('a.ts', languageConfig, () => 'a', cache)
+    parseTokens('b.ts', languageConfig, () => 'b', cache)
+    cache.markSaved()
+
+    cache.retain(['a.ts'])
+
+    expect(cache.size).toBe(1)
+    expect(cache.get('b.ts', 'b')).toBeUndefined()
+    expect(cache.isChanged).toBe(true)
+  })
+})
@@ -589,6 +589,7 @@ console.log('Total:', formatCurrency(total));
       // The real implementation should gracefully handle when no language config is found
       expect(result.tokenScores).toBeDefined()
       expect(result.tokenCallers).toBeDefined()
+      expect(result.symbolGraph).toBeDefined()
 
       // Verify that the structure is correct even if no tokens are found
       expect(typeof result.tokenScores).toBe('object')
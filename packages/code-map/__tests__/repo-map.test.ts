@@ -0,0 +1,113 @@
+import { describe, it, expect } from 'bun:test'
+
+import { findRepoMapSeeds, rankSymbols, renderRepoMap } from '../src/repo-map'
+
+import type { SymbolGraph } from '../src/repo-map'
+
+const graph: SymbolGraph = {
+  definitions: {
+    'src/config.rs': {
+      Config: { line: 3, signature: 'pub struct Config {' },
+      load: { line: 10, signature: 'pub fn load() -> Config {' },
+    },
+    'src/server.rs': {
+      Server: { line: 1, signature: 'pub struct Server;' },
+      'Server::run': { line: 4, signature: 'pub fn run(&self) {' },
+    },
+    'src/main.rs': {
+      main: { line: 1, signature: 'fn main() {' },
+    },
+    'src/cli.rs': {
+      parse_args: { line: 1, signature: 'pub fn parse_args() -> Args {' },
+    },
+    'src/util.rs': {
+      helper: { line: 2, signature: 'pub fn helper() {' },
+    },
+  },
+  references: {
+    'src/main.rs': {
+      'src/server.rs': { Server: 1, 'Server::run': 1 },
+      'src/config.rs': { load: 1 },
+    },
+    'src/server.rs': {
+      'src/config.rs': { Config: 2 },
+    },
+    'src/cli.rs': {
+      'src/util.rs': { helper: 1 },
+    },
+  },
+}
+
+const countLines = (text: string) => text.split('\n').length
+
+describe('rankSymbols', () => {
+  it('should rank definitions by the references flowing to them', () => {
+    const ranked = rankSymbols(graph)
+    const tokens = ranked.map((symbol) => symbol.token)
+
+    expect(ranked[0]).toMatchObject({ file: 'src/config.rs', token: 'Config' })
+    expect(tokens).toHaveLength(7)
+    expect(tokens.indexOf('helper')).toBeLessThan(tokens.indexOf('parse_args'))
+  })
+
+  it('should personalize the ranking towards seed files', () => {
+    const ranked = rankSymbols(graph, {
+      files: ['src/cli.rs'],
+      identifiers: [],
+    })
+
+    expect(ranked[0]).toMatchObject({ file: 'src/util.rs', token: 'helper' })
+  })
+
+  it('should boost seed identifiers', () => {
+    const ranked = rankSymbols(graph, { files: [], identifiers: ['load'] })
+
+    expect(ranked[0].token).toBe('load')
+  })
+
+  it('should return nothing for an empty graph', () => {
+    expect(rankSymbols({ definitions: {}, references: {} })).toEqual([])
+  })
+})
+
+describe('findRepoMapSeeds', () => {
+  it('should find mentioned files and identifiers', () => {
+    const seeds = findRepoMapSeeds(
+      'Why does Server::run hang when load is called from src/main.rs?',
+      graph,
+    )
+
+    expect(seeds.files).toEqual(['src/main.rs'])
+    expect(seeds.identifiers).toContain('Server::run')
+    expect(seeds.identifiers).toContain('load')
+    expect(seeds.identifiers).not.toContain('Config')
+  })
+
+  it('should match unique file names without their directory', () => {
+    expect(findRepoMapSeeds('The bug is in cli.rs.', graph).files).toEqual([
+      'src/cli.rs',
+    ])
+  })
+})
+
+describe('renderRepoMap', () => {
+  const ranked = rankSymbols(graph)
+
+  it('should group symbols by file in line order', () => {
+    const repoMap = renderRepoMap(ranked, 100, countLines)
+
+    expect(repoMap.split('\n').slice(0, 3)).toEqual([
+      'src/config.rs:',
+      '  3: pub struct Config {',
+      '  10: pub fn load() -> Config {',
+    ])
+    expect(repoMap).toContain('src/util.rs:\n  2: pub fn helper() {')
+  })
+
+  it('should render as many symbols as fit in the budget', () => {
+    expect(renderRepoMap(ranked, 2, countLines)).toBe(
+      'src/config.rs:\n  3: pub struct Config {',
+    )
+    expect(renderRepoMap(ranked, 0, countLines)).toBe('')
+  })
+})
@@ -0,0 +1,104 @@
+import { describe, it, expect } from 'bun:test'
+
+import { renderRustCrateDocs } from '../src/rust/crate-docs'
+
+import type { RustCrateDocs } from '../src/rust/crate-docs'
+
+const docs: RustCrateDocs = {
+  crateName: 'tinyjson',
+  docs: 'A tiny JSON library.\n\nParse with `parse`, then inspect the `Value`.',
+  items: [
+    {
+      path: 'tinyjson::Value',
+      kind: 'enum',
+      signature: 'pub enum Value {\n    Null,\n    Str(String),\n}',
+      docs: 'A parsed JSON value.',
+      file: 'src/value.rs',
+      line: 3,
+    },
+    {
+      path: 'tinyjson::Value::as_str',
+      kind: 'fn',
+      signature: 'pub fn as_str(&self) -> Option<&str> { ... }',
+      docs: 'Returns the string if the value is a JSON string.',
+      file: 'src/value.rs',
+      line: 12,
+    },
+    {
+      path: 'tinyjson::parse',
+      kind: 'fn',
+      signature: 'pub fn parse(input: &str) -> Result<Value, Error> { ... }',
+      docs: 'Parses a JSON document into a `Value`.',
+      file: 'src/lib.rs',
+      line: 8,
+    },
+  ],
+  examples: [
+    { file: 'examples/parse_file.rs', source: 'fn main() {}\n' },
+    { file: 'examples/pretty.rs', source: 'fn main() {}\n' },
+  ],
+}
+
+describe('renderRustCrateDocs', () => {
+  it('should render the best matching items first', () => {
+    const output = renderRustCrateDocs(docs, {
+      title: 'tinyjson 0.3.1',
+      topic: 'how to parse a string',
+      maxTokens: 10_000,
+    })
+
+    expect(output.startsWith('# tinyjson 0.3.1\n\nA tiny JSON library.')).toBe(
+      true,
+    )
+    expect(output).not.toContain('inspect the `Value`')
+    const headings = output
+      .split('\n')
+      .filter((line) => line.startsWith('## '))
+    expect(headings).toEqual([
+      '## `tinyjson::parse` (fn, src/lib.rs:8)',
+      '## `tinyjson::Value` (enum, src/value.rs:3)',
+      '## `tinyjson::Value::as_str` (fn, src/value.rs:12)',
+      '## Example: examples/parse_file.rs',
+    ])
+  })
+
+  it('should look up items by path', () => {
+    const output = renderRustCrateDocs(docs, {
+      title: 'tinyjson 0.3.1',
+      topic: 'Value::as_str',
+      maxTokens: 10_000,
+    })
+
+    expect(output).toContain(
+      '## `tinyjson::Value::as_str` (fn, src/value.rs:12)\n\n```rust\npub fn as_str(&self) -> Option<&str> { ... }\n```\n\nReturns the string',
+    )
+  })
+
+  it('should render an overview when nothing matches', () => {
+    const output = renderRustCrateDocs(docs, {
+      title: 'tinyjson 0.3.1',
+      topic: 'websockets',
+      maxTokens: 10_000,
+    })
+
+    expect(output).toContain('No items match "websockets"')
+    expect(output).toContain('inspect the `Value`')
+    expect(output).toContain(
+      '## Items\n- `tinyjson::Value` (enum): A parsed JSON value.\n- `tinyjson::parse` (fn): Parses a JSON document into a `Value`.',
+    )
+    expect(output).toContain(
+      '## Examples\n- examples/parse_file.rs\n- examples/pretty.rs',
+    )
+  })
+
+  it('should stay within the token budget', () => {
+    const output = renderRustCrateDocs(docs, {
+      title: 'tinyjson 0.3.1',
+      topic: 'value',
+      maxTokens: 40,
+    })
+
+    expect(output.length).toBeLessThan(40 * 4 + 100)
+    expect(output).toContain('more section(s) omitted')
+  })
+})
@@ -0,0 +1,205 @@
+import { describe, it, expect } from 'bun:test'
+
+import {
+  applyCargoSuggestions,
+  parseCargoDiagnostics,
+  summarizeCargoDiagnostics,
+} from '../src/rust/diagnostics'
+
+function span(
+  file: string,
+  line: number,
+  columns: [number, number],
+  overrides: Record<string, unknown> = {},
+) {
+  return {
+    file_name: file,
+    line_start: line,
+    line_end: line,
+    column_start: columns[0],
+    column_end: columns[1],
+    is_primary: true,
+    label: null,
+    suggested_replacement: null,
+    suggestion_applicability: null,
+    ...overrides,
+  }
+}
+
+function compilerMessage(message: Record<string, unknown>) {
+  return JSON.stringify({
+    reason: 'compiler-message',
+    package_id: 'path+file:///work/app#0.1.0',
+    message: {
+      code: null,
+      spans: [],
+      children: [],
+      rendered: null,
+      ...message,
+    },
+  })
+}
+
+const mismatchedTypes = compilerMessage({
+  level: 'error',
+  message: 'mismatched types',
+  code: { code: 'E0308' },
+  spans: [
+    span('src/config.rs', 12, [20, 25], { label: 'expected `&str`' }),
+    span('src/config.rs', 10, [8, 12], { is_primary: false }),
+  ],
+  children: [
+    {
+      level: 'help',
+      message: 'consider borrowing here',
+      code: null,
+      spans: [
+        span('src/config.rs', 12, [20, 20], {
+          suggested_replacement: '&',
+          suggestion_applicability: 'MachineApplicable',
+        }),
+      ],
+      children: [],
+      rendered: null,
+    },
+  ],
+  rendered: 'error[E0308]: mismatched types\n --> src/config.rs:12:20\n',
+})
+
+const unusedImport = compilerMessage({
+  level: 'warning',
+  message: 'unused import: `std::fmt`',
+  code: { code: 'unused_imports' },
+  spans: [span('src/main.rs', 1, [5, 13])],
+})
+
+const output = [
+  JSON.stringify({ reason: 'compiler-artifact', package_id: 'dep' }),
+  unusedImport,
+  mismatchedTypes,
+  // Reported again when checking the library's tests
+  mismatchedTypes,
+  'warning: build script output that is not JSON',
+  compilerMessage({
+    level: 'error',
+    message: 'aborting due to 1 previous error',
+  }),
+  JSON.stringify({ reason: 'build-finished', success: false }),
+].join('\n')
+
+describe('parseCargoDiagnostics', () => {
+  it('should parse compiler messages with spans and suggestions', () => {
+    const diagnostics = parseCargoDiagnostics(output)
+
+    expect(diagnostics).toHaveLength(3)
+    expect(diagnostics[1]).toEqual({
+      level: 'error',
+      code: 'E0308',
+      message: 'mismatched types',
+      packageId: 'path+file:///work/app#0.1.0',
+      primarySpan: {
+        file: 'src/config.rs',
+        startLine: 12,
+        startColumn: 20,
+        endLine: 12,
+        endColumn: 25,
+        label: 'expected `&str`',
+      },
+      suggestions: [
+        {
+          file: 'src/config.rs',
+          startLine: 12,
+          startColumn: 20,
+          endLine: 12,
+          endColumn: 20,
+          message: 'consider borrowing here',
+          replacement: '&',
+          applicability: 'MachineApplicable',
+        },
+      ],
+      rendered: 'error[E0308]: mismatched types\n --> src/config.rs:12:20\n',
+    })
+  })
+})
+
+describe('summarizeCargoDiagnostics', () => {
+  const diagnostics = parseCargoDiagnostics(output)
+
+  it('should dedupe and group diagnostics by file, errors first', () => {
+    const summary = summarizeCargoDiagnostics(diagnostics, 10)
+
+    expect(summary.errorCount).toBe(1)
+    expect(summary.warningCount).toBe(1)
+    expect(summary.omitted).toBe(0)
+    expect(
+      summary.files.map(({ file, diagnostics }) => [
+        file,
+        diagnostics.map((d) => d.code),
+      ]),
+    ).toEqual([
+      ['src/config.rs', ['E0308']],
+      ['src/main.rs', ['unused_imports']],
+    ])
+  })
+
+  it('should keep the most severe diagnostics within the limit', () => {
+    const summary = summarizeCargoDiagnostics(diagnostics, 1)
+
+    expect(summary.files.map(({ file }) => file)).toEqual(['src/config.rs'])
+    expect(summary.omitted).toBe(1)
+  })
+})
+
+describe('applyCargoSuggestions', () => {
+  const source =
+    'fn main() {\n    let name = "🦀".to_string();\n    greet(name);\n}\n'
+  const at = (line: number, start: number, end: number) => ({
+    startLine: line,
+    startColumn: start,
+    endLine: line,
+    endColumn: end,
+  })
+
+  it('should apply every suggestion, counting columns in characters', () => {
+    const result = applyCargoSuggestions(source, [
+      { ...at(3, 11, 11), replacement: '&', message: 'consider borrowing' },
+      { ...at(2, 19, 31), replacement: '.to_owned()' },
+      { ...at(3, 11, 11), replacement: '&' },
+    ])
+
+    expect(result).toEqual({
+      content:
+        'fn main() {\n    let name = "🦀".to_owned();\n    greet(&name);\n}\n',
+      messages: [
+        'Applied the suggestion at 2:19',
+        'Applied the suggestion at 3:11: consider borrowing',
+      ],
+    })
+  })
+
+  it('should refuse overlapping spans', () => {
+    const result = applyCargoSuggestions(source, [
+      { ...at(2, 5, 13), replacement: 'let name' },
+      { ...at(2, 9, 13), replacement: 'label' },
+    ])
+
+    expect(result).toEqual({
+      error: expect.stringContaining('2:5 and 2:9 overlap'),
+    })
+  })
+
+  it('should refuse spans outside the file and placeholders', () => {
+    expect(
+      applyCargoSuggestions(source, [{ ...at(3, 5, 40), replacement: '' }]),
+    ).toHaveProperty('error')
+    expect(
+      applyCargoSuggestions(source, [
+        {
+          ...at(1, 11, 11),
+          replacement: '/* value */',
+          applicability: 'HasPlaceholders',
+        },
+      ]),
+    ).toHaveProperty('error')
+  })
+})
@@ -0,0 +1,198 @@
+import { describe, it, expect } from 'bun:test'
+
+import {
+  expandRustMacroInvocation,
+  expandRustMacros,
+  matchMacroPattern,
+} from '../src/rust/macros'
+import { symbols } from './helpers'
+
+import type { RustFileSymbols } from '../src/rust/file-symbols'
+import type {
+  MacroToken,
+  RustMacroDefinition,
+  RustMacroInvocation,
+} from '../src/rust/macros'
+
+const text = (value: string): MacroToken => ({ kind: 'text', text: value })
+const v = (name: string, fragment?: string): MacroToken => ({
+  kind: 'var',
+  name,
+  fragment,
+})
+const group = (delimiter: string, ...tokens: MacroToken[]): MacroToken => ({
+  kind: 'group',
+  delimiter,
+  tokens,
+})
+const repeat = (
+  separator: string | undefined,
+  op: string,
+  ...tokens: MacroToken[]
+): MacroToken => ({
+  kind: 'repeat',
+  tokens,
+  separator: separator ? [text(separator)] : [],
+  op,
+})
+
+function invocation(
+  name: string,
+  ...tokens: MacroToken[]
+): RustMacroInvocation {
+  return { name, tokens, modulePath: [], line: 1 }
+}
+
+// macro_rules! define_ids { ($($name:ident),*) => { $(pub struct $name;)* } }
+const defineIds: RustMacroDefinition = {
+  name: 'define_ids',
+  rules: [
+    {
+      pattern: [repeat(',', '*', v('name', 'ident'))],
+      body: [
+        repeat(
+          undefined,
+          '*',
+          text('pub'),
+          text('struct'),
+          v('name'),
+          text(';'),
+        ),
+      ],
+    },
+  ],
+}
+
+// macro_rules! impl_named {
+//   ($t:ty => $label:expr) => { impl Named for $t { fn name(&self) {} } };
+//   ($t:ty) => { impl $t { fn name(&self) {} } };
+// }
+const implNamed: RustMacroDefinition = {
+  name: 'impl_named',
+  rules: [
+    {
+      pattern: [v('t', 'ty'), text('=>'), v('label', 'expr')],
+      body: [
+        text('impl'),
+        text('Named'),
+        text('for'),
+        v('t'),
+        group(
+          '{',
+          text('fn'),
+          text('name'),
+          group('(', text('&'), text('self')),
+          group('{'),
+        ),
+      ],
+    },
+    {
+      pattern: [v('t', 'ty')],
+      body: [
+        text('impl'),
+        v('t'),
+        group('{', text('fn'), text('name'), group('('), group('{')),
+      ],
+    },
+  ],
+}
+
+describe('matchMacroPattern', () => {
+  it('should bind metavariables inside repetitions', () => {
+    expect(
+      matchMacroPattern(defineIds.rules[0].pattern, [
+        text('A'),
+        text(','),
+        text('B'),
+      ]),
+    ).toEqual({ name: ['A', 'B'] })
+  })
+
+  it('should bind multi-token fragments up to the next literal', () => {
+    expect(
+      matchMacroPattern(implNamed.rules[0].pattern, [
+        text('Vec'),
+        text('<'),
+        text('u8'),
+        text('>'),
+        text('=>'),
+        text('"bytes"'),
+      ]),
+    ).toEqual({ t: ['Vec < u8 >'], label: ['"bytes"'] })
+  })
+
+  it('should reject input that does not match', () => {
+    expect(
+      matchMacroPattern(defineIds.rules[0].pattern, [text('A'), text('B')]),
+    ).toBeUndefined()
+  })
+})
+
+describe('expandRustMacroInvocation', () => {
+  it('should list items generated for every repetition', () => {
+    const expansion = expandRustMacroInvocation(
+      defineIds,
+      invocation('define_ids', text('UserId'), text(','), text('OrderId')),
+    )
+    expect(expansion.definitions).toEqual([
+      { token: 'UserId', modulePath: [], macro: 'define_ids' },
+      { token: 'OrderId', modulePath: [], macro: 'define_ids' },
+    ])
+  })
+
+  it('should qualify items in generated impls and record trait impls', () => {
+    const traitImpl = expandRustMacroInvocation(
+      implNamed,
+      invocation(
+        'impl_named',
+        text('Vec'),
+        text('<'),
+        text('u8'),
+        text('>'),
+        text('=>'),
+        text('"bytes"'),
+      ),
+    )
+    expect(traitImpl.definitions.map((d) => d.token)).toEqual([
+      '<Vec as Named>::name',
+    ])
+    expect(traitImpl.impliedImpls).toEqual([
+      {
+        typeName: 'Vec',
+        traitName: 'Named',
+        traitPath: ['Named'],
+        line: 1,
+        origin: 'macro',
+      },
+    ])
+
+    // Falls through to the second rule
+    const inherent = expandRustMacroInvocation(
+      implNamed,
+      invocation('impl_named', text('Config')),
+    )
+    expect(inherent.definitions.map((d) => d.token)).toEqual(['Config::name'])
+    expect(inherent.impliedImpls).toEqual([])
+  })
+})
+
+describe('expandRustMacros', () => {
+  it('should expand invocations of macros defined in other files', () => {
+    const files = new Map<string, RustFileSymbols>([
+      ['src/macros.rs', symbols({ macros: [defineIds] })],
+      [
+        'src/ids.rs',
+        symbols({
+          macroInvocations: [invocation('define_ids', text('TenantId'))],
+        }),
+      ],
+    ])
+
+    expect(expandRustMacros(files)).toEqual(
+      new Map([['src/ids.rs', ['TenantId']]]),
+    )
+    expect(files.get('src/ids.rs')?.definitions).toEqual([
+      { token: 'TenantId', modulePath: [], macro: 'define_ids' },
+    ])
+  })
+})
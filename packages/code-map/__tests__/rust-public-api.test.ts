@@ -0,0 +1,114 @@
+import { describe, it, expect } from 'bun:test'
+
+import { buildRustPublicApi, diffRustPublicApi } from '../src/rust/public-api'
+import { symbols } from './helpers'
+
+import type { RustCrateApi } from '../src/rust/public-api'
+
+describe('buildRustPublicApi', () => {
+  it('should export items reachable through pub mod and pub use', () => {
+    const files = new Map([
+      [
+        'src/lib.rs',
+        symbols({
+          modules: [['config'], ['internal']],
+          definitions: [
+            { token: 'config', modulePath: [], visibility: 'public' },
+            { token: 'internal', modulePath: [], visibility: 'private' },
+            { token: 'ids', modulePath: [], visibility: 'public' },
+          ],
+          macros: [{ name: 'ids', rules: [] }],
+          imports: [
+            {
+              modulePath: [],
+              alias: 'Engine',
+              path: ['internal', 'Engine'],
+              isGlob: false,
+              visibility: 'public',
+            },
+          ],
+        }),
+      ],
+      [
+        'src/config.rs',
+        symbols({
+          definitions: [
+            { token: 'Settings', modulePath: [], visibility: 'public' },
+            { token: 'Settings::load', modulePath: [], visibility: 'public' },
+            { token: 'Settings::reset', modulePath: [], visibility: 'crate' },
+            {
+              token: '<Settings as Default>::default',
+              modulePath: [],
+              visibility: 'public',
+            },
+            { token: 'helper', modulePath: [], visibility: 'private' },
+          ],
+        }),
+      ],
+      [
+        'src/internal.rs',
+        symbols({
+          definitions: [
+            { token: 'Engine', modulePath: [], visibility: 'public' },
+            { token: 'Engine::start', modulePath: [], visibility: 'public' },
+            { token: 'Worker', modulePath: [], visibility: 'public' },
+          ],
+        }),
+      ],
+      [
+        'src/main.rs',
+        symbols({
+          definitions: [{ token: 'run', modulePath: [], visibility: 'public' }],
+        }),
+      ],
+    ])
+
+    const [crate, ...others] = buildRustPublicApi(files, 'app')
+
+    expect(others).toEqual([])
+    expect(crate.crateName).toBe('app')
+    expect(crate.items.map((item) => item.path).sort()).toEqual([
+      '<app::config::Settings as Default>::default',
+      'app::Engine',
+      'app::Engine::start',
+      'app::config',
+      'app::config::Settings',
+      'app::config::Settings::load',
+      'app::ids',
+    ])
+    expect(crate.items.find((item) => item.path === 'app::Engine')).toEqual({
+      path: 'app::Engine',
+      file: 'src/internal.rs',
+      token: 'Engine',
+      reexported: true,
+    })
+  })
+})
+
+describe('diffRustPublicApi', () => {
+  it('should report added and removed item paths', () => {
+    const item = (path: string) => ({
+      path,
+      file: 'src/lib.rs',
+      token: path.split('::').at(-1) ?? path,
+      reexported: false,
+    })
+    const api = (...paths: string[]): RustCrateApi[] => [
+      { crateName: 'app', crateKey: '.', items: paths.map(item) },
+    ]
+
+    expect(
+      diffRustPublicApi(
+        api('app::Config', 'app::Config::load'),
+        api('app::Config', 'app::Config::reload'),
+      ),
+    ).toEqual({
+      added: [item('app::Config::reload')],
+      removed: [item('app::Config::load')],
+    })
+    expect(diffRustPublicApi(api('app::Config'), api('app::Config'))).toEqual({
+      added: [],
+      removed: [],
+    })
+  })
+})
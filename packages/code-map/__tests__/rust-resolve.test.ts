@@ -0,0 +1,353 @@
+import { describe, it, expect } from 'bun:test'
+
+import { buildRustModuleIndex, locateRustModule } from '../src/rust/resolve'
+import { symbols } from './helpers'
+
+import type { CargoCrate, CargoWorkspace } from '../src/rust/cargo'
+import type { RustReference } from '../src/rust/file-symbols'
+
+function ref(
+  path: string[],
+  token = path.slice(-2).join('::'),
+): RustReference {
+  return { token, path, modulePath: [], kind: 'path' }
+}
+
+function member(
+  name: string,
+  dependencies: CargoCrate['dependencies'] = [],
+): CargoCrate {
+  return {
+    name,
+    manifestPath: `crates/${name}/Cargo.toml`,
+    rootDir: `crates/${name}`,
+    targets: [{ kind: 'lib', name, path: `crates/${name}/src/lib.rs` }],
+    features: {},
+    dependencies,
+  }
+}
+
+describe('locateRustModule', () => {
+  it('should map conventional Cargo layouts to module paths', () => {
+    expect(locateRustModule('src/lib.rs', 'app')).toEqual({
+      crateKey: '.',
+      crateName: 'app',
+      modulePath: [],
+      isLibrary: true,
+    })
+    expect(locateRustModule('crates/my-core/src/net/mod.rs', 'app')).toEqual({
+      crateKey: 'crates/my-core',
+      crateName: 'my_core',
+      modulePath: ['net'],
+      isLibrary: true,
+    })
+    expect(
+      locateRustModule('crates/my-core/src/net/tcp.rs', 'app').modulePath,
+    ).toEqual(['net', 'tcp'])
+  })
+
+  it('should treat binaries, tests and examples as separate crate roots', () => {
+    const bin = locateRustModule('src/bin/tool.rs', 'app')
+    expect(bin.crateKey).toBe('src/bin/tool')
+    expect(bin.isLibrary).toBe(false)
+
+    const test = locateRustModule('tests/integration.rs', 'app')
+    expect(test.crateKey).toBe('tests/integration')
+    expect(test.modulePath).toEqual([])
+  })
+})
+
+describe('RustModuleIndex', () => {
+  it('should attribute same-named items to the module in scope', () => {
+    const files = new Map([
+      ['src/lib.rs', symbols({ modules: [['a'], ['b']] })],
+      [
+        'src/a.rs',
+        symbols({
+          definitions: [
+            { token: 'Config', modulePath: [] },
+            { token: 'Config::new', modulePath: [] },
+          ],
+        }),
+      ],
+      [
+        'src/b.rs',
+        symbols({
+          definitions: [
+            { token: 'Config', modulePath: [] },
+            { token: 'Config::new', modulePath: [] },
+          ],
+        }),
+      ],
+      [
+        'src/main_loop.rs',
+        symbols({
+          imports: [
+            {
+              modulePath: [],
+              alias: 'Config',
+              path: ['crate', 'b', 'Config'],
+              isGlob: false,
+              visibility: 'private',
+            },
+          ],
+        }),
+      ],
+    ])
+    const index = buildRustModuleIndex(files, 'app')
+
+    expect(
+      index.resolve('src/main_loop.rs', ref(['Config', 'new'])),
+    ).toEqual({ file: 'src/b.rs', token: 'Config::new' })
+    expect(
+      index.resolve('src/main_loop.rs', ref(['crate', 'a', 'Config', 'new'])),
+    ).toEqual({ file: 'src/a.rs', token: 'Config::new' })
+    expect(
+      index.resolve('src/a.rs', ref(['super', 'b', 'Config'], 'Config')),
+    ).toEqual({ file: 'src/b.rs', token: 'Config' })
+  })
+
+  it('should follow pub use re-exports and glob imports', () => {
+    const files = new Map([
+      [
+        'src/lib.rs',
+        symbols({
+          modules: [['inner']],
+          imports: [
+            {
+              modulePath: [],
+              alias: 'Engine',
+              path: ['inner', 'Engine'],
+              isGlob: false,
+              visibility: 'public',
+            },
+          ],
+        }),
+      ],
+      [
+        'src/inner.rs',
+        symbols({ definitions: [{ token: 'Engine', modulePath: [] }] }),
+      ],
+      [
+        'src/prelude.rs',
+        symbols({
+          imports: [
+            {
+              modulePath: [],
+              alias: undefined,
+              path: ['crate', 'inner'],
+              isGlob: true,
+              visibility: 'private',
+            },
+          ],
+        }),
+      ],
+    ])
+    const index = buildRustModuleIndex(files, 'app')
+
+    expect(
+      index.resolve('src/prelude.rs', ref(['crate', 'Engine'], 'Engine')),
+    ).toEqual({ file: 'src/inner.rs', token: 'Engine' })
+    expect(index.resolve('src/prelude.rs', ref(['Engine'], 'Engine'))).toEqual(
+      { file: 'src/inner.rs', token: 'Engine' },
+    )
+  })
+
+  it('should resolve other crates by name and trait impl items by type', () => {
+    const files = new Map([
+      [
+        'crates/greet/src/lib.rs',
+        symbols({
+          definitions: [
+            { token: 'Greeting', modulePath: [] },
+            { token: '<Greeting as Greeter>::greet', modulePath: [] },
+          ],
+        }),
+      ],
+      ['crates/app/src/main.rs', symbols({})],
+    ])
+    const index = buildRustModuleIndex(files, 'workspace')
+
+    expect(
+      index.resolve(
+        'crates/app/src/main.rs',
+        ref(['greet', 'Greeting', 'greet'], 'Greeting::greet'),
+      ),
+    ).toEqual({
+      file: 'crates/greet/src/lib.rs',
+      token: '<Greeting as Greeter>::greet',
+    })
+  })
+
+  it('should only attribute method calls with a unique member name', () => {
+    const files = new Map([
+      [
+        'src/a.rs',
+        symbols({
+          definitions: [
+            { token: 'A::run', modulePath: [] },
+            { token: 'A::only_here', modulePath: [] },
+          ],
+        }),
+      ],
+      [
+        'src/b.rs',
+        symbols({ definitions: [{ token: 'B::run', modulePath: [] }] }),
+      ],
+    ])
+    const index = buildRustModuleIndex(files, 'app')
+    const method = (token: string): RustReference => ({
+      token,
+      path: [token],
+      modulePath: [],
+      kind: 'method',
+    })
+
+    expect(index.resolve('src/b.rs', method('run'))).toBeUndefined()
+    expect(index.resolve('src/b.rs', method('only_here'))).toEqual({
+      file: 'src/a.rs',
+      token: 'A::only_here',
+    })
+  })
+
+  it('should attribute trait method calls to the impl or the trait', () => {
+    const greeter = {
+      name: 'Greeter',
+      line: 1,
+      modulePath: [],
+    }
+    const method = (token: string): RustReference => ({
+      token,
+      path: [token],
+      modulePath: [],
+      kind: 'method',
+    })
+    const oneImpl = buildRustModuleIndex(
+      new Map([
+        [
+          'src/lib.rs',
+          symbols({
+            definitions: [
+              { token: 'Greeter', modulePath: [] },
+              { token: 'Greeter::greet', modulePath: [] },
+              { token: '<Greeting as Greeter>::greet', modulePath: [] },
+            ],
+            traits: [greeter],
+          }),
+        ],
+        ['src/main.rs', symbols({})],
+      ]),
+      'app',
+    )
+    const twoImpls = buildRustModuleIndex(
+      new Map([
+        [
+          'src/lib.rs',
+          symbols({
+            definitions: [
+              { token: 'Greeter::greet', modulePath: [] },
+              { token: '<Greeting as Greeter>::greet', modulePath: [] },
+              { token: '<Farewell as Greeter>::greet', modulePath: [] },
+            ],
+            traits: [greeter],
+          }),
+        ],
+        ['src/main.rs', symbols({})],
+      ]),
+      'app',
+    )
+    const withInherent = buildRustModuleIndex(
+      new Map([
+        [
+          'src/lib.rs',
+          symbols({
+            definitions: [
+              { token: 'Greeter::greet', modulePath: [] },
+              { token: '<Greeting as Greeter>::greet', modulePath: [] },
+              { token: '<Farewell as Greeter>::greet', modulePath: [] },
+              { token: 'Crowd::greet', modulePath: [] },
+            ],
+            traits: [greeter],
+          }),
+        ],
+        ['src/main.rs', symbols({})],
+      ]),
+      'app',
+    )
+
+    expect(oneImpl.resolve('src/main.rs', method('greet'))).toEqual({
+      file: 'src/lib.rs',
+      token: '<Greeting as Greeter>::greet',
+    })
+    // Several impls of the trait: the call goes through the trait
+    expect(twoImpls.resolve('src/main.rs', method('greet'))).toEqual({
+      file: 'src/lib.rs',
+      token: 'Greeter::greet',
+    })
+    // An inherent method of the same name may be called instead
+    expect(withInherent.resolve('src/main.rs', method('greet'))).toBeUndefined()
+  })
+
+  it('should only cross crate boundaries along declared dependencies', () => {
+    const workspace: CargoWorkspace = {
+      rootManifestPath: 'Cargo.toml',
+      members: [
+        member('core'),
+        member('other'),
+        member('app', [
+          {
+            name: 'engine_core',
+            package: 'core',
+            kind: 'normal',
+            optional: false,
+            workspaceMember: 'core',
+          },
+        ]),
+      ],
+    }
+    const engine = symbols({
+      definitions: [
+        { token: 'Engine', modulePath: [] },
+        { token: 'Engine::start', modulePath: [] },
+      ],
+    })
+    const files = new Map([
+      ['crates/core/src/lib.rs', engine],
+      ['crates/core/tests/it.rs', symbols({})],
+      ['crates/other/src/lib.rs', engine],
+      [
+        'crates/app/src/main.rs',
+        symbols({
+          imports: [
+            {
+              modulePath: [],
+              alias: 'ec',
+              path: ['engine_core'],
+              isGlob: false,
+              visibility: 'private',
+            },
+          ],
+        }),
+      ],
+    ])
+    const index = buildRustModuleIndex(files, 'workspace', workspace)
+    const app = 'crates/app/src/main.rs'
+    const core = { file: 'crates/core/src/lib.rs', token: 'Engine::start' }
+
+    expect(index.resolve(app, ref(['engine_core', 'Engine', 'start']))).toEqual(
+      core,
+    )
+    expect(index.resolve(app, ref(['ec', 'Engine', 'start']))).toEqual(core)
+    // Not a dependency, and `core` is only visible under its renamed name
+    expect(index.resolve(app, ref(['other', 'Engine', 'start']))).toBeUndefined()
+    expect(index.resolve(app, ref(['core', 'Engine', 'start']))).toBeUndefined()
+    // Integration tests see their own package's library
+    expect(
+      index.resolve('crates/core/tests/it.rs', ref(['core', 'Engine', 'start'])),
+    ).toEqual(core)
+
+    expect(index.canReference(app, 'crates/core/src/lib.rs')).toBe(true)
+    expect(index.canReference(app, 'crates/other/src/lib.rs')).toBe(false)
+    expect(index.canReference('crates/core/src/lib.rs', app)).toBe(false)
+  })
+})
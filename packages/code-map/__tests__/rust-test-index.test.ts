@@ -0,0 +1,186 @@
+import { describe, it, expect } from 'bun:test'
+
+import {
+  buildRustTestIndex,
+  findAffectedRustTests,
+  getRustTestCommands,
+} from '../src/rust/test-index'
+import { symbols } from './helpers'
+
+import type { CargoWorkspace } from '../src/rust/cargo'
+import type { RustFileSymbols, RustReference } from '../src/rust/file-symbols'
+
+function ref(
+  path: string[],
+  line: number,
+  modulePath: string[] = [],
+): RustReference {
+  return {
+    token: path.slice(-2).join('::'),
+    path,
+    modulePath,
+    kind: 'path',
+    range: { startLine: line, startColumn: 5, endLine: line, endColumn: 9 },
+  }
+}
+
+function testFunction(
+  name: string,
+  startLine: number,
+  modulePath: string[] = [],
+) {
+  return {
+    name,
+    modulePath,
+    startLine,
+    endLine: startLine + 4,
+    attribute: '#[test]',
+  }
+}
+
+const files = new Map<string, RustFileSymbols>([
+  [
+    'src/lib.rs',
+    symbols({
+      modules: [['config']],
+      definitions: [{ token: 'parse', modulePath: [], visibility: 'public' }],
+      docTests: [{ token: 'parse', modulePath: [], line: 3 }],
+    }),
+  ],
+  [
+    'src/config.rs',
+    symbols({
+      definitions: [
+        { token: 'Config', modulePath: [], visibility: 'public' },
+        { token: 'Config::load', modulePath: [], visibility: 'public' },
+        { token: 'fixture', modulePath: ['tests'], visibility: 'private' },
+        { token: 'loads', modulePath: ['tests'], visibility: 'private' },
+      ],
+      imports: [
+        {
+          modulePath: ['tests'],
+          alias: undefined,
+          path: ['super'],
+          isGlob: true,
+          visibility: 'private',
+        },
+      ],
+      modules: [['tests']],
+      testModules: [['tests']],
+      tests: [testFunction('loads', 20, ['tests'])],
+      references: [
+        ref(['Config', 'load'], 8),
+        ref(['fixture'], 21, ['tests']),
+        ref(['Config', 'load'], 22, ['tests']),
+      ],
+    }),
+  ],
+  [
+    'tests/cli.rs',
+    symbols({
+      tests: [{ ...testFunction('runs', 4), attribute: '#[tokio::test]' }],
+      references: [ref(['app', 'parse'], 5)],
+    }),
+  ],
+  ['src/bin/tool.rs', symbols({ tests: [testFunction('smoke', 1)] })],
+])
+
+describe('buildRustTestIndex', () => {
+  it('should index unit, integration and doc-tests with their targets', () => {
+    const { tests } = buildRustTestIndex(files, 'app')
+
+    expect(
+      tests.map(({ path, kind, target }) => ({ path, kind, target })),
+    ).toEqual([
+      { path: 'parse', kind: 'doc', target: { kind: 'lib' } },
+      {
+        path: 'config::tests::loads',
+        kind: 'unit',
+        target: { kind: 'lib' },
+      },
+      {
+        path: 'runs',
+        kind: 'integration',
+        target: { kind: 'test', name: 'cli' },
+      },
+      { path: 'smoke', kind: 'unit', target: { kind: 'bin', name: 'tool' } },
+    ])
+  })
+
+  it('should record the non-test items each test references', () => {
+    const { tests } = buildRustTestIndex(files, 'app')
+    const referencesOf = (path: string) =>
+      tests.find((test) => test.path === path)?.references
+
+    expect(referencesOf('config::tests::loads')).toEqual([
+      { file: 'src/config.rs', token: 'Config::load' },
+    ])
+    expect(referencesOf('runs')).toEqual([
+      { file: 'src/lib.rs', token: 'parse' },
+    ])
+  })
+
+  it('should take packages and target names from the workspace', () => {
+    const workspace: CargoWorkspace = {
+      rootManifestPath: 'Cargo.toml',
+      members: [
+        {
+          name: 'my-app',
+          manifestPath: 'Cargo.toml',
+          rootDir: '',
+          targets: [
+            { kind: 'lib', name: 'app', path: 'src/lib.rs' },
+            { kind: 'test', name: 'cli-smoke', path: 'tests/cli.rs' },
+          ],
+          features: {},
+          dependencies: [],
+        },
+      ],
+    }
+    const { tests } = buildRustTestIndex(files, 'app', workspace)
+    const runs = tests.find((test) => test.path === 'runs')
+
+    expect(runs).toMatchObject({
+      package: 'my-app',
+      target: { kind: 'test', name: 'cli-smoke' },
+    })
+    expect(runs?.references).toEqual([{ file: 'src/lib.rs', token: 'parse' }])
+  })
+})
+
+describe('findAffectedRustTests', () => {
+  const index = buildRustTestIndex(files, 'app')
+  const affected = (changedFiles: string[]) =>
+    findAffectedRustTests(index, changedFiles).map((test) => test.path)
+
+  it('should select tests in and depending on the changed files', () => {
+    expect(affected(['src/lib.rs'])).toEqual(['parse', 'runs'])
+    expect(affected(['./src/config.rs'])).toEqual(['config::tests::loads'])
+    expect(affected(['README.md'])).toEqual([])
+  })
+})
+
+describe('getRustTestCommands', () => {
+  const { tests } = buildRustTestIndex(files, 'app')
+
+  it('should group tests by package and target', () => {
+    expect(getRustTestCommands(tests)).toEqual([
+      'cargo test --doc parse',
+      'cargo test --lib config::tests::loads',
+      'cargo test --test cli runs',
+      'cargo test --bin tool smoke',
+    ])
+  })
+
+  it('should pass several filters to libtest', () => {
+    const unit = tests[1]
+    expect(
+      getRustTestCommands([
+        { ...unit, package: 'app' },
+        { ...unit, package: 'app', path: 'config::tests::saves' },
+      ]),
+    ).toEqual([
+      'cargo test -p app --lib -- config::tests::loads config::tests::saves',
+    ])
+  })
+})
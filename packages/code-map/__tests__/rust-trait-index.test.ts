@@ -0,0 +1,113 @@
+import { describe, it, expect } from 'bun:test'
+
+import { loadCargoWorkspace } from '../src/rust/cargo'
+import { buildRustTraitIndex } from '../src/rust/trait-index'
+import { symbols } from './helpers'
+
+import type { RustFileSymbols } from '../src/rust/file-symbols'
+
+describe('buildRustTraitIndex', () => {
+  const files = new Map<string, RustFileSymbols>([
+    [
+      'src/greet.rs',
+      symbols({
+        traits: [{ name: 'Greeter', line: 1, modulePath: ['greet'] }],
+        impls: [
+          {
+            typeName: 'English',
+            traitName: 'Greeter',
+            header: 'impl Greeter for English',
+            line: 5,
+            isBlanket: false,
+          },
+          {
+            typeName: 'English',
+            traitName: undefined,
+            header: 'impl English',
+            line: 9,
+            isBlanket: false,
+          },
+          {
+            typeName: 'T',
+            traitName: 'Greeter',
+            header: 'impl<T: Display> Greeter for T',
+            line: 13,
+            isBlanket: true,
+          },
+        ],
+      }),
+    ],
+    [
+      'src/model.rs',
+      symbols({
+        impliedImpls: [
+          {
+            typeName: 'English',
+            traitName: 'Debug',
+            traitPath: ['Debug'],
+            line: 2,
+            origin: 'derive',
+          },
+          {
+            typeName: 'French',
+            traitName: 'Greeter',
+            traitPath: ['crate', 'greet', 'Greeter'],
+            line: 8,
+            origin: 'macro',
+          },
+        ],
+      }),
+    ],
+  ])
+
+  it('should list every impl of a trait, including blanket and macro impls', () => {
+    const index = buildRustTraitIndex(files)
+
+    expect(index.traits).toEqual({
+      Greeter: [{ file: 'src/greet.rs', line: 1 }],
+    })
+    expect(
+      index.implementors.Greeter.map(({ typeName, isBlanket, origin }) => ({
+        typeName,
+        isBlanket,
+        origin,
+      })),
+    ).toEqual([
+      { typeName: 'English', isBlanket: false, origin: 'impl' },
+      { typeName: 'T', isBlanket: true, origin: 'impl' },
+      { typeName: 'French', isBlanket: false, origin: 'macro' },
+    ])
+  })
+
+  it('should list traits implemented for a type, excluding blanket impls and inherent impls', () => {
+    const index = buildRustTraitIndex(files)
+
+    expect(
+      index.implementedTraits.English.map(({ traitName, file, line }) => ({
+        traitName,
+        file,
+        line,
+      })),
+    ).toEqual([
+      { traitName: 'Greeter', file: 'src/greet.rs', line: 5 },
+      { traitName: 'Debug', file: 'src/model.rs', line: 2 },
+    ])
+    expect(index.implementedTraits.T).toBeUndefined()
+  })
+
+  it('should attribute impls to the workspace crate declaring them', () => {
+    const workspace = loadCargoWorkspace({
+      'Cargo.toml': '[package]\nname = "greetings"\n',
+    })
+    const { implementors } = buildRustTraitIndex(files, workspace)
+
+    expect(implementors.Greeter.map(({ crateName }) => crateName)).toEqual([
+      'greetings',
+      'greetings',
+      'greetings',
+    ])
+    expect(
+      buildRustTraitIndex(files).implementors.Greeter[0].crateName,
+    ).toBeUndefined()
+  })
+})
@@ -0,0 +1,406 @@
+import { describe, it, expect } from 'bun:test'
+
+import { parseRustdocJson, toRustCrateDocs } from '../src/rust/rustdoc'
+
+const noGenerics = { params: [], where_predicates: [] }
+const span = (filename: string, line: number) => ({
+  filename,
+  begin: [line, 0],
+  end: [line, 1],
+})
+const item = ({
+  id,
+  name = null,
+  ...rest
+}: {
+  id: number
+  name?: string | null
+  inner: unknown
+  [field: string]: unknown
+}) => ({
+  id,
+  crate_id: 0,
+  name,
+  span: span('src/lib.rs', id),
+  visibility: 'public',
+  docs: null,
+  links: {},
+  attrs: [],
+  deprecation: null,
+  ...rest,
+})
+const reference = (type: unknown) => ({
+  borrowed_ref: { lifetime: null, is_mutable: false, type },
+})
+const selfRef = reference({ generic: 'Self' })
+const strRef = reference({ primitive: 'str' })
+const valueType = { resolved_path: { path: 'Value', id: 1, args: null } }
+const header = { is_const: false, is_unsafe: false, is_async: false }
+
+/** `cargo rustdoc -- --output-format json` output of a tiny crate */
+const rustdocJson = JSON.stringify({
+  root: 0,
+  crate_version: '0.3.1',
+  includes_private: false,
+  format_version: 39,
+  index: {
+    0: item({
+      id: 0,
+      name: 'tinyjson',
+      docs: 'A tiny JSON library.',
+      inner: { module: { is_crate: true, items: [1, 7] } },
+    }),
+    1: item({
+      id: 1,
+      name: 'Value',
+      inner: {
+        enum: {
+          generics: noGenerics,
+          has_stripped_variants: false,
+          variants: [2, 3],
+          impls: [5, 8, 13],
+        },
+      },
+    }),
+    2: item({
+      id: 2,
+      name: 'Null',
+      inner: { variant: { kind: 'plain', discriminant: null } },
+    }),
+    3: item({
+      id: 3,
+      name: 'Str',
+      inner: { variant: { kind: { tuple: [4] }, discriminant: null } },
+    }),
+    4: item({
+      id: 4,
+      name: '0',
+      visibility: 'default',
+      inner: { struct_field: { resolved_path: { path: 'String', id: 30 } } },
+    }),
+    5: item({
+      id: 5,
+      inner: {
+        impl: {
+          is_unsafe: false,
+          generics: noGenerics,
+          trait: null,
+          for: valueType,
+          items: [6],
+          is_negative: false,
+          is_synthetic: false,
+          blanket_impl: null,
+        },
+      },
+    }),
+    6: item({
+      id: 6,
+      name: 'as_str',
+      docs: 'Returns the string if the value is a JSON string.',
+      inner: {
+        function: {
+          sig: {
+            inputs: [['self', selfRef]],
+            output: {
+              resolved_path: {
+                path: 'Option',
+                id: 31,
+                args: {
+                  angle_bracketed: {
+                    args: [{ type: strRef }],
+                    constraints: [],
+                  },
+                },
+              },
+            },
+          },
+          generics: noGenerics,
+          header,
+        },
+      },
+    }),
+    7: item({
+      id: 7,
+      name: 'parse',
+      deprecation: { since: '0.3.0', note: 'use `from_str`' },
+      inner: {
+        function: {
+          sig: {
+            inputs: [['input', strRef]],
+            output: {
+              resolved_path: {
+                path: 'Result',
+                id: 32,
+                args: {
+                  angle_bracketed: {
+                    args: [
+                      { type: valueType },
+                      { type: { resolved_path: { path: 'Error', id: 14 } } },
+                    ],
+                    constraints: [],
+                  },
+                },
+              },
+            },
+          },
+          generics: noGenerics,
+          header,
+        },
+      },
+    }),
+    8: item({
+      id: 8,
+      span: span('src/value.rs', 20),
+      inner: {
+        impl: {
+          is_unsafe: false,
+          generics: noGenerics,
+          trait: { path: 'Display', id: 20, args: null },
+          for: valueType,
+          items: [9],
+          is_negative: false,
+          is_synthetic: false,
+          blanket_impl: null,
+        },
+      },
+    }),
+    9: item({
+      id: 9,
+      name: 'fmt',
+      visibility: 'default',
+      inner: { function: { sig: { inputs: [] }, generics: noGenerics } },
+    }),
+    10: item({
+      id: 10,
+      name: 'ToJson',
+      inner: {
+        trait: {
+          is_auto: false,
+          is_unsafe: false,
+          items: [11],
+          generics: noGenerics,
+          bounds: [],
+          implementations: [12],
+        },
+      },
+    }),
+    11: item({
+      id: 11,
+      name: 'to_json',
+      visibility: 'default',
+      inner: {
+        function: {
+          sig: { inputs: [['self', selfRef]], output: valueType },
+          generics: noGenerics,
+          header,
+        },
+      },
+    }),
+    12: item({
+      id: 12,
+      inner: {
+        impl: {
+          is_unsafe: false,
+          generics: {
+            params: [
+              {
+                name: 'T',
+                kind: {
+                  type: {
+                    bounds: [
+                      {
+                        trait_bound: {
+                          trait: { path: 'Display', id: 20, args: null },
+                          generic_params: [],
+                          modifier: 'none',
+                        },
+                      },
+                    ],
+                    default: null,
+                    is_synthetic: false,
+                  },
+                },
+              },
+            ],
+            where_predicates: [],
+          },
+          trait: { path: 'ToJson', id: 10, args: null },
+          for: { generic: 'T' },
+          items: [],
+          is_negative: false,
+          is_synthetic: false,
+          blanket_impl: null,
+        },
+      },
+    }),
+    13: item({
+      id: 13,
+      inner: {
+        impl: {
+          is_unsafe: false,
+          generics: noGenerics,
+          trait: { path: 'Send', id: 33, args: null },
+          for: valueType,
+          items: [],
+          is_negative: false,
+          is_synthetic: true,
+          blanket_impl: null,
+        },
+      },
+    }),
+    14: item({
+      id: 14,
+      name: 'Error',
+      inner: {
+        struct: {
+          kind: { plain: { fields: [15], has_stripped_fields: true } },
+          generics: noGenerics,
+          impls: [16],
+        },
+      },
+    }),
+    15: item({
+      id: 15,
+      name: 'line',
+      inner: { struct_field: { primitive: 'usize' } },
+    }),
+    16: item({
+      id: 16,
+      attrs: ['#[automatically_derived]'],
+      inner: {
+        impl: {
+          is_unsafe: false,
+          generics: noGenerics,
+          trait: { path: 'Clone', id: 34, args: null },
+          for: { resolved_path: { path: 'Error', id: 14, args: null } },
+          items: [],
+          is_negative: false,
+          is_synthetic: false,
+          blanket_impl: null,
+        },
+      },
+    }),
+  },
+  paths: {
+    0: { crate_id: 0, path: ['tinyjson'], kind: 'module' },
+    1: { crate_id: 0, path: ['tinyjson', 'Value'], kind: 'enum' },
+    7: { crate_id: 0, path: ['tinyjson', 'parse'], kind: 'function' },
+    10: { crate_id: 0, path: ['tinyjson', 'ToJson'], kind: 'trait' },
+    14: { crate_id: 0, path: ['tinyjson', 'Error'], kind: 'struct' },
+    20: { crate_id: 1, path: ['core', 'fmt', 'Display'], kind: 'trait' },
+    34: { crate_id: 1, path: ['core', 'clone', 'Clone'], kind: 'trait' },
+  },
+  external_crates: { 1: { name: 'core', html_root_url: null } },
+})
+
+describe('parseRustdocJson', () => {
+  const index = parseRustdocJson(rustdocJson)
+
+  it('should index public items with rendered signatures', () => {
+    expect(index.crateName).toBe('tinyjson')
+    expect(index.version).toBe('0.3.1')
+    expect(index.docs).toBe('A tiny JSON library.')
+    expect(
+      index.items.map(({ path, kind, signature }) => ({
+        path,
+        kind,
+        signature,
+      })),
+    ).toEqual([
+      {
+        path: 'tinyjson::Error',
+        kind: 'struct',
+        signature:
+          'pub struct Error {\n    pub line: usize,\n    /* private fields */\n}',
+      },
+      {
+        path: 'tinyjson::parse',
+        kind: 'fn',
+        signature: 'pub fn parse(input: &str) -> Result<Value, Error>',
+      },
+      {
+        path: 'tinyjson::ToJson',
+        kind: 'trait',
+        signature: 'pub trait ToJson',
+      },
+      {
+        path: 'tinyjson::ToJson::to_json',
+        kind: 'fn',
+        signature: 'fn to_json(&self) -> Value',
+      },
+      {
+        path: 'tinyjson::Value',
+        kind: 'enum',
+        signature: 'pub enum Value {\n    Null,\n    Str(String),\n}',
+      },
+      {
+        path: 'tinyjson::Value::as_str',
+        kind: 'fn',
+        signature: 'pub fn as_str(&self) -> Option<&str>',
+      },
+    ])
+  })
+
+  it('should keep docs, deprecations and source locations', () => {
+    const parse = index.items.find((item) => item.path === 'tinyjson::parse')
+    const asStr = index.items.find(
+      (item) => item.path === 'tinyjson::Value::as_str',
+    )
+
+    expect(parse?.deprecation).toBe('Deprecated since 0.3.0: use `from_str`')
+    expect(asStr).toMatchObject({
+      docs: 'Returns the string if the value is a JSON string.',
+      file: 'src/lib.rs',
+      line: 6,
+    })
+  })
+
+  it('should list declared trait impls without synthetic ones', () => {
+    expect(index.traitImpls).toEqual([
+      {
+        traitName: 'Display',
+        typeName: 'Value',
+        file: 'src/value.rs',
+        line: 20,
+        header: 'impl core::fmt::Display for Value',
+        isBlanket: false,
+        origin: 'impl',
+        crateName: 'tinyjson',
+      },
+      {
+        traitName: 'ToJson',
+        typeName: 'T',
+        file: 'src/lib.rs',
+        line: 12,
+        header: 'impl<T: Display> tinyjson::ToJson for T',
+        isBlanket: true,
+        origin: 'impl',
+        crateName: 'tinyjson',
+      },
+      {
+        traitName: 'Clone',
+        typeName: 'Error',
+        file: 'src/lib.rs',
+        line: 16,
+        header: 'impl core::clone::Clone for Error',
+        isBlanket: false,
+        origin: 'derive',
+        crateName: 'tinyjson',
+      },
+    ])
+  })
+
+  it('should convert to crate docs', () => {
+    expect(toRustCrateDocs(index)).toMatchObject({
+      crateName: 'tinyjson',
+      docs: 'A tiny JSON library.',
+      examples: [],
+    })
+  })
+
+  it('should reject other JSON', () => {
+    expect(() => parseRustdocJson('{"name": "app"}')).toThrow(
+      'Not a rustdoc JSON file',
+    )
+  })
+})
@@ -0,0 +1,24 @@
+#ifndef GREETER_H
+#define GREETER_H
+
+// Table of function pointers standing in for a trait
+typedef struct GreeterVTable {
+    char* (*greet)(const void* self, const char* name);
+} GreeterVTable;
+
+// Struct definition
+struct Greeting {
+    char* prefix;
+};
+
+enum GreetingStyle {
+    GREETING_PLAIN,
+    GREETING_EXCITED,
+};
+
+// Functions exported to Rust through FFI
+struct Greeting* greeting_new(const char* prefix);
+char* greeting_greet(const struct Greeting* greeting, const char* name);
+void greeting_free(struct Greeting* greeting);
+
+#endif
@@ -1,5 +1,8 @@
 // Trait definition
 trait Greeter {
+    type Output;
+    const PUNCTUATION: &'static str;
+
     fn greet(&self, name: &str) -> String;
 }
 
@@ -9,16 +12,25 @@ struct Greeting {
 }
 
 impl Greeting {
+    const DEFAULT_PREFIX: &'static str = "Hello";
+
     fn new(prefix: &str) -> Self {
         Greeting {
             prefix: prefix.to_string(),
         }
     }
+
+    fn default_greeting() -> Self {
+        Self::new(Self::DEFAULT_PREFIX)
+    }
 }
 
 impl Greeter for Greeting {
+    type Output = String;
+    const PUNCTUATION: &'static str = "!";
+
     fn greet(&self, name: &str) -> String {
-        format!("{}, {}!", self.prefix, name)
+        format!("{}, {}{}", self.prefix, name, Self::PUNCTUATION)
     }
 }
 
@@ -1,3 +1,15 @@
 import './types'
 export * from './parse'
+export * from './parse-cache'
 export * from './languages'
+export * from './navigation'
+export * from './repo-map'
+export * from './source-range'
+export * from './syntax'
+export * from './rust/public-api'
+export * from './rust/outline'
+export * from './rust/edit'
+export * from './rust/test-index'
+export * from './rust/diagnostics'
+export * from './rust/crate-docs'
+export * from './rust/rustdoc'
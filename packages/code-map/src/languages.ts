@@ -1,3 +1,4 @@
+import { createHash } from 'crypto'
 import * as fs from 'fs'
 import * as path from 'path'
 
@@ -8,33 +9,46 @@ import { Language, Parser, Query } from 'web-tree-sitter'
 
 import { initTreeSitterForNode } from './init-node'
 import { DEBUG_PARSING } from './parse'
+import { qualifyRustCapture } from './rust/symbols'
 
 /* ------------------------------------------------------------------ */
 /* 1. Query imports (these work in all bundled environments)         */
 /* ------------------------------------------------------------------ */
+import cQuery from './tree-sitter-queries/tree-sitter-c-tags.scm'
 import csharpQuery from './tree-sitter-queries/tree-sitter-c_sharp-tags.scm'
 import cppQuery from './tree-sitter-queries/tree-sitter-cpp-tags.scm'
 import goQuery from './tree-sitter-queries/tree-sitter-go-tags.scm'
 import javaQuery from './tree-sitter-queries/tree-sitter-java-tags.scm'
 import javascriptQuery from './tree-sitter-queries/tree-sitter-javascript-tags.scm'
+import phpQuery from './tree-sitter-queries/tree-sitter-php-tags.scm'
 import pythonQuery from './tree-sitter-queries/tree-sitter-python-tags.scm'
 import rubyQuery from './tree-sitter-queries/tree-sitter-ruby-tags.scm'
 import rustQuery from './tree-sitter-queries/tree-sitter-rust-tags.scm'
 import typescriptQuery from './tree-sitter-queries/tree-sitter-typescript-tags.scm'
 import { getDirnameDynamically } from './utils'
 
+import type { Node } from 'web-tree-sitter'
+
 /* ------------------------------------------------------------------ */
 /* 2. Types and interfaces                                           */
 /* ------------------------------------------------------------------ */
 export interface LanguageConfig {
+  /** File name suffixes, which may span several dots, e.g. `.rs.in` */
   extensions: string[]
+  /** Matches files by name regardless of their extension */
+  matchFileName?: (fileName: string) => boolean
+  /** Name of a bundled WASM file, or an absolute path */
   wasmFile: string
   queryPathOrContent: string
+  /** Maps a capture to the token name it is recorded under (defaults to the node text) */
+  qualifyCapture?: (captureName: string, node: Node) => string
 
   /* Loaded lazily ↓ */
   parser?: Parser
   query?: Query
   language?: Language
+  /** Hash of the grammar and query, part of the key of cached parse results */
+  cacheKey?: string
 }
 
 export interface RuntimeLanguageLoader {
@@ -51,6 +65,7 @@ export const WASM_FILES = {
   'tree-sitter-go.wasm': 'tree-sitter-go.wasm',
   'tree-sitter-java.wasm': 'tree-sitter-java.wasm',
   'tree-sitter-javascript.wasm': 'tree-sitter-javascript.wasm',
+  'tree-sitter-php.wasm': 'tree-sitter-php.wasm',
   'tree-sitter-python.wasm': 'tree-sitter-python.wasm',
   'tree-sitter-ruby.wasm': 'tree-sitter-ruby.wasm',
   'tree-sitter-rust.wasm': 'tree-sitter-rust.wasm',
@@ -63,7 +78,7 @@ export const WASM_FILES = {
 /* ------------------------------------------------------------------ */
 export const languageTable: LanguageConfig[] = [
   {
-    extensions: ['.ts'],
+    extensions: ['.ts', '.mts', '.cts'],
     wasmFile: WASM_FILES['tree-sitter-typescript.wasm'],
     queryPathOrContent: typescriptQuery,
   },
@@ -73,12 +88,12 @@ export const languageTable: LanguageConfig[] = [
     queryPathOrContent: typescriptQuery,
   },
   {
-    extensions: ['.js', '.jsx'],
+    extensions: ['.js', '.jsx', '.mjs', '.cjs'],
     wasmFile: WASM_FILES['tree-sitter-javascript.wasm'],
     queryPathOrContent: javascriptQuery,
   },
   {
-    extensions: ['.py'],
+    extensions: ['.py', '.pyi'],
     wasmFile: WASM_FILES['tree-sitter-python.wasm'],
     queryPathOrContent: pythonQuery,
   },
@@ -93,14 +108,24 @@ export const languageTable: LanguageConfig[] = [
     queryPathOrContent: csharpQuery,
   },
   {
-    extensions: ['.cpp', '.hpp'],
+    extensions: ['.cpp', '.cc', '.cxx', '.hpp', '.hh', '.hxx'],
     wasmFile: WASM_FILES['tree-sitter-cpp.wasm'],
     queryPathOrContent: cppQuery,
   },
   {
-    extensions: ['.rs'],
+    // There is no separate C grammar in @vscode/tree-sitter-wasm; the C++
+    // grammar extends the C one and parses C sources. `.h` headers get the C
+    // query, since the C++ one also captures struct forward declarations
+    extensions: ['.c', '.h'],
+    wasmFile: WASM_FILES['tree-sitter-cpp.wasm'],
+    queryPathOrContent: cQuery,
+  },
+  {
+    // `.rs.in` files are Rust sources spliced in with `include!`
+    extensions: ['.rs', '.rs.in'],
     wasmFile: WASM_FILES['tree-sitter-rust.wasm'],
     queryPathOrContent: rustQuery,
+    qualifyCapture: qualifyRustCapture,
   },
   {
     extensions: ['.rb'],
@@ -112,10 +137,119 @@ export const languageTable: LanguageConfig[] = [
     wasmFile: WASM_FILES['tree-sitter-go.wasm'],
     queryPathOrContent: goQuery,
   },
+  {
+    extensions: ['.php'],
+    wasmFile: WASM_FILES['tree-sitter-php.wasm'],
+    queryPathOrContent: phpQuery,
+  },
 ]
 
 /* ------------------------------------------------------------------ */
-/* 5. WASM directory management                                      */
+/* 5. Registered languages                                           */
+/* ------------------------------------------------------------------ */
+export interface LanguageRegistration {
+  /**
+   * Identifies the language within its project; registering a name again
+   * replaces it
+   */
+  name: string
+  /**
+   * Project the language is configured for, e.g. by its languages.json. The
+   * language then only applies to absolute paths in that directory. Without
+   * it, the language applies to files of every project.
+   */
+  projectRoot?: string
+  /** File name suffixes, e.g. `.proto` */
+  extensions?: string[]
+  /** Matches files by name, e.g. `(fileName) => fileName === 'BUILD'` */
+  matchFileName?: (fileName: string) => boolean
+  /** Path to the grammar's WASM file */
+  wasmPath: string
+  /**
+   * Tags query capturing definitions as `@identifier` and references as
+   * `@call.identifier`, or the path to a `.scm` file containing it
+   */
+  query: string
+}
+
+/**
+ * Languages registered at runtime, checked before the built-in table. Keyed
+ * by project root, with languages of every project under `''`, then by name.
+ */
+const registeredLanguages = new Map<string, Map<string, LanguageConfig>>()
+
+function getProjectKey(projectRoot: string | undefined): string {
+  return projectRoot ? path.resolve(projectRoot) : ''
+}
+
+/**
+ * Adds a tree-sitter grammar for files the built-in languages don't cover,
+ * or replaces the built-in grammar for some extensions. Relative paths are
+ * resolved against the current working directory.
+ */
+export function registerLanguage(registration: LanguageRegistration): void {
+  const { name, extensions = [], matchFileName, wasmPath, query } = registration
+  const projectKey = getProjectKey(registration.projectRoot)
+  if (extensions.length === 0 && !matchFileName) {
+    throw new Error(
+      `Language "${name}" needs extensions or a file name matcher`,
+    )
+  }
+  const isQueryFile = !query.includes('\n') && query.trimEnd().endsWith('.scm')
+  const config: LanguageConfig = {
+    extensions,
+    matchFileName,
+    wasmFile: path.resolve(wasmPath),
+    queryPathOrContent: isQueryFile ? path.resolve(query.trim()) : query,
+  }
+  const languages = registeredLanguages.get(projectKey) ?? new Map()
+  registeredLanguages.set(projectKey, languages)
+  // Keep the grammar loaded for an identical earlier registration
+  const previous = languages.get(name)
+  if (
+    previous?.wasmFile === config.wasmFile &&
+    previous.queryPathOrContent === config.queryPathOrContent
+  ) {
+    const { parser, query: loadedQuery, language, cacheKey } = previous
+    Object.assign(config, { parser, query: loadedQuery, language, cacheKey })
+  }
+  languages.delete(name)
+  languages.set(name, config)
+}
+
+export function unregisterLanguage(name: string, projectRoot?: string): void {
+  const projectKey = getProjectKey(projectRoot)
+  const languages = registeredLanguages.get(projectKey)
+  languages?.delete(name)
+  if (languages?.size === 0) {
+    registeredLanguages.delete(projectKey)
+  }
+}
+
+/** Names of the languages registered for the project, or for every project */
+export function getRegisteredLanguageNames(projectRoot?: string): string[] {
+  const languages = registeredLanguages.get(getProjectKey(projectRoot))
+  return [...(languages?.keys() ?? [])]
+}
+
+/**
+ * Registered languages that apply to a file, those of its project first and
+ * later registrations before earlier ones
+ */
+function getRegisteredLanguages(filePath: string): LanguageConfig[] {
+  return [...registeredLanguages]
+    .filter(
+      ([projectKey]) =>
+        projectKey === '' ||
+        (path.isAbsolute(filePath) &&
+          filePath.startsWith(`${projectKey}${path.sep}`)),
+    )
+    .sort(([a], [b]) => b.length - a.length)
+    .flatMap(([, languages]) => [...languages.values()].reverse())
+}
+
+/* ------------------------------------------------------------------ */
+/* 6. WASM directory management                                      */
 /* ------------------------------------------------------------------ */
 let customWasmDir: string | undefined
 
@@ -132,7 +266,7 @@ export function getWasmDir(): string | undefined {
 }
 
 /* ------------------------------------------------------------------ */
-/* 6. WASM path resolver                                             */
+/* 7. WASM path resolver                                             */
 /* ------------------------------------------------------------------ */
 
 /**
@@ -140,6 +274,11 @@ export function getWasmDir(): string | undefined {
  * Works for both ESM and CJS builds of the SDK.
  */
 function resolveWasmPath(wasmFileName: string): string {
+  // Grammars registered at runtime
+  if (path.isAbsolute(wasmFileName)) {
+    return wasmFileName
+  }
+
   const customWasmDirPath = getWasmDir()
   if (customWasmDirPath) {
     return path.join(customWasmDirPath, wasmFileName)
@@ -200,12 +339,12 @@ function tryResolveFromPackage(wasmFileName: string): string | null {
 }
 
 /* ------------------------------------------------------------------ */
-/* 7. One-time library init                                          */
+/* 8. One-time library init                                          */
 /* ------------------------------------------------------------------ */
 // Initialize tree-sitter with Node.js-specific configuration
 
 /* ------------------------------------------------------------------ */
-/* 8. Unified runtime loader                                         */
+/* 9. Unified runtime loader                                         */
 /* ------------------------------------------------------------------ */
 class UnifiedLanguageLoader implements RuntimeLanguageLoader {
   private parserReady: Promise<void>
@@ -241,18 +380,48 @@ class UnifiedLanguageLoader implements RuntimeLanguageLoader {
 }
 
 /* ------------------------------------------------------------------ */
-/* 9. Helper functions                                               */
+/* 10. Helper functions                                              */
 /* ------------------------------------------------------------------ */
 export function findLanguageConfigByExtension(
   filePath: string,
 ): LanguageConfig | undefined {
-  const ext = path.extname(filePath)
-  return languageTable.find((c) => c.extensions.includes(ext))
+  const fileName = path.basename(filePath)
+  const matches = (c: LanguageConfig) =>
+    c.extensions.some((ext) => fileName.endsWith(ext) && fileName !== ext) ||
+    (c.matchFileName?.(fileName) ?? false)
+  return (
+    getRegisteredLanguages(filePath).find(matches) ??
+    languageTable.find(matches)
+  )
+}
+
+/** Whether a file is parsed as Rust, e.g. `.rs` and `.rs.in` files */
+export function isRustSourceFile(filePath: string): boolean {
+  return (
+    findLanguageConfigByExtension(filePath)?.wasmFile ===
+    WASM_FILES['tree-sitter-rust.wasm']
+  )
 }
 
 /* ------------------------------------------------------------------ */
-/* 10. Language configuration loader                                 */
+/* 11. Language configuration loader                                */
 /* ------------------------------------------------------------------ */
+/**
+ * Identifies a grammar and its query. Grammars registered at runtime are
+ * hashed by their contents, since they may be rebuilt at the same path.
+ */
+function getGrammarCacheKey(wasmFile: string, queryContent: string): string {
+  const hash = createHash('sha1').update(wasmFile).update('\0')
+  if (path.isAbsolute(wasmFile)) {
+    try {
+      hash.update(fs.readFileSync(wasmFile))
+    } catch {
+      // The loader reports missing grammars
+    }
+  }
+  return hash.update('\0').update(queryContent).digest('hex')
+}
+
 export async function createLanguageConfig(
   filePath: string,
   runtimeLoader: RuntimeLanguageLoader,
@@ -281,6 +450,7 @@ export async function createLanguageConfig(
       cfg.language = lang
       cfg.parser = parser
       cfg.query = new Query(lang, queryContent)
+      cfg.cacheKey = getGrammarCacheKey(cfg.wasmFile, queryContent)
     } catch (err) {
       // Let the runtime-specific implementation handle error logging
       throw err
@@ -291,7 +461,7 @@ export async function createLanguageConfig(
 }
 
 /* ------------------------------------------------------------------ */
-/* 11. Public API                                                    */
+/* 12. Public API                                                   */
 /* ------------------------------------------------------------------ */
 const unifiedLoader = new UnifiedLanguageLoader()
 
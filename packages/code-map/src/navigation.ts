@@ -0,0 +1,243 @@
+import * as path from 'path'
+
+import { getLanguageConfig } from './languages'
+import { parseTokens } from './parse'
+import { expandRustMacros } from './rust/macros'
+import { matchesItemPath } from './rust/outline'
+import { buildRustModuleIndex } from './rust/resolve'
+import { isSameRange, rangeContains } from './source-range'
+
+import type { ParsedTokens, TokenLocation } from './parse'
+import type { ParseCache } from './parse-cache'
+import type { CargoWorkspace } from './rust/cargo'
+import type { RustFileSymbols } from './rust/file-symbols'
+import type { RustItemSite, RustModuleIndex } from './rust/resolve'
+import type { SourceRange } from './source-range'
+
+export interface SymbolLocation {
+  file: string
+  token: string
+  range: SourceRange
+}
+
+export interface SymbolMatch extends SymbolLocation {
+  /**
+   * Whether the match was resolved through Rust module paths and imports.
+   * Other matches only share the symbol's name.
+   */
+  resolved: boolean
+}
+
+export interface DefinitionResult {
+  /** The identifier or call at the requested position */
+  symbol: SymbolLocation & { kind: TokenLocation['kind'] }
+  definitions: SymbolMatch[]
+}
+
+export interface ReferencesResult {
+  definitions: SymbolLocation[]
+  references: SymbolMatch[]
+}
+
+const siteKey = (site: RustItemSite) => `${site.file}\0${site.token}`
+
+function getRangeSize(range: SourceRange): number {
+  const lines = range.endLine - range.startLine
+  return lines * 1e6 + range.endColumn - range.startColumn
+}
+
+/** Last segment of a token path: `<A as B>::run` -> `run` */
+function getLastSegment(token: string): string {
+  const separator = token.lastIndexOf('::')
+  return separator === -1 ? token : token.slice(separator + 2)
+}
+
+/**
+ * Position-level definitions and references of a set of parsed files.
+ *
+ * Rust references are resolved through module paths and imports like in
+ * `getFileTokenScores`; everything else, including Rust method calls with
+ * ambiguous names, is matched by name.
+ */
+export class SymbolIndex {
+  constructor(
+    private files: Map<string, ParsedTokens>,
+    private rustIndex?: RustModuleIndex,
+  ) {}
+
+  /** Finds where the symbol at a 1-based line and column is defined */
+  findDefinition(
+    filePath: string,
+    line: number,
+    column: number,
+  ): DefinitionResult | undefined {
+    const location = this.files
+      .get(filePath)
+      ?.locations?.filter(({ range }) => rangeContains(range, line, column))
+      .sort((a, b) => getRangeSize(a.range) - getRangeSize(b.range))[0]
+    if (!location) {
+      return undefined
+    }
+    const { token, range } = location
+    const symbol = { file: filePath, ...location }
+    if (location.kind === 'definition') {
+      return {
+        symbol,
+        definitions: [{ file: filePath, token, range, resolved: true }],
+      }
+    }
+
+    const site = this.resolveRustReference(filePath, range)
+    const resolved = site
+      ? this.getDefinitions(
+          (definition) =>
+            definition.file === site.file && definition.token === site.token,
+        )
+      : []
+    if (resolved.length > 0) {
+      return {
+        symbol,
+        definitions: resolved.map((definition) => ({
+          ...definition,
+          resolved: true,
+        })),
+      }
+    }
+
+    const definitions = this.getDefinitions(
+      (definition) =>
+        (definition.token === token ||
+          getLastSegment(definition.token) === token) &&
+        (this.rustIndex?.canReference(filePath, definition.file) ?? true),
+    )
+    return {
+      symbol,
+      definitions: definitions.map((definition) => ({
+        ...definition,
+        resolved: false,
+      })),
+    }
+  }
+
+  /**
+   * Finds the definitions of `symbol`, e.g. `parse` or `Config::new`, and the
+   * references to them. `filePath` restricts definitions to a single file.
+   */
+  findReferences(symbol: string, filePath?: string): ReferencesResult {
+    const definitions = this.getDefinitions(
+      (definition) =>
+        matchesItemPath(definition.token, symbol) &&
+        (!filePath || definition.file === filePath),
+    )
+    const targets = new Set(definitions.map(siteKey))
+    const name = getLastSegment(symbol)
+
+    const references: SymbolMatch[] = []
+    for (const [file, parsed] of this.files) {
+      for (const { token, kind, range } of parsed.locations ?? []) {
+        if (kind !== 'reference') {
+          continue
+        }
+        const site = this.resolveRustReference(file, range)
+        if (site) {
+          if (targets.has(siteKey(site))) {
+            references.push({ file, token, range, resolved: true })
+          }
+          continue
+        }
+        const matchesName = matchesItemPath(token, symbol) || token === name
+        const canReference =
+          definitions.length === 0 ||
+          definitions.some(
+            (definition) =>
+              this.rustIndex?.canReference(file, definition.file) ?? true,
+          )
+        if (matchesName && canReference) {
+          references.push({ file, token, range, resolved: false })
+        }
+      }
+    }
+
+    return { definitions, references }
+  }
+
+  private getDefinitions(
+    predicate: (definition: SymbolLocation) => boolean,
+  ): SymbolLocation[] {
+    const definitions: SymbolLocation[] = []
+    for (const [file, parsed] of this.files) {
+      for (const { token, kind, range } of parsed.locations ?? []) {
+        const definition = { file, token, range }
+        if (kind === 'definition' && predicate(definition)) {
+          definitions.push(definition)
+        }
+      }
+    }
+    return definitions
+  }
+
+  private resolveRustReference(
+    filePath: string,
+    range: SourceRange,
+  ): RustItemSite | undefined {
+    const reference = this.files
+      .get(filePath)
+      ?.rust?.references.find(
+        (ref) => ref.range !== undefined && isSameRange(ref.range, range),
+      )
+    return reference && this.rustIndex?.resolve(filePath, reference)
+  }
+}
+
+export function buildSymbolIndex(
+  files: Map<string, ParsedTokens>,
+  fallbackCrateName: string,
+  workspace?: CargoWorkspace,
+): SymbolIndex {
+  const rustSymbols = new Map<string, RustFileSymbols>()
+  for (const [filePath, parsed] of files) {
+    if (parsed.rust) {
+      rustSymbols.set(filePath, parsed.rust)
+    }
+  }
+  const rustIndex =
+    rustSymbols.size > 0
+      ? buildRustModuleIndex(rustSymbols, fallbackCrateName, workspace)
+      : undefined
+  return new SymbolIndex(files, rustIndex)
+}
+
+/** Parses the given files and builds their symbol index */
+export async function getSymbolIndex(
+  projectRoot: string,
+  filePaths: string[],
+  readFile?: (filePath: string) => string | null,
+  cargoWorkspace?: CargoWorkspace,
+  cache?: ParseCache,
+): Promise<SymbolIndex> {
+  const files = new Map<string, ParsedTokens>()
+  for (const filePath of filePaths) {
+    const fullPath = path.join(projectRoot, filePath)
+    const languageConfig = await getLanguageConfig(fullPath)
+    if (!languageConfig) {
+      continue
+    }
+    files.set(
+      filePath,
+      readFile
+        ? parseTokens(filePath, languageConfig, readFile, cache)
+        : parseTokens(fullPath, languageConfig, undefined, cache),
+    )
+  }
+
+  // Resolve references to items generated by macros from other files
+  const rustSymbols = new Map<string, RustFileSymbols>()
+  for (const [filePath, { rust }] of files) {
+    if (rust) {
+      rustSymbols.set(filePath, rust)
+    }
+  }
+  expandRustMacros(rustSymbols)
+
+  return buildSymbolIndex(files, path.basename(projectRoot), cargoWorkspace)
+}
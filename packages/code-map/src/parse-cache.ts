@@ -0,0 +1,204 @@
+import { createHash } from 'crypto'
+
+import type { ParsedTokens } from './parse'
+import type { Edit, Point, Tree } from 'web-tree-sitter'
+
+/**
+ * Bump whenever the parsing code changes what `parseTokens` returns. Changes
+ * to grammars and queries are detected by their cache key instead.
+ */
+export const PARSE_CACHE_VERSION = 5
+/** Syntax trees kept for incremental re-parsing, least recently used first */
+const MAX_TREES = 64
+
+interface ParseCacheEntry {
+  hash: string
+  /** Cache key of the grammar and query the file was parsed with */
+  grammar?: string
+  parsed: ParsedTokens
+}
+
+export interface SerializedParseCache {
+  version: number
+  entries: Record<string, ParseCacheEntry>
+}
+
+interface CachedTree {
+  sourceCode: string
+  tree: Tree
+  grammar?: string
+}
+
+export function hashSource(sourceCode: string): string {
+  return createHash('sha1').update(sourceCode).digest('hex')
+}
+
+function getPoint(sourceCode: string, index: number): Point {
+  let row = 0
+  let lineStart = 0
+  for (
+    let i = sourceCode.indexOf('\n');
+    i !== -1 && i < index;
+    i = sourceCode.indexOf('\n', i + 1)
+  ) {
+    row++
+    lineStart = i + 1
+  }
+  return { row, column: index - lineStart }
+}
+
+/**
+ * Describes the change from `oldSource` to `newSource` as a single edit
+ * spanning everything between their common prefix and common suffix.
+ */
+export function getSourceEdit(oldSource: string, newSource: string): Edit {
+  const maxPrefix = Math.min(oldSource.length, newSource.length)
+  let start = 0
+  while (start < maxPrefix && oldSource[start] === newSource[start]) {
+    start++
+  }
+  let oldEnd = oldSource.length
+  let newEnd = newSource.length
+  while (
+    oldEnd > start &&
+    newEnd > start &&
+    oldSource[oldEnd - 1] === newSource[newEnd - 1]
+  ) {
+    oldEnd--
+    newEnd--
+  }
+  return {
+    startIndex: start,
+    oldEndIndex: oldEnd,
+    newEndIndex: newEnd,
+    startPosition: getPoint(oldSource, start),
+    oldEndPosition: getPoint(oldSource, oldEnd),
+    newEndPosition: getPoint(newSource, newEnd),
+  }
+}
+
+/**
+ * Parse results keyed by file path and content hash, so files that did not
+ * change are not re-parsed. Serializable, so the cache can outlive a session.
+ *
+ * Also keeps the syntax trees of recently parsed files in memory: when such a
+ * file changes, e.g. after an edit by the agent, it is re-parsed
+ * incrementally from its previous tree.
+ */
+export class ParseCache {
+  private entries: Map<string, ParseCacheEntry>
+  private trees = new Map<string, CachedTree>()
+  private changed = false
+
+  constructor(entries: Record<string, ParseCacheEntry> = {}) {
+    this.entries = new Map(Object.entries(entries))
+  }
+
+  /** Restores a serialized cache, or returns an empty one if it is outdated */
+  static fromJSON(data: unknown): ParseCache {
+    const serialized = data as Partial<SerializedParseCache> | null
+    if (
+      serialized?.version !== PARSE_CACHE_VERSION ||
+      typeof serialized.entries !== 'object'
+    ) {
+      return new ParseCache()
+    }
+    return new ParseCache(serialized.entries)
+  }
+
+  toJSON(): SerializedParseCache {
+    return {
+      version: PARSE_CACHE_VERSION,
+      entries: Object.fromEntries(this.entries),
+    }
+  }
+
+  /** Whether entries were added or removed since the cache was last saved */
+  get isChanged(): boolean {
+    return this.changed
+  }
+
+  markSaved(): void {
+    this.changed = false
+  }
+
+  get size(): number {
+    return this.entries.size
+  }
+
+  /**
+   * Cached parse results, if neither the file nor the grammar it was parsed
+   * with changed since
+   */
+  get(
+    filePath: string,
+    sourceCode: string,
+    grammar?: string,
+  ): ParsedTokens | undefined {
+    const entry = this.entries.get(filePath)
+    if (
+      !entry ||
+      entry.hash !== hashSource(sourceCode) ||
+      entry.grammar !== grammar
+    ) {
+      return undefined
+    }
+    // Callers may extend the results, e.g. with macro expansions
+    return structuredClone(entry.parsed)
+  }
+
+  set(
+    filePath: string,
+    sourceCode: string,
+    parsed: ParsedTokens,
+    grammar?: string,
+  ): void {
+    this.entries.set(filePath, {
+      hash: hashSource(sourceCode),
+      ...(grammar ? { grammar } : {}),
+      parsed: structuredClone(parsed),
+    })
+    this.changed = true
+  }
+
+  /** The last syntax tree of a file, if parsed with the same grammar */
+  getTree(filePath: string, grammar?: string): CachedTree | undefined {
+    const cached = this.trees.get(filePath)
+    return cached?.grammar === grammar ? cached : undefined
+  }
+
+  setTree(
+    filePath: string,
+    sourceCode: string,
+    tree: Tree,
+    grammar?: string,
+  ): void {
+    const previous = this.trees.get(filePath)
+    if (previous && previous.tree !== tree) {
+      previous.tree.delete()
+    }
+    this.trees.delete(filePath)
+    this.trees.set(filePath, { sourceCode, tree, grammar })
+
+    for (const [oldestPath, oldest] of this.trees) {
+      if (this.trees.size <= MAX_TREES) {
+        break
+      }
+      oldest.tree.delete()
+      this.trees.delete(oldestPath)
+    }
+  }
+
+  /** Drops entries for files not in `filePaths`, e.g. deleted files */
+  retain(filePaths: Iterable<string>): void {
+    const kept = new Set(filePaths)
+    for (const filePath of this.entries.keys()) {
+      if (!kept.has(filePath)) {
+        this.entries.delete(filePath)
+        this.trees.get(filePath)?.tree.delete()
+        this.trees.delete(filePath)
+        this.changed = true
+      }
+    }
+  }
+}
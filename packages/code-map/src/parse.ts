@@ -1,14 +1,25 @@
 import * as fs from 'fs'
 import * as path from 'path'
 
-import { getLanguageConfig } from './languages'
+import { getLanguageConfig, WASM_FILES } from './languages'
+import { getSourceEdit } from './parse-cache'
+import { collectRustFileSymbols } from './rust/file-symbols'
+import { expandRustMacros } from './rust/macros'
+import { buildRustModuleIndex } from './rust/resolve'
+import { getSourceRange } from './source-range'
 
 import type { LanguageConfig } from './languages';
-import type { Parser, Query } from 'web-tree-sitter'
+import type { ParseCache } from './parse-cache'
+import type { SymbolGraph } from './repo-map'
+import type { CargoWorkspace } from './rust/cargo'
+import type { RustFileSymbols } from './rust/file-symbols'
+import type { SourceRange } from './source-range'
+import type { Node, Parser, Query, Tree } from 'web-tree-sitter'
 
 export const DEBUG_PARSING = false
 const IGNORE_TOKENS = ['__init__', '__post_init__', '__call__', 'constructor']
 const MAX_CALLERS = 25
+const MAX_SIGNATURE_LENGTH = 120
 
 export interface TokenCallerMap {
   [filePath: string]: {
@@ -16,123 +27,318 @@ export interface TokenCallerMap {
   }
 }
 
+/** Where a token is defined (`identifier`) or referenced (`call.identifier`) */
+export interface TokenLocation {
+  token: string
+  kind: 'definition' | 'reference'
+  range: SourceRange
+  /** First line of the defining item, for definitions */
+  signature?: string
+}
+
+export interface ParsedTokens {
+  numLines: number
+  identifiers: string[]
+  calls: string[]
+  /** Positions of the captured identifiers and calls, in source order */
+  locations?: TokenLocation[]
+  /** Module-level symbols used to resolve Rust call sites */
+  rust?: RustFileSymbols
+}
+
+interface RustMemberDefinition {
+  file: string
+  token: string
+  score: number
+}
+
+/** `<Greeting as Greeter>::greet` and `Greeting::greet` -> `greet` */
+function getRustMemberName(token: string): string | undefined {
+  const separator = token.lastIndexOf('::')
+  return separator === -1 ? undefined : token.slice(separator + 2)
+}
+
 export interface FileTokenData {
   tokenScores: { [filePath: string]: { [token: string]: number } }
   tokenCallers: TokenCallerMap
+  /** Definitions and cross-file references, for ranking a repository map */
+  symbolGraph: SymbolGraph
+}
+
+/**
+ * Parses the files a language is configured for. With a `cache`, only files
+ * that changed since they were cached are parsed.
+ */
+export async function parseProjectFiles(
+  projectRoot: string,
+  filePaths: string[],
+  readFile?: (filePath: string) => string | null,
+  cache?: ParseCache,
+): Promise<Map<string, ParsedTokens>> {
+  const parsedFiles = new Map<string, ParsedTokens>()
+  for (const filePath of filePaths) {
+    const fullPath = path.join(projectRoot, filePath)
+    const languageConfig = await getLanguageConfig(fullPath)
+    if (!languageConfig) {
+      continue
+    }
+    if (readFile) {
+      // When readFile is provided, use relative filePath
+      parsedFiles.set(
+        filePath,
+        parseTokens(filePath, languageConfig, readFile, cache),
+      )
+    } else {
+      // When readFile is not provided, use full path to read from file system
+      parsedFiles.set(
+        filePath,
+        parseTokens(fullPath, languageConfig, undefined, cache),
+      )
+    }
+  }
+  return parsedFiles
 }
 
+/**
+ * Scores the tokens defined in each file and maps them to the files calling
+ * them. With a `cache`, only files that changed since they were cached are
+ * parsed.
+ */
 export async function getFileTokenScores(
   projectRoot: string,
   filePaths: string[],
   readFile?: (filePath: string) => string | null,
+  cargoWorkspace?: CargoWorkspace,
+  cache?: ParseCache,
 ): Promise<FileTokenData> {
   const startTime = Date.now()
+  const parsedFiles = await parseProjectFiles(
+    projectRoot,
+    filePaths,
+    readFile,
+    cache,
+  )
+  const tokenData = scoreParsedFiles(projectRoot, parsedFiles, cargoWorkspace)
+
+  if (DEBUG_PARSING) {
+    const endTime = Date.now()
+    console.log(`Parsed ${filePaths.length} files in ${endTime - startTime}ms`)
+  }
+
+  return tokenData
+}
+
+/**
+ * Scores and links the tokens of already parsed files, e.g. to update the
+ * scores after re-parsing only the files that changed. `parsedFiles` is not
+ * modified.
+ */
+export function scoreParsedFiles(
+  projectRoot: string,
+  parsedFiles: Map<string, ParsedTokens>,
+  cargoWorkspace?: CargoWorkspace,
+): FileTokenData {
   const tokenScores: { [filePath: string]: { [token: string]: number } } = {}
   const externalCalls: { [token: string]: number } = {}
   const fileCallsMap = new Map<string, string[]>()
+  const rustSymbols = new Map<string, RustFileSymbols>()
+  const tokenBaseScores = new Map<string, number>()
+  const symbolGraph: SymbolGraph = { definitions: {}, references: {} }
 
   // First pass: collect all identifiers and calls
-  for (const filePath of filePaths) {
+  for (const [filePath, parseResults] of parsedFiles) {
     const fullPath = path.join(projectRoot, filePath)
-    const languageConfig = await getLanguageConfig(fullPath)
-    if (languageConfig) {
-      let parseResults
-      if (readFile) {
-        // When readFile is provided, use relative filePath
-        parseResults = parseTokens(filePath, languageConfig, readFile)
-      } else {
-        // When readFile is not provided, use full path to read from file system
-        parseResults = parseTokens(fullPath, languageConfig)
+    const { identifiers, calls, numLines, locations, rust } = parseResults
+    if (rust) {
+      // Macro expansion adds to the definitions and implied impls
+      rustSymbols.set(filePath, {
+        ...rust,
+        definitions: [...rust.definitions],
+        impliedImpls: [...rust.impliedImpls],
+      })
+    }
+
+    const definitions: SymbolGraph['definitions'][string] = {}
+    symbolGraph.definitions[filePath] = definitions
+    for (const { token, kind, range, signature } of locations ?? []) {
+      if (kind === 'definition' && !IGNORE_TOKENS.includes(token)) {
+        definitions[token] ??= {
+          line: range.startLine,
+          signature: signature ?? token,
+        }
       }
-      const { identifiers, calls, numLines } = parseResults
+    }
 
-      const tokenScoresForFile: { [token: string]: number } = {}
-      tokenScores[filePath] = tokenScoresForFile
+    const tokenScoresForFile: { [token: string]: number } = {}
+    tokenScores[filePath] = tokenScoresForFile
 
-      const dirs = path.dirname(fullPath).split(path.sep)
-      const depth = dirs.length
-      const tokenBaseScore =
-        0.8 ** depth * Math.sqrt(numLines / (identifiers.length + 1))
+    const dirs = path.dirname(fullPath).split(path.sep)
+    const depth = dirs.length
+    const tokenBaseScore =
+      0.8 ** depth * Math.sqrt(numLines / (identifiers.length + 1))
+    tokenBaseScores.set(filePath, tokenBaseScore)
 
-      // Store defined tokens
-      for (const identifier of identifiers) {
-        if (!IGNORE_TOKENS.includes(identifier)) {
-          tokenScoresForFile[identifier] = tokenBaseScore
-        }
+    // Store defined tokens
+    for (const identifier of identifiers) {
+      if (!IGNORE_TOKENS.includes(identifier)) {
+        tokenScoresForFile[identifier] = tokenBaseScore
       }
+    }
 
-      // Store calls for this file
-      fileCallsMap.set(filePath, calls)
+    // Store calls for this file
+    fileCallsMap.set(filePath, calls)
 
-      // Track external calls
-      for (const call of calls) {
-        if (!tokenScoresForFile[call]) {
-          externalCalls[call] = (externalCalls[call] ?? 0) + 1
-        }
+    // Track external calls
+    for (const call of calls) {
+      if (!tokenScoresForFile[call]) {
+        externalCalls[call] = (externalCalls[call] ?? 0) + 1
       }
     }
   }
+
+  // Items generated by macros from other files are defined where invoked
+  for (const [filePath, tokens] of expandRustMacros(rustSymbols)) {
+    for (const token of tokens) {
+      tokenScores[filePath][token] ??= tokenBaseScores.get(filePath) ?? 0
+    }
+  }
+
   // Build a map of tokens to their defining files for O(1) lookup
   const tokenDefinitionMap = new Map<string, string>()
   const highestScores = new Map<string, number>()
+  const definitionCounts = new Map<string, number>()
+  // Rust method calls (`greeting.greet()`) only name the member, while its
+  // definitions are qualified (`<Greeting as Greeter>::greet`)
+  const memberDefinitionMap = new Map<string, RustMemberDefinition>()
   for (const [filePath, scores] of Object.entries(tokenScores)) {
+    const isRust = rustSymbols.has(filePath)
     for (const [token, score] of Object.entries(scores)) {
+      definitionCounts.set(token, (definitionCounts.get(token) ?? 0) + 1)
       const currentHighestScore = highestScores.get(token) ?? -Infinity
       // Keep the file with the higher score for this token
       if (score > currentHighestScore) {
         highestScores.set(token, score)
         tokenDefinitionMap.set(token, filePath)
       }
+
+      const member = isRust ? getRustMemberName(token) : undefined
+      if (member && score > (memberDefinitionMap.get(member)?.score ?? -1)) {
+        memberDefinitionMap.set(member, { file: filePath, token, score })
+      }
     }
   }
 
   const tokenCallers: TokenCallerMap = {}
 
-  // For each file's calls, add it as a caller to the defining file's tokens
-  for (const [callingFile, calls] of fileCallsMap.entries()) {
-    for (const call of calls) {
-      const definingFile = tokenDefinitionMap.get(call)
-      if (!definingFile || callingFile === definingFile) {
-        continue
-      }
+  const addCaller = (
+    definingFile: string | undefined,
+    call: string,
+    callingFile: string,
+  ) => {
+    if (!definingFile || callingFile === definingFile) {
+      return
+    }
 
-      // Skip token names in default objects, e.g. toString, hasOwnProperty
-      if (call in {}) {
-        continue
-      }
+    // Skip token names in default objects, e.g. toString, hasOwnProperty
+    if (call in {}) {
+      return
+    }
 
-      if (!tokenCallers[definingFile]) {
-        tokenCallers[definingFile] = {}
-      }
+    const references = (symbolGraph.references[callingFile] ??= {})
+    const referencedTokens = (references[definingFile] ??= {})
+    referencedTokens[call] = (referencedTokens[call] ?? 0) + 1
+
+    if (!tokenCallers[definingFile]) {
+      tokenCallers[definingFile] = {}
+    }
+
+    if (!tokenCallers[definingFile][call]) {
+      tokenCallers[definingFile][call] = []
+    }
+    const callerFiles = tokenCallers[definingFile][call]
+    if (
+      callerFiles.length < MAX_CALLERS &&
+      !callerFiles.includes(callingFile)
+    ) {
+      callerFiles.push(callingFile)
+    }
+  }
+
+  // Rust call sites are resolved through module paths and imports instead of
+  // the global highest-score definition, since modules often reuse names.
+  // With a Cargo workspace, calls only cross into crates declared as
+  // dependencies.
+  const rustIndex =
+    rustSymbols.size > 0
+      ? buildRustModuleIndex(
+          rustSymbols,
+          path.basename(projectRoot),
+          cargoWorkspace,
+        )
+      : undefined
 
-      if (!tokenCallers[definingFile][call]) {
-        tokenCallers[definingFile][call] = []
+  // For each file's calls, add it as a caller to the defining file's tokens
+  for (const [callingFile, calls] of fileCallsMap.entries()) {
+    const rust = rustSymbols.get(callingFile)
+    if (rust && rustIndex) {
+      for (const reference of rust.references) {
+        const site = rustIndex.resolve(callingFile, reference)
+        const member =
+          reference.kind === 'method'
+            ? memberDefinitionMap.get(reference.token)
+            : undefined
+        if (site) {
+          addCaller(site.file, site.token, callingFile)
+        } else if (member) {
+          // Unresolved method call: the best scored member of that name
+          if (rustIndex.canReference(callingFile, member.file)) {
+            addCaller(member.file, member.token, callingFile)
+          }
+        } else if (definitionCounts.get(reference.token) === 1) {
+          // Unresolved, but only one file defines the token
+          const definingFile = tokenDefinitionMap.get(reference.token)
+          if (
+            definingFile &&
+            rustIndex.canReference(callingFile, definingFile)
+          ) {
+            addCaller(definingFile, reference.token, callingFile)
+          }
+        }
       }
-      const callerFiles = tokenCallers[definingFile][call]
-      if (
-        callerFiles.length < MAX_CALLERS &&
-        !callerFiles.includes(callingFile)
-      ) {
-        callerFiles.push(callingFile)
+      continue
+    }
+
+    for (const call of calls) {
+      const definingFile = tokenDefinitionMap.get(call)
+      const member = memberDefinitionMap.get(call)
+      if (definingFile || !member) {
+        addCaller(definingFile, call, callingFile)
+      } else {
+        addCaller(member.file, member.token, callingFile)
       }
     }
   }
 
   // Apply call frequency boost to token scores
-  for (const scores of Object.values(tokenScores)) {
+  for (const [filePath, scores] of Object.entries(tokenScores)) {
+    const isRust = rustSymbols.has(filePath)
     for (const token of Object.keys(scores)) {
       const numCalls = externalCalls[token] ?? 0
       if (typeof numCalls !== 'number') continue
-      scores[token] *= 1 + Math.log(1 + numCalls)
+      // Method calls count for the qualified members they may call
+      const member = isRust ? getRustMemberName(token) : undefined
+      const memberCalls = member ? externalCalls[member] : undefined
+      scores[token] *=
+        1 +
+        Math.log(
+          1 + numCalls + (typeof memberCalls === 'number' ? memberCalls : 0),
+        )
       // Round to 3 decimal places
       scores[token] = Math.round(scores[token] * 1000) / 1000
     }
   }
 
   if (DEBUG_PARSING) {
-    const endTime = Date.now()
-    console.log(`Parsed ${filePaths.length} files in ${endTime - startTime}ms`)
-
     try {
       fs.writeFileSync(
         '../debug/debug-parse.json',
@@ -148,14 +354,15 @@ export async function getFileTokenScores(
     }
   }
 
-  return { tokenScores, tokenCallers }
+  return { tokenScores, tokenCallers, symbolGraph }
 }
 
 export function parseTokens(
   filePath: string,
   languageConfig: LanguageConfig,
   readFile?: (filePath: string) => string | null,
-) {
+  cache?: ParseCache,
+): ParsedTokens {
   const { parser, query } = languageConfig
 
   try {
@@ -169,13 +376,23 @@ export function parseTokens(
         calls: [] as string[],
       }
     }
+    const cached = cache?.get(filePath, sourceCode, languageConfig.cacheKey)
+    if (cached) {
+      return cached
+    }
     const numLines = sourceCode.match(/\n/g)?.length ?? 0 + 1
     if (!parser || !query) {
       throw new Error('Parser or query not found')
     }
-    const parseResults = parseFile(parser, query, sourceCode)
-    const identifiers = Array.from(new Set(parseResults.identifier))
-    const calls = Array.from(new Set(parseResults['call.identifier']))
+    const { tokens, locations, rust, tree } = parseFile(
+      parser,
+      query,
+      sourceCode,
+      languageConfig,
+      cache?.getTree(filePath, languageConfig.cacheKey),
+    )
+    const identifiers = Array.from(new Set(tokens.identifier))
+    const calls = Array.from(new Set(tokens['call.identifier']))
 
     if (DEBUG_PARSING) {
       console.log(`\nParsing ${filePath}:`)
@@ -183,11 +400,22 @@ export function parseTokens(
       console.log('Calls:', calls)
     }
 
-    return {
+    const parsed: ParsedTokens = {
       numLines,
       identifiers: identifiers ?? [],
       calls: calls ?? [],
+      ...(locations.length > 0 && { locations }),
+      ...(rust && { rust }),
+    }
+    if (cache) {
+      cache.set(filePath, sourceCode, parsed, languageConfig.cacheKey)
+      if (tree) {
+        cache.setTree(filePath, sourceCode, tree, languageConfig.cacheKey)
+      }
+    } else {
+      tree?.delete()
     }
+    return parsed
   } catch (e) {
     if (DEBUG_PARSING) {
       console.error(`Error parsing query: ${e}`)
@@ -201,25 +429,109 @@ export function parseTokens(
   }
 }
 
+/** First line of the item a definition capture names, e.g. a function header */
+function getSignature(node: Node): string {
+  const item = node.parent ?? node
+  const firstLine = item.text.split('\n', 1)[0].trim()
+  return firstLine.length > MAX_SIGNATURE_LENGTH
+    ? `${firstLine.slice(0, MAX_SIGNATURE_LENGTH)}...`
+    : firstLine
+}
+
+function isRustLanguageConfig(languageConfig: LanguageConfig): boolean {
+  return languageConfig.wasmFile === WASM_FILES['tree-sitter-rust.wasm']
+}
+
+/**
+ * Parses `sourceCode` and collects its captures. Given the tree and source of
+ * a previous version of the file, the file is re-parsed incrementally; the
+ * previous tree is left as it was. The caller owns the returned tree.
+ */
 function parseFile(
   parser: Parser,
   query: Query,
   sourceCode: string,
-): { [key: string]: string[] } {
-  const tree = parser.parse(sourceCode)
+  languageConfig: LanguageConfig,
+  previous?: { sourceCode: string; tree: Tree },
+): {
+  tokens: { [key: string]: string[] }
+  locations: TokenLocation[]
+  rust?: RustFileSymbols
+  tree?: Tree
+} {
+  let tree: Tree | null
+  if (previous) {
+    const oldTree = previous.tree.copy()
+    try {
+      oldTree.edit(getSourceEdit(previous.sourceCode, sourceCode))
+      tree = parser.parse(sourceCode, oldTree)
+    } finally {
+      oldTree.delete()
+    }
+  } else {
+    tree = parser.parse(sourceCode)
+  }
   if (!tree) {
-    return {}
+    return { tokens: {}, locations: [] }
+  }
+  try {
+    return collectCaptures(query, tree, languageConfig)
+  } catch (e) {
+    tree.delete()
+    throw e
   }
+}
+
+function collectCaptures(
+  query: Query,
+  tree: Tree,
+  languageConfig: LanguageConfig,
+): {
+  tokens: { [key: string]: string[] }
+  locations: TokenLocation[]
+  rust?: RustFileSymbols
+  tree: Tree
+} {
   const captures = query.captures(tree.rootNode)
-  const result: { [key: string]: string[] } = {}
+  const tokens: { [key: string]: string[] } = {}
+  const locations: TokenLocation[] = []
+  const { qualifyCapture } = languageConfig
 
   for (const capture of captures) {
     const { name, node } = capture
-    if (!result[name]) {
-      result[name] = []
+    if (!tokens[name]) {
+      tokens[name] = []
+    }
+    const token = qualifyCapture ? qualifyCapture(name, node) : node.text
+    tokens[name].push(token)
+    if (name === 'identifier') {
+      locations.push({
+        token,
+        kind: 'definition',
+        range: getSourceRange(node),
+        signature: getSignature(node),
+      })
+    } else if (name === 'call.identifier') {
+      locations.push({
+        token,
+        kind: 'reference',
+        range: getSourceRange(node),
+      })
     }
-    result[name].push(node.text)
   }
 
-  return result
+  if (isRustLanguageConfig(languageConfig)) {
+    const rust = collectRustFileSymbols(tree.rootNode, captures)
+    // Items expanded from local macros, and traits implied by #[derive]
+    tokens.identifier = [
+      ...(tokens.identifier ?? []),
+      ...rust.definitions.filter((d) => d.macro).map((d) => d.token),
+    ]
+    tokens['call.identifier'] = [
+      ...(tokens['call.identifier'] ?? []),
+      ...rust.impliedImpls.map((impl) => impl.traitName),
+    ]
+    return { tokens, locations, rust, tree }
+  }
+  return { tokens, locations, tree }
 }
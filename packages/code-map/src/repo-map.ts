@@ -0,0 +1,282 @@
+import * as path from 'path'
+
+export interface SymbolDefinition {
+  /** 1-based line of the definition */
+  line: number
+  /** First line of the defining item */
+  signature: string
+}
+
+/**
+ * Definitions per file and the references between files, as collected by
+ * `getFileTokenScores`. Serializable, so it can be part of the project file
+ * context.
+ */
+export interface SymbolGraph {
+  /** File -> token -> definition */
+  definitions: Record<string, Record<string, SymbolDefinition>>
+  /** Referencing file -> defining file -> token -> number of references */
+  references: Record<string, Record<string, Record<string, number>>>
+}
+
+export interface RepoMapSeeds {
+  /** Files the ranking is personalized towards, e.g. ones named in a prompt */
+  files: string[]
+  /** Identifiers whose definitions and references weigh more */
+  identifiers: string[]
+}
+
+export interface RankedSymbol {
+  file: string
+  token: string
+  rank: number
+  definition: SymbolDefinition
+}
+
+interface Edge {
+  to: string
+  token: string
+  weight: number
+}
+
+const DAMPING = 0.85
+const MAX_ITERATIONS = 50
+const TOLERANCE = 1e-8
+/** Extra weight of references to identifiers in the seeds */
+const SEED_IDENTIFIER_WEIGHT = 10
+/** Weight of tokens defined in many files, which are likely generic names */
+const COMMON_TOKEN_WEIGHT = 0.1
+const COMMON_TOKEN_MIN_FILES = 5
+/** Share of a file's rank given to its definitions whether referenced or not */
+const DEFINITION_BASE_SHARE = 0.05
+const MIN_IDENTIFIER_LENGTH = 3
+
+/** Last segment of a token path: `Config::new` -> `new` */
+function getLastSegment(token: string): string {
+  const separator = token.lastIndexOf('::')
+  return separator === -1 ? token : token.slice(separator + 2)
+}
+
+/**
+ * Finds the files and identifiers of the graph that `text`, e.g. a user
+ * prompt, mentions: files by path or unambiguous file name, identifiers by
+ * name or qualified path.
+ */
+export function findRepoMapSeeds(
+  text: string,
+  graph: SymbolGraph,
+): RepoMapSeeds {
+  const files = Object.keys(graph.definitions)
+  const words = new Set(text.match(/[\w.\-/]*\w/g) ?? [])
+  const fileNameCounts = new Map<string, number>()
+  for (const file of files) {
+    const name = path.posix.basename(file)
+    fileNameCounts.set(name, (fileNameCounts.get(name) ?? 0) + 1)
+  }
+  const mentionedFiles = files.filter((file) => {
+    const name = path.posix.basename(file)
+    return (
+      text.includes(file) || (words.has(name) && fileNameCounts.get(name) === 1)
+    )
+  })
+
+  const names = new Set(
+    (text.match(/[A-Za-z_]\w*(?:::[A-Za-z_]\w*)*/g) ?? []).filter(
+      (name) => name.length >= MIN_IDENTIFIER_LENGTH,
+    ),
+  )
+  const identifiers = new Set<string>()
+  for (const definitions of Object.values(graph.definitions)) {
+    for (const token of Object.keys(definitions)) {
+      // Bare method names like `new` are too common to point at one item
+      const member = getLastSegment(token)
+      const matchesMember = /[A-Z_]/.test(member) && names.has(member)
+      if (names.has(token) || matchesMember) {
+        identifiers.add(token)
+      }
+    }
+  }
+
+  return { files: mentionedFiles, identifiers: [...identifiers] }
+}
+
+/**
+ * Ranks files with PageRank over the reference graph, personalized towards
+ * the seed files, then ranks each definition by the share of rank flowing to
+ * it through references.
+ *
+ * Without seed files, the ranking is the plain PageRank of the graph.
+ */
+export function rankSymbols(
+  graph: SymbolGraph,
+  seeds: RepoMapSeeds = { files: [], identifiers: [] },
+): RankedSymbol[] {
+  const files = [
+    ...new Set([
+      ...Object.keys(graph.definitions),
+      ...Object.keys(graph.references),
+    ]),
+  ]
+  if (files.length === 0) {
+    return []
+  }
+
+  const definingFileCounts = new Map<string, number>()
+  for (const definitions of Object.values(graph.definitions)) {
+    for (const token of Object.keys(definitions)) {
+      definingFileCounts.set(token, (definingFileCounts.get(token) ?? 0) + 1)
+    }
+  }
+  const seedIdentifiers = new Set(seeds.identifiers)
+  const getTokenWeight = (token: string, count: number) => {
+    let weight = Math.sqrt(count)
+    if (seedIdentifiers.has(token)) {
+      weight *= SEED_IDENTIFIER_WEIGHT
+    }
+    if ((definingFileCounts.get(token) ?? 0) >= COMMON_TOKEN_MIN_FILES) {
+      weight *= COMMON_TOKEN_WEIGHT
+    }
+    return weight
+  }
+
+  // Weighted edges from referencing to defining files, per token
+  const edges = new Map<string, Edge[]>()
+  const outWeights = new Map<string, number>()
+  for (const [from, targets] of Object.entries(graph.references)) {
+    const fromEdges: Edge[] = []
+    for (const [to, tokens] of Object.entries(targets)) {
+      for (const [token, count] of Object.entries(tokens)) {
+        fromEdges.push({ to, token, weight: getTokenWeight(token, count) })
+      }
+    }
+    edges.set(from, fromEdges)
+    outWeights.set(from, fromEdges.reduce((sum, edge) => sum + edge.weight, 0))
+  }
+
+  const seedFiles = seeds.files.filter((file) => files.includes(file))
+  const personalization = new Map<string, number>(
+    seedFiles.length > 0
+      ? seedFiles.map((file) => [file, 1 / seedFiles.length])
+      : files.map((file) => [file, 1 / files.length]),
+  )
+
+  let ranks = new Map(files.map((file) => [file, 1 / files.length]))
+  for (let i = 0; i < MAX_ITERATIONS; i++) {
+    const next = new Map<string, number>()
+    let danglingRank = 0
+    for (const file of files) {
+      const rank = ranks.get(file) ?? 0
+      const total = outWeights.get(file) ?? 0
+      if (total === 0) {
+        danglingRank += rank
+        continue
+      }
+      for (const { to, weight } of edges.get(file) ?? []) {
+        next.set(to, (next.get(to) ?? 0) + (rank * weight) / total)
+      }
+    }
+
+    let delta = 0
+    for (const file of files) {
+      const restart = personalization.get(file) ?? 0
+      const rank =
+        DAMPING * ((next.get(file) ?? 0) + danglingRank * restart) +
+        (1 - DAMPING) * restart
+      delta += Math.abs(rank - (ranks.get(file) ?? 0))
+      next.set(file, rank)
+    }
+    ranks = next
+    if (delta < TOLERANCE) {
+      break
+    }
+  }
+
+  // Spread each file's rank over the definitions it references
+  const symbolRanks = new Map<string, Map<string, number>>()
+  const addRank = (file: string, token: string, rank: number) => {
+    if (!graph.definitions[file]?.[token]) {
+      return
+    }
+    let fileRanks = symbolRanks.get(file)
+    if (!fileRanks) {
+      fileRanks = new Map()
+      symbolRanks.set(file, fileRanks)
+    }
+    fileRanks.set(token, (fileRanks.get(token) ?? 0) + rank)
+  }
+  for (const [from, fromEdges] of edges) {
+    const rank = ranks.get(from) ?? 0
+    const total = outWeights.get(from) ?? 0
+    for (const { to, token, weight } of fromEdges) {
+      addRank(to, token, (rank * weight) / total)
+    }
+  }
+  // Files nobody references still list their definitions, ranked lower
+  for (const [file, definitions] of Object.entries(graph.definitions)) {
+    const tokens = Object.keys(definitions)
+    const share =
+      ((ranks.get(file) ?? 0) * DEFINITION_BASE_SHARE) / tokens.length
+    for (const token of tokens) {
+      const weight = seedIdentifiers.has(token) ? SEED_IDENTIFIER_WEIGHT : 1
+      addRank(file, token, share * weight)
+    }
+  }
+
+  const ranked: RankedSymbol[] = []
+  for (const [file, fileRanks] of symbolRanks) {
+    for (const [token, rank] of fileRanks) {
+      const definition = graph.definitions[file][token]
+      ranked.push({ file, token, rank, definition })
+    }
+  }
+  return ranked.sort(
+    (a, b) =>
+      b.rank - a.rank ||
+      a.file.localeCompare(b.file) ||
+      a.definition.line - b.definition.line,
+  )
+}
+
+function formatRepoMap(symbols: RankedSymbol[]): string {
+  const byFile = new Map<string, RankedSymbol[]>()
+  for (const symbol of symbols) {
+    const fileSymbols = byFile.get(symbol.file) ?? []
+    fileSymbols.push(symbol)
+    byFile.set(symbol.file, fileSymbols)
+  }
+  return [...byFile]
+    .map(([file, fileSymbols]) => {
+      const lines = fileSymbols
+        .map(({ definition }) => definition)
+        .sort((a, b) => a.line - b.line)
+        .map(({ line, signature }) => `  ${line}: ${signature}`)
+      return [`${file}:`, ...lines].join('\n')
+    })
+    .join('\n')
+}
+
+/**
+ * Renders the top ranked symbols grouped by file, most important file first,
+ * with as many symbols as fit in `tokenBudget`.
+ */
+export function renderRepoMap(
+  ranked: RankedSymbol[],
+  tokenBudget: number,
+  countTokens: (text: string) => number,
+): string {
+  // Binary search for the largest prefix of the ranking that fits
+  let low = 0
+  let high = ranked.length
+  let best = ''
+  while (low <= high) {
+    const count = Math.floor((low + high) / 2)
+    const rendered = formatRepoMap(ranked.slice(0, count))
+    if (countTokens(rendered) <= tokenBudget) {
+      best = rendered
+      low = count + 1
+    } else {
+      high = count - 1
+    }
+  }
+  return best
+}
@@ -0,0 +1,553 @@
+import * as path from 'path'
+
+import { parse as parseToml } from 'smol-toml'
+
+type TomlTable = ReturnType<typeof parseToml>
+type TomlValue = TomlTable[string]
+
+export type CargoTargetKind =
+  | 'lib'
+  | 'bin'
+  | 'example'
+  | 'test'
+  | 'bench'
+  | 'build-script'
+
+export interface CargoTarget {
+  kind: CargoTargetKind
+  name: string
+  /** Root source file, relative to the project root */
+  path: string
+}
+
+export type CargoDependencyKind = 'normal' | 'dev' | 'build'
+
+export interface CargoDependency {
+  /** Name the dependency is imported under in code (`package` renames applied) */
+  name: string
+  /** Package name on the registry or in the workspace */
+  package: string
+  kind: CargoDependencyKind
+  optional: boolean
+  versionReq?: string
+  /** Exact version resolved in Cargo.lock */
+  lockedVersion?: string
+  /** Package name of the workspace member this dependency points at */
+  workspaceMember?: string
+}
+
+export interface CargoCrate {
+  name: string
+  version?: string
+  manifestPath: string
+  /** Directory containing the manifest, relative to the project root ('' for the root) */
+  rootDir: string
+  targets: CargoTarget[]
+  features: Record<string, string[]>
+  dependencies: CargoDependency[]
+}
+
+export interface CargoWorkspace {
+  rootManifestPath: string
+  members: CargoCrate[]
+}
+
+interface LockedPackage {
+  name: string
+  version: string
+  /** e.g. `registry+https://github.com/rust-lang/crates.io-index` */
+  source?: string
+  dependencies: string[]
+}
+
+export interface LockedRegistryPackage {
+  name: string
+  version: string
+  source: string
+}
+
+const posix = path.posix
+const DEPENDENCY_TABLES: [string, CargoDependencyKind][] = [
+  ['dependencies', 'normal'],
+  ['dev-dependencies', 'dev'],
+  ['build-dependencies', 'build'],
+]
+const AUTO_TARGET_DIRS: [string, CargoTargetKind][] = [
+  ['examples', 'example'],
+  ['tests', 'test'],
+  ['benches', 'bench'],
+]
+
+/** Name code uses to refer to a package (`my-crate` -> `my_crate`) */
+export function toRustCrateName(name: string): string {
+  return name.replace(/-/g, '_')
+}
+
+/** Name other crates import the member's library under, before renames */
+export function getLibraryCrateName(crate: CargoCrate): string {
+  return (
+    crate.targets.find((target) => target.kind === 'lib')?.name ??
+    toRustCrateName(crate.name)
+  )
+}
+
+function asTable(value: TomlValue | undefined): TomlTable | undefined {
+  return typeof value === 'object' &&
+    value !== null &&
+    !Array.isArray(value) &&
+    !(value instanceof Date)
+    ? value
+    : undefined
+}
+
+function asString(value: TomlValue | undefined): string | undefined {
+  return typeof value === 'string' ? value : undefined
+}
+
+function asStringArray(value: TomlValue | undefined): string[] {
+  return Array.isArray(value)
+    ? value.filter((v): v is string => typeof v === 'string')
+    : []
+}
+
+function globToRegExp(pattern: string): RegExp {
+  const source = pattern
+    .replace(/\/+$/, '')
+    .split('')
+    .map((ch, i, chars) => {
+      if (ch === '*') {
+        if (chars[i + 1] === '*') {
+          return ''
+        }
+        return chars[i - 1] === '*' ? '.*' : '[^/]*'
+      }
+      if (ch === '?') {
+        return '[^/]'
+      }
+      return /[.+^${}()|[\]\\]/.test(ch) ? `\\${ch}` : ch
+    })
+    .join('')
+  return new RegExp(`^${source}$`)
+}
+
+function tryParseToml(content: string): TomlTable | undefined {
+  try {
+    return parseToml(content)
+  } catch {
+    return undefined
+  }
+}
+
+function parseLockfile(content: string | undefined): LockedPackage[] {
+  const packages = content ? tryParseToml(content)?.package : undefined
+  if (!Array.isArray(packages)) {
+    return []
+  }
+  return packages.flatMap((entry) => {
+    const table = asTable(entry)
+    const name = asString(table?.name)
+    const version = asString(table?.version)
+    const source = asString(table?.source)
+    return name && version
+      ? [
+          {
+            name,
+            version,
+            ...(source ? { source } : {}),
+            dependencies: asStringArray(table?.dependencies),
+          },
+        ]
+      : []
+  })
+}
+
+/** Picks the locked version of `dependency` as seen from `dependent` */
+function findLockedVersion(
+  lockedPackages: LockedPackage[],
+  dependent: string,
+  dependency: string,
+): string | undefined {
+  const candidates = lockedPackages.filter((p) => p.name === dependency)
+  if (candidates.length <= 1) {
+    return candidates[0]?.version
+  }
+  // Several versions are locked; the dependent's entry names the one it uses
+  const entry = lockedPackages.find((p) => p.name === dependent)
+  const spec = entry?.dependencies.find(
+    (d) => d === dependency || d.startsWith(`${dependency} `),
+  )
+  return spec?.split(' ')[1] ?? candidates[0].version
+}
+
+/** Applies `key.workspace = true` inheritance from `[workspace.package]` */
+function inheritField(
+  value: TomlValue | undefined,
+  workspacePackage: TomlTable | undefined,
+  key: string,
+): string | undefined {
+  if (asTable(value)?.workspace === true) {
+    return asString(workspacePackage?.[key])
+  }
+  return asString(value)
+}
+
+function discoverTargets(
+  manifest: TomlTable,
+  packageName: string,
+  rootDir: string,
+  filePaths: Set<string>,
+): CargoTarget[] {
+  const targets: CargoTarget[] = []
+  const resolve = (p: string) => posix.join(rootDir, p)
+  const addTarget = (target: CargoTarget) => {
+    // Explicit targets take precedence over auto-discovered ones
+    const isDuplicate = targets.some(
+      (t) =>
+        t.kind === target.kind &&
+        (t.name === target.name || t.path === target.path),
+    )
+    if (!isDuplicate) {
+      targets.push(target)
+    }
+  }
+  const pkg = asTable(manifest.package)
+
+  const lib = asTable(manifest.lib)
+  const libPath = asString(lib?.path) ?? 'src/lib.rs'
+  if (lib || filePaths.has(resolve(libPath))) {
+    addTarget({
+      kind: 'lib',
+      name: asString(lib?.name) ?? toRustCrateName(packageName),
+      path: resolve(libPath),
+    })
+  }
+
+  const explicitTargets: [string, CargoTargetKind, string][] = [
+    ['bin', 'bin', 'src/bin'],
+    ['example', 'example', 'examples'],
+    ['test', 'test', 'tests'],
+    ['bench', 'bench', 'benches'],
+  ]
+  for (const [key, kind, dir] of explicitTargets) {
+    const entries = manifest[key]
+    for (const entry of Array.isArray(entries) ? entries : []) {
+      const name = asString(asTable(entry)?.name)
+      if (name) {
+        const targetPath = asString(asTable(entry)?.path) ?? `${dir}/${name}.rs`
+        addTarget({ kind, name, path: resolve(targetPath) })
+      }
+    }
+  }
+
+  if (filePaths.has(resolve('src/main.rs'))) {
+    addTarget({ kind: 'bin', name: packageName, path: resolve('src/main.rs') })
+  }
+
+  const autoDirs: [string, CargoTargetKind, TomlValue | undefined][] = [
+    ['src/bin', 'bin', pkg?.autobins],
+    ...AUTO_TARGET_DIRS.map(
+      ([dir, kind]): [string, CargoTargetKind, TomlValue | undefined] => [
+        dir,
+        kind,
+        pkg?.[`auto${dir}`],
+      ],
+    ),
+  ]
+  for (const [dir, kind, enabled] of autoDirs) {
+    if (enabled === false) {
+      continue
+    }
+    const prefix = `${resolve(dir)}/`
+    for (const filePath of filePaths) {
+      if (!filePath.startsWith(prefix) || !filePath.endsWith('.rs')) {
+        continue
+      }
+      const rel = filePath.slice(prefix.length).split('/')
+      if (rel.length === 1) {
+        addTarget({ kind, name: rel[0].replace(/\.rs$/, ''), path: filePath })
+      } else if (rel.length === 2 && rel[1] === 'main.rs') {
+        addTarget({ kind, name: rel[0], path: filePath })
+      }
+    }
+  }
+
+  const build = pkg?.build
+  if (build !== false) {
+    const buildPath = asString(build) ?? 'build.rs'
+    if (typeof build === 'string' || filePaths.has(resolve(buildPath))) {
+      addTarget({
+        kind: 'build-script',
+        name: 'build-script-build',
+        path: resolve(buildPath),
+      })
+    }
+  }
+
+  return targets
+}
+
+function collectDependencyTables(
+  manifest: TomlTable,
+): [TomlTable, CargoDependencyKind][] {
+  const tables: [TomlTable, CargoDependencyKind][] = []
+  const addFrom = (source: TomlTable) => {
+    for (const [key, kind] of DEPENDENCY_TABLES) {
+      const table = asTable(source[key])
+      if (table) {
+        tables.push([table, kind])
+      }
+    }
+  }
+  addFrom(manifest)
+  // [target.'cfg(..)'.dependencies] and friends
+  for (const platform of Object.values(asTable(manifest.target) ?? {})) {
+    const platformTable = asTable(platform)
+    if (platformTable) {
+      addFrom(platformTable)
+    }
+  }
+  return tables
+}
+
+function parseCrate(params: {
+  manifestPath: string
+  manifest: TomlTable
+  workspace: TomlTable | undefined
+  workspaceDir: string
+  filePaths: Set<string>
+}): CargoCrate | undefined {
+  const { manifestPath, manifest, workspace, workspaceDir, filePaths } = params
+  const pkg = asTable(manifest.package)
+  const name = asString(pkg?.name)
+  if (!pkg || !name) {
+    return undefined
+  }
+  const manifestDir = posix.dirname(manifestPath)
+  const rootDir = manifestDir === '.' ? '' : manifestDir
+  const workspacePackage = asTable(workspace?.package)
+  const workspaceDependencies = asTable(workspace?.dependencies)
+
+  const features: Record<string, string[]> = {}
+  for (const [feature, enables] of Object.entries(
+    asTable(manifest.features) ?? {},
+  )) {
+    features[feature] = asStringArray(enables)
+  }
+
+  const dependencies: CargoDependency[] = []
+  for (const [table, kind] of collectDependencyTables(manifest)) {
+    for (const [key, spec] of Object.entries(table)) {
+      let details = asTable(spec) ?? { version: spec }
+      if (details.workspace === true) {
+        const inherited = workspaceDependencies?.[key]
+        details = {
+          ...(asTable(inherited) ?? { version: inherited ?? '*' }),
+          ...details,
+        }
+      }
+      const dependencyPath = asString(details.path)
+      dependencies.push({
+        name: toRustCrateName(key),
+        package: asString(details.package) ?? key,
+        kind,
+        optional: details.optional === true,
+        versionReq: asString(details.version),
+        workspaceMember: dependencyPath
+          ? posix.join(
+              details.workspace === true ? workspaceDir : rootDir,
+              dependencyPath,
+            )
+          : undefined,
+      })
+    }
+  }
+
+  return {
+    name,
+    version: inheritField(pkg.version, workspacePackage, 'version'),
+    manifestPath,
+    rootDir,
+    targets: discoverTargets(manifest, name, rootDir, filePaths),
+    features,
+    dependencies,
+  }
+}
+
+/**
+ * Builds a model of the Cargo workspace in the project from its manifests:
+ * member crates, their targets, features and dependency edges, with exact
+ * versions from Cargo.lock when it is present.
+ *
+ * `files` maps project-relative paths to contents, like `projectFiles`.
+ */
+export function loadCargoWorkspace(
+  files: Record<string, string>,
+): CargoWorkspace | undefined {
+  const filePaths = new Set(Object.keys(files))
+  const manifests = Object.keys(files)
+    .filter((filePath) => posix.basename(filePath) === 'Cargo.toml')
+    .sort((a, b) => a.split('/').length - b.split('/').length)
+    .flatMap((manifestPath) => {
+      const manifest = tryParseToml(files[manifestPath])
+      return manifest ? [{ manifestPath, manifest }] : []
+    })
+  if (manifests.length === 0) {
+    return undefined
+  }
+
+  const root =
+    manifests.find(({ manifest }) => asTable(manifest.workspace)) ??
+    manifests[0]
+  const workspace = asTable(root.manifest.workspace)
+  const workspaceDir = posix.dirname(root.manifestPath)
+
+  let memberManifests = manifests
+  if (workspace) {
+    const include = asStringArray(workspace.members).map((p) =>
+      globToRegExp(posix.join(workspaceDir, p)),
+    )
+    const exclude = asStringArray(workspace.exclude).map((p) =>
+      globToRegExp(posix.join(workspaceDir, p)),
+    )
+    memberManifests = manifests.filter(({ manifestPath }) => {
+      const dir = posix.dirname(manifestPath)
+      return (
+        manifestPath === root.manifestPath ||
+        (include.some((re) => re.test(dir)) &&
+          !exclude.some((re) => re.test(dir)))
+      )
+    })
+  }
+
+  const members = memberManifests.flatMap(({ manifestPath, manifest }) => {
+    const crate = parseCrate({
+      manifestPath,
+      manifest,
+      workspace,
+      workspaceDir,
+      filePaths,
+    })
+    return crate ? [crate] : []
+  })
+
+  // Resolve workspace edges and locked versions
+  const lockedPackages = parseLockfile(
+    files[posix.join(workspaceDir, 'Cargo.lock')],
+  )
+  for (const crate of members) {
+    for (const dependency of crate.dependencies) {
+      // Path dependencies were recorded by directory; map them to members
+      const memberDir = dependency.workspaceMember?.replace(/^\.$/, '')
+      dependency.workspaceMember =
+        memberDir !== undefined
+          ? members.find((m) => m.rootDir === memberDir)?.name
+          : undefined
+      if (!dependency.workspaceMember) {
+        dependency.lockedVersion = findLockedVersion(
+          lockedPackages,
+          crate.name,
+          dependency.package,
+        )
+      }
+    }
+  }
+
+  return {
+    rootManifestPath: root.manifestPath,
+    members,
+  }
+}
+
+/** Returns the member crate whose directory contains `filePath` */
+export function findCrateForFile(
+  workspace: CargoWorkspace,
+  filePath: string,
+): CargoCrate | undefined {
+  let best: CargoCrate | undefined
+  for (const crate of workspace.members) {
+    const contains =
+      crate.rootDir === '' || filePath.startsWith(`${crate.rootDir}/`)
+    if (contains && (!best || crate.rootDir.length > best.rootDir.length)) {
+      best = crate
+    }
+  }
+  return best
+}
+
+/**
+ * Returns the crates affected by changes to the given crates: the crates
+ * themselves plus every member that depends on them, directly or transitively.
+ */
+export function getDependentCrates(
+  workspace: CargoWorkspace,
+  crateNames: string[],
+): string[] {
+  const affected = new Set(crateNames)
+  let changed = true
+  while (changed) {
+    changed = false
+    for (const crate of workspace.members) {
+      if (
+        !affected.has(crate.name) &&
+        crate.dependencies.some(
+          (d) => d.workspaceMember && affected.has(d.workspaceMember),
+        )
+      ) {
+        affected.add(crate.name)
+        changed = true
+      }
+    }
+  }
+  return [...affected]
+}
+
+/** `Serde_JSON`, `serde-json` and `serde_json` name the same package */
+function normalizePackageName(name: string): string {
+  return name.trim().toLowerCase().replace(/[\s_]+/g, '-')
+}
+
+/**
+ * Finds the package from a crates.io-style registry that the project compiles
+ * against for `name`, which may be the package name or the name code imports
+ * it under. The version a workspace member depends on directly wins over
+ * other locked versions; otherwise the highest locked version is returned.
+ *
+ * `files` maps project-relative paths to contents, like `loadCargoWorkspace`.
+ */
+export function findLockedRegistryPackage(
+  files: Record<string, string>,
+  name: string,
+): LockedRegistryPackage | undefined {
+  const query = normalizePackageName(name)
+  const workspace = loadCargoWorkspace(files)
+  const workspaceDir = workspace
+    ? posix.dirname(workspace.rootManifestPath)
+    : '.'
+  const dependency = workspace?.members
+    .flatMap((crate) => crate.dependencies)
+    .find(
+      (d) =>
+        !d.workspaceMember &&
+        (normalizePackageName(d.name) === query ||
+          normalizePackageName(d.package) === query),
+    )
+  const packageName = normalizePackageName(dependency?.package ?? name)
+
+  const candidates = parseLockfile(
+    files[posix.join(workspaceDir, 'Cargo.lock')],
+  )
+    .flatMap((p) =>
+      p.source &&
+      /^(registry|sparse)\+/.test(p.source) &&
+      normalizePackageName(p.name) === packageName
+        ? [{ name: p.name, version: p.version, source: p.source }]
+        : [],
+    )
+    .sort((a, b) =>
+      b.version.localeCompare(a.version, undefined, { numeric: true }),
+    )
+  return (
+    candidates.find((p) => p.version === dependency?.lockedVersion) ??
+    candidates[0]
+  )
+}
@@ -0,0 +1,439 @@
+import * as path from 'path'
+
+import { getLibraryCrateName, loadCargoWorkspace } from './cargo'
+import { getLeadingAttributes, namedChildrenOf } from './file-symbols'
+import { getRustOutline, matchesItemPath, parseRustSource } from './outline'
+import { getRustPublicApi } from './public-api'
+import { locateRustModule } from './resolve'
+
+import type { RustOutlineItem, RustOutlineItemKind } from './outline'
+import type { Node, Tree } from 'web-tree-sitter'
+
+const posix = path.posix
+const CHARS_PER_TOKEN = 4
+const MAX_EXAMPLES = 3
+const MAX_SUMMARY_LENGTH = 120
+/** Outline entries that are not items of their own */
+const MEMBER_KINDS = new Set<RustOutlineItemKind>(['field', 'variant', 'impl'])
+const TYPE_KINDS = new Set<RustOutlineItemKind>(['struct', 'enum', 'union'])
+const TARGET_DIR = /^(examples|tests|benches)\//
+const STOP_WORDS = new Set([
+  'a',
+  'an',
+  'and',
+  'for',
+  'how',
+  'in',
+  'of',
+  'on',
+  'the',
+  'to',
+  'use',
+  'using',
+  'with',
+])
+/** `#[doc = "text"]` and `#![doc = include_str!("file")]` */
+const DOC_ATTRIBUTE =
+  /^#!?\[\s*doc\s*=\s*(?:"((?:[^"\\]|\\.)*)"|include_str!\s*\(\s*"([^"]+)"\s*\))\s*\]$/s
+
+export interface RustDocItem {
+  /** Path dependents name the item by, e.g. `serde_json::Value::as_str` */
+  path: string
+  kind: RustOutlineItemKind
+  /** Declaration with bodies elided; types list their public fields */
+  signature: string
+  /** Doc comment as Markdown, empty for undocumented items */
+  docs: string
+  /** Deprecation notice, e.g. `Deprecated since 1.2.0: use bar instead` */
+  deprecation?: string
+  file: string
+  line: number
+}
+
+export interface RustCrateDocs {
+  crateName: string
+  /** Inner doc comments of the crate root */
+  docs: string
+  items: RustDocItem[]
+  /** Programs under `examples/` */
+  examples: { file: string; source: string }[]
+}
+
+interface ParsedFile {
+  tree: Tree
+  root: Node
+  outline: RustOutlineItem[]
+}
+
+function flattenOutline(items: RustOutlineItem[]): RustOutlineItem[] {
+  return items.flatMap((item) => [item, ...flattenOutline(item.children)])
+}
+
+/** Text of a doc attribute, reading `include_str!` files from `files` */
+function getDocAttributeText(
+  attribute: string,
+  file: string,
+  files: Record<string, string>,
+): string | undefined {
+  const match = DOC_ATTRIBUTE.exec(attribute.trim())
+  if (!match) {
+    return undefined
+  }
+  if (match[2] !== undefined) {
+    return files[posix.normalize(posix.join(posix.dirname(file), match[2]))]
+  }
+  return match[1].replace(/\\(["\\n])/g, (_, ch) => (ch === 'n' ? '\n' : ch))
+}
+
+function getItemDocs(
+  node: Node,
+  file: string,
+  files: Record<string, string>,
+): string {
+  const { attributes, docs } = getLeadingAttributes(node)
+  return [
+    ...docs,
+    ...attributes.flatMap(
+      (attribute) => getDocAttributeText(attribute, file, files) ?? [],
+    ),
+  ]
+    .join('\n')
+    .trim()
+}
+
+/** `//!` and `/*! */` comments and `#![doc = ..]` attributes of a file */
+function getInnerDocs(
+  root: Node,
+  file: string,
+  files: Record<string, string>,
+): string {
+  const lines: string[] = []
+  for (const node of namedChildrenOf(root)) {
+    if (node.type === 'inner_attribute_item') {
+      const text = getDocAttributeText(node.text, file, files)
+      if (text !== undefined) {
+        lines.push(text)
+      }
+    } else if (node.type.endsWith('comment')) {
+      if (node.text.startsWith('//!')) {
+        lines.push(node.text.replace(/^\/\/! ?/, '').trimEnd())
+      } else if (node.text.startsWith('/*!')) {
+        const body = node.text.replace(/^\/\*!|\*\/$/g, '')
+        lines.push(
+          ...body.split('\n').map((line) => line.replace(/^\s*\* ?/, '')),
+        )
+      }
+    } else {
+      break
+    }
+  }
+  return lines.join('\n').trim()
+}
+
+/** Types are shown with their public fields, like rustdoc does */
+function getSignature(item: RustOutlineItem): string {
+  if (!TYPE_KINDS.has(item.kind) || item.children.length === 0) {
+    return item.signature
+  }
+  const members = item.children.filter(
+    (member) => member.kind === 'variant' || /^pub\s/.test(member.signature),
+  )
+  return [
+    `${item.signature} {`,
+    ...members.map((member) => `    ${member.signature},`),
+    ...(members.length < item.children.length
+      ? ['    /* private fields */']
+      : []),
+    '}',
+  ].join('\n')
+}
+
+/**
+ * Extracts the documentation of a crate from its sources: crate and module
+ * docs, and the public API with each item's signature and doc comment. Item
+ * paths follow `pub use` re-exports, preferring the shortest path when an
+ * item is exported under several.
+ *
+ * `files` maps paths relative to `crateDir` to contents and should include
+ * `Cargo.toml`, the library sources, and files the docs include with
+ * `include_str!` such as `README.md`.
+ */
+export async function buildRustCrateDocs(
+  crateDir: string,
+  files: Record<string, string>,
+): Promise<RustCrateDocs> {
+  const workspace = loadCargoWorkspace(files)
+  const crate = workspace?.members.find((member) => member.rootDir === '')
+  const sourcePaths = Object.keys(files)
+    .filter((file) => file.endsWith('.rs') && !TARGET_DIR.test(file))
+    .sort()
+  const apis = await getRustPublicApi(
+    crateDir,
+    sourcePaths,
+    (file) => files[file] ?? null,
+    workspace,
+  )
+  const api = apis.find(({ crateKey }) => crateKey === '.')
+  const crateName =
+    api?.crateName ??
+    (crate ? getLibraryCrateName(crate) : path.basename(crateDir))
+
+  const parsedFiles = new Map<string, ParsedFile | undefined>()
+  const parse = async (file: string) => {
+    if (!parsedFiles.has(file)) {
+      const tree = await parseRustSource(file, files[file] ?? '')
+      parsedFiles.set(
+        file,
+        tree && {
+          tree,
+          root: tree.rootNode,
+          outline: flattenOutline(getRustOutline(tree.rootNode)).filter(
+            (item) => !MEMBER_KINDS.has(item.kind),
+          ),
+        },
+      )
+    }
+    return parsedFiles.get(file)
+  }
+
+  try {
+    const moduleDocs = new Map<string, string>()
+    for (const file of sourcePaths) {
+      const location = locateRustModule(file, crateName, crate)
+      if (!location.isLibrary || file === 'src/main.rs') {
+        continue
+      }
+      const parsed = await parse(file)
+      if (parsed) {
+        moduleDocs.set(
+          location.modulePath.join('::'),
+          getInnerDocs(parsed.root, file, files),
+        )
+      }
+    }
+
+    const items: RustDocItem[] = []
+    const itemIndexes = new Map<string, number>()
+    for (const { path: itemPath, file, token } of api?.items ?? []) {
+      // Trait impl items are documented on the trait
+      if (token.startsWith('<')) {
+        continue
+      }
+      const parsed = await parse(file)
+      const matches =
+        parsed?.outline.filter((item) => matchesItemPath(item.path, token)) ??
+        []
+      const match =
+        matches.find((item) => itemPath.endsWith(`::${item.path}`)) ??
+        matches[0]
+      if (!parsed || !match) {
+        continue
+      }
+
+      const key = `${file}:${match.startIndex}`
+      const existing = itemIndexes.get(key)
+      if (existing !== undefined) {
+        const segments = (p: string) => p.split('::').length
+        if (segments(itemPath) < segments(items[existing].path)) {
+          items[existing].path = itemPath
+        }
+        continue
+      }
+
+      const node =
+        parsed.root.descendantForIndex(match.startIndex, match.endIndex) ??
+        parsed.root
+      let docs = getItemDocs(node, file, files)
+      if (!docs && match.kind === 'mod') {
+        const { modulePath } = locateRustModule(file, crateName, crate)
+        docs =
+          moduleDocs.get(
+            [...modulePath, ...match.path.split('::')].join('::'),
+          ) ?? ''
+      }
+      itemIndexes.set(key, items.length)
+      items.push({
+        path: itemPath,
+        kind: match.kind,
+        signature: getSignature(match),
+        docs,
+        file,
+        line: match.startLine,
+      })
+    }
+
+    return {
+      crateName,
+      docs: moduleDocs.get('') ?? '',
+      items,
+      examples: Object.keys(files)
+        .filter((file) => file.startsWith('examples/') && file.endsWith('.rs'))
+        .sort()
+        .map((file) => ({ file, source: files[file] })),
+    }
+  } finally {
+    for (const parsed of parsedFiles.values()) {
+      parsed?.tree.delete()
+    }
+  }
+}
+
+function getSearchTerms(topic: string): string[] {
+  const terms = topic
+    .toLowerCase()
+    .split(/[^a-z0-9_]+/)
+    .filter((term) => term.length > 1 && !STOP_WORDS.has(term))
+  return [...new Set(terms)]
+}
+
+function scoreItem(item: RustDocItem, topic: string, terms: string[]): number {
+  if (topic.trim() && matchesItemPath(item.path, topic.trim())) {
+    return 100
+  }
+  const itemPath = item.path.toLowerCase()
+  const name = itemPath.slice(itemPath.lastIndexOf('::') + 2)
+  const docs = item.docs.toLowerCase()
+  let score = 0
+  for (const term of terms) {
+    if (name === term) {
+      score += 8
+    } else if (name.includes(term)) {
+      score += 4
+    } else if (itemPath.includes(term)) {
+      score += 2
+    }
+    if (docs.includes(term)) {
+      score += 1
+    }
+  }
+  return score
+}
+
+/** First paragraph of a doc comment, on one line */
+function getSummary(docs: string): string {
+  const summary = docs.split(/\n\s*\n/)[0].replace(/\s+/g, ' ').trim()
+  return summary.length > MAX_SUMMARY_LENGTH
+    ? `${summary.slice(0, MAX_SUMMARY_LENGTH)}…`
+    : summary
+}
+
+function renderItem(item: RustDocItem): string {
+  return [
+    `## \`${item.path}\` (${item.kind}, ${item.file}:${item.line})`,
+    `\`\`\`rust\n${item.signature}\n\`\`\``,
+    ...(item.deprecation ? [`_${item.deprecation}_`] : []),
+    ...(item.docs ? [item.docs] : []),
+  ].join('\n\n')
+}
+
+/**
+ * Joins sections after the title until the budget is spent. The first
+ * section is truncated rather than left out, so the output is never empty.
+ */
+function joinSections(
+  title: string,
+  sections: string[],
+  maxChars: number,
+): string {
+  const included = [title]
+  let length = title.length
+  for (const section of sections) {
+    const isFirst = included.length === 1
+    if (length + section.length + 2 > maxChars && !isFirst) {
+      break
+    }
+    const remaining = Math.max(0, maxChars - length - 2)
+    const text =
+      section.length > remaining ? `${section.slice(0, remaining)}…` : section
+    included.push(text)
+    length += text.length + 2
+  }
+  const omitted = sections.length - (included.length - 1)
+  if (omitted > 0) {
+    included.push(
+      `_${omitted} more section(s) omitted. Narrow the topic or raise max_tokens to see them._`,
+    )
+  }
+  return included.join('\n\n')
+}
+
+/**
+ * Renders the docs of the items matching `topic` as Markdown, best matches
+ * first, with matching examples, in about `maxTokens` tokens. A topic may
+ * also be an item path such as `Value::as_str`. Without matches, renders an
+ * overview: the crate docs and the top-level items.
+ */
+export function renderRustCrateDocs(
+  docs: RustCrateDocs,
+  params: { title: string; topic: string; maxTokens: number },
+): string {
+  const { title, topic, maxTokens } = params
+  const terms = getSearchTerms(topic)
+  const matches = docs.items
+    .map((item, index) => ({
+      item,
+      index,
+      score: scoreItem(item, topic, terms),
+    }))
+    .filter(({ score }) => score > 0)
+    .sort(
+      (a, b) =>
+        b.score - a.score ||
+        a.item.path.split('::').length - b.item.path.split('::').length ||
+        a.index - b.index,
+    )
+  const fileMatches = (file: string) =>
+    terms.some((term) => file.toLowerCase().includes(term))
+  const examples = docs.examples
+    .filter(
+      ({ file, source }) =>
+        fileMatches(file) ||
+        terms.some((term) => source.toLowerCase().includes(term)),
+    )
+    .sort((a, b) => Number(fileMatches(b.file)) - Number(fileMatches(a.file)))
+    .slice(0, MAX_EXAMPLES)
+
+  const sections: string[] = []
+  if (matches.length > 0) {
+    const summary = getSummary(docs.docs)
+    if (summary) {
+      sections.push(summary)
+    }
+    sections.push(...matches.map(({ item }) => renderItem(item)))
+  } else {
+    if (terms.length > 0) {
+      sections.push(`No items match "${topic}". Overview of the crate:`)
+    }
+    if (docs.docs) {
+      sections.push(docs.docs)
+    }
+    const topLevel = docs.items.filter(
+      (item) => item.path.split('::').length === 2,
+    )
+    if (topLevel.length > 0) {
+      sections.push(
+        [
+          '## Items',
+          ...topLevel.map((item) => {
+            const summary = getSummary(item.docs)
+            return `- \`${item.path}\` (${item.kind})${summary ? `: ${summary}` : ''}`
+          }),
+        ].join('\n'),
+      )
+    }
+    if (examples.length === 0 && docs.examples.length > 0) {
+      sections.push(
+        ['## Examples', ...docs.examples.map(({ file }) => `- ${file}`)].join(
+          '\n',
+        ),
+      )
+    }
+  }
+  sections.push(
+    ...examples.map(
+      ({ file, source }) =>
+        `## Example: ${file}\n\n\`\`\`rust\n${source.trimEnd()}\n\`\`\``,
+    ),
+  )
+  return joinSections(`# ${title}`, sections, maxTokens * CHARS_PER_TOKEN)
+}
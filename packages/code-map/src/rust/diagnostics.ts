@@ -0,0 +1,349 @@
+import type { SourceRange } from '../source-range'
+
+export type CargoDiagnosticLevel =
+  | 'error'
+  | 'warning'
+  | 'note'
+  | 'help'
+  | 'failure-note'
+  | 'error: internal compiler error'
+
+export interface CargoDiagnosticSpan extends SourceRange {
+  file: string
+  label?: string
+}
+
+export interface CargoSuggestion extends CargoDiagnosticSpan {
+  /** What the suggestion does, e.g. `consider borrowing here` */
+  message: string
+  replacement: string
+  /** `MachineApplicable` suggestions can be applied without review */
+  applicability?: string
+}
+
+export interface CargoDiagnostic {
+  level: CargoDiagnosticLevel
+  /** Error or lint code, e.g. `E0308` or `clippy::needless_return` */
+  code?: string
+  message: string
+  /** Package id of the crate being compiled */
+  packageId?: string
+  primarySpan?: CargoDiagnosticSpan
+  suggestions: CargoSuggestion[]
+  /** The diagnostic as rustc prints it to a terminal */
+  rendered?: string
+}
+
+export interface CargoDiagnosticFile {
+  /** Empty for diagnostics without a location, e.g. linker errors */
+  file: string
+  diagnostics: CargoDiagnostic[]
+}
+
+export interface CargoDiagnosticSummary {
+  /** Files with their diagnostics, files with errors first */
+  files: CargoDiagnosticFile[]
+  errorCount: number
+  warningCount: number
+  /** Diagnostics left out beyond the limit */
+  omitted: number
+}
+
+/** Subset of rustc's JSON diagnostic format that is read */
+interface RustcSpan {
+  file_name: string
+  line_start: number
+  line_end: number
+  column_start: number
+  column_end: number
+  is_primary: boolean
+  label: string | null
+  suggested_replacement: string | null
+  suggestion_applicability: string | null
+}
+
+interface RustcDiagnostic {
+  message: string
+  code: { code: string } | null
+  level: CargoDiagnosticLevel
+  spans: RustcSpan[]
+  children: RustcDiagnostic[]
+  rendered: string | null
+}
+
+/** Summary lines rustc emits as diagnostics of their own */
+const SUMMARY_MESSAGE =
+  /^(?:aborting due to|\d+ warnings? emitted|could not compile|for more information about this error)/
+const LEVEL_ORDER: Partial<Record<CargoDiagnosticLevel, number>> = {
+  'error: internal compiler error': 0,
+  error: 0,
+  warning: 1,
+}
+
+function toSpan({
+  file_name,
+  line_start,
+  column_start,
+  line_end,
+  column_end,
+  label,
+}: RustcSpan): CargoDiagnosticSpan {
+  return {
+    file: file_name,
+    startLine: line_start,
+    startColumn: column_start,
+    endLine: line_end,
+    endColumn: column_end,
+    ...(label && { label }),
+  }
+}
+
+function collectSuggestions(
+  diagnostic: RustcDiagnostic,
+  out: CargoSuggestion[],
+) {
+  for (const span of diagnostic.spans) {
+    if (span.suggested_replacement === null) {
+      continue
+    }
+    out.push({
+      ...toSpan(span),
+      message: diagnostic.message,
+      replacement: span.suggested_replacement,
+      ...(span.suggestion_applicability && {
+        applicability: span.suggestion_applicability,
+      }),
+    })
+  }
+  for (const child of diagnostic.children) {
+    collectSuggestions(child, out)
+  }
+}
+
+function toDiagnostic(
+  message: RustcDiagnostic,
+  packageId: string | undefined,
+): CargoDiagnostic {
+  const primary = message.spans.find((span) => span.is_primary)
+  const suggestions: CargoSuggestion[] = []
+  collectSuggestions(message, suggestions)
+  return {
+    level: message.level,
+    ...(message.code && { code: message.code.code }),
+    message: message.message,
+    ...(packageId && { packageId }),
+    ...(primary && { primarySpan: toSpan(primary) }),
+    suggestions,
+    ...(message.rendered && { rendered: message.rendered }),
+  }
+}
+
+/**
+ * Parses the output of `cargo check/clippy/build --message-format=json` into
+ * compiler diagnostics. Lines that are not JSON compiler messages, such as
+ * build script output, are skipped.
+ */
+export function parseCargoDiagnostics(output: string): CargoDiagnostic[] {
+  const diagnostics: CargoDiagnostic[] = []
+  for (const line of output.split('\n')) {
+    if (!line.startsWith('{')) {
+      continue
+    }
+    let parsed: {
+      reason?: string
+      package_id?: string
+      message?: RustcDiagnostic
+    }
+    try {
+      parsed = JSON.parse(line)
+    } catch {
+      continue
+    }
+    const { reason, package_id, message } = parsed
+    if (reason !== 'compiler-message' || !message) {
+      continue
+    }
+    const isSummary =
+      message.spans.length === 0 && SUMMARY_MESSAGE.test(message.message)
+    if (!isSummary) {
+      diagnostics.push(toDiagnostic(message, package_id))
+    }
+  }
+  return diagnostics
+}
+
+const levelOrder = (diagnostic: CargoDiagnostic) =>
+  LEVEL_ORDER[diagnostic.level] ?? 2
+
+function diagnosticKey({ level, message, primarySpan }: CargoDiagnostic) {
+  const at = primarySpan
+    ? `${primarySpan.file}:${primarySpan.startLine}:${primarySpan.startColumn}`
+    : ''
+  return `${level}\0${message}\0${at}`
+}
+
+/**
+ * Dedupes diagnostics, e.g. ones reported for both a library and its tests,
+ * and groups the `limit` most severe by file in line order.
+ */
+export function summarizeCargoDiagnostics(
+  diagnostics: CargoDiagnostic[],
+  limit: number,
+): CargoDiagnosticSummary {
+  const unique = new Map<string, CargoDiagnostic>()
+  for (const diagnostic of diagnostics) {
+    const key = diagnosticKey(diagnostic)
+    if (!unique.has(key)) {
+      unique.set(key, diagnostic)
+    }
+  }
+  const all = [...unique.values()]
+  const selected = all
+    .map((diagnostic, index) => ({ diagnostic, index }))
+    .sort(
+      (a, b) =>
+        levelOrder(a.diagnostic) - levelOrder(b.diagnostic) ||
+        a.index - b.index,
+    )
+    .slice(0, Math.max(0, limit))
+    .map(({ diagnostic }) => diagnostic)
+
+  const byFile = new Map<string, CargoDiagnostic[]>()
+  for (const diagnostic of selected) {
+    const file = diagnostic.primarySpan?.file ?? ''
+    const fileDiagnostics = byFile.get(file) ?? []
+    fileDiagnostics.push(diagnostic)
+    byFile.set(file, fileDiagnostics)
+  }
+  const files = [...byFile].map(([file, fileDiagnostics]) => ({
+    file,
+    diagnostics: fileDiagnostics.sort(
+      (a, b) =>
+        (a.primarySpan?.startLine ?? 0) - (b.primarySpan?.startLine ?? 0),
+    ),
+  }))
+
+  const errors = all.filter((diagnostic) => levelOrder(diagnostic) === 0)
+  const warnings = all.filter((diagnostic) => diagnostic.level === 'warning')
+  return {
+    files,
+    errorCount: errors.length,
+    warningCount: warnings.length,
+    omitted: all.length - selected.length,
+  }
+}
+
+/** A replacement of a span of one file, e.g. a selected `CargoSuggestion` */
+export interface SpanReplacement extends SourceRange {
+  replacement: string
+  message?: string
+  applicability?: string
+}
+
+interface SpanEdit {
+  start: number
+  end: number
+  suggestion: SpanReplacement
+}
+
+const formatPosition = ({ startLine, startColumn }: SourceRange) =>
+  `${startLine}:${startColumn}`
+
+/**
+ * Offset in `source` of a 1-based line and column, counting columns in
+ * characters like rustc does rather than UTF-16 code units
+ */
+function toOffset(
+  source: string,
+  lineStarts: number[],
+  line: number,
+  column: number,
+): number | undefined {
+  const lineStart = lineStarts[line - 1]
+  if (lineStart === undefined || column < 1) {
+    return undefined
+  }
+  const lineEnd = lineStarts[line] ?? source.length + 1
+  let offset = lineStart
+  for (let i = 1; i < column; i++) {
+    if (offset >= lineEnd - 1) {
+      return undefined
+    }
+    offset += source.codePointAt(offset)! > 0xffff ? 2 : 1
+  }
+  return offset
+}
+
+/**
+ * Applies span replacements, such as rustc's suggested fixes, to a file's
+ * source all at once. Duplicates are applied once; overlapping spans, spans
+ * outside the file and suggestions with placeholders fail the whole batch.
+ */
+export function applyCargoSuggestions(
+  source: string,
+  suggestions: SpanReplacement[],
+): { content: string; messages: string[] } | { error: string } {
+  const lineStarts = [0]
+  for (let i = 0; i < source.length; i++) {
+    if (source[i] === '\n') {
+      lineStarts.push(i + 1)
+    }
+  }
+
+  const edits: SpanEdit[] = []
+  for (const suggestion of suggestions) {
+    if (suggestion.applicability === 'HasPlaceholders') {
+      return {
+        error: `The suggestion at ${formatPosition(suggestion)} contains placeholders to fill in. Edit the code with str_replace instead.`,
+      }
+    }
+    const start = toOffset(
+      source,
+      lineStarts,
+      suggestion.startLine,
+      suggestion.startColumn,
+    )
+    const end = toOffset(
+      source,
+      lineStarts,
+      suggestion.endLine,
+      suggestion.endColumn,
+    )
+    if (start === undefined || end === undefined || end < start) {
+      return {
+        error: `The span at ${formatPosition(suggestion)} is outside the file. The file may have changed since the diagnostics were produced; run cargo again.`,
+      }
+    }
+    const duplicate = edits.some(
+      (edit) =>
+        edit.start === start &&
+        edit.end === end &&
+        edit.suggestion.replacement === suggestion.replacement,
+    )
+    if (!duplicate) {
+      edits.push({ start, end, suggestion })
+    }
+  }
+
+  edits.sort((a, b) => a.start - b.start || a.end - b.end)
+  for (let i = 1; i < edits.length; i++) {
+    const previous = edits[i - 1]
+    const current = edits[i]
+    if (current.start < previous.end || current.start === previous.start) {
+      return {
+        error: `The suggestions at ${formatPosition(previous.suggestion)} and ${formatPosition(current.suggestion)} overlap. Apply them in separate calls, re-running cargo in between.`,
+      }
+    }
+  }
+
+  let content = source
+  for (const { start, end, suggestion } of [...edits].reverse()) {
+    content =
+      content.slice(0, start) + suggestion.replacement + content.slice(end)
+  }
+  const messages = edits.map(
+    ({ suggestion }) =>
+      `Applied the suggestion at ${formatPosition(suggestion)}${suggestion.message ? `: ${suggestion.message}` : ''}`,
+  )
+  return { content, messages }
+}
@@ -0,0 +1,280 @@
+import { namedChildrenOf } from './file-symbols'
+import {
+  findRustOutlineItems,
+  getRustOutline,
+  withRustSource,
+} from './outline'
+
+import type { RustOutlineItem } from './outline'
+import type { Node } from 'web-tree-sitter'
+
+const INDENT_UNIT = '    '
+/** Bodies whose members are separated by commas */
+const MEMBER_LIST_TYPES = new Set([
+  'field_declaration_list',
+  'enum_variant_list',
+])
+
+export type RustSymbolEditAction =
+  | 'replace'
+  | 'insert_before'
+  | 'insert_after'
+  | 'append'
+  | 'delete'
+
+export type RustSymbolEdit =
+  | {
+      /** Item path as listed by the outline, e.g. `<Greeting as Greeter>::greet` */
+      symbol: string
+      action: Exclude<RustSymbolEditAction, 'delete'>
+      /** New source */
+      content: string
+    }
+  | { symbol: string; action: 'delete' }
+
+function getLineStart(source: string, index: number): number {
+  return source.lastIndexOf('\n', index - 1) + 1
+}
+
+function getIndentation(source: string, index: number): string {
+  const lineStart = getLineStart(source, index)
+  return /^[ \t]*/.exec(source.slice(lineStart))?.[0] ?? ''
+}
+
+function splice(
+  source: string,
+  start: number,
+  end: number,
+  text: string,
+): string {
+  return source.slice(0, start) + text + source.slice(end)
+}
+
+/**
+ * Strips the common indentation of a snippet and indents every line after
+ * the first with `indent`, for placing it at an `indent`-ed position.
+ */
+function reindent(content: string, indent: string): string {
+  const lines = content.replace(/^\n+|\s+$/g, '').split('\n')
+  const common = Math.min(
+    ...lines
+      .filter((line) => line.trim())
+      .map((line) => /^[ \t]*/.exec(line)?.[0].length ?? 0),
+  )
+  return lines
+    .map((line, i) => {
+      const stripped = line.trim() ? line.slice(common) : ''
+      return i === 0 || !stripped ? stripped : indent + stripped
+    })
+    .join('\n')
+}
+
+/** The node an outline item was built from */
+function findItemNode(root: Node, item: RustOutlineItem): Node | undefined {
+  for (
+    let node: Node | null = root.descendantForIndex(item.startIndex);
+    node;
+    node = node.parent
+  ) {
+    if (
+      node.startIndex === item.startIndex &&
+      node.endIndex === item.endIndex
+    ) {
+      return node
+    }
+  }
+  return undefined
+}
+
+/** Start of the item including the attributes and doc comments above it */
+function getLeadingStart(node: Node): number {
+  let start = node.startIndex
+  for (
+    let prev = node.previousNamedSibling;
+    prev &&
+    (prev.type === 'attribute_item' ||
+      (prev.type.endsWith('comment') && /^\/\/\/|^\/\*\*/.test(prev.text)));
+    prev = prev.previousNamedSibling
+  ) {
+    start = prev.startIndex
+  }
+  return start
+}
+
+function withComma(member: string): string {
+  return member.endsWith(',') ? member : `${member},`
+}
+
+function appendMember(
+  source: string,
+  node: Node,
+  content: string,
+  indent: string,
+): string | { error: string } {
+  const body = node.childForFieldName('body')
+  const close = body?.lastChild
+  if (!body || close?.type !== '}') {
+    return { error: 'The item has no body to append to.' }
+  }
+
+  const isMemberList = MEMBER_LIST_TYPES.has(body.type)
+  const last = namedChildrenOf(body)
+    .filter((member) => !member.type.endsWith('comment'))
+    .at(-1)
+  const isMultiline =
+    last &&
+    getLineStart(source, last.startIndex) !==
+      getLineStart(source, body.startIndex)
+  const memberIndent = isMultiline
+    ? getIndentation(source, last.startIndex)
+    : indent + INDENT_UNIT
+  const reindented = reindent(content, memberIndent)
+  const member = isMemberList ? withComma(reindented) : reindented
+
+  let updated: string
+  const closeLineStart = getLineStart(source, close.startIndex)
+  if (source.slice(closeLineStart, close.startIndex).trim() === '') {
+    updated = splice(
+      source,
+      closeLineStart,
+      closeLineStart,
+      `${memberIndent}${member}\n`,
+    )
+  } else {
+    let start = close.startIndex
+    while (source[start - 1] === ' ') {
+      start--
+    }
+    updated = splice(
+      source,
+      start,
+      close.startIndex,
+      `\n${memberIndent}${member}\n${indent}`,
+    )
+  }
+
+  // Separate the new member from the previous one
+  if (
+    isMemberList &&
+    last &&
+    !source.slice(last.endIndex, close.startIndex).trimStart().startsWith(',')
+  ) {
+    updated = splice(updated, last.endIndex, last.endIndex, ',')
+  }
+  return updated
+}
+
+function applyEdit(
+  source: string,
+  root: Node,
+  edit: RustSymbolEdit,
+): string | { error: string } {
+  const matches = findRustOutlineItems(getRustOutline(root), edit.symbol)
+  if (matches.length === 0) {
+    return {
+      error: `No item found at path "${edit.symbol}". Read the file with outline mode to list its items.`,
+    }
+  }
+  if (matches.length > 1) {
+    const candidates = matches
+      .map((item) => `${item.path} (line ${item.startLine})`)
+      .join(', ')
+    return {
+      error: `"${edit.symbol}" matches ${matches.length} items: ${candidates}. Use a more qualified path, e.g. \`<Type as Trait>::item\`.`,
+    }
+  }
+
+  const rawContent = edit.action === 'delete' ? '' : edit.content
+  if (typeof rawContent !== 'string') {
+    return { error: `"content" is required for "${edit.action}".` }
+  }
+
+  const [item] = matches
+  const node = findItemNode(root, item)
+  if (!node) {
+    return { error: `Could not locate "${item.path}" in the syntax tree.` }
+  }
+  const indent = getIndentation(source, item.startIndex)
+  const content = reindent(rawContent, indent)
+  // Fields and variants are separated by commas rather than blank lines
+  const isMember = item.kind === 'field' || item.kind === 'variant'
+
+  switch (edit.action) {
+    case 'replace':
+      return splice(source, item.startIndex, item.endIndex, content)
+    case 'insert_before': {
+      const start = getLineStart(source, getLeadingStart(node))
+      const inserted = isMember ? `${withComma(content)}\n` : `${content}\n\n`
+      return splice(source, start, start, `${indent}${inserted}`)
+    }
+    case 'insert_after': {
+      if (!isMember) {
+        return splice(
+          source,
+          item.endIndex,
+          item.endIndex,
+          `\n\n${indent}${content}`,
+        )
+      }
+      const comma = /^\s*,/.exec(source.slice(item.endIndex))
+      const end = item.endIndex + (comma?.[0].length ?? 0)
+      return splice(
+        source,
+        item.endIndex,
+        end,
+        `,\n${indent}${withComma(content)}`,
+      )
+    }
+    case 'delete': {
+      const start = getLineStart(source, getLeadingStart(node))
+      let end = item.endIndex
+      // Remove the rest of the line, and one blank line after the item
+      while (end < source.length && /[ \t,]/.test(source[end])) {
+        end++
+      }
+      for (let i = 0; i < 2 && source[end] === '\n'; i++) {
+        end++
+      }
+      return splice(source, start, end, '')
+    }
+    case 'append':
+      return appendMember(source, node, rawContent, indent)
+  }
+}
+
+/**
+ * Applies structural edits to Rust source, addressing items by their outline
+ * path rather than by matching text. Each edit is applied to the result of
+ * the previous one; edits that fail are skipped and reported in `messages`.
+ *
+ * - `replace`: replaces the item, keeping its attributes and doc comments
+ * - `insert_before` / `insert_after`: adds a sibling item
+ * - `append`: adds a field, variant or item at the end of the item's body
+ * - `delete`: removes the item with its attributes and doc comments
+ */
+export async function editRustSymbols(
+  filePath: string,
+  sourceCode: string,
+  edits: RustSymbolEdit[],
+): Promise<{ content: string; messages: string[] } | { error: string }> {
+  if (!filePath.endsWith('.rs')) {
+    return { error: 'Symbol edits are only supported for Rust files.' }
+  }
+  let content = sourceCode
+  const messages: string[] = []
+  for (const edit of edits) {
+    const current = content
+    const result = await withRustSource(filePath, current, (root) =>
+      applyEdit(current, root, edit),
+    )
+    if (result === undefined) {
+      return { error: 'Could not parse the file.' }
+    }
+    if (typeof result === 'string') {
+      content = result
+    } else {
+      messages.push(`${edit.action} ${edit.symbol}: ${result.error}`)
+    }
+  }
+  return { content, messages }
+}
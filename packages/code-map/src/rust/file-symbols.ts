@@ -0,0 +1,640 @@
+import { getSourceRange } from '../source-range'
+import {
+  expandRustMacroInvocation,
+  getDerivedTraitPaths,
+  parseRustMacroDefinition,
+  toMacroTokens,
+} from './macros'
+import {
+  getEnclosingRustImpl,
+  getRustItemContainer,
+  getRustTypeName,
+  qualifyRustCapture,
+} from './symbols'
+
+import type { SourceRange } from '../source-range'
+import type {
+  RustImpliedImpl,
+  RustMacroDefinition,
+  RustMacroInvocation,
+} from './macros'
+import type { Node, QueryCapture } from 'web-tree-sitter'
+
+/**
+ * How far an item is visible: `pub`, `pub(crate)`, `pub(super)`, any other
+ * `pub(in path)`, or private to its module (no modifier, or `pub(self)`).
+ */
+export type RustVisibility =
+  | 'public'
+  | 'crate'
+  | 'super'
+  | 'restricted'
+  | 'private'
+
+export interface RustDefinition {
+  token: string
+  /** Inline modules (`mod name { .. }`) between the file's module and the item */
+  modulePath: string[]
+  /** Name of the `macro_rules!` macro the item was generated by */
+  macro?: string
+  /** Unknown for macro-generated items */
+  visibility?: RustVisibility
+}
+
+export interface RustImport {
+  modulePath: string[]
+  /** Local name the import is bound to; undefined for glob imports */
+  alias: string | undefined
+  /** Path as written, e.g. `['crate', 'config', 'Settings']` */
+  path: string[]
+  isGlob: boolean
+  visibility: RustVisibility
+}
+
+export interface RustReference {
+  /** Token the reference is recorded under, matching `calls` from parseTokens */
+  token: string
+  /** Path as written at the call site, with `Self` replaced by the impl type */
+  path: string[]
+  modulePath: string[]
+  /** Method calls (`value.method()`) have no path to resolve */
+  kind: 'path' | 'method'
+  /** Position of the referenced name; unset for references from derives */
+  range?: SourceRange
+}
+
+export interface RustTraitDefinition {
+  name: string
+  line: number
+  modulePath: string[]
+}
+
+export interface RustImplBlock {
+  /** Self type name, or the type parameter for blanket impls */
+  typeName: string
+  traitName: string | undefined
+  /** Header as written, e.g. `impl<T: Display> Greeter for Wrapper<T>` */
+  header: string
+  line: number
+  /** `impl<T> Trait for T`: implemented for every type satisfying the bounds */
+  isBlanket: boolean
+}
+
+export interface RustTestFunction {
+  name: string
+  modulePath: string[]
+  /** 1-based lines of the function, attributes excluded */
+  startLine: number
+  endLine: number
+  /** The test attribute as written, e.g. `#[tokio::test]` */
+  attribute: string
+}
+
+export interface RustDocTest {
+  /** Token of the documented item, as in `definitions` */
+  token: string
+  modulePath: string[]
+  line: number
+}
+
+export interface RustFileSymbols {
+  definitions: RustDefinition[]
+  imports: RustImport[]
+  references: RustReference[]
+  /** Module declarations, both inline (`mod a { }`) and out-of-line (`mod a;`) */
+  modules: string[][]
+  /** `macro_rules!` definitions, for expanding invocations in other files */
+  macros: RustMacroDefinition[]
+  /** Item-level invocations of macros not defined in this file */
+  macroInvocations: RustMacroInvocation[]
+  /** Trait impls from `#[derive(..)]` and macro expansions */
+  impliedImpls: RustImpliedImpl[]
+  traits: RustTraitDefinition[]
+  /** Explicit `impl` blocks, inherent and trait */
+  impls: RustImplBlock[]
+  /** Functions marked `#[test]`, `#[tokio::test]` and the like */
+  tests: RustTestFunction[]
+  /** Items whose doc comments contain Rust code blocks */
+  docTests: RustDocTest[]
+  /** Modules compiled only for tests (`#[cfg(test)] mod tests`) */
+  testModules: string[][]
+}
+
+export function namedChildrenOf(node: Node): Node[] {
+  return node.namedChildren.filter((child): child is Node => child !== null)
+}
+
+/**
+ * Visibility of an item node. Items of traits and trait impls are as visible
+ * as the trait itself, and `macro_rules!` macros are public when marked
+ * `#[macro_export]`.
+ */
+export function getRustVisibility(item: Node): RustVisibility {
+  const container = getRustItemContainer(item)
+  if (container && (container.kind === 'trait' || container.traitName)) {
+    return 'public'
+  }
+  if (item.type === 'macro_definition') {
+    for (
+      let prev = item.previousNamedSibling;
+      prev?.type === 'attribute_item';
+      prev = prev.previousNamedSibling
+    ) {
+      if (/^#\[\s*macro_export\b/.test(prev.text)) {
+        return 'public'
+      }
+    }
+    return 'private'
+  }
+
+  const modifier = namedChildrenOf(item).find(
+    (child) => child.type === 'visibility_modifier',
+  )
+  switch (modifier?.text.replace(/\s+/g, '')) {
+    case undefined:
+    case 'pub(self)':
+      return 'private'
+    case 'pub':
+      return 'public'
+    case 'crate':
+    case 'pub(crate)':
+      return 'crate'
+    case 'pub(super)':
+      return 'super'
+    default:
+      return 'restricted'
+  }
+}
+
+/** Names of the inline modules enclosing a node, outermost first */
+export function getInlineModulePath(node: Node | null): string[] {
+  const modulePath: string[] = []
+  for (let cur = node; cur; cur = cur.parent) {
+    if (cur.type === 'mod_item' && cur.childForFieldName('body')) {
+      const name = cur.childForFieldName('name')?.text
+      if (name) {
+        modulePath.unshift(name)
+      }
+    }
+  }
+  return modulePath
+}
+
+/** Splits a path node into its segments: `a::b::C` -> `['a', 'b', 'C']` */
+export function getPathSegments(node: Node | null): string[] {
+  if (!node) {
+    return []
+  }
+  if (
+    node.type === 'scoped_identifier' ||
+    node.type === 'scoped_type_identifier'
+  ) {
+    const name = node.childForFieldName('name')?.text
+    return [
+      ...getPathSegments(node.childForFieldName('path')),
+      ...(name ? [name] : []),
+    ]
+  }
+  if (node.type === 'generic_type') {
+    return getPathSegments(node.childForFieldName('type'))
+  }
+  return [node.text]
+}
+
+function collectUseTree(
+  node: Node,
+  prefix: string[],
+  modulePath: string[],
+  visibility: RustVisibility,
+  out: RustImport[],
+) {
+  switch (node.type) {
+    case 'use_as_clause': {
+      const alias = node.childForFieldName('alias')?.text
+      out.push({
+        modulePath,
+        alias,
+        path: [...prefix, ...getPathSegments(node.childForFieldName('path'))],
+        isGlob: false,
+        visibility,
+      })
+      return
+    }
+    case 'use_wildcard': {
+      const [target] = namedChildrenOf(node)
+      out.push({
+        modulePath,
+        alias: undefined,
+        path: [...prefix, ...getPathSegments(target ?? null)],
+        isGlob: true,
+        visibility,
+      })
+      return
+    }
+    case 'scoped_use_list': {
+      const listPrefix = [
+        ...prefix,
+        ...getPathSegments(node.childForFieldName('path')),
+      ]
+      const list = node.childForFieldName('list')
+      if (list) {
+        collectUseTree(list, listPrefix, modulePath, visibility, out)
+      }
+      return
+    }
+    case 'use_list': {
+      for (const child of namedChildrenOf(node)) {
+        collectUseTree(child, prefix, modulePath, visibility, out)
+      }
+      return
+    }
+    default: {
+      const segments = getPathSegments(node)
+      // `use foo::{self}` binds the module itself
+      const path =
+        segments.length === 1 && segments[0] === 'self'
+          ? prefix
+          : [...prefix, ...segments]
+      const alias = path[path.length - 1]
+      if (alias) {
+        out.push({ modulePath, alias, path, isGlob: false, visibility })
+      }
+    }
+  }
+}
+
+function collectImports(root: Node): RustImport[] {
+  const imports: RustImport[] = []
+  for (const node of root.descendantsOfType([
+    'use_declaration',
+    'extern_crate_declaration',
+  ])) {
+    if (!node) {
+      continue
+    }
+    const modulePath = getInlineModulePath(node)
+    const visibility = getRustVisibility(node)
+    if (node.type === 'extern_crate_declaration') {
+      const name = node.childForFieldName('name')?.text
+      const alias = node.childForFieldName('alias')?.text ?? name
+      if (name) {
+        imports.push({
+          modulePath,
+          alias,
+          path: [name],
+          isGlob: false,
+          visibility,
+        })
+      }
+      continue
+    }
+    const argument = node.childForFieldName('argument')
+    if (argument) {
+      collectUseTree(argument, [], modulePath, visibility, imports)
+    }
+  }
+  return imports
+}
+
+function toReference(captureName: string, node: Node): RustReference {
+  const token = qualifyRustCapture(captureName, node)
+  const modulePath = getInlineModulePath(node)
+  const parent = node.parent
+
+  if (parent?.type === 'field_expression') {
+    return { token, path: [node.text], modulePath, kind: 'method' }
+  }
+
+  if (
+    parent &&
+    (parent.type === 'scoped_identifier' ||
+      parent.type === 'scoped_type_identifier') &&
+    parent.childForFieldName('name')?.id === node.id
+  ) {
+    const path = getPathSegments(parent)
+    if (path[0] === 'Self') {
+      const typeName = getEnclosingRustImpl(parent)?.typeName
+      if (typeName) {
+        path[0] = typeName
+      }
+    }
+    return { token, path, modulePath, kind: 'path' }
+  }
+
+  return { token, path: [node.text], modulePath, kind: 'path' }
+}
+
+/** Item types whose doc comments rustdoc runs as doc-tests */
+const DOCUMENTED_ITEM_TYPES = [
+  'function_item',
+  'function_signature_item',
+  'struct_item',
+  'enum_item',
+  'union_item',
+  'trait_item',
+  'type_item',
+  'const_item',
+  'static_item',
+  'mod_item',
+  'macro_definition',
+]
+/** Code block attributes rustdoc still compiles as Rust */
+const RUST_CODE_BLOCK_ATTRIBUTE =
+  /^(?:rust|ignore(?:-[\w-]+)?|should_panic|no_run|compile_fail|test_harness|standalone_crate|edition\d+|E\d+)$/
+const TEST_ATTRIBUTE = /^#\[\s*(?:[\w:]+::)?test\b/
+const CFG_TEST_ATTRIBUTE = /^#\[\s*cfg\s*\(\s*test\s*\)\s*\]/
+
+/** Item types `#[derive(..)]` can be attached to */
+const DERIVABLE_ITEM_TYPES = new Set(['struct_item', 'enum_item', 'union_item'])
+/** Parents of macro invocations in item position */
+const ITEM_CONTAINER_TYPES = new Set(['source_file', 'declaration_list'])
+
+/** Names of the type parameters an impl declares: `impl<'a, T: X, U>` */
+function getTypeParameterNames(impl: Node): string[] {
+  const parameters = impl.childForFieldName('type_parameters')
+  return (parameters ? namedChildrenOf(parameters) : []).flatMap((param) => {
+    switch (param.type) {
+      case 'type_identifier':
+        return [param.text]
+      case 'constrained_type_parameter':
+        return param.childForFieldName('left')?.text ?? []
+      case 'type_parameter':
+        return param.childForFieldName('name')?.text ?? []
+      default:
+        return []
+    }
+  })
+}
+
+function collectImpls(root: Node): RustImplBlock[] {
+  const impls: RustImplBlock[] = []
+  for (const node of root.descendantsOfType('impl_item')) {
+    const typeName = getRustTypeName(node?.childForFieldName('type') ?? null)
+    if (!node || !typeName) {
+      continue
+    }
+    const body = node.childForFieldName('body')
+    const header = node.text
+      .slice(0, body ? body.startIndex - node.startIndex : undefined)
+      .replace(/\s+/g, ' ')
+      .trim()
+    impls.push({
+      typeName,
+      traitName: getRustTypeName(node.childForFieldName('trait')),
+      header,
+      line: node.startPosition.row + 1,
+      isBlanket: getTypeParameterNames(node).includes(typeName),
+    })
+  }
+  return impls
+}
+
+function collectTraits(root: Node): RustTraitDefinition[] {
+  const traits: RustTraitDefinition[] = []
+  for (const node of root.descendantsOfType('trait_item')) {
+    const name = node?.childForFieldName('name')?.text
+    if (node && name) {
+      traits.push({
+        name,
+        line: node.startPosition.row + 1,
+        modulePath: getInlineModulePath(node.parent),
+      })
+    }
+  }
+  return traits
+}
+
+/** Attributes and outer doc comment lines above an item, top to bottom */
+export function getLeadingAttributes(item: Node): {
+  attributes: string[]
+  docs: string[]
+} {
+  const attributes: string[] = []
+  const docs: string[] = []
+  for (
+    let prev = item.previousNamedSibling;
+    prev && (prev.type === 'attribute_item' || prev.type.endsWith('comment'));
+    prev = prev.previousNamedSibling
+  ) {
+    if (prev.type === 'attribute_item') {
+      attributes.unshift(prev.text)
+    } else if (prev.text.startsWith('///')) {
+      docs.unshift(prev.text.replace(/^\/\/\/ ?/, '').trimEnd())
+    } else if (prev.text.startsWith('/**')) {
+      const body = prev.text.replace(/^\/\*\*|\*\/$/g, '')
+      docs.unshift(
+        ...body.split('\n').map((line) => line.replace(/^\s*\* ?/, '')),
+      )
+    }
+  }
+  return { attributes, docs }
+}
+
+/** Whether doc comment lines contain a code block rustdoc runs as a test */
+function hasRustCodeBlock(docs: string[]): boolean {
+  let inBlock = false
+  for (const line of docs) {
+    const fence = /^\s*```(.*)$/.exec(line)
+    if (!fence) {
+      continue
+    }
+    if (inBlock) {
+      inBlock = false
+      continue
+    }
+    inBlock = true
+    const attributes = fence[1].split(/[\s,]+/).filter(Boolean)
+    if (attributes.every((attr) => RUST_CODE_BLOCK_ATTRIBUTE.test(attr))) {
+      return true
+    }
+  }
+  return false
+}
+
+function collectTests(root: Node): RustTestFunction[] {
+  const tests: RustTestFunction[] = []
+  for (const node of root.descendantsOfType('function_item')) {
+    const name = node?.childForFieldName('name')?.text
+    if (!node || !name) {
+      continue
+    }
+    const attribute = getLeadingAttributes(node).attributes.find((attr) =>
+      TEST_ATTRIBUTE.test(attr),
+    )
+    if (attribute) {
+      tests.push({
+        name,
+        modulePath: getInlineModulePath(node.parent),
+        startLine: node.startPosition.row + 1,
+        endLine: node.endPosition.row + 1,
+        attribute,
+      })
+    }
+  }
+  return tests
+}
+
+function collectDocTests(root: Node): RustDocTest[] {
+  const docTests: RustDocTest[] = []
+  for (const node of root.descendantsOfType(DOCUMENTED_ITEM_TYPES)) {
+    const nameNode = node?.childForFieldName('name')
+    if (!node || !nameNode) {
+      continue
+    }
+    if (hasRustCodeBlock(getLeadingAttributes(node).docs)) {
+      docTests.push({
+        token: qualifyRustCapture('identifier', nameNode),
+        modulePath: getInlineModulePath(node.parent),
+        line: node.startPosition.row + 1,
+      })
+    }
+  }
+  return docTests
+}
+
+/** Collects derived trait impls, recording each derived trait as a reference */
+function collectDerives(
+  root: Node,
+  references: RustReference[],
+): RustImpliedImpl[] {
+  const impls: RustImpliedImpl[] = []
+  for (const attribute of root.descendantsOfType('attribute_item')) {
+    let item = attribute?.nextNamedSibling ?? null
+    while (
+      item &&
+      (item.type === 'attribute_item' || item.type.endsWith('comment'))
+    ) {
+      item = item.nextNamedSibling
+    }
+    const typeName = item?.childForFieldName('name')?.text
+    if (!attribute || !item || !typeName) {
+      continue
+    }
+    if (!DERIVABLE_ITEM_TYPES.has(item.type)) {
+      continue
+    }
+    for (const traitPath of getDerivedTraitPaths(attribute)) {
+      const traitName = traitPath[traitPath.length - 1]
+      impls.push({
+        typeName,
+        traitName,
+        traitPath,
+        line: item.startPosition.row + 1,
+        origin: 'derive',
+      })
+      references.push({
+        token: traitName,
+        path: traitPath,
+        modulePath: getInlineModulePath(item),
+        kind: 'path',
+      })
+    }
+  }
+  return impls
+}
+
+function collectMacroInvocations(root: Node): RustMacroInvocation[] {
+  const invocations: RustMacroInvocation[] = []
+  for (const node of root.descendantsOfType('macro_invocation')) {
+    if (!node?.parent || !ITEM_CONTAINER_TYPES.has(node.parent.type)) {
+      continue
+    }
+    const name = getPathSegments(node.childForFieldName('macro')).at(-1)
+    const tree = namedChildrenOf(node).find(
+      (child) => child.type === 'token_tree',
+    )
+    if (name && tree) {
+      invocations.push({
+        name,
+        tokens: toMacroTokens(tree),
+        modulePath: getInlineModulePath(node),
+        line: node.startPosition.row + 1,
+        container: getRustItemContainer(node),
+      })
+    }
+  }
+  return invocations
+}
+
+/**
+ * Collects the module-level facts the Rust resolver needs from a parsed file:
+ * item definitions, `use`/`extern crate` bindings, module declarations and
+ * path-qualified references.
+ *
+ * Invocations of macros defined in the same file are expanded on a
+ * best-effort basis, and `#[derive(..)]` attributes become implied trait
+ * impls referencing the derived traits. Test functions, doc-tested items and
+ * `#[cfg(test)]` modules are recorded for the test index.
+ */
+export function collectRustFileSymbols(
+  root: Node,
+  captures: QueryCapture[],
+): RustFileSymbols {
+  const definitions: RustDefinition[] = []
+  const references: RustReference[] = []
+
+  for (const { name, node } of captures) {
+    if (name === 'identifier') {
+      definitions.push({
+        token: qualifyRustCapture(name, node),
+        modulePath: getInlineModulePath(node.parent?.parent ?? null),
+        ...(node.parent && { visibility: getRustVisibility(node.parent) }),
+      })
+    } else if (name === 'call.identifier') {
+      references.push({
+        ...toReference(name, node),
+        range: getSourceRange(node),
+      })
+    }
+  }
+
+  const modules: string[][] = []
+  const testModules: string[][] = []
+  for (const node of root.descendantsOfType('mod_item')) {
+    const name = node?.childForFieldName('name')?.text
+    if (node && name) {
+      const modulePath = [...getInlineModulePath(node.parent), name]
+      modules.push(modulePath)
+      const { attributes } = getLeadingAttributes(node)
+      if (attributes.some((attr) => CFG_TEST_ATTRIBUTE.test(attr))) {
+        testModules.push(modulePath)
+      }
+    }
+  }
+
+  const macros: RustMacroDefinition[] = []
+  for (const node of root.descendantsOfType('macro_definition')) {
+    const definition = node && parseRustMacroDefinition(node)
+    if (definition) {
+      macros.push(definition)
+    }
+  }
+
+  const impliedImpls = collectDerives(root, references)
+  const macroInvocations: RustMacroInvocation[] = []
+  for (const invocation of collectMacroInvocations(root)) {
+    const definition = macros.find((m) => m.name === invocation.name)
+    if (!definition) {
+      macroInvocations.push(invocation)
+      continue
+    }
+    const expansion = expandRustMacroInvocation(definition, invocation)
+    definitions.push(...expansion.definitions)
+    impliedImpls.push(...expansion.impliedImpls)
+  }
+
+  return {
+    definitions,
+    imports: collectImports(root),
+    references,
+    modules,
+    macros,
+    macroInvocations,
+    impliedImpls,
+    traits: collectTraits(root),
+    impls: collectImpls(root),
+    tests: collectTests(root),
+    docTests: collectDocTests(root),
+    testModules,
+  }
+}
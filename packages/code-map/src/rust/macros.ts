@@ -0,0 +1,548 @@
+import { formatRustAssociatedName } from './symbols'
+
+import type { RustDefinition, RustFileSymbols } from './file-symbols'
+import type { RustImplContainer } from './symbols'
+import type { Node } from 'web-tree-sitter'
+
+/**
+ * A token of a `macro_rules!` pattern or body, or of a macro invocation.
+ * Nested delimited trees become groups; `$name` and `$name:frag` become vars
+ * and `$( .. ) sep op` becomes a repetition.
+ */
+export type MacroToken =
+  | { kind: 'text'; text: string }
+  | { kind: 'group'; delimiter: string; tokens: MacroToken[] }
+  | { kind: 'var'; name: string; fragment?: string }
+  | { kind: 'repeat'; tokens: MacroToken[]; separator: MacroToken[]; op: string }
+
+export interface RustMacroRule {
+  pattern: MacroToken[]
+  body: MacroToken[]
+}
+
+export interface RustMacroDefinition {
+  name: string
+  rules: RustMacroRule[]
+}
+
+export interface RustMacroInvocation {
+  name: string
+  tokens: MacroToken[]
+  modulePath: string[]
+  line: number
+  /** Impl or trait the invocation sits in, e.g. `impl Foo { getters!(..); }` */
+  container?: RustImplContainer
+}
+
+/** A trait implementation that exists without an `impl` block in the source */
+export interface RustImpliedImpl {
+  typeName: string
+  traitName: string
+  /** Trait path as written, e.g. `['serde', 'Serialize']` */
+  traitPath: string[]
+  line: number
+  origin: 'derive' | 'macro'
+}
+
+export interface RustMacroExpansion {
+  definitions: RustDefinition[]
+  impliedImpls: RustImpliedImpl[]
+}
+
+type Bindings = Record<string, string[]>
+
+interface GeneratedItems {
+  tokens: string[]
+  impls: { typeName: string; traitName: string }[]
+}
+
+interface MatchState {
+  pos: number
+  bindings: Bindings
+}
+
+const MAX_MATCH_STATES = 256
+const CLOSING_DELIMITERS: Record<string, string> = {
+  '(': ')',
+  '[': ']',
+  '{': '}',
+}
+const SINGLE_TOKEN_FRAGMENTS = new Set(['ident', 'lifetime', 'literal', 'tt'])
+const ITEM_KEYWORDS = new Set([
+  'fn',
+  'struct',
+  'enum',
+  'union',
+  'trait',
+  'type',
+  'const',
+  'static',
+  'mod',
+])
+const IDENTIFIER = /^(r#)?[A-Za-z_][A-Za-z0-9_]*$/
+const PUNCTUATION = /^[^\w\s'"]+$/
+
+function nonNullChildren(node: Node): Node[] {
+  return node.children.filter((child): child is Node => child !== null)
+}
+
+function isIdentifier(text: string): boolean {
+  return IDENTIFIER.test(text) && text !== '_' && !ITEM_KEYWORDS.has(text)
+}
+
+/** Tree-sitter glues adjacent punctuation (`>,`); split it into operators */
+function splitPunctuation(text: string): string[] {
+  if (!PUNCTUATION.test(text)) {
+    return [text]
+  }
+  return text.match(/::|->|=>|\.\.=|\.\.\.|\.\.|&&|\|\||==|!=|./g) ?? [text]
+}
+
+function toMacroToken(node: Node): MacroToken[] {
+  switch (node.type) {
+    case 'line_comment':
+    case 'block_comment':
+      return []
+    case 'token_tree':
+    case 'token_tree_pattern':
+      return [
+        {
+          kind: 'group',
+          delimiter: node.child(0)?.type ?? '(',
+          tokens: toMacroTokens(node),
+        },
+      ]
+    case 'token_binding_pattern':
+      return [
+        {
+          kind: 'var',
+          name: (node.childForFieldName('name')?.text ?? '').replace(/^\$/, ''),
+          fragment: node.childForFieldName('type')?.text,
+        },
+      ]
+    case 'metavariable':
+      return [{ kind: 'var', name: node.text.replace(/^\$/, '') }]
+    case 'token_repetition':
+    case 'token_repetition_pattern': {
+      const children = nonNullChildren(node)
+      const open = children.findIndex((child) => child.type === '(')
+      const close = children.findLastIndex((child) => child.type === ')')
+      const suffix = children.slice(close + 1).map((child) => child.text)
+      const op = suffix.pop() ?? '*'
+      return [
+        {
+          kind: 'repeat',
+          tokens: children.slice(open + 1, close).flatMap(toMacroToken),
+          separator: suffix
+            .flatMap(splitPunctuation)
+            .map((text): MacroToken => ({ kind: 'text', text })),
+          op,
+        },
+      ]
+    }
+    default:
+      return splitPunctuation(node.text).map(
+        (text): MacroToken => ({ kind: 'text', text }),
+      )
+  }
+}
+
+/** Converts the contents of a token tree, without its own delimiters */
+export function toMacroTokens(tree: Node): MacroToken[] {
+  const children = nonNullChildren(tree)
+  const inner =
+    children[0]?.type && children[0].type in CLOSING_DELIMITERS
+      ? children.slice(1, -1)
+      : children
+  return inner.flatMap(toMacroToken)
+}
+
+export function renderMacroTokens(tokens: MacroToken[]): string {
+  return tokens
+    .map((token) => {
+      switch (token.kind) {
+        case 'text':
+          return token.text
+        case 'group':
+          return `${token.delimiter}${renderMacroTokens(token.tokens)}${CLOSING_DELIMITERS[token.delimiter] ?? ''}`
+        case 'var':
+          return `$${token.name}`
+        case 'repeat':
+          return `$(${renderMacroTokens(token.tokens)})${renderMacroTokens(token.separator)}${token.op}`
+      }
+    })
+    .join(' ')
+}
+
+export function parseRustMacroDefinition(
+  node: Node,
+): RustMacroDefinition | undefined {
+  const name = node.childForFieldName('name')?.text
+  if (!name) {
+    return undefined
+  }
+  const rules: RustMacroRule[] = []
+  for (const rule of node.namedChildren) {
+    const left = rule?.childForFieldName('left')
+    const right = rule?.childForFieldName('right')
+    if (rule?.type === 'macro_rule' && left && right) {
+      rules.push({ pattern: toMacroTokens(left), body: toMacroTokens(right) })
+    }
+  }
+  return { name, rules }
+}
+
+function bind(bindings: Bindings, name: string, value: string): Bindings {
+  return { ...bindings, [name]: [...(bindings[name] ?? []), value] }
+}
+
+function matchToken(
+  token: MacroToken,
+  input: MacroToken[],
+  states: MatchState[],
+): MatchState[] {
+  const next: MatchState[] = []
+  for (const state of states) {
+    const actual = input[state.pos]
+    switch (token.kind) {
+      case 'text':
+        if (actual?.kind === 'text' && actual.text === token.text) {
+          next.push({ pos: state.pos + 1, bindings: state.bindings })
+        }
+        break
+      case 'group':
+        if (actual?.kind === 'group' && actual.delimiter === token.delimiter) {
+          const inner = matchTokens(token.tokens, actual.tokens, [
+            { pos: 0, bindings: state.bindings },
+          ])
+          for (const { pos, bindings } of inner) {
+            if (pos === actual.tokens.length) {
+              next.push({ pos: state.pos + 1, bindings })
+            }
+          }
+        }
+        break
+      case 'var': {
+        const fragment = token.fragment ?? 'tt'
+        if (SINGLE_TOKEN_FRAGMENTS.has(fragment)) {
+          const matches =
+            actual &&
+            (fragment !== 'ident' ||
+              (actual.kind === 'text' && IDENTIFIER.test(actual.text)))
+          if (matches) {
+            next.push({
+              pos: state.pos + 1,
+              bindings: bind(
+                state.bindings,
+                token.name,
+                renderMacroTokens([actual]),
+              ),
+            })
+          }
+          break
+        }
+        // Other fragments (ty, expr, path, ..) span one or more tokens
+        const minLength = fragment === 'vis' ? 0 : 1
+        for (let end = state.pos + minLength; end <= input.length; end++) {
+          next.push({
+            pos: end,
+            bindings: bind(
+              state.bindings,
+              token.name,
+              renderMacroTokens(input.slice(state.pos, end)),
+            ),
+          })
+        }
+        break
+      }
+      case 'repeat': {
+        if (token.op !== '+') {
+          next.push(state)
+        }
+        let frontier = [state]
+        for (let i = 0; frontier.length > 0 && i <= input.length; i++) {
+          const start =
+            i > 0 ? matchTokens(token.separator, input, frontier) : frontier
+          frontier = matchTokens(token.tokens, input, start).filter(
+            (s) => s.pos > state.pos,
+          )
+          next.push(...frontier)
+          if (token.op === '?') {
+            break
+          }
+        }
+        break
+      }
+    }
+    if (next.length >= MAX_MATCH_STATES) {
+      break
+    }
+  }
+  return next.slice(0, MAX_MATCH_STATES)
+}
+
+function matchTokens(
+  pattern: MacroToken[],
+  input: MacroToken[],
+  states: MatchState[],
+): MatchState[] {
+  let current = states
+  for (const token of pattern) {
+    if (current.length === 0) {
+      break
+    }
+    current = matchToken(token, input, current)
+  }
+  return current
+}
+
+/**
+ * Matches invocation tokens against a `macro_rules!` pattern, returning the
+ * fragments each metavariable bound to (several inside repetitions).
+ */
+export function matchMacroPattern(
+  pattern: MacroToken[],
+  input: MacroToken[],
+): Bindings | undefined {
+  return matchTokens(pattern, input, [{ pos: 0, bindings: {} }]).find(
+    (state) => state.pos === input.length,
+  )?.bindings
+}
+
+/** Leading type name of a bound fragment: `&'a mut Vec<u8>` -> `Vec` */
+function getFragmentTypeName(fragment: string): string | undefined {
+  const stripped = fragment.replace(/^(&|'\w+|mut\b|dyn\b|\s)+/, '')
+  const path = /^[A-Za-z_]\w*(\s*::\s*[A-Za-z_]\w*)*/.exec(stripped)?.[0]
+  return path?.split('::').at(-1)?.trim()
+}
+
+/** Names a token stands for once bindings are substituted */
+function getTokenNames(token: MacroToken | undefined, bindings: Bindings) {
+  if (token?.kind === 'text') {
+    return isIdentifier(token.text) ? [token.text] : []
+  }
+  if (token?.kind === 'var') {
+    return (bindings[token.name] ?? []).flatMap(
+      (value) => getFragmentTypeName(value) ?? [],
+    )
+  }
+  return []
+}
+
+/** Type and trait names of an `impl .. for ..` header, skipping generics */
+function parseImplHeader(
+  header: MacroToken[],
+  bindings: Bindings,
+): { typeNames: string[]; traitName: string | undefined } {
+  const before: string[][] = []
+  const after: string[][] = []
+  let depth = 0
+  let seenFor = false
+  for (const token of header) {
+    if (token.kind === 'text') {
+      if (token.text === '<') {
+        depth++
+        continue
+      }
+      if (token.text === '>') {
+        depth--
+        continue
+      }
+      if (depth === 0 && token.text === 'for') {
+        seenFor = true
+        continue
+      }
+      if (token.text === 'where') {
+        break
+      }
+    }
+    const names = getTokenNames(token, bindings)
+    if (depth > 0 || names.length === 0) {
+      continue
+    }
+    if (seenFor) {
+      after.push(names)
+    } else {
+      before.push(names)
+    }
+  }
+  if (seenFor) {
+    return { typeNames: after[0] ?? [], traitName: before.at(-1)?.[0] }
+  }
+  return { typeNames: before[0] ?? [], traitName: undefined }
+}
+
+function collectGeneratedItems(
+  body: MacroToken[],
+  bindings: Bindings,
+  containers: RustImplContainer[],
+  out: GeneratedItems,
+) {
+  for (let i = 0; i < body.length; i++) {
+    const token = body[i]
+    if (token.kind === 'group' || token.kind === 'repeat') {
+      collectGeneratedItems(token.tokens, bindings, containers, out)
+      continue
+    }
+    if (token.kind !== 'text') {
+      continue
+    }
+
+    if (token.text === 'impl') {
+      const bodyIndex = body.findIndex(
+        (t, j) => j > i && t.kind === 'group' && t.delimiter === '{',
+      )
+      const implBody = body[bodyIndex]
+      if (implBody?.kind !== 'group') {
+        continue
+      }
+      const { typeNames, traitName } = parseImplHeader(
+        body.slice(i + 1, bodyIndex),
+        bindings,
+      )
+      const implContainers = typeNames.map(
+        (typeName): RustImplContainer => ({ typeName, traitName, kind: 'impl' }),
+      )
+      if (traitName) {
+        out.impls.push(...typeNames.map((typeName) => ({ typeName, traitName })))
+      }
+      collectGeneratedItems(implBody.tokens, bindings, implContainers, out)
+      i = bodyIndex
+      continue
+    }
+
+    if (ITEM_KEYWORDS.has(token.text)) {
+      for (const name of getTokenNames(body[i + 1], bindings)) {
+        if (containers.length === 0) {
+          out.tokens.push(name)
+        }
+        for (const container of containers) {
+          out.tokens.push(formatRustAssociatedName(container, name))
+        }
+      }
+    }
+  }
+}
+
+/**
+ * Best-effort expansion of a `macro_rules!` invocation: picks the first rule
+ * whose pattern matches and lists the `fn`/`struct`/.. items and trait impls
+ * its body would generate. Identifier concatenation (`paste!`) and nested
+ * macro calls are not followed.
+ */
+export function expandRustMacroInvocation(
+  definition: RustMacroDefinition,
+  invocation: RustMacroInvocation,
+): RustMacroExpansion {
+  for (const rule of definition.rules) {
+    const bindings = matchMacroPattern(rule.pattern, invocation.tokens)
+    if (!bindings) {
+      continue
+    }
+    const generated: GeneratedItems = { tokens: [], impls: [] }
+    collectGeneratedItems(
+      rule.body,
+      bindings,
+      invocation.container ? [invocation.container] : [],
+      generated,
+    )
+    return {
+      definitions: [...new Set(generated.tokens)].map((token) => ({
+        token,
+        modulePath: invocation.modulePath,
+        macro: definition.name,
+      })),
+      impliedImpls: generated.impls.map(({ typeName, traitName }) => ({
+        typeName,
+        traitName,
+        traitPath: [traitName],
+        line: invocation.line,
+        origin: 'macro',
+      })),
+    }
+  }
+  return { definitions: [], impliedImpls: [] }
+}
+
+/**
+ * Trait paths named by a `#[derive(..)]` attribute, including derives nested
+ * in `#[cfg_attr(.., derive(..))]`.
+ */
+export function getDerivedTraitPaths(attributeItem: Node): string[][] {
+  const attribute = attributeItem.namedChildren.find(
+    (child) => child?.type === 'attribute',
+  )
+  const args = attribute?.childForFieldName('arguments')
+  if (!attribute || !args) {
+    return []
+  }
+  const tokens = toMacroTokens(args)
+  const attributeName = attribute.namedChildren[0]?.text
+  return attributeName === 'derive'
+    ? splitDerivePaths(tokens)
+    : findNestedDerives(tokens)
+}
+
+function findNestedDerives(tokens: MacroToken[]): string[][] {
+  return tokens.flatMap((token, i) => {
+    if (token.kind !== 'group') {
+      return []
+    }
+    const previous = tokens[i - 1]
+    return previous?.kind === 'text' && previous.text === 'derive'
+      ? splitDerivePaths(token.tokens)
+      : findNestedDerives(token.tokens)
+  })
+}
+
+function splitDerivePaths(tokens: MacroToken[]): string[][] {
+  const paths: string[][] = [[]]
+  for (const token of tokens) {
+    if (token.kind !== 'text') {
+      continue
+    }
+    if (token.text === ',') {
+      paths.push([])
+    } else if (token.text !== '::') {
+      paths[paths.length - 1].push(token.text)
+    }
+  }
+  return paths.filter((path) => path.length > 0)
+}
+
+/**
+ * Expands invocations of macros defined in other files of the project.
+ * Generated items are added to the invoking file's symbols; returns the new
+ * tokens per file.
+ */
+export function expandRustMacros(
+  files: Map<string, RustFileSymbols>,
+): Map<string, string[]> {
+  const macros = new Map<string, RustMacroDefinition>()
+  for (const symbols of files.values()) {
+    for (const definition of symbols.macros) {
+      if (!macros.has(definition.name)) {
+        macros.set(definition.name, definition)
+      }
+    }
+  }
+
+  const generated = new Map<string, string[]>()
+  for (const [filePath, symbols] of files) {
+    const tokens: string[] = []
+    for (const invocation of symbols.macroInvocations) {
+      const definition = macros.get(invocation.name)
+      if (!definition) {
+        continue
+      }
+      const expansion = expandRustMacroInvocation(definition, invocation)
+      symbols.definitions.push(...expansion.definitions)
+      symbols.impliedImpls.push(...expansion.impliedImpls)
+      tokens.push(...expansion.definitions.map(({ token }) => token))
+    }
+    if (tokens.length > 0) {
+      generated.set(filePath, tokens)
+    }
+  }
+  return generated
+}
@@ -0,0 +1,316 @@
+import { getLanguageConfig } from '../languages'
+import { namedChildrenOf } from './file-symbols'
+import { describeRustContainer, formatRustAssociatedName } from './symbols'
+
+import type { RustImplContainer } from './symbols'
+import type { Node, Tree } from 'web-tree-sitter'
+
+const MAX_SIGNATURE_LENGTH = 200
+const MEMBER_TYPES = new Set(['field_declaration', 'enum_variant'])
+const MEMBER_LIST_TYPES = new Set([
+  'field_declaration_list',
+  'enum_variant_list',
+])
+
+const ITEM_KINDS = {
+  function_item: 'fn',
+  function_signature_item: 'fn',
+  struct_item: 'struct',
+  enum_item: 'enum',
+  union_item: 'union',
+  trait_item: 'trait',
+  impl_item: 'impl',
+  mod_item: 'mod',
+  const_item: 'const',
+  static_item: 'static',
+  type_item: 'type',
+  associated_type: 'type',
+  macro_definition: 'macro',
+} as const
+
+export type RustOutlineItemKind =
+  | (typeof ITEM_KINDS)[keyof typeof ITEM_KINDS]
+  | 'field'
+  | 'variant'
+
+export interface RustOutlineItem {
+  /**
+   * Path of the item within its file, e.g. `Greeting::new`,
+   * `<Greeting as Display>::fmt` or `inner::helper`
+   */
+  path: string
+  kind: RustOutlineItemKind
+  /** Declaration with function bodies elided */
+  signature: string
+  startLine: number
+  endLine: number
+  /** Offsets of the item in the source, excluding attributes and doc comments */
+  startIndex: number
+  endIndex: number
+  /** Fields, variants, and items of traits, impls and inline modules */
+  children: RustOutlineItem[]
+}
+
+function collapse(text: string): string {
+  const collapsed = text.replace(/\s+/g, ' ').trim()
+  return collapsed.length > MAX_SIGNATURE_LENGTH
+    ? `${collapsed.slice(0, MAX_SIGNATURE_LENGTH)}…`
+    : collapsed
+}
+
+/** Text of an item up to its body */
+function getHeader(node: Node, body: Node): string {
+  return collapse(node.text.slice(0, body.startIndex - node.startIndex))
+}
+
+function getRange(node: Node) {
+  return {
+    startLine: node.startPosition.row + 1,
+    endLine: node.endPosition.row + 1,
+    startIndex: node.startIndex,
+    endIndex: node.endIndex,
+  }
+}
+
+function outlineMembers(
+  body: Node,
+  kind: 'field' | 'variant',
+  ownerPath: string,
+): RustOutlineItem[] {
+  return namedChildrenOf(body).flatMap((member) => {
+    const name = member.childForFieldName('name')?.text
+    if (!name || !MEMBER_TYPES.has(member.type)) {
+      return []
+    }
+    return {
+      path: `${ownerPath}::${name}`,
+      kind,
+      signature: collapse(member.text),
+      ...getRange(member),
+      children: [],
+    }
+  })
+}
+
+function outlineItem(
+  node: Node,
+  modulePath: string[],
+  container: RustImplContainer | undefined,
+): RustOutlineItem | undefined {
+  if (!Object.hasOwn(ITEM_KINDS, node.type)) {
+    return undefined
+  }
+  const kind = ITEM_KINDS[node.type as keyof typeof ITEM_KINDS]
+  const body = node.childForFieldName('body')
+  const name = node.childForFieldName('name')?.text ?? ''
+  const path = [
+    ...modulePath,
+    container ? formatRustAssociatedName(container, name) : name,
+  ].join('::')
+  const item = { path, kind, ...getRange(node) }
+
+  switch (node.type) {
+    case 'impl_item': {
+      const impl = describeRustContainer(node)
+      const self = impl?.traitName
+        ? `<${impl.typeName} as ${impl.traitName}>`
+        : impl?.typeName
+      return {
+        ...item,
+        path: [...modulePath, self ?? ''].join('::'),
+        signature: body ? getHeader(node, body) : collapse(node.text),
+        children: body ? outlineItems(body, modulePath, impl) : [],
+      }
+    }
+    case 'trait_item':
+      return {
+        ...item,
+        signature: body ? getHeader(node, body) : collapse(node.text),
+        children: body
+          ? outlineItems(body, modulePath, describeRustContainer(node))
+          : [],
+      }
+    case 'mod_item':
+      return {
+        ...item,
+        signature: body ? getHeader(node, body) : collapse(node.text),
+        children: body
+          ? outlineItems(body, [...modulePath, name], undefined)
+          : [],
+      }
+    case 'struct_item':
+    case 'union_item':
+    case 'enum_item':
+      if (body && MEMBER_LIST_TYPES.has(body.type)) {
+        return {
+          ...item,
+          signature: getHeader(node, body),
+          children: outlineMembers(
+            body,
+            node.type === 'enum_item' ? 'variant' : 'field',
+            path,
+          ),
+        }
+      }
+      break
+    case 'function_item':
+      if (body) {
+        return {
+          ...item,
+          signature: `${getHeader(node, body)} { ... }`,
+          children: [],
+        }
+      }
+      break
+    case 'macro_definition':
+      return {
+        ...item,
+        signature: `macro_rules! ${name} { ... }`,
+        children: [],
+      }
+  }
+  return { ...item, signature: collapse(node.text), children: [] }
+}
+
+function outlineItems(
+  parent: Node,
+  modulePath: string[],
+  container: RustImplContainer | undefined,
+): RustOutlineItem[] {
+  return namedChildrenOf(parent).flatMap(
+    (node) => outlineItem(node, modulePath, container) ?? [],
+  )
+}
+
+/**
+ * Lists the items of a parsed Rust file with their signatures and line
+ * ranges. Function bodies are elided; struct fields, enum variants and the
+ * items of traits, impls and inline modules are listed as children.
+ */
+export function getRustOutline(root: Node): RustOutlineItem[] {
+  return outlineItems(root, [], undefined)
+}
+
+/** Renders an outline as indented `<lines>: <signature>` entries */
+export function renderRustOutline(
+  items: RustOutlineItem[],
+  indent = '',
+): string {
+  return items
+    .map((item) => {
+      const lines =
+        item.startLine === item.endLine
+          ? `${item.startLine}`
+          : `${item.startLine}-${item.endLine}`
+      const entry = `${indent}${lines}: ${item.signature}`
+      if (item.children.length === 0) {
+        return entry
+      }
+      return `${entry}\n${renderRustOutline(item.children, `${indent}  `)}`
+    })
+    .join('\n')
+}
+
+/**
+ * Whether an outline item is named by `query`: its full path, a suffix of it
+ * (`Greeting::new` for `inner::Greeting::new`), or `Type::item` for an item of
+ * `impl Trait for Type`.
+ */
+export function matchesItemPath(itemPath: string, query: string): boolean {
+  const inherentPath = itemPath.replace(/<(\w+) as [^>]+>/g, '$1')
+  return [itemPath, inherentPath].some(
+    (candidate) => candidate === query || candidate.endsWith(`::${query}`),
+  )
+}
+
+/** Finds the outline items named by `query`, searching nested items too */
+export function findRustOutlineItems(
+  items: RustOutlineItem[],
+  query: string,
+): RustOutlineItem[] {
+  return items.flatMap((item) => [
+    ...(matchesItemPath(item.path, query) ? [item] : []),
+    ...findRustOutlineItems(item.children, query),
+  ])
+}
+
+/**
+ * Parses Rust source, or returns undefined for other files. The caller owns
+ * the tree and must `delete()` it.
+ */
+export async function parseRustSource(
+  filePath: string,
+  sourceCode: string,
+): Promise<Tree | undefined> {
+  if (!filePath.endsWith('.rs')) {
+    return undefined
+  }
+  const languageConfig = await getLanguageConfig(filePath)
+  return languageConfig?.parser?.parse(sourceCode) ?? undefined
+}
+
+/**
+ * Calls `use` with the syntax tree of Rust source and frees the tree
+ * afterwards, or returns undefined if the file is not Rust or cannot be
+ * parsed. Nodes must not escape `use`.
+ */
+export async function withRustSource<T>(
+  filePath: string,
+  sourceCode: string,
+  use: (root: Node) => T,
+): Promise<T | undefined> {
+  const tree = await parseRustSource(filePath, sourceCode)
+  if (!tree) {
+    return undefined
+  }
+  try {
+    return use(tree.rootNode)
+  } finally {
+    tree.delete()
+  }
+}
+
+/**
+ * Renders the outline of a Rust source file, or returns undefined if the file
+ * is not Rust or cannot be parsed.
+ */
+export async function outlineRustSource(
+  filePath: string,
+  sourceCode: string,
+): Promise<string | undefined> {
+  return withRustSource(filePath, sourceCode, (root) =>
+    renderRustOutline(getRustOutline(root)),
+  )
+}
+
+/**
+ * Extracts the source of the items named by `itemPaths` from a Rust file,
+ * each preceded by a comment with its path and line range. Returns undefined
+ * if the file is not Rust or cannot be parsed.
+ */
+export async function extractRustItems(
+  filePath: string,
+  sourceCode: string,
+  itemPaths: string[],
+): Promise<string | undefined> {
+  const outline = await withRustSource(filePath, sourceCode, getRustOutline)
+  if (!outline) {
+    return undefined
+  }
+  const lines = sourceCode.split('\n')
+  return itemPaths
+    .map((query) => {
+      const matches = findRustOutlineItems(outline, query)
+      if (matches.length === 0) {
+        return `// No item found at path "${query}"`
+      }
+      return matches
+        .map(
+          (item) =>
+            `// ${item.path} (lines ${item.startLine}-${item.endLine})\n` +
+            lines.slice(item.startLine - 1, item.endLine).join('\n'),
+        )
+        .join('\n\n')
+    })
+    .join('\n\n')
+}
@@ -0,0 +1,38 @@
+import * as path from 'path'
+
+import { getLanguageConfig } from '../languages'
+import { parseTokens } from '../parse'
+import { expandRustMacros } from './macros'
+
+import type { ParseCache } from '../parse-cache'
+import type { RustFileSymbols } from './file-symbols'
+
+/**
+ * Parses the Rust files among `filePaths` into their module-level symbols.
+ * Macro invocations are expanded across files, so macro-generated items and
+ * impls are included. With a `cache`, only files that changed since they were
+ * cached are parsed.
+ */
+export async function parseRustFiles(
+  projectRoot: string,
+  filePaths: string[],
+  readFile?: (filePath: string) => string | null,
+  cache?: ParseCache,
+): Promise<Map<string, RustFileSymbols>> {
+  const files = new Map<string, RustFileSymbols>()
+  for (const filePath of filePaths) {
+    const fullPath = path.join(projectRoot, filePath)
+    const languageConfig = await getLanguageConfig(fullPath)
+    if (!languageConfig) {
+      continue
+    }
+    const { rust } = readFile
+      ? parseTokens(filePath, languageConfig, readFile, cache)
+      : parseTokens(fullPath, languageConfig, undefined, cache)
+    if (rust) {
+      files.set(filePath, rust)
+    }
+  }
+  expandRustMacros(files)
+  return files
+}
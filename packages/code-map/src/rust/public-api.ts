@@ -0,0 +1,274 @@
+import * as path from 'path'
+
+import { parseRustFiles } from './project'
+import { buildRustModuleIndex } from './resolve'
+
+import type { CargoWorkspace } from './cargo'
+import type { RustFileSymbols, RustVisibility } from './file-symbols'
+
+export interface RustPublicItem {
+  /** Path dependents name the item by, e.g. `my_crate::config::Settings` */
+  path: string
+  /** File defining the item */
+  file: string
+  /** Token the item is recorded under in `file` */
+  token: string
+  /** Exported through a `pub use` rather than where it is defined */
+  reexported: boolean
+}
+
+export interface RustCrateApi {
+  crateName: string
+  /** Crate root, see `RustModuleLocation.crateKey` */
+  crateKey: string
+  items: RustPublicItem[]
+}
+
+export interface RustPublicApiDiff {
+  added: RustPublicItem[]
+  removed: RustPublicItem[]
+}
+
+interface ModuleItem {
+  file: string
+  token: string
+  visibility: RustVisibility | undefined
+}
+
+const joinKey = (parts: string[]) => parts.join('::')
+
+/** `src/main.rs` shares its crate key with `src/lib.rs` but exports nothing */
+const isBinaryRoot = (filePath: string) =>
+  /(^|\/)src\/main\.rs$/.test(filePath)
+
+/** `Type::item` and `<Type as Trait>::item` -> `Type` */
+function getOwnerName(token: string): string | undefined {
+  const traitImpl = /^<(.+?) as .+>::/.exec(token)
+  if (traitImpl) {
+    return traitImpl[1]
+  }
+  const separator = token.indexOf('::')
+  return separator === -1 ? undefined : token.slice(0, separator)
+}
+
+/**
+ * Computes the public API of each library crate: every item reachable from
+ * the crate root through `pub mod` declarations and `pub use` re-exports,
+ * plus `#[macro_export]` macros. Associated items are included when their
+ * type or trait is exported.
+ *
+ * Items generated by macros have unknown visibility and are left out, and
+ * re-exports are only followed when they resolve to an item in the project.
+ */
+export function buildRustPublicApi(
+  files: Map<string, RustFileSymbols>,
+  fallbackCrateName: string,
+  workspace?: CargoWorkspace,
+): RustCrateApi[] {
+  const index = buildRustModuleIndex(files, fallbackCrateName, workspace)
+  const moduleVisibility = new Map<string, RustVisibility | undefined>()
+  const moduleItems = new Map<string, ModuleItem[]>()
+
+  for (const [filePath, symbols] of files) {
+    const location = index.getLocation(filePath)
+    if (!location?.isLibrary || isBinaryRoot(filePath)) {
+      continue
+    }
+    const declaredModules = new Set(symbols.modules.map(joinKey))
+    for (const definition of symbols.definitions) {
+      const moduleKey = joinKey([
+        location.crateKey,
+        ...location.modulePath,
+        ...definition.modulePath,
+      ])
+      const { token, visibility } = definition
+      if (declaredModules.has(joinKey([...definition.modulePath, token]))) {
+        moduleVisibility.set(joinKey([moduleKey, token]), visibility)
+      }
+      if (!getOwnerName(token)) {
+        const items = moduleItems.get(moduleKey) ?? []
+        items.push({ file: filePath, token, visibility })
+        moduleItems.set(moduleKey, items)
+      }
+    }
+  }
+
+  const isExportedModule = (crateKey: string, modulePath: string[]) =>
+    modulePath.every((_, i) => {
+      const moduleKey = joinKey([crateKey, ...modulePath.slice(0, i + 1)])
+      return moduleVisibility.get(moduleKey) === 'public'
+    })
+
+  const crates = new Map<
+    string,
+    { crateName: string; items: Map<string, RustPublicItem> }
+  >()
+  const associatedItems: { crateKey: string; file: string; token: string }[] =
+    []
+
+  for (const [filePath, symbols] of files) {
+    const location = index.getLocation(filePath)
+    if (!location?.isLibrary || isBinaryRoot(filePath)) {
+      continue
+    }
+    const { crateKey, crateName } = location
+    let crate = crates.get(crateKey)
+    if (!crate) {
+      crate = { crateName, items: new Map() }
+      crates.set(crateKey, crate)
+    }
+    const items = crate.items
+    const addItem = (
+      segments: string[],
+      item: Omit<RustPublicItem, 'path'>,
+    ) => {
+      const itemPath = joinKey([crateName, ...segments])
+      if (!items.has(itemPath)) {
+        items.set(itemPath, { path: itemPath, ...item })
+      }
+    }
+
+    const macroNames = new Set(symbols.macros.map((m) => m.name))
+    for (const definition of symbols.definitions) {
+      const { token, modulePath, visibility, macro } = definition
+      if (visibility !== 'public' || macro) {
+        continue
+      }
+      // `#[macro_export]` places macros at the crate root
+      if (macroNames.has(token)) {
+        addItem([token], { file: filePath, token, reexported: false })
+        continue
+      }
+      // Exported with their type, which may be re-exported from anywhere
+      if (getOwnerName(token)) {
+        associatedItems.push({ crateKey, file: filePath, token })
+        continue
+      }
+      const fullModulePath = [...location.modulePath, ...modulePath]
+      if (!isExportedModule(crateKey, fullModulePath)) {
+        continue
+      }
+      addItem([...fullModulePath, token], {
+        file: filePath,
+        token,
+        reexported: false,
+      })
+    }
+
+    for (const imported of symbols.imports) {
+      const fullModulePath = [...location.modulePath, ...imported.modulePath]
+      if (
+        imported.visibility !== 'public' ||
+        !isExportedModule(crateKey, fullModulePath)
+      ) {
+        continue
+      }
+
+      if (imported.isGlob) {
+        const target = index.resolveModulePath(
+          filePath,
+          imported.path,
+          imported.modulePath,
+        )
+        const targetItems = target && moduleItems.get(joinKey(target))
+        for (const item of targetItems ?? []) {
+          if (item.visibility === 'public') {
+            addItem([...fullModulePath, item.token], {
+              file: item.file,
+              token: item.token,
+              reexported: true,
+            })
+          }
+        }
+        continue
+      }
+
+      if (!imported.alias || imported.alias === '_') {
+        continue
+      }
+      const site = index.resolve(filePath, {
+        token: imported.alias,
+        path: imported.path,
+        modulePath: imported.modulePath,
+        kind: 'path',
+      })
+      if (site) {
+        addItem([...fullModulePath, imported.alias], {
+          file: site.file,
+          token: site.token,
+          reexported: true,
+        })
+      }
+    }
+  }
+
+  // Associated items are exported under the path of their type or trait
+  for (const { crateKey, file, token } of associatedItems) {
+    const crate = crates.get(crateKey)
+    const owner = getOwnerName(token)
+    // Prefer the type's own path over re-exports of it
+    const candidates = crate
+      ? [...crate.items.values()].filter((item) => item.token === owner)
+      : []
+    const ownerItem =
+      candidates.find((item) => !item.reexported) ?? candidates[0]
+    if (!crate || !owner || !ownerItem) {
+      continue
+    }
+    const itemPath = token.startsWith('<')
+      ? token.replace(`<${owner} `, `<${ownerItem.path} `)
+      : `${ownerItem.path}${token.slice(owner.length)}`
+    if (!crate.items.has(itemPath)) {
+      crate.items.set(itemPath, {
+        path: itemPath,
+        file,
+        token,
+        reexported: false,
+      })
+    }
+  }
+
+  return [...crates].map(([crateKey, { crateName, items }]) => ({
+    crateName,
+    crateKey,
+    items: [...items.values()].sort((a, b) => a.path.localeCompare(b.path)),
+  }))
+}
+
+/** Parses the given Rust files and computes the public API of each crate */
+export async function getRustPublicApi(
+  projectRoot: string,
+  filePaths: string[],
+  readFile?: (filePath: string) => string | null,
+  cargoWorkspace?: CargoWorkspace,
+): Promise<RustCrateApi[]> {
+  const files = await parseRustFiles(projectRoot, filePaths, readFile)
+  return buildRustPublicApi(files, path.basename(projectRoot), cargoWorkspace)
+}
+
+/**
+ * Compares two public API snapshots by item path. Removed items are breaking
+ * changes under semver; added items are minor changes. An empty diff means
+ * the change is internal as far as item names go.
+ */
+export function diffRustPublicApi(
+  before: RustCrateApi[],
+  after: RustCrateApi[],
+): RustPublicApiDiff {
+  const toMap = (crates: RustCrateApi[]) =>
+    new Map(
+      crates.flatMap((crate) =>
+        crate.items.map((item): [string, RustPublicItem] => [item.path, item]),
+      ),
+    )
+  const beforeItems = toMap(before)
+  const afterItems = toMap(after)
+  return {
+    added: [...afterItems.values()].filter(
+      (item) => !beforeItems.has(item.path),
+    ),
+    removed: [...beforeItems.values()].filter(
+      (item) => !afterItems.has(item.path),
+    ),
+  }
+}
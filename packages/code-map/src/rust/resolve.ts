@@ -0,0 +1,484 @@
+import {
+  findCrateForFile,
+  getLibraryCrateName,
+  toRustCrateName,
+} from './cargo'
+
+import type { CargoCrate, CargoWorkspace } from './cargo'
+import type { RustFileSymbols, RustReference } from './file-symbols'
+
+/** Directories whose files are compiled as separate crate roots */
+const TARGET_DIRS = ['tests', 'examples', 'benches']
+const MAX_RESOLVE_DEPTH = 8
+
+export interface RustModuleLocation {
+  /** Unique key of the crate root the file belongs to */
+  crateKey: string
+  /** Name other crates use to refer to this crate (`my-crate` -> `my_crate`) */
+  crateName: string
+  /** Module path of the file inside its crate, `[]` for the crate root */
+  modulePath: string[]
+  /** Whether this is the crate's library target that others can import */
+  isLibrary: boolean
+}
+
+export interface RustItemSite {
+  file: string
+  token: string
+}
+
+/** An associated item, indexed by its bare name for method calls */
+interface MemberSite extends RustItemSite {
+  /** Trait the item is declared in or implements; unset for inherent items */
+  traitName?: string
+  /** Whether this is the trait's declaration rather than an impl */
+  isDeclaration: boolean
+}
+
+interface ModuleScope {
+  crateKey: string
+  modulePath: string[]
+}
+
+interface ScopedPath {
+  scope: ModuleScope
+  path: string[]
+}
+
+const joinKey = (parts: string[]) => parts.join('::')
+
+function getFileModulePath(dirs: string[], stem: string): string[] {
+  const isCrateRoot = dirs.length === 0 && ['lib', 'main'].includes(stem)
+  if (stem === 'mod' || isCrateRoot) {
+    return dirs
+  }
+  return [...dirs, stem]
+}
+
+/**
+ * Derives the crate and module a Rust file belongs to from its path, following
+ * Cargo's conventional layout: `src/lib.rs`/`src/main.rs` are crate roots,
+ * `src/a/b.rs` and `src/a/b/mod.rs` are module `a::b`, and `src/bin/*`,
+ * `tests/*`, `examples/*` and `benches/*` are separate crate roots.
+ *
+ * When the workspace member owning the file is known, the layout is read
+ * relative to its directory and the crate is named after its library target.
+ */
+export function locateRustModule(
+  filePath: string,
+  fallbackCrateName: string,
+  crate?: CargoCrate,
+): RustModuleLocation {
+  if (!crate) {
+    return locateByLayout(filePath, fallbackCrateName)
+  }
+  const prefix = crate.rootDir ? `${crate.rootDir}/` : ''
+  const crateName = getLibraryCrateName(crate)
+  const location = locateByLayout(filePath.slice(prefix.length), crateName)
+  return {
+    ...location,
+    crateKey:
+      location.crateKey === '.'
+        ? crate.rootDir || '.'
+        : `${prefix}${location.crateKey}`,
+    crateName,
+  }
+}
+
+function locateByLayout(
+  filePath: string,
+  fallbackCrateName: string,
+): RustModuleLocation {
+  const parts = filePath.split(/[\\/]/).filter(Boolean)
+  // `.rs.in` sources are spliced into a module with `include!`
+  const stem = (parts.pop() ?? '').replace(/\.rs(\.in)?$/, '')
+
+  let targetIndex = parts.lastIndexOf('src')
+  if (targetIndex === -1) {
+    targetIndex = parts.findLastIndex((part) => TARGET_DIRS.includes(part))
+  }
+  if (targetIndex === -1) {
+    // Loose file such as build.rs: its own crate root
+    return {
+      crateKey: [...parts, stem].join('/'),
+      crateName: toRustCrateName(stem),
+      modulePath: [],
+      isLibrary: false,
+    }
+  }
+
+  const crateDir = parts.slice(0, targetIndex)
+  const crateName = toRustCrateName(crateDir.at(-1) ?? fallbackCrateName)
+  const targetDir = parts[targetIndex]
+  let rel = parts.slice(targetIndex + 1)
+
+  if (targetDir === 'src' && rel[0] !== 'bin') {
+    return {
+      crateKey: crateDir.join('/') || '.',
+      crateName,
+      modulePath: getFileModulePath(rel, stem),
+      isLibrary: true,
+    }
+  }
+
+  const rootDir = [...crateDir, targetDir]
+  if (targetDir === 'src') {
+    rootDir.push('bin')
+    rel = rel.slice(1)
+  }
+  if (rel.length === 0) {
+    return {
+      crateKey: [...rootDir, stem].join('/'),
+      crateName,
+      modulePath: [],
+      isLibrary: false,
+    }
+  }
+  return {
+    crateKey: [...rootDir, rel[0]].join('/'),
+    crateName,
+    modulePath: getFileModulePath(rel.slice(1), stem),
+    isLibrary: false,
+  }
+}
+
+/**
+ * Module-aware index of Rust items. Resolves paths at a call site through
+ * `crate::`/`self::`/`super::` prefixes, `use` bindings, glob imports and
+ * `pub use` re-exports to the file that defines the item.
+ *
+ * Given a Cargo workspace, other crates are only reachable through the
+ * dependencies the calling crate declares, under their (possibly renamed)
+ * names.
+ */
+export class RustModuleIndex {
+  private readonly items = new Map<string, RustItemSite>()
+  /** `<Type as Trait>::item` is also reachable as `Type::item` unless shadowed */
+  private readonly traitItems = new Map<string, RustItemSite>()
+  private readonly modules = new Set<string>()
+  private readonly imports = new Map<string, ScopedPath>()
+  private readonly globImports = new Map<string, ScopedPath[]>()
+  private readonly membersByName = new Map<string, MemberSite[]>()
+  private readonly crateKeysByName = new Map<string, string>()
+  private readonly locations = new Map<string, RustModuleLocation>()
+  /** Workspace member owning each crate root */
+  private readonly packages = new Map<string, CargoCrate>()
+
+  constructor(private readonly workspace?: CargoWorkspace) {}
+
+  addFile(
+    filePath: string,
+    location: RustModuleLocation,
+    symbols: RustFileSymbols,
+  ): void {
+    const { crateKey, crateName, modulePath, isLibrary } = location
+    this.locations.set(filePath, location)
+    const owner = this.workspace && findCrateForFile(this.workspace, filePath)
+    if (owner) {
+      this.packages.set(crateKey, owner)
+    }
+    if (isLibrary && !this.crateKeysByName.has(crateName)) {
+      this.crateKeysByName.set(crateName, crateKey)
+    }
+
+    for (let i = 0; i <= modulePath.length; i++) {
+      this.modules.add(joinKey([crateKey, ...modulePath.slice(0, i)]))
+    }
+    for (const declared of symbols.modules) {
+      this.modules.add(joinKey([crateKey, ...modulePath, ...declared]))
+    }
+
+    for (const definition of symbols.definitions) {
+      const moduleKey = [crateKey, ...modulePath, ...definition.modulePath]
+      const site = { file: filePath, token: definition.token }
+      const itemKey = joinKey([...moduleKey, definition.token])
+      if (!this.items.has(itemKey)) {
+        this.items.set(itemKey, site)
+      }
+
+      const traitImpl = /^<(.+) as .+>::(.+)$/.exec(definition.token)
+      if (traitImpl) {
+        const aliasKey = joinKey([...moduleKey, traitImpl[1], traitImpl[2]])
+        if (!this.traitItems.has(aliasKey)) {
+          this.traitItems.set(aliasKey, site)
+        }
+      }
+
+      const member = definition.token.split('::').at(-1)
+      if (member && member !== definition.token) {
+        const owner = definition.token.slice(0, -member.length - 2)
+        const isDeclaration = symbols.traits.some(
+          (trait) =>
+            trait.name === owner &&
+            joinKey(trait.modulePath) === joinKey(definition.modulePath),
+        )
+        const traitName = isDeclaration
+          ? owner
+          : /^<.+ as (.+)>$/.exec(owner)?.[1]
+        const sites = this.membersByName.get(member) ?? []
+        sites.push({
+          ...site,
+          ...(traitName ? { traitName } : {}),
+          isDeclaration,
+        })
+        this.membersByName.set(member, sites)
+      }
+    }
+
+    for (const imported of symbols.imports) {
+      const scope = {
+        crateKey,
+        modulePath: [...modulePath, ...imported.modulePath],
+      }
+      const scopeKey = joinKey([crateKey, ...scope.modulePath])
+      if (imported.isGlob) {
+        const globs = this.globImports.get(scopeKey) ?? []
+        globs.push({ scope, path: imported.path })
+        this.globImports.set(scopeKey, globs)
+      } else if (imported.alias && imported.alias !== '_') {
+        this.imports.set(joinKey([scopeKey, imported.alias]), {
+          scope,
+          path: imported.path,
+        })
+      }
+    }
+  }
+
+  getLocation(filePath: string): RustModuleLocation | undefined {
+    return this.locations.get(filePath)
+  }
+
+  /**
+   * Whether code in `fromFile` can name items in `toFile`: both are in the
+   * same workspace member, or the caller's member depends on the callee's.
+   * Always true outside a known workspace.
+   */
+  canReference(fromFile: string, toFile: string): boolean {
+    const from = this.getPackage(fromFile)
+    const to = this.getPackage(toFile)
+    if (!from || !to || from === to) {
+      return true
+    }
+    return from.dependencies.some((dep) => dep.workspaceMember === to.name)
+  }
+
+  private getPackage(filePath: string): CargoCrate | undefined {
+    const location = this.locations.get(filePath)
+    return location && this.packages.get(location.crateKey)
+  }
+
+  /** Crate root an extern crate name refers to from `crateKey` */
+  private findCrateKey(crateKey: string, name: string): string | undefined {
+    const owner = this.packages.get(crateKey)
+    if (!owner || !this.workspace) {
+      return this.crateKeysByName.get(name)
+    }
+    // Binaries, tests and examples see their own package's library by name
+    if (name === getLibraryCrateName(owner)) {
+      return owner.rootDir || '.'
+    }
+    const dependency = owner.dependencies.find(
+      (dep) => dep.name === name && dep.workspaceMember,
+    )
+    const member = this.workspace.members.find(
+      (m) => m.name === dependency?.workspaceMember,
+    )
+    return member && (member.rootDir || '.')
+  }
+
+  /** Finds the definition a reference in `filePath` points at */
+  resolve(
+    filePath: string,
+    reference: RustReference,
+  ): RustItemSite | undefined {
+    const location = this.locations.get(filePath)
+    if (!location) {
+      return undefined
+    }
+
+    if (reference.kind === 'method') {
+      return this.resolveMethod(filePath, reference.token)
+    }
+
+    const scope = {
+      crateKey: location.crateKey,
+      modulePath: [...location.modulePath, ...reference.modulePath],
+    }
+    const absolute = this.resolvePath(reference.path, scope, 0)
+    return absolute && this.lookup(absolute, 0)
+  }
+
+  /**
+   * Receiver types are unknown, so a method call is attributed to the only
+   * impl of a method with that name, or else to the trait declaring it when
+   * all impls implement that trait. Other names are ambiguous.
+   */
+  private resolveMethod(
+    filePath: string,
+    name: string,
+  ): RustItemSite | undefined {
+    const sites =
+      this.membersByName
+        .get(name)
+        ?.filter((site) => this.canReference(filePath, site.file)) ?? []
+    const impls = sites.filter((site) => !site.isDeclaration)
+    if (impls.length === 1) {
+      return toItemSite(impls[0])
+    }
+    const declarations = sites.filter((site) => site.isDeclaration)
+    const [declaration] = declarations
+    if (
+      declarations.length === 1 &&
+      impls.every((impl) => impl.traitName === declaration.traitName)
+    ) {
+      return toItemSite(declaration)
+    }
+    return undefined
+  }
+
+  /**
+   * Resolves a path written in `filePath` (inside the inline modules
+   * `modulePath`) to `[crateKey, ...modulePath]`, without checking that the
+   * target exists
+   */
+  resolveModulePath(
+    filePath: string,
+    path: string[],
+    modulePath: string[],
+  ): string[] | undefined {
+    const location = this.locations.get(filePath)
+    return (
+      location &&
+      this.resolvePath(
+        path,
+        {
+          crateKey: location.crateKey,
+          modulePath: [...location.modulePath, ...modulePath],
+        },
+        0,
+      )
+    )
+  }
+
+  private hasName(modulePath: string[], name: string): boolean {
+    const key = joinKey([...modulePath, name])
+    return this.items.has(key) || this.modules.has(key)
+  }
+
+  /** Turns a path as written in `scope` into `[crateKey, ...segments]` */
+  private resolvePath(
+    segments: string[],
+    scope: ModuleScope,
+    depth: number,
+  ): string[] | undefined {
+    if (depth > MAX_RESOLVE_DEPTH || segments.length === 0) {
+      return undefined
+    }
+    const [first, ...rest] = segments
+    const here = [scope.crateKey, ...scope.modulePath]
+
+    if (first === 'crate' || first === '$crate') {
+      return [scope.crateKey, ...rest]
+    }
+    if (first === 'self') {
+      return [...here, ...rest]
+    }
+    if (first === 'super') {
+      let numSupers = 0
+      while (segments[numSupers] === 'super') {
+        numSupers++
+      }
+      return [
+        scope.crateKey,
+        ...scope.modulePath.slice(0, -numSupers),
+        ...segments.slice(numSupers),
+      ]
+    }
+
+    // Items and modules declared in the current module shadow imports
+    if (this.hasName(here, first)) {
+      return [...here, ...segments]
+    }
+
+    const imported = this.imports.get(joinKey([...here, first]))
+    if (imported) {
+      const target = this.resolvePath(imported.path, imported.scope, depth + 1)
+      return target && [...target, ...rest]
+    }
+
+    const crateKey = this.findCrateKey(scope.crateKey, first)
+    if (crateKey) {
+      return [crateKey, ...rest]
+    }
+
+    for (const glob of this.globImports.get(joinKey(here)) ?? []) {
+      const prefix = this.resolvePath(glob.path, glob.scope, depth + 1)
+      if (prefix && this.hasName(prefix, first)) {
+        return [...prefix, ...segments]
+      }
+    }
+
+    return undefined
+  }
+
+  /** Finds the item an absolute path names, following re-exports */
+  private lookup(absolute: string[], depth: number): RustItemSite | undefined {
+    if (depth > MAX_RESOLVE_DEPTH) {
+      return undefined
+    }
+    for (let i = absolute.length - 1; i >= 1; i--) {
+      const modulePath = absolute.slice(0, i)
+      const itemKey = joinKey([...modulePath, absolute.slice(i).join('::')])
+      const site = this.items.get(itemKey) ?? this.traitItems.get(itemKey)
+      if (site) {
+        return site
+      }
+
+      // `pub use` re-export of the next segment from this module
+      const reexport = this.imports.get(joinKey(absolute.slice(0, i + 1)))
+      if (reexport) {
+        const target = this.resolvePath(
+          reexport.path,
+          reexport.scope,
+          depth + 1,
+        )
+        const found =
+          target && this.lookup([...target, ...absolute.slice(i + 1)], depth + 1)
+        if (found) {
+          return found
+        }
+      }
+
+      for (const glob of this.globImports.get(joinKey(modulePath)) ?? []) {
+        const prefix = this.resolvePath(glob.path, glob.scope, depth + 1)
+        const found =
+          prefix && this.lookup([...prefix, ...absolute.slice(i)], depth + 1)
+        if (found) {
+          return found
+        }
+      }
+    }
+    return undefined
+  }
+}
+
+function toItemSite({ file, token }: RustItemSite): RustItemSite {
+  return { file, token }
+}
+
+export function buildRustModuleIndex(
+  files: Map<string, RustFileSymbols>,
+  fallbackCrateName: string,
+  workspace?: CargoWorkspace,
+): RustModuleIndex {
+  const index = new RustModuleIndex(workspace)
+  for (const [filePath, symbols] of files) {
+    const crate = workspace && findCrateForFile(workspace, filePath)
+    index.addFile(
+      filePath,
+      locateRustModule(filePath, fallbackCrateName, crate),
+      symbols,
+    )
+  }
+  return index
+}
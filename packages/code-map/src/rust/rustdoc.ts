@@ -0,0 +1,795 @@
+import type { RustCrateDocs, RustDocItem } from './crate-docs'
+import type { RustOutlineItemKind } from './outline'
+import type { RustTraitImpl } from './trait-index'
+
+/**
+ * Items of the JSON written by `cargo rustdoc -- --output-format json`, typed
+ * as far as the index reads them. Field names renamed across format versions
+ * are accepted under both names.
+ */
+type RustdocId = string | number
+
+interface RustdocPath {
+  path?: string
+  /** Older formats */
+  name?: string
+  id?: RustdocId
+  args?: RustdocGenericArgs | null
+}
+
+interface RustdocGenericArgs {
+  angle_bracketed?: {
+    args: RustdocGenericArg[]
+    constraints?: RustdocConstraint[]
+    bindings?: RustdocConstraint[]
+  }
+  parenthesized?: { inputs: RustdocType[]; output?: RustdocType | null }
+}
+
+type RustdocGenericArg =
+  | 'infer'
+  | { lifetime?: string; type?: RustdocType; const?: { expr: string } }
+
+interface RustdocTerm {
+  type?: RustdocType
+  constant?: { expr: string }
+}
+
+interface RustdocConstraint {
+  name: string
+  args?: RustdocGenericArgs | null
+  binding: { equality?: RustdocTerm; constraint?: RustdocBound[] }
+}
+
+interface RustdocBound {
+  trait_bound?: {
+    trait: RustdocPath
+    generic_params?: RustdocGenericParam[]
+    modifier?: string
+  }
+  outlives?: string
+  use?: (string | Record<string, string>)[]
+}
+
+type RustdocType =
+  | 'infer'
+  | {
+      resolved_path?: RustdocPath
+      dyn_trait?: {
+        traits: { trait: RustdocPath }[]
+        lifetime?: string | null
+      }
+      generic?: string
+      primitive?: string
+      function_pointer?: { sig?: RustdocSignature; decl?: RustdocSignature }
+      tuple?: RustdocType[]
+      slice?: RustdocType
+      array?: { type: RustdocType; len: string }
+      pat?: { type: RustdocType }
+      impl_trait?: RustdocBound[]
+      raw_pointer?: RustdocReference
+      borrowed_ref?: RustdocReference & { lifetime?: string | null }
+      qualified_path?: {
+        name: string
+        args?: RustdocGenericArgs | null
+        self_type: RustdocType
+        trait?: RustdocPath | null
+      }
+    }
+
+interface RustdocReference {
+  is_mutable?: boolean
+  mutable?: boolean
+  type: RustdocType
+}
+
+interface RustdocGenericParam {
+  name: string
+  kind: {
+    lifetime?: { outlives: string[] }
+    type?: {
+      bounds: RustdocBound[]
+      default?: RustdocType | null
+      is_synthetic?: boolean
+      synthetic?: boolean
+    }
+    const?: { type: RustdocType; default?: string | null }
+  }
+}
+
+interface RustdocGenerics {
+  params: RustdocGenericParam[]
+  where_predicates: {
+    bound_predicate?: { type: RustdocType; bounds: RustdocBound[] }
+    lifetime_predicate?: { lifetime: string; outlives: string[] }
+    eq_predicate?: { lhs: RustdocType; rhs: RustdocTerm }
+  }[]
+}
+
+interface RustdocSignature {
+  inputs: [string, RustdocType][]
+  output?: RustdocType | null
+}
+
+interface RustdocFunction {
+  sig?: RustdocSignature
+  decl?: RustdocSignature
+  generics: RustdocGenerics
+  header?: {
+    is_const?: boolean
+    is_unsafe?: boolean
+    is_async?: boolean
+    const_?: boolean
+    unsafe_?: boolean
+    async_?: boolean
+    abi?: string | Record<string, unknown>
+  }
+}
+
+interface RustdocImpl {
+  is_unsafe?: boolean
+  generics: RustdocGenerics
+  trait?: RustdocPath | null
+  for: RustdocType
+  items: RustdocId[]
+  is_negative?: boolean
+  negative?: boolean
+  is_synthetic?: boolean
+  synthetic?: boolean
+  blanket_impl?: RustdocType | null
+}
+
+interface RustdocFields {
+  fields?: RustdocId[]
+  has_stripped_fields?: boolean
+  fields_stripped?: boolean
+}
+
+type RustdocStructKind =
+  | 'unit'
+  | { tuple?: (RustdocId | null)[]; plain?: RustdocFields }
+
+interface RustdocItemInner {
+  module?: { items: RustdocId[] }
+  function?: RustdocFunction
+  struct?: {
+    kind: RustdocStructKind
+    generics: RustdocGenerics
+    impls: RustdocId[]
+  }
+  struct_field?: RustdocType
+  union?: RustdocFields & { generics: RustdocGenerics; impls: RustdocId[] }
+  enum?: {
+    generics: RustdocGenerics
+    variants: RustdocId[]
+    has_stripped_variants?: boolean
+    variants_stripped?: boolean
+    impls: RustdocId[]
+  }
+  variant?: {
+    kind:
+      | 'plain'
+      | {
+          tuple?: (RustdocId | null)[]
+          struct?: RustdocFields
+        }
+    discriminant?: { expr: string } | null
+  }
+  trait?: {
+    is_auto?: boolean
+    is_unsafe?: boolean
+    items: RustdocId[]
+    generics: RustdocGenerics
+    bounds: RustdocBound[]
+  }
+  impl?: RustdocImpl
+  type_alias?: { type: RustdocType; generics: RustdocGenerics }
+  constant?: {
+    type?: RustdocType
+    type_?: RustdocType
+    const?: { expr: string }
+    expr?: string
+  }
+  static?: {
+    type: RustdocType
+    is_mutable?: boolean
+    mutable?: boolean
+  }
+  macro?: string
+  proc_macro?: { kind: string }
+  assoc_const?: {
+    type: RustdocType
+    value?: string | null
+    default?: string | null
+  }
+  assoc_type?: {
+    generics: RustdocGenerics
+    bounds: RustdocBound[]
+    type?: RustdocType | null
+    default?: RustdocType | null
+  }
+}
+
+interface RustdocItem {
+  crate_id: number
+  name: string | null
+  span?: { filename: string; begin: [number, number] } | null
+  visibility: string | Record<string, unknown>
+  docs?: string | null
+  attrs?: unknown[]
+  deprecation?: { since?: string | null; note?: string | null } | null
+  inner: RustdocItemInner | string
+}
+
+interface RustdocCrate {
+  root: RustdocId
+  crate_version?: string | null
+  format_version: number
+  index: Record<string, RustdocItem>
+  paths: Record<string, { crate_id: number; path: string[]; kind: string }>
+}
+
+export interface RustdocIndex {
+  crateName: string
+  version?: string
+  formatVersion: number
+  /** Docs of the crate root */
+  docs: string
+  /** Public items under their canonical paths, with associated items */
+  items: RustDocItem[]
+  /** Trait impls the crate declares, without auto trait and blanket copies */
+  traitImpls: RustTraitImpl[]
+}
+
+const ITEM_KINDS: Record<string, RustOutlineItemKind> = {
+  module: 'mod',
+  function: 'fn',
+  struct: 'struct',
+  union: 'union',
+  enum: 'enum',
+  trait: 'trait',
+  type_alias: 'type',
+  constant: 'const',
+  static: 'static',
+  macro: 'macro',
+  proc_macro: 'macro',
+  assoc_const: 'const',
+  assoc_type: 'type',
+}
+const PRIVATE_FIELDS = '/* private fields */'
+
+function getInnerKind(item: RustdocItem): string {
+  return typeof item.inner === 'string'
+    ? item.inner
+    : (Object.keys(item.inner)[0] ?? '')
+}
+
+function getPathName(path: RustdocPath): string {
+  return path.path ?? path.name ?? ''
+}
+
+function lastSegment(path: string): string {
+  return path.split('::').pop() ?? path
+}
+
+function isMutable(reference: RustdocReference): boolean {
+  return reference.is_mutable ?? reference.mutable ?? false
+}
+
+function renderPath(path: RustdocPath): string {
+  return `${getPathName(path)}${renderGenericArgs(path.args)}`
+}
+
+function renderTerm(term: RustdocTerm): string {
+  return term.type ? renderType(term.type) : (term.constant?.expr ?? '_')
+}
+
+function renderGenericArgs(
+  args: RustdocGenericArgs | null | undefined,
+): string {
+  if (args?.parenthesized) {
+    const { inputs, output } = args.parenthesized
+    return `(${inputs.map(renderType).join(', ')})${output ? ` -> ${renderType(output)}` : ''}`
+  }
+  if (!args?.angle_bracketed) {
+    return ''
+  }
+  const { args: genericArgs } = args.angle_bracketed
+  const constraints =
+    args.angle_bracketed.constraints ?? args.angle_bracketed.bindings ?? []
+  const rendered = [
+    ...genericArgs.map((arg) =>
+      arg === 'infer'
+        ? '_'
+        : (arg.lifetime ??
+          (arg.type ? renderType(arg.type) : (arg.const?.expr ?? '_'))),
+    ),
+    ...constraints.map(
+      ({ name, args: constraintArgs, binding }) =>
+        `${name}${renderGenericArgs(constraintArgs)}${
+          binding.equality
+            ? ` = ${renderTerm(binding.equality)}`
+            : `: ${renderBounds(binding.constraint ?? [])}`
+        }`,
+    ),
+  ]
+  return rendered.length > 0 ? `<${rendered.join(', ')}>` : ''
+}
+
+function renderBounds(bounds: RustdocBound[]): string {
+  return bounds
+    .map((bound) => {
+      if (bound.trait_bound) {
+        const { trait, generic_params = [], modifier } = bound.trait_bound
+        const higherRanked =
+          generic_params.length > 0
+            ? `for<${generic_params.map((param) => param.name).join(', ')}> `
+            : ''
+        return `${modifier === 'maybe' ? '?' : ''}${higherRanked}${renderPath(trait)}`
+      }
+      if (bound.use) {
+        const params = bound.use.map((param) =>
+          typeof param === 'string' ? param : Object.values(param)[0],
+        )
+        return `use<${params.join(', ')}>`
+      }
+      return bound.outlives ?? ''
+    })
+    .join(' + ')
+}
+
+function renderType(type: RustdocType): string {
+  if (type === 'infer') {
+    return '_'
+  }
+  if (type.resolved_path) {
+    return renderPath(type.resolved_path)
+  }
+  if (type.generic !== undefined || type.primitive !== undefined) {
+    return type.generic ?? type.primitive!
+  }
+  if (type.borrowed_ref) {
+    const { lifetime, type: referenced } = type.borrowed_ref
+    return `&${lifetime ? `${lifetime} ` : ''}${isMutable(type.borrowed_ref) ? 'mut ' : ''}${renderType(referenced)}`
+  }
+  if (type.raw_pointer) {
+    return `*${isMutable(type.raw_pointer) ? 'mut' : 'const'} ${renderType(type.raw_pointer.type)}`
+  }
+  if (type.tuple) {
+    const elements = type.tuple.map(renderType)
+    return elements.length === 1
+      ? `(${elements[0]},)`
+      : `(${elements.join(', ')})`
+  }
+  if (type.slice) {
+    return `[${renderType(type.slice)}]`
+  }
+  if (type.array) {
+    return `[${renderType(type.array.type)}; ${type.array.len}]`
+  }
+  if (type.pat) {
+    return renderType(type.pat.type)
+  }
+  if (type.impl_trait) {
+    return `impl ${renderBounds(type.impl_trait)}`
+  }
+  if (type.dyn_trait) {
+    const { traits, lifetime } = type.dyn_trait
+    const bounds = [
+      ...traits.map(({ trait }) => renderPath(trait)),
+      ...(lifetime ? [lifetime] : []),
+    ]
+    return `dyn ${bounds.join(' + ')}`
+  }
+  if (type.function_pointer) {
+    const signature = type.function_pointer.sig ?? type.function_pointer.decl
+    return signature
+      ? `fn(${signature.inputs.map(([, input]) => renderType(input)).join(', ')})${renderOutput(signature)}`
+      : 'fn()'
+  }
+  if (type.qualified_path) {
+    const { name, args, self_type, trait } = type.qualified_path
+    const selfType = renderType(self_type)
+    return `${trait ? `<${selfType} as ${renderPath(trait)}>` : selfType}::${name}${renderGenericArgs(args)}`
+  }
+  return '_'
+}
+
+function renderOutput(signature: RustdocSignature): string {
+  const { output } = signature
+  return output && !(typeof output === 'object' && output.tuple?.length === 0)
+    ? ` -> ${renderType(output)}`
+    : ''
+}
+
+function renderGenerics(generics: RustdocGenerics | undefined): string {
+  const params = (generics?.params ?? []).flatMap(({ name, kind }) => {
+    if (kind.lifetime) {
+      const { outlives } = kind.lifetime
+      return [outlives.length > 0 ? `${name}: ${outlives.join(' + ')}` : name]
+    }
+    if (kind.type) {
+      // `impl Trait` arguments are desugared to synthetic type parameters
+      if (kind.type.is_synthetic ?? kind.type.synthetic) {
+        return []
+      }
+      const bounds = renderBounds(kind.type.bounds)
+      return [
+        `${name}${bounds ? `: ${bounds}` : ''}${kind.type.default ? ` = ${renderType(kind.type.default)}` : ''}`,
+      ]
+    }
+    if (kind.const) {
+      return [`const ${name}: ${renderType(kind.const.type)}`]
+    }
+    return []
+  })
+  return params.length > 0 ? `<${params.join(', ')}>` : ''
+}
+
+function renderWhereClause(generics: RustdocGenerics | undefined): string {
+  const predicates = (generics?.where_predicates ?? []).map((predicate) => {
+    if (predicate.bound_predicate) {
+      const { type, bounds } = predicate.bound_predicate
+      return `${renderType(type)}: ${renderBounds(bounds)}`
+    }
+    if (predicate.lifetime_predicate) {
+      const { lifetime, outlives } = predicate.lifetime_predicate
+      return `${lifetime}: ${outlives.join(' + ')}`
+    }
+    const { lhs, rhs } = predicate.eq_predicate!
+    return `${renderType(lhs)} == ${renderTerm(rhs)}`
+  })
+  return predicates.length > 0 ? ` where ${predicates.join(', ')}` : ''
+}
+
+function renderVisibility(item: RustdocItem): string {
+  return item.visibility === 'public' ? 'pub ' : ''
+}
+
+function renderSelfParam(type: RustdocType): string {
+  if (typeof type === 'object') {
+    if (type.generic === 'Self') {
+      return 'self'
+    }
+    const reference = type.borrowed_ref
+    if (
+      reference &&
+      typeof reference.type === 'object' &&
+      reference.type.generic === 'Self'
+    ) {
+      return `&${reference.lifetime ? `${reference.lifetime} ` : ''}${isMutable(reference) ? 'mut ' : ''}self`
+    }
+  }
+  return `self: ${renderType(type)}`
+}
+
+function renderFunction(item: RustdocItem, fn: RustdocFunction): string {
+  const { header = {} } = fn
+  const signature = fn.sig ?? fn.decl ?? { inputs: [] }
+  const qualifiers = [
+    ...((header.is_const ?? header.const_) ? ['const'] : []),
+    ...((header.is_async ?? header.async_) ? ['async'] : []),
+    ...((header.is_unsafe ?? header.unsafe_) ? ['unsafe'] : []),
+    ...(typeof header.abi === 'object' && 'C' in header.abi
+      ? ['extern "C"']
+      : []),
+  ]
+  const inputs = signature.inputs.map(([name, type]) =>
+    name === 'self' ? renderSelfParam(type) : `${name}: ${renderType(type)}`,
+  )
+  const prefix = `${renderVisibility(item)}${qualifiers.map((qualifier) => `${qualifier} `).join('')}`
+  return `${prefix}fn ${item.name}${renderGenerics(fn.generics)}(${inputs.join(', ')})${renderOutput(signature)}${renderWhereClause(fn.generics)}`
+}
+
+function renderMembers(
+  declaration: string,
+  members: string[],
+  hiddenMembersNote: string | undefined,
+): string {
+  if (members.length === 0 && !hiddenMembersNote) {
+    return `${declaration} {}`
+  }
+  return [
+    `${declaration} {`,
+    ...members.map((member) => `    ${member},`),
+    ...(hiddenMembersNote ? [`    ${hiddenMembersNote}`] : []),
+    '}',
+  ].join('\n')
+}
+
+/** Formats items the way rustdoc shows their declarations, bodies elided */
+function getSignature(item: RustdocItem, crate: RustdocCrate): string {
+  const inner = item.inner
+  const vis = renderVisibility(item)
+  const name = item.name ?? ''
+  if (typeof inner === 'string') {
+    return `${vis}${name}`
+  }
+  const getItem = (id: RustdocId | null) =>
+    id === null ? undefined : crate.index[String(id)]
+  // Fields of variants are as visible as their enum
+  const renderFields = (
+    ids: (RustdocId | null)[],
+    named: boolean,
+    isVariant = false,
+  ) =>
+    ids.flatMap((id) => {
+      const field = getItem(id)
+      if (
+        typeof field?.inner !== 'object' ||
+        !field.inner.struct_field ||
+        (!isVariant && field.visibility !== 'public')
+      ) {
+        return []
+      }
+      const type = renderType(field.inner.struct_field)
+      return [
+        named
+          ? `${renderVisibility(field)}${field.name}: ${type}`
+          : `${renderVisibility(field)}${type}`,
+      ]
+    })
+
+  if (inner.function) {
+    return renderFunction(item, inner.function)
+  }
+  if (inner.struct) {
+    const { kind, generics } = inner.struct
+    const declaration = `${vis}struct ${name}${renderGenerics(generics)}`
+    const whereClause = renderWhereClause(generics)
+    if (kind === 'unit') {
+      return `${declaration}${whereClause};`
+    }
+    if (kind.tuple) {
+      const fields = renderFields(kind.tuple, false)
+      if (fields.length < kind.tuple.length) {
+        fields.push(PRIVATE_FIELDS)
+      }
+      return `${declaration}(${fields.join(', ')})${whereClause};`
+    }
+    const { fields = [] } = kind.plain ?? {}
+    const rendered = renderFields(fields, true)
+    const hasHiddenFields =
+      Boolean(kind.plain?.has_stripped_fields ?? kind.plain?.fields_stripped) ||
+      rendered.length < fields.length
+    return renderMembers(
+      `${declaration}${whereClause}`,
+      rendered,
+      hasHiddenFields ? PRIVATE_FIELDS : undefined,
+    )
+  }
+  if (inner.union) {
+    const { fields = [], generics } = inner.union
+    const rendered = renderFields(fields, true)
+    const hasHiddenFields =
+      Boolean(inner.union.has_stripped_fields ?? inner.union.fields_stripped) ||
+      rendered.length < fields.length
+    return renderMembers(
+      `${vis}union ${name}${renderGenerics(generics)}${renderWhereClause(generics)}`,
+      rendered,
+      hasHiddenFields ? PRIVATE_FIELDS : undefined,
+    )
+  }
+  if (inner.enum) {
+    const { generics, variants } = inner.enum
+    const rendered = variants.flatMap((id) => {
+      const variant = getItem(id)
+      if (typeof variant?.inner !== 'object' || !variant.inner.variant) {
+        return []
+      }
+      const { kind, discriminant } = variant.inner.variant
+      if (kind === 'plain') {
+        return [
+          `${variant.name}${discriminant ? ` = ${discriminant.expr}` : ''}`,
+        ]
+      }
+      if (kind.tuple) {
+        const fields = renderFields(kind.tuple, false, true)
+        return [`${variant.name}(${fields.join(', ')})`]
+      }
+      const fields = renderFields(kind.struct?.fields ?? [], true, true)
+      return [`${variant.name} { ${fields.join(', ')} }`]
+    })
+    const hasHiddenVariants =
+      inner.enum.has_stripped_variants ?? inner.enum.variants_stripped
+    return renderMembers(
+      `${vis}enum ${name}${renderGenerics(generics)}${renderWhereClause(generics)}`,
+      rendered,
+      hasHiddenVariants ? '// some variants omitted' : undefined,
+    )
+  }
+  if (inner.trait) {
+    const { generics, bounds, is_auto, is_unsafe } = inner.trait
+    const supertraits = renderBounds(bounds)
+    return `${vis}${is_unsafe ? 'unsafe ' : ''}${is_auto ? 'auto ' : ''}trait ${name}${renderGenerics(generics)}${supertraits ? `: ${supertraits}` : ''}${renderWhereClause(generics)}`
+  }
+  if (inner.type_alias) {
+    const { type, generics } = inner.type_alias
+    return `${vis}type ${name}${renderGenerics(generics)}${renderWhereClause(generics)} = ${renderType(type)};`
+  }
+  if (inner.constant) {
+    const { type = inner.constant.type_ } = inner.constant
+    const expr = inner.constant.const?.expr ?? inner.constant.expr
+    return `${vis}const ${name}: ${type ? renderType(type) : '_'}${expr && expr !== '_' ? ` = ${expr}` : ''};`
+  }
+  if (inner.static) {
+    const mutable = inner.static.is_mutable ?? inner.static.mutable
+    return `${vis}static ${mutable ? 'mut ' : ''}${name}: ${renderType(inner.static.type)};`
+  }
+  if (inner.macro !== undefined) {
+    return inner.macro
+  }
+  if (inner.proc_macro) {
+    const { kind } = inner.proc_macro
+    return kind === 'derive'
+      ? `#[derive(${name})]`
+      : kind === 'attr'
+        ? `#[${name}]`
+        : `${name}!() { /* proc-macro */ }`
+  }
+  if (inner.assoc_const) {
+    const value = inner.assoc_const.value ?? inner.assoc_const.default
+    return `const ${name}: ${renderType(inner.assoc_const.type)}${value ? ` = ${value}` : ''};`
+  }
+  if (inner.assoc_type) {
+    const { generics, bounds } = inner.assoc_type
+    const type = inner.assoc_type.type ?? inner.assoc_type.default
+    const rendered = renderBounds(bounds)
+    return `type ${name}${renderGenerics(generics)}${rendered ? `: ${rendered}` : ''}${type ? ` = ${renderType(type)}` : ''};`
+  }
+  if (inner.module) {
+    return `${vis}mod ${name}`
+  }
+  return `${vis}${getInnerKind(item)} ${name}`
+}
+
+function formatDeprecation(item: RustdocItem): string | undefined {
+  if (!item.deprecation) {
+    return undefined
+  }
+  const { since, note } = item.deprecation
+  return `Deprecated${since ? ` since ${since}` : ''}${note ? `: ${note}` : ''}`
+}
+
+function isAutomaticallyDerived(item: RustdocItem): boolean {
+  return (item.attrs ?? []).some((attr) =>
+    (typeof attr === 'string' ? attr : JSON.stringify(attr)).includes(
+      'automatically_derived',
+    ),
+  )
+}
+
+function getImplementingTypeName(type: RustdocType): string {
+  if (typeof type === 'object') {
+    if (type.resolved_path) {
+      return lastSegment(getPathName(type.resolved_path))
+    }
+    if (type.borrowed_ref) {
+      return getImplementingTypeName(type.borrowed_ref.type)
+    }
+  }
+  return renderType(type)
+}
+
+/**
+ * Indexes the JSON rustdoc writes with `--output-format json`: the crate's
+ * public items under the paths the compiler resolved, with rendered
+ * signatures, docs and deprecations, and the trait impls the crate declares.
+ */
+export function parseRustdocJson(content: string): RustdocIndex {
+  const crate = JSON.parse(content) as RustdocCrate
+  if (
+    typeof crate?.index !== 'object' ||
+    typeof crate.paths !== 'object' ||
+    crate.root === undefined
+  ) {
+    throw new Error('Not a rustdoc JSON file')
+  }
+  const getItem = (id: RustdocId) => crate.index[String(id)]
+  const root = getItem(crate.root)
+  const crateName =
+    crate.paths[String(crate.root)]?.path[0] ?? root?.name ?? 'crate'
+
+  const items: RustDocItem[] = []
+  const addItem = (item: RustdocItem, itemPath: string) => {
+    const kind = ITEM_KINDS[getInnerKind(item)]
+    if (!kind) {
+      return
+    }
+    const deprecation = formatDeprecation(item)
+    items.push({
+      path: itemPath,
+      kind,
+      signature: getSignature(item, crate),
+      docs: item.docs ?? '',
+      file: item.span?.filename ?? '',
+      line: item.span?.begin[0] ?? 0,
+      ...(deprecation ? { deprecation } : {}),
+    })
+  }
+
+  for (const [id, summary] of Object.entries(crate.paths)) {
+    const item = crate.index[id]
+    if (summary.crate_id !== 0 || id === String(crate.root) || !item) {
+      continue
+    }
+    const itemPath = summary.path.join('::')
+    addItem(item, itemPath)
+    if (typeof item.inner === 'string') {
+      continue
+    }
+
+    // Associated items are documented under the type or trait they belong to,
+    // methods of types only from inherent impls
+    const type = item.inner.struct ?? item.inner.union ?? item.inner.enum
+    const memberIds = item.inner.trait
+      ? item.inner.trait.items
+      : (type?.impls ?? []).flatMap((implId) => {
+          const impl = getItem(implId)
+          const inner = typeof impl?.inner === 'object' && impl.inner.impl
+          return inner && !inner.trait ? inner.items : []
+        })
+    for (const memberId of memberIds) {
+      const member = getItem(memberId)
+      if (
+        member?.name &&
+        (item.inner.trait || member.visibility === 'public')
+      ) {
+        addItem(member, `${itemPath}::${member.name}`)
+      }
+    }
+  }
+
+  const traitImpls: RustTraitImpl[] = []
+  for (const item of Object.values(crate.index)) {
+    const impl = typeof item.inner === 'object' && item.inner.impl
+    if (
+      item.crate_id !== 0 ||
+      !impl ||
+      !impl.trait ||
+      impl.blanket_impl ||
+      (impl.is_synthetic ?? impl.synthetic) ||
+      (impl.is_negative ?? impl.negative)
+    ) {
+      continue
+    }
+    const traitPath =
+      (impl.trait.id !== undefined &&
+        crate.paths[String(impl.trait.id)]?.path.join('::')) ||
+      getPathName(impl.trait)
+    const implType = impl.for
+    const isBlanket =
+      typeof implType === 'object' &&
+      implType.generic !== undefined &&
+      impl.generics.params.some((param) => param.name === implType.generic)
+    traitImpls.push({
+      traitName: lastSegment(traitPath),
+      typeName: getImplementingTypeName(implType),
+      file: item.span?.filename ?? '',
+      line: item.span?.begin[0] ?? 0,
+      header: `${impl.is_unsafe ? 'unsafe ' : ''}impl${renderGenerics(impl.generics)} ${traitPath}${renderGenericArgs(impl.trait.args)} for ${renderType(implType)}${renderWhereClause(impl.generics)}`,
+      isBlanket,
+      origin: isAutomaticallyDerived(item) ? 'derive' : 'impl',
+      crateName,
+    })
+  }
+
+  return {
+    crateName,
+    ...(crate.crate_version ? { version: crate.crate_version } : {}),
+    formatVersion: crate.format_version,
+    docs: root?.docs ?? '',
+    items: items.sort((a, b) => a.path.localeCompare(b.path)),
+    traitImpls,
+  }
+}
+
+/** Documentation of a crate from its rustdoc index, for rendering */
+export function toRustCrateDocs(index: RustdocIndex): RustCrateDocs {
+  return {
+    crateName: index.crateName,
+    docs: index.docs,
+    items: index.items,
+    examples: [],
+  }
+}
@@ -0,0 +1,185 @@
+import type { Node } from 'web-tree-sitter'
+
+/** Item kinds that become associated items when nested in an impl or trait body */
+const ASSOCIATED_ITEM_TYPES = new Set([
+  'function_item',
+  'function_signature_item',
+  'const_item',
+  'type_item',
+  'associated_type',
+])
+
+export interface RustImplContainer {
+  /** Self type of an `impl` block, or the trait name for a `trait` block */
+  typeName: string | undefined
+  /** Trait being implemented, only set for `impl Trait for Type` */
+  traitName: string | undefined
+  kind: 'impl' | 'trait'
+}
+
+/**
+ * Returns the bare name of a type node, stripping generics, paths and
+ * references: `Vec<T>` -> `Vec`, `fmt::Display` -> `Display`, `&Foo` -> `Foo`.
+ */
+export function getRustTypeName(typeNode: Node | null): string | undefined {
+  if (!typeNode) {
+    return undefined
+  }
+  switch (typeNode.type) {
+    case 'type_identifier':
+    case 'primitive_type':
+    case 'identifier':
+      return typeNode.text
+    case 'generic_type':
+    case 'reference_type':
+    case 'pointer_type':
+      return getRustTypeName(typeNode.childForFieldName('type'))
+    case 'scoped_type_identifier':
+    case 'scoped_identifier':
+      return typeNode.childForFieldName('name')?.text
+    default:
+      return typeNode.text
+  }
+}
+
+/** Describes an `impl_item` or `trait_item` node */
+export function describeRustContainer(
+  container: Node,
+): RustImplContainer | undefined {
+  if (container.type === 'impl_item') {
+    return {
+      typeName: getRustTypeName(container.childForFieldName('type')),
+      traitName: getRustTypeName(container.childForFieldName('trait')),
+      kind: 'impl',
+    }
+  }
+  if (container.type === 'trait_item') {
+    return {
+      typeName: container.childForFieldName('name')?.text,
+      traitName: undefined,
+      kind: 'trait',
+    }
+  }
+  return undefined
+}
+
+/**
+ * Finds the impl or trait that directly owns an item, i.e. the item sits in
+ * the container's `declaration_list` body.
+ */
+export function getRustItemContainer(
+  item: Node,
+): RustImplContainer | undefined {
+  const body = item.parent
+  if (!body || body.type !== 'declaration_list' || !body.parent) {
+    return undefined
+  }
+  return describeRustContainer(body.parent)
+}
+
+/** Finds the closest enclosing impl block, used to resolve `Self` */
+export function getEnclosingRustImpl(
+  node: Node,
+): RustImplContainer | undefined {
+  for (let cur = node.parent; cur; cur = cur.parent) {
+    if (cur.type === 'impl_item' || cur.type === 'trait_item') {
+      return describeRustContainer(cur)
+    }
+  }
+  return undefined
+}
+
+/**
+ * Formats an associated item name the way it is written in Rust paths:
+ * `Type::item` for inherent impls and traits, `<Type as Trait>::item` for
+ * trait impls.
+ */
+export function formatRustAssociatedName(
+  container: RustImplContainer,
+  name: string,
+): string {
+  if (!container.typeName) {
+    return name
+  }
+  if (container.traitName) {
+    return `<${container.typeName} as ${container.traitName}>::${name}`
+  }
+  return `${container.typeName}::${name}`
+}
+
+function isFieldChild(parent: Node, field: string, node: Node): boolean {
+  return parent.childForFieldName(field)?.id === node.id
+}
+
+/** Last segment of a path node: `a::b::C` -> `C` */
+function getLastPathSegment(pathNode: Node | null): string | undefined {
+  if (!pathNode) {
+    return undefined
+  }
+  if (pathNode.type === 'scoped_identifier') {
+    return pathNode.childForFieldName('name')?.text
+  }
+  return getRustTypeName(pathNode)
+}
+
+/** CamelCase names such as `Empty`, unlike `new` or `PUNCTUATION` */
+function isRustVariantName(name: string): boolean {
+  return /^[A-Z]/.test(name) && /[a-z]/.test(name)
+}
+
+/**
+ * Maps a Rust tags capture to the token recorded by `getFileTokenScores`.
+ *
+ * Items nested in `impl`/`trait` bodies are qualified with their owner so
+ * that e.g. `Greeting::new` does not compete with every other `new` in the
+ * workspace. Path calls are qualified when the path ends in a type name
+ * (`Greeting::new(..)`, `Self::new(..)`), while module paths such as
+ * `io::stdin()` and method calls keep the bare name. `getFileTokenScores`
+ * maps method calls to the qualified members they may call.
+ */
+export function qualifyRustCapture(captureName: string, node: Node): string {
+  const parent = node.parent
+  if (!parent) {
+    return node.text
+  }
+
+  if (captureName === 'identifier') {
+    if (
+      ASSOCIATED_ITEM_TYPES.has(parent.type) &&
+      isFieldChild(parent, 'name', node)
+    ) {
+      const container = getRustItemContainer(parent)
+      if (container) {
+        return formatRustAssociatedName(container, node.text)
+      }
+    }
+    return node.text
+  }
+
+  if (
+    captureName === 'call.identifier' &&
+    parent.type === 'scoped_identifier' &&
+    isFieldChild(parent, 'name', node)
+  ) {
+    const owner = getLastPathSegment(parent.childForFieldName('path'))
+    if (owner === 'Self') {
+      // `Self::X` in `impl Trait for Type` names `<Type as Trait>::X`,
+      // except enum variants (`Self::Empty`), which belong to the type
+      const container = getEnclosingRustImpl(parent)
+      if (!container) {
+        return node.text
+      }
+      return formatRustAssociatedName(
+        isRustVariantName(node.text)
+          ? { ...container, traitName: undefined }
+          : container,
+        node.text,
+      )
+    }
+    if (owner && /^[A-Z]/.test(owner)) {
+      return `${owner}::${node.text}`
+    }
+  }
+
+  return node.text
+}
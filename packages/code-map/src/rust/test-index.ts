@@ -0,0 +1,286 @@
+import * as path from 'path'
+
+import { findCrateForFile } from './cargo'
+import { parseRustFiles } from './project'
+import { buildRustModuleIndex } from './resolve'
+
+import type { ParseCache } from '../parse-cache'
+import type { CargoCrate, CargoTargetKind, CargoWorkspace } from './cargo'
+import type { RustFileSymbols } from './file-symbols'
+import type { RustItemSite, RustModuleLocation } from './resolve'
+
+export type RustTestKind = 'unit' | 'integration' | 'doc'
+
+export interface RustTestTarget {
+  kind: Extract<CargoTargetKind, 'lib' | 'bin' | 'test' | 'example' | 'bench'>
+  /** Target name; unset for the library */
+  name?: string
+}
+
+export interface RustTest {
+  /** Function name, or the documented item's token for doc-tests */
+  name: string
+  /** Path libtest and rustdoc filter by, e.g. `config::tests::parses` */
+  path: string
+  kind: RustTestKind
+  file: string
+  line: number
+  /** Package to select with `cargo test -p`, when the workspace is known */
+  package?: string
+  /** Target the test is compiled into; unknown for loose files like build.rs */
+  target?: RustTestTarget
+  /** Non-test items the test body references, resolved to their definitions */
+  references: RustItemSite[]
+}
+
+export interface RustTestIndex {
+  tests: RustTest[]
+}
+
+/** Filters passed to a single `cargo test` at most; beyond, run the target */
+const MAX_TEST_FILTERS = 20
+const TARGET_KINDS_BY_DIR: Record<string, RustTestTarget['kind']> = {
+  tests: 'test',
+  examples: 'example',
+  benches: 'bench',
+}
+
+const joinKey = (parts: string[]) => parts.join('::')
+const siteKey = (site: RustItemSite) => `${site.file}\0${site.token}`
+
+const startsWith = (modulePath: string[], prefix: string[]) =>
+  prefix.every((segment, i) => modulePath[i] === segment)
+
+/** `<Type as Trait>::item` -> `Type::item`, the way rustdoc names doc-tests */
+function toDocTestName(token: string): string {
+  return token.replace(/^<(.+) as .+>::/, '$1::')
+}
+
+/**
+ * Finds the Cargo target a file is compiled into: a declared target rooted at
+ * the file, else the one Cargo's layout conventions imply.
+ */
+function getTestTarget(
+  filePath: string,
+  location: RustModuleLocation,
+  crate?: CargoCrate,
+): RustTestTarget | undefined {
+  const declared = crate?.targets.find((target) => target.path === filePath)
+  if (declared && declared.kind !== 'build-script') {
+    return declared.kind === 'lib'
+      ? { kind: 'lib' }
+      : { kind: declared.kind, name: declared.name }
+  }
+
+  if (location.isLibrary) {
+    // Binary-only packages have no library to select with `--lib`
+    if (crate && !crate.targets.some((target) => target.kind === 'lib')) {
+      const bin = crate.targets.find((target) => target.kind === 'bin')
+      return bin && { kind: 'bin', name: bin.name }
+    }
+    return { kind: 'lib' }
+  }
+
+  const segments = location.crateKey.split('/')
+  const [dir, name] = segments.slice(-2)
+  const kind =
+    dir === 'bin' && segments.at(-3) === 'src'
+      ? 'bin'
+      : Object.hasOwn(TARGET_KINDS_BY_DIR, dir)
+        ? TARGET_KINDS_BY_DIR[dir]
+        : undefined
+  if (!kind || !name) {
+    return undefined
+  }
+  const matching = crate?.targets.find(
+    (target) =>
+      target.kind === kind &&
+      (target.path === `${location.crateKey}.rs` ||
+        target.path.startsWith(`${location.crateKey}/`)),
+  )
+  return { kind, name: matching?.name ?? name }
+}
+
+/**
+ * Builds the test index of a set of parsed Rust files: every `#[test]`-like
+ * function and doc-tested item, with the path to select it by and the
+ * non-test items each test function references.
+ *
+ * Test functions and items of `#[cfg(test)]` modules are not counted as
+ * references, so a test depends on the code under test only.
+ */
+export function buildRustTestIndex(
+  files: Map<string, RustFileSymbols>,
+  fallbackCrateName: string,
+  workspace?: CargoWorkspace,
+): RustTestIndex {
+  const moduleIndex = buildRustModuleIndex(files, fallbackCrateName, workspace)
+
+  const testSites = new Set<string>()
+  const testModuleKeys: string[][] = []
+  for (const [file, symbols] of files) {
+    const location = moduleIndex.getLocation(file)
+    if (!location) {
+      continue
+    }
+    for (const test of symbols.tests) {
+      testSites.add(siteKey({ file, token: test.name }))
+    }
+    for (const modulePath of symbols.testModules) {
+      testModuleKeys.push([
+        location.crateKey,
+        ...location.modulePath,
+        ...modulePath,
+      ])
+    }
+  }
+  const isTestItem = (site: RustItemSite) => {
+    if (testSites.has(siteKey(site))) {
+      return true
+    }
+    const location = moduleIndex.getLocation(site.file)
+    const definition = files
+      .get(site.file)
+      ?.definitions.find(({ token }) => token === site.token)
+    if (!location || !definition) {
+      return false
+    }
+    const modulePath = [
+      location.crateKey,
+      ...location.modulePath,
+      ...definition.modulePath,
+    ]
+    return testModuleKeys.some((prefix) => startsWith(modulePath, prefix))
+  }
+
+  const tests: RustTest[] = []
+  for (const [file, symbols] of files) {
+    const location = moduleIndex.getLocation(file)
+    if (!location) {
+      continue
+    }
+    const crate = workspace && findCrateForFile(workspace, file)
+    const target = getTestTarget(file, location, crate)
+    const common = {
+      file,
+      ...(crate && { package: crate.name }),
+      ...(target && { target }),
+    }
+
+    for (const test of symbols.tests) {
+      const references = new Map<string, RustItemSite>()
+      for (const reference of symbols.references) {
+        const line = reference.range?.startLine
+        if (!line || line < test.startLine || line > test.endLine) {
+          continue
+        }
+        const site = moduleIndex.resolve(file, reference)
+        if (site && !isTestItem(site)) {
+          references.set(siteKey(site), site)
+        }
+      }
+      tests.push({
+        name: test.name,
+        path: joinKey([...location.modulePath, ...test.modulePath, test.name]),
+        kind: target?.kind === 'test' ? 'integration' : 'unit',
+        line: test.startLine,
+        ...common,
+        references: [...references.values()],
+      })
+    }
+
+    // rustdoc only runs doc-tests of library crates
+    if (target?.kind !== 'lib') {
+      continue
+    }
+    for (const docTest of symbols.docTests) {
+      tests.push({
+        name: docTest.token,
+        path: joinKey([
+          ...location.modulePath,
+          ...docTest.modulePath,
+          toDocTestName(docTest.token),
+        ]),
+        kind: 'doc',
+        line: docTest.line,
+        ...common,
+        references: [],
+      })
+    }
+  }
+
+  return { tests }
+}
+
+/**
+ * Returns the tests affected by changes to the given files: tests defined in
+ * them, and tests referencing items they define.
+ */
+export function findAffectedRustTests(
+  index: RustTestIndex,
+  changedFiles: string[],
+): RustTest[] {
+  const changed = new Set(
+    changedFiles.map((file) => path.posix.normalize(file)),
+  )
+  return index.tests.filter(
+    (test) =>
+      changed.has(test.file) ||
+      test.references.some((reference) => changed.has(reference.file)),
+  )
+}
+
+function getTargetArgs(test: RustTest): string[] {
+  if (test.kind === 'doc') {
+    return ['--doc']
+  }
+  if (!test.target) {
+    return []
+  }
+  const { kind, name } = test.target
+  return kind === 'lib' ? ['--lib'] : [`--${kind}`, name ?? '']
+}
+
+/**
+ * Formats the `cargo test` commands running exactly the given tests, one per
+ * package and target: `cargo test -p app --lib config::tests::parses`.
+ */
+export function getRustTestCommands(tests: RustTest[]): string[] {
+  const filtersByCommand = new Map<string, Set<string>>()
+  for (const test of tests) {
+    const command = [
+      'cargo',
+      'test',
+      ...(test.package ? ['-p', test.package] : []),
+      ...getTargetArgs(test),
+    ].join(' ')
+    const filters = filtersByCommand.get(command) ?? new Set()
+    filters.add(test.path)
+    filtersByCommand.set(command, filters)
+  }
+
+  return [...filtersByCommand].map(([command, filters]) => {
+    if (filters.size > MAX_TEST_FILTERS) {
+      return command
+    }
+    // libtest takes several filters after `--`, cargo only one before it
+    return filters.size === 1
+      ? `${command} ${[...filters][0]}`
+      : `${command} -- ${[...filters].join(' ')}`
+  })
+}
+
+/** Parses the given Rust files and builds their test index */
+export async function getRustTestIndex(
+  projectRoot: string,
+  filePaths: string[],
+  readFile?: (filePath: string) => string | null,
+  cargoWorkspace?: CargoWorkspace,
+  cache?: ParseCache,
+): Promise<RustTestIndex> {
+  return buildRustTestIndex(
+    await parseRustFiles(projectRoot, filePaths, readFile, cache),
+    path.basename(projectRoot),
+    cargoWorkspace,
+  )
+}
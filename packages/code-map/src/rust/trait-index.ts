@@ -0,0 +1,112 @@
+import { findCrateForFile } from './cargo'
+import { parseRustFiles } from './project'
+
+import type { ParseCache } from '../parse-cache'
+import type { CargoWorkspace } from './cargo'
+import type { RustFileSymbols } from './file-symbols'
+
+export interface RustTraitLocation {
+  file: string
+  line: number
+}
+
+export interface RustTraitImpl extends RustTraitLocation {
+  traitName: string
+  /** Implementing type; the type parameter for blanket impls */
+  typeName: string
+  /** Impl header as written, for explicit impls */
+  header?: string
+  isBlanket: boolean
+  origin: 'impl' | 'derive' | 'macro'
+  /**
+   * Crate declaring the impl, for impls read from rustdoc JSON and impls in
+   * the members of a Cargo workspace
+   */
+  crateName?: string
+}
+
+export interface RustTraitIndex {
+  /** Trait name -> where traits with that name are defined */
+  traits: Record<string, RustTraitLocation[]>
+  /** Trait name -> impls of it, including blanket impls */
+  implementors: Record<string, RustTraitImpl[]>
+  /** Type name -> traits implemented for it, excluding blanket impls */
+  implementedTraits: Record<string, RustTraitImpl[]>
+}
+
+function pushTo<T>(record: Record<string, T[]>, key: string, value: T) {
+  if (!Object.hasOwn(record, key)) {
+    record[key] = []
+  }
+  record[key].push(value)
+}
+
+/**
+ * Builds the trait index from parsed Rust files: which types implement each
+ * trait and which traits each type implements, from explicit `impl` blocks,
+ * `#[derive(..)]` attributes and macro-generated impls. With a Cargo
+ * workspace, impls are attributed to the member crate declaring them.
+ */
+export function buildRustTraitIndex(
+  files: Map<string, RustFileSymbols>,
+  workspace?: CargoWorkspace,
+): RustTraitIndex {
+  const index: RustTraitIndex = {
+    traits: {},
+    implementors: {},
+    implementedTraits: {},
+  }
+  const addImpl = (impl: RustTraitImpl) => {
+    pushTo(index.implementors, impl.traitName, impl)
+    if (!impl.isBlanket) {
+      pushTo(index.implementedTraits, impl.typeName, impl)
+    }
+  }
+
+  for (const [file, symbols] of files) {
+    for (const trait of symbols.traits) {
+      pushTo(index.traits, trait.name, { file, line: trait.line })
+    }
+
+    const crateName = workspace && findCrateForFile(workspace, file)?.name
+    const crate = crateName ? { crateName } : {}
+    for (const impl of symbols.impls) {
+      if (impl.traitName) {
+        addImpl({
+          ...impl,
+          traitName: impl.traitName,
+          file,
+          origin: 'impl',
+          ...crate,
+        })
+      }
+    }
+    for (const { traitName, typeName, line, origin } of symbols.impliedImpls) {
+      addImpl({
+        traitName,
+        typeName,
+        file,
+        line,
+        isBlanket: false,
+        origin,
+        ...crate,
+      })
+    }
+  }
+
+  return index
+}
+
+/** Parses the given Rust files and builds their trait index */
+export async function getRustTraitIndex(
+  projectRoot: string,
+  filePaths: string[],
+  readFile?: (filePath: string) => string | null,
+  cargoWorkspace?: CargoWorkspace,
+  cache?: ParseCache,
+): Promise<RustTraitIndex> {
+  return buildRustTraitIndex(
+    await parseRustFiles(projectRoot, filePaths, readFile, cache),
+    cargoWorkspace,
+  )
+}
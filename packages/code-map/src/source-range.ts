@@ -0,0 +1,45 @@
+import type { Node } from 'web-tree-sitter'
+
+/**
+ * A span of source code. Lines and columns are 1-based, and `endColumn` is
+ * the column just past the last character.
+ */
+export interface SourceRange {
+  startLine: number
+  startColumn: number
+  endLine: number
+  endColumn: number
+}
+
+export function getSourceRange(node: Node): SourceRange {
+  return {
+    startLine: node.startPosition.row + 1,
+    startColumn: node.startPosition.column + 1,
+    endLine: node.endPosition.row + 1,
+    endColumn: node.endPosition.column + 1,
+  }
+}
+
+/** Whether a 1-based position lies in the range, or right after its end */
+export function rangeContains(
+  range: SourceRange,
+  line: number,
+  column: number,
+): boolean {
+  const afterStart =
+    line > range.startLine ||
+    (line === range.startLine && column >= range.startColumn)
+  const beforeEnd =
+    line < range.endLine ||
+    (line === range.endLine && column <= range.endColumn)
+  return afterStart && beforeEnd
+}
+
+export function isSameRange(a: SourceRange, b: SourceRange): boolean {
+  return (
+    a.startLine === b.startLine &&
+    a.startColumn === b.startColumn &&
+    a.endLine === b.endLine &&
+    a.endColumn === b.endColumn
+  )
+}
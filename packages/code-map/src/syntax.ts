@@ -0,0 +1,102 @@
+import { getLanguageConfig } from './languages'
+import { getSourceRange } from './source-range'
+
+import type { SourceRange } from './source-range'
+import type { Node } from 'web-tree-sitter'
+
+export interface SyntaxIssue extends SourceRange {
+  /** `error` for text the parser skipped, `missing` for a token it assumed */
+  kind: 'error' | 'missing'
+  /** The skipped text, shortened, or the missing token, e.g. `}` */
+  text: string
+}
+
+const MAX_ISSUE_TEXT_LENGTH = 40
+
+function shortenText(text: string): string {
+  const singleLine = text.replace(/\s+/g, ' ').trim()
+  return singleLine.length > MAX_ISSUE_TEXT_LENGTH
+    ? `${singleLine.slice(0, MAX_ISSUE_TEXT_LENGTH)}…`
+    : singleLine
+}
+
+/** Collects the outermost `ERROR` nodes and every `MISSING` node of a tree */
+export function collectSyntaxIssues(root: Node): SyntaxIssue[] {
+  const issues: SyntaxIssue[] = []
+  const visit = (node: Node) => {
+    if (node.isMissing) {
+      issues.push({ kind: 'missing', text: node.type, ...getSourceRange(node) })
+    } else if (node.isError) {
+      issues.push({
+        kind: 'error',
+        text: shortenText(node.text),
+        ...getSourceRange(node),
+      })
+    } else if (node.hasError) {
+      for (const child of node.children) {
+        if (child) {
+          visit(child)
+        }
+      }
+    }
+  }
+  visit(root)
+  return issues
+}
+
+/**
+ * Parses a file and returns its syntax errors, or undefined if no grammar is
+ * known for the file.
+ */
+export async function findSyntaxIssues(
+  filePath: string,
+  sourceCode: string,
+): Promise<SyntaxIssue[] | undefined> {
+  const languageConfig = await getLanguageConfig(filePath)
+  const tree = languageConfig?.parser?.parse(sourceCode)
+  if (!tree) {
+    return undefined
+  }
+  try {
+    return collectSyntaxIssues(tree.rootNode)
+  } finally {
+    tree.delete()
+  }
+}
+
+/**
+ * Returns the syntax errors of the edited file that it did not have before
+ * the edit. Positions move with edits, so errors are matched by their kind
+ * and text; the later ones of a kind are reported as new.
+ */
+export async function findNewSyntaxIssues(
+  filePath: string,
+  before: string | null,
+  after: string,
+): Promise<SyntaxIssue[]> {
+  const afterIssues = await findSyntaxIssues(filePath, after)
+  if (!afterIssues || afterIssues.length === 0) {
+    return []
+  }
+  const beforeIssues =
+    before === null ? [] : ((await findSyntaxIssues(filePath, before)) ?? [])
+
+  const remaining = new Map<string, number>()
+  for (const { kind, text } of beforeIssues) {
+    const key = `${kind}\0${text}`
+    remaining.set(key, (remaining.get(key) ?? 0) + 1)
+  }
+  return afterIssues.filter(({ kind, text }) => {
+    const key = `${kind}\0${text}`
+    const count = remaining.get(key) ?? 0
+    remaining.set(key, count - 1)
+    return count <= 0
+  })
+}
+
+export function formatSyntaxIssue(issue: SyntaxIssue): string {
+  const at = `Line ${issue.startLine}, column ${issue.startColumn}`
+  return issue.kind === 'missing'
+    ? `${at}: missing \`${issue.text}\``
+    : `${at}: syntax error at \`${issue.text}\``
+}
@@ -4,6 +4,9 @@
 (class_declaration
   name: (name) @identifier)
 
+(trait_declaration
+  name: (name) @identifier)
+
 (function_definition
   name: (name) @identifier)
 
@@ -11,7 +14,7 @@
   name: (name) @identifier)
 
 (object_creation_expression
-  type: (qualified_name) @call.identifier)
+  [(name) (qualified_name)] @call.identifier)
 
 (function_call_expression
   function: (name) @call.identifier)
@@ -9,6 +9,10 @@
 (const_item name: (identifier) @identifier)
 (static_item name: (identifier) @identifier)
 
+; Associated items (qualified with their impl/trait in parse.ts)
+(function_signature_item name: (identifier) @identifier)
+(associated_type name: (type_identifier) @identifier)
+
 ; Function and macro calls
 (call_expression function: (identifier) @call.identifier)
 (call_expression function: (field_expression field: (field_identifier) @call.identifier))
@@ -17,10 +21,10 @@
 ; Struct instantiation
 (struct_expression (type_identifier) @call.identifier)
 
-; Enum variant usage
-(scoped_identifier path: (identifier) name: (identifier) @call.identifier)
+; Path usage: enum variants and associated functions (qualified with their type in parse.ts)
+(scoped_identifier path: (_) name: (identifier) @call.identifier)
 
 ; implementations
 
 (impl_item trait: (type_identifier) @call.identifier)
-(impl_item type: (type_identifier) @call.identifier !trait)
\ No newline at end of file
+(impl_item type: (type_identifier) @call.identifier !trait)
@@ -300,6 +300,7 @@ async function copyWasmFiles() {
     'tree-sitter-go.wasm',
     'tree-sitter-java.wasm',
     'tree-sitter-javascript.wasm',
+    'tree-sitter-php.wasm',
     'tree-sitter-python.wasm',
     'tree-sitter-ruby.wasm',
     'tree-sitter-rust.wasm',
@@ -0,0 +1,145 @@
+import { afterEach, describe, expect, it } from 'bun:test'
+import { spawn } from 'child_process'
+import os from 'os'
+
+import { BackgroundProcessManager } from '../shell/background-process-manager'
+import {
+  readBackgroundProcess,
+  runBackgroundCommand,
+  signalBackgroundProcess,
+} from '../tools/background-process'
+
+import type { LevelCodeSpawn } from '@levelcode/common/types/spawn'
+
+describe('background processes', () => {
+  const backgroundProcesses = new BackgroundProcessManager()
+  const start = async (command: string) => {
+    const [result] = await runBackgroundCommand({
+      command,
+      cwd: os.tmpdir(),
+      spawn: spawn as unknown as LevelCodeSpawn,
+      backgroundProcesses,
+    })
+    return (result.value as { processId: number }).processId
+  }
+
+  afterEach(async () => {
+    await backgroundProcesses.shutdown()
+  })
+
+  it('should wait for readiness and stop the process', async () => {
+    const processId = await start(
+      'echo starting; sleep 0.2; echo "Listening on 3000"; sleep 30',
+    )
+    const [ready] = await readBackgroundProcess({
+      process_id: processId,
+      wait_for: 'Listening on \\d+',
+      backgroundProcesses,
+    })
+    const [idle] = await readBackgroundProcess({
+      process_id: processId,
+      backgroundProcesses,
+    })
+    const [stopped] = await signalBackgroundProcess({
+      process_id: processId,
+      backgroundProcesses,
+    })
+
+    expect(ready.value).toMatchObject({
+      status: 'running',
+      output: 'starting\nListening on 3000\n',
+      matched: true,
+    })
+    expect(idle.value).toMatchObject({ output: '', offset: 27 })
+    expect(stopped.value).toMatchObject({
+      status: 'error',
+      signal: 'SIGTERM',
+      message: 'Sent SIGTERM; the process exited.',
+    })
+    expect(backgroundProcesses.list()).toMatchObject([
+      { id: processId, command: expect.stringContaining('Listening') },
+    ])
+  })
+
+  it('should read output by offset', async () => {
+    const processId = await start("printf 'abc'")
+    const [all] = await readBackgroundProcess({
+      process_id: processId,
+      wait_seconds: 5,
+      backgroundProcesses,
+    })
+    const [rest] = await readBackgroundProcess({
+      process_id: processId,
+      offset: 1,
+      backgroundProcesses,
+    })
+
+    expect(all.value).toMatchObject({
+      status: 'completed',
+      exitCode: 0,
+      output: 'abc',
+      offset: 0,
+      nextOffset: 3,
+    })
+    expect(rest.value).toMatchObject({ output: 'bc', nextOffset: 3 })
+  })
+
+  const waitForExit = (processId: number) =>
+    readBackgroundProcess({
+      process_id: processId,
+      wait_seconds: 5,
+      backgroundProcesses,
+    })
+
+  it('should give processes ids that are not their pids', async () => {
+    const first = await start('true')
+    const second = await start('true')
+
+    const [{ pid }] = backgroundProcesses.list()
+    expect(second).toBe(first + 1)
+    expect(pid).not.toBe(first)
+  })
+
+  it('should drop the oldest exited processes', async () => {
+    const processIds: number[] = []
+    for (let i = 0; i < 25; i++) {
+      const processId = await start('true')
+      await waitForExit(processId)
+      processIds.push(processId)
+    }
+
+    expect(backgroundProcesses.list().map(({ id }) => id)).toEqual(
+      processIds.slice(5),
+    )
+  })
+
+  it('should kill processes when this process gets SIGTERM', async () => {
+    // Another handler keeps the signal from ending the test run
+    const keepRunning = () => {}
+    process.on('SIGTERM', keepRunning)
+    try {
+      const processId = await start('sleep 30')
+      process.emit('SIGTERM', 'SIGTERM')
+      const [result] = await waitForExit(processId)
+
+      expect(result.value).toMatchObject({
+        status: 'error',
+        signal: 'SIGKILL',
+      })
+    } finally {
+      process.off('SIGTERM', keepRunning)
+    }
+  })
+
+  it('should report unknown processes', async () => {
+    const [result] = await readBackgroundProcess({
+      process_id: -1,
+      backgroundProcesses,
+    })
+
+    expect(result.value).toEqual({
+      errorMessage:
+        'No background process -1. Start one with run_terminal_command and process_type BACKGROUND.',
+    })
+  })
+})
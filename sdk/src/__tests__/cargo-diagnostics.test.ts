@@ -0,0 +1,96 @@
+import { describe, expect, it } from 'bun:test'
+import { EventEmitter } from 'events'
+import { PassThrough } from 'stream'
+
+import { cargoDiagnostics } from '../tools/cargo-diagnostics'
+
+import type { LevelCodeSpawn } from '@levelcode/common/types/spawn'
+
+const unusedVariable = JSON.stringify({
+  reason: 'compiler-message',
+  package_id: 'demo 0.1.0',
+  message: {
+    message: 'unused variable: `x`',
+    code: { code: 'unused_variables' },
+    level: 'warning',
+    spans: [
+      {
+        file_name: 'src/main.rs',
+        line_start: 2,
+        line_end: 2,
+        column_start: 9,
+        column_end: 10,
+        is_primary: true,
+        label: null,
+        suggested_replacement: null,
+        suggestion_applicability: null,
+      },
+    ],
+    children: [],
+    rendered: 'warning: unused variable: `x`',
+  },
+})
+
+/** A spawn whose cargo writes `chunks` to stdout and exits with 0 */
+function createSpawn(chunks: string[]) {
+  const calls: { args: readonly string[]; env?: NodeJS.ProcessEnv }[] = []
+  const spawn = ((_command: string, args: readonly string[], options) => {
+    calls.push({ args, env: options?.env })
+    const child = Object.assign(new EventEmitter(), {
+      stdout: new PassThrough(),
+      stderr: new PassThrough(),
+      kill: () => true,
+    })
+    setTimeout(() => {
+      for (const chunk of chunks) {
+        child.stdout.write(chunk)
+      }
+      child.stdout.end()
+      child.stderr.end()
+      setTimeout(() => child.emit('close', 0), 10)
+    }, 0)
+    return child
+  }) as LevelCodeSpawn
+  return { spawn, calls }
+}
+
+describe('cargoDiagnostics', () => {
+  it('should parse messages split across output chunks', async () => {
+    const artifact = JSON.stringify({ reason: 'compiler-artifact' })
+    const output = `${artifact}\n${unusedVariable}\n`
+    const middle = artifact.length + 20
+    const { spawn } = createSpawn([
+      output.slice(0, middle),
+      output.slice(middle),
+    ])
+
+    const [result] = await cargoDiagnostics({ projectPath: '/project', spawn })
+
+    expect(result).toMatchObject({
+      type: 'json',
+      value: {
+        success: true,
+        warningCount: 1,
+        files: [
+          {
+            file: 'src/main.rs',
+            diagnostics: [{ message: 'unused variable: `x`' }],
+          },
+        ],
+      },
+    })
+  })
+
+  it('should pass the client environment to cargo', async () => {
+    const { spawn, calls } = createSpawn([])
+
+    await cargoDiagnostics({
+      projectPath: '/project',
+      spawn,
+      env: { CARGO_TARGET_DIR: '/tmp/target' },
+    })
+
+    expect(calls[0].env?.CARGO_TARGET_DIR).toBe('/tmp/target')
+    expect(calls[0].env?.PATH).toBe(process.env.PATH)
+  })
+})
@@ -0,0 +1,187 @@
+import { describe, expect, it } from 'bun:test'
+import fs from 'fs'
+import os from 'os'
+import path from 'path'
+
+import { loadCargoWorkspace } from '@levelcode/code-map/rust/cargo'
+
+import { loadFileChangeHooks } from '../agents/load-file-change-hooks'
+import {
+  getHookCommands,
+  runFileChangeHooksAfterEdit,
+} from '../tools/run-file-change-hooks'
+
+import type { LevelCodeFileSystem } from '@levelcode/common/types/filesystem'
+
+function createFs(files: Record<string, string>): LevelCodeFileSystem {
+  return {
+    readFile: async (filePath: string) => {
+      if (filePath in files) {
+        return files[filePath]
+      }
+      throw new Error(`File not found: ${filePath}`)
+    },
+  } as unknown as LevelCodeFileSystem
+}
+
+describe('loadFileChangeHooks', () => {
+  it('should load the hooks from hooks.json', async () => {
+    const hooks = await loadFileChangeHooks({
+      cwd: '/project',
+      fs: createFs({
+        '/project/.agents/hooks.json': JSON.stringify({
+          fileChangeHooks: [
+            {
+              name: 'rustfmt',
+              filePattern: '*.rs',
+              command: 'cargo fmt -- {file}',
+              required: true,
+            },
+          ],
+        }),
+      }),
+    })
+
+    expect(hooks).toEqual([
+      {
+        name: 'rustfmt',
+        filePattern: '*.rs',
+        command: 'cargo fmt -- {file}',
+        required: true,
+      },
+    ])
+  })
+
+  it('should ignore missing and invalid config files', async () => {
+    expect(
+      await loadFileChangeHooks({ cwd: '/project', fs: createFs({}) }),
+    ).toEqual([])
+    expect(
+      await loadFileChangeHooks({
+        cwd: '/project',
+        fs: createFs({
+          '/project/.agents/hooks.json': JSON.stringify({
+            fileChangeHooks: [{ name: 'missing-command', filePattern: '*' }],
+          }),
+        }),
+      }),
+    ).toEqual([])
+  })
+})
+
+describe('getHookCommands', () => {
+  const workspace = loadCargoWorkspace({
+    'Cargo.toml': '[workspace]\nmembers = ["crates/*"]\n',
+    'crates/core/Cargo.toml': '[package]\nname = "app-core"\n',
+    'crates/cli/Cargo.toml': '[package]\nname = "app-cli"\n',
+  })
+  const files = [
+    'crates/core/src/lib.rs',
+    'crates/core/src/config.rs',
+    'crates/cli/src/main.rs',
+    'README.md',
+  ]
+
+  it('should run {file} commands once per matching file', () => {
+    expect(
+      getHookCommands(
+        { name: 'rustfmt', filePattern: '*.rs', command: 'rustfmt {file}' },
+        ['src/my file.rs', 'README.md'],
+      ),
+    ).toEqual(["rustfmt 'src/my file.rs'"])
+  })
+
+  it('should run {crate} commands once per crate', () => {
+    expect(
+      getHookCommands(
+        {
+          name: 'check',
+          filePattern: '*.rs',
+          command: 'cargo check -p {crate}',
+        },
+        files,
+        workspace,
+      ),
+    ).toEqual(['cargo check -p app-core', 'cargo check -p app-cli'])
+  })
+
+  it('should run other commands once', () => {
+    expect(
+      getHookCommands(
+        {
+          name: 'lint',
+          filePattern: 'crates/core/**',
+          command: 'lint {files}',
+        },
+        files,
+      ),
+    ).toEqual(['lint crates/core/src/lib.rs crates/core/src/config.rs'])
+  })
+})
+
+describe('runFileChangeHooksAfterEdit', () => {
+  const editResult = [
+    {
+      type: 'json' as const,
+      value: { file: 'src/lib.rs', message: 'Updated file', unifiedDiff: '' },
+    },
+  ]
+  const run = (required: boolean) =>
+    runFileChangeHooksAfterEdit({
+      editResult,
+      file: 'src/lib.rs',
+      hooks: [
+        { name: 'format', filePattern: '*.rs', command: 'true' },
+        { name: 'check', filePattern: '*.rs', command: 'exit 1', required },
+      ],
+      projectPath: os.tmpdir(),
+      fs: createFs({}),
+    })
+
+  it('should add the hook results to the edit result', async () => {
+    const [result] = await run(false)
+    expect(result).toMatchObject({
+      value: {
+        message: 'Updated file',
+        hooks: [
+          { hookName: 'format', exitCode: 0, passed: true },
+          { hookName: 'check', exitCode: 1, passed: false, required: false },
+        ],
+      },
+    })
+  })
+
+  it('should fail the edit when a required hook fails', async () => {
+    const [result] = await run(true)
+    expect(result).toMatchObject({
+      value: {
+        file: 'src/lib.rs',
+        errorMessage: expect.stringContaining('hook(s) failed: check'),
+      },
+    })
+  })
+
+  it('should note when the hooks changed the file', async () => {
+    const projectPath = fs.mkdtempSync(path.join(os.tmpdir(), 'hooks-'))
+    fs.mkdirSync(path.join(projectPath, 'src'))
+    fs.writeFileSync(path.join(projectPath, 'src/lib.rs'), 'fn a() {}\n')
+    const runHook = (command: string) =>
+      runFileChangeHooksAfterEdit({
+        editResult,
+        file: 'src/lib.rs',
+        hooks: [{ name: 'format', filePattern: '*.rs', command }],
+        projectPath,
+        fs: fs.promises as unknown as LevelCodeFileSystem,
+      })
+
+    try {
+      const [unchanged] = await runHook('true')
+      const [changed] = await runHook('echo >> {file}')
+
+      expect(unchanged.value).not.toHaveProperty('changedByHooks')
+      expect(changed).toMatchObject({ value: { changedByHooks: true } })
+    } finally {
+      fs.rmSync(projectPath, { recursive: true, force: true })
+    }
+  })
+})
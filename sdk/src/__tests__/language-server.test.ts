@@ -0,0 +1,394 @@
+import { afterEach, describe, expect, it } from 'bun:test'
+import { EventEmitter } from 'events'
+import { PassThrough } from 'stream'
+
+import { JsonRpcConnection } from '../lsp/json-rpc'
+import { LanguageServerManager } from '../lsp/manager'
+import { applyTextEdits, getWorkspaceEditChanges } from '../lsp/protocol'
+import {
+  findLanguageServerConfig,
+  mergeLanguageServerConfigs,
+} from '../lsp/servers'
+import { lspDiagnostics, lspHover, lspRename } from '../tools/language-server'
+
+import type { LevelCodeFileSystem } from '@levelcode/common/types/filesystem'
+import type { LevelCodeSpawn } from '@levelcode/common/types/spawn'
+
+function createFs(files: Record<string, string>): LevelCodeFileSystem {
+  return {
+    readFile: async (filePath: string) => {
+      if (filePath in files) {
+        return files[filePath]
+      }
+      throw new Error(`File not found: ${filePath}`)
+    },
+    writeFile: async (filePath: string, content: string) => {
+      files[filePath] = content
+    },
+    readdir: async () => [],
+  } as unknown as LevelCodeFileSystem
+}
+
+function createConnectionPair() {
+  const toServer = new PassThrough()
+  const toClient = new PassThrough()
+  return {
+    client: new JsonRpcConnection(toClient, toServer),
+    server: new JsonRpcConnection(toServer, toClient),
+    toClient,
+  }
+}
+
+/** Spawns an in-process language server answering with the given handlers */
+function createFakeServer(
+  handlers: Record<string, (params: any) => unknown>,
+  capabilities: Record<string, unknown> = {},
+) {
+  const notifications: { method: string; params: any }[] = []
+  let spawned = 0
+  const spawn = (() => {
+    spawned++
+    const stdin = new PassThrough()
+    const stdout = new PassThrough()
+    const child = Object.assign(new EventEmitter(), {
+      stdin,
+      stdout,
+      stderr: new PassThrough(),
+      kill: () => {
+        child.emit('exit', null, 'SIGTERM')
+        return true
+      },
+    })
+    const server = new JsonRpcConnection(stdin, stdout)
+    server.onRequest('initialize', () => ({ capabilities }))
+    server.onRequest('shutdown', () => null)
+    server.onNotification('exit', () => child.emit('exit', 0, null))
+    for (const method of [
+      'textDocument/didOpen',
+      'textDocument/didChange',
+      'textDocument/didSave',
+    ]) {
+      server.onNotification(method, (params) =>
+        notifications.push({ method, params }),
+      )
+    }
+    for (const [method, handler] of Object.entries(handlers)) {
+      server.onRequest(method, handler)
+    }
+    return child
+  }) as unknown as LevelCodeSpawn
+  return { spawn, notifications, getSpawnCount: () => spawned }
+}
+
+describe('JsonRpcConnection', () => {
+  it('should send requests and receive their responses', async () => {
+    const { client, server } = createConnectionPair()
+    server.onRequest('add', (params) => {
+      const { a, b } = params as { a: number; b: number }
+      return a + b
+    })
+
+    expect(await client.request<number>('add', { a: 1, b: 2 })).toBe(3)
+  })
+
+  it('should reject requests the other side does not handle', async () => {
+    const { client } = createConnectionPair()
+
+    await expect(client.request('unknown')).rejects.toThrow(
+      'Unhandled method unknown',
+    )
+  })
+
+  it('should read notifications split across chunks', async () => {
+    const { client, toClient } = createConnectionPair()
+    const received: unknown[] = []
+    client.onNotification('note', (params) => received.push(params))
+
+    const body = JSON.stringify({
+      jsonrpc: '2.0',
+      method: 'note',
+      params: { text: 'héllo' },
+    })
+    const message = `Content-Length: ${Buffer.byteLength(body)}\r\n\r\n${body}`
+    toClient.write(message.slice(0, 10))
+    toClient.write(message.slice(10) + message)
+    await new Promise((resolve) => setImmediate(resolve))
+
+    expect(received).toEqual([{ text: 'héllo' }, { text: 'héllo' }])
+  })
+})
+
+describe('applyTextEdits', () => {
+  const range = (
+    startLine: number,
+    startCharacter: number,
+    endLine: number,
+    endCharacter: number,
+  ) => ({
+    start: { line: startLine, character: startCharacter },
+    end: { line: endLine, character: endCharacter },
+  })
+
+  it('should apply edits in any order', () => {
+    const content = 'fn load() {}\n\nfn main() {\n    load();\n}\n'
+
+    expect(
+      applyTextEdits(content, [
+        { range: range(3, 4, 3, 8), newText: 'load_config' },
+        { range: range(0, 3, 0, 7), newText: 'load_config' },
+      ]),
+    ).toBe('fn load_config() {}\n\nfn main() {\n    load_config();\n}\n')
+  })
+
+  it('should keep the order of insertions at the same position', () => {
+    expect(
+      applyTextEdits('b', [
+        { range: range(0, 0, 0, 0), newText: 'a' },
+        { range: range(0, 0, 0, 0), newText: '-' },
+      ]),
+    ).toBe('a-b')
+  })
+
+  it('should group workspace edits by document', () => {
+    const edit = { range: range(0, 0, 0, 1), newText: 'x' }
+    const changes = getWorkspaceEditChanges({
+      documentChanges: [
+        { textDocument: { uri: 'file:///a.rs' }, edits: [edit] },
+        { textDocument: { uri: 'file:///a.rs' }, edits: [edit] },
+      ],
+    })
+
+    expect([...changes]).toEqual([['file:///a.rs', [edit, edit]]])
+  })
+})
+
+describe('language server configs', () => {
+  it('should replace defaults with custom servers of the same id', () => {
+    const configs = mergeLanguageServerConfigs([
+      {
+        id: 'rust-analyzer',
+        command: '/opt/rust-analyzer',
+        languageIds: { '.rs': 'rust' },
+      },
+      { id: 'gopls', command: 'gopls', languageIds: { '.go': 'go' } },
+    ])
+
+    expect(findLanguageServerConfig(configs, 'src/lib.rs')?.command).toBe(
+      '/opt/rust-analyzer',
+    )
+    expect(findLanguageServerConfig(configs, 'main.go')?.id).toBe('gopls')
+    expect(findLanguageServerConfig(configs, 'app.tsx')?.id).toBe(
+      'typescript-language-server',
+    )
+    expect(findLanguageServerConfig(configs, 'README.md')).toBeUndefined()
+  })
+})
+
+describe('language server tools', () => {
+  let manager: LanguageServerManager
+  afterEach(async () => {
+    await manager.shutdown()
+  })
+
+  it('should sync files from disk and return hover information', async () => {
+    const files = { '/project/src/lib.rs': 'fn main() {}\n' }
+    const { spawn, notifications, getSpawnCount } = createFakeServer({
+      'textDocument/hover': ({ position }) => ({
+        contents: { kind: 'markdown', value: `fn main() @ ${position.line}` },
+      }),
+    })
+    manager = new LanguageServerManager({})
+    const params = {
+      path: 'src/lib.rs',
+      line: 1,
+      column: 4,
+      projectPath: '/project',
+      fs: createFs(files),
+      spawn,
+      languageServers: manager,
+    }
+
+    expect(await lspHover(params)).toEqual([
+      {
+        type: 'json',
+        value: {
+          contents: 'fn main() @ 0',
+          message: 'Type information from rust-analyzer',
+        },
+      },
+    ])
+
+    files['/project/src/lib.rs'] = 'pub fn main() {}\n'
+    await lspHover(params)
+    await lspHover(params)
+
+    expect(getSpawnCount()).toBe(1)
+    expect(notifications.map(({ method }) => method)).toEqual([
+      'textDocument/didOpen',
+      'textDocument/didChange',
+      'textDocument/didSave',
+    ])
+    expect(notifications[0].params.textDocument).toMatchObject({
+      uri: 'file:///project/src/lib.rs',
+      languageId: 'rust',
+    })
+  })
+
+  it('should write the edits of a rename', async () => {
+    const files = {
+      '/project/src/config.rs': 'pub fn load() {}\n',
+      '/project/src/main.rs': 'fn main() {\n    config::load();\n}\n',
+    }
+    const { spawn } = createFakeServer({
+      'textDocument/rename': ({ newName }) => ({
+        changes: {
+          'file:///project/src/config.rs': [
+            {
+              range: {
+                start: { line: 0, character: 7 },
+                end: { line: 0, character: 11 },
+              },
+              newText: newName,
+            },
+          ],
+          'file:///project/src/main.rs': [
+            {
+              range: {
+                start: { line: 1, character: 12 },
+                end: { line: 1, character: 16 },
+              },
+              newText: newName,
+            },
+          ],
+        },
+      }),
+    })
+    manager = new LanguageServerManager({})
+
+    const [result] = await lspRename({
+      path: 'src/config.rs',
+      line: 1,
+      column: 8,
+      new_name: 'load_config',
+      projectPath: '/project',
+      fs: createFs(files),
+      spawn,
+      languageServers: manager,
+    })
+
+    expect(files).toEqual({
+      '/project/src/config.rs': 'pub fn load_config() {}\n',
+      '/project/src/main.rs': 'fn main() {\n    config::load_config();\n}\n',
+    })
+    expect(result).toMatchObject({
+      value: {
+        files: [
+          { file: 'src/config.rs', edits: 1 },
+          { file: 'src/main.rs', edits: 1 },
+        ],
+        message: 'Renamed to "load_config" with 2 edit(s) in 2 file(s)',
+      },
+    })
+  })
+
+  it('should pull diagnostics and report files without a server', async () => {
+    const { spawn } = createFakeServer(
+      {
+        'textDocument/diagnostic': () => ({
+          kind: 'full',
+          items: [
+            {
+              range: {
+                start: { line: 2, character: 4 },
+                end: { line: 2, character: 9 },
+              },
+              severity: 2,
+              source: 'rustc',
+              message: 'unused variable: `value`',
+            },
+            {
+              range: {
+                start: { line: 0, character: 0 },
+                end: { line: 0, character: 2 },
+              },
+              severity: 1,
+              code: 'E0308',
+              source: 'rustc',
+              message: 'mismatched types',
+            },
+          ],
+        }),
+      },
+      { diagnosticProvider: {} },
+    )
+    manager = new LanguageServerManager({})
+
+    const [result] = await lspDiagnostics({
+      paths: ['src/lib.rs', 'notes.txt'],
+      projectPath: '/project',
+      fs: createFs({
+        '/project/src/lib.rs': 'fn main() {\n\n    let value = 1;\n}\n',
+        '/project/notes.txt': '',
+      }),
+      spawn,
+      languageServers: manager,
+    })
+
+    expect(result).toMatchObject({
+      value: {
+        files: [
+          {
+            file: 'src/lib.rs',
+            diagnostics: [
+              { severity: 'error', code: 'E0308', message: 'mismatched types' },
+              {
+                severity: 'warning',
+                message: 'unused variable: `value`',
+                range: { startLine: 3, startColumn: 5 },
+              },
+            ],
+          },
+          {
+            file: 'notes.txt',
+            diagnostics: [],
+            errorMessage: 'No language server is configured for .txt files',
+          },
+        ],
+        errorCount: 1,
+        warningCount: 1,
+      },
+    })
+  })
+
+  it('should report servers that fail to start', async () => {
+    const spawn = (() => {
+      const child = Object.assign(new EventEmitter(), {
+        stdin: new PassThrough(),
+        stdout: new PassThrough(),
+        stderr: new PassThrough(),
+        kill: () => true,
+      })
+      setImmediate(() => {
+        child.emit('error', new Error('spawn rust-analyzer ENOENT'))
+      })
+      return child
+    }) as unknown as LevelCodeSpawn
+    manager = new LanguageServerManager({})
+
+    const [result] = await lspHover({
+      path: 'src/lib.rs',
+      line: 1,
+      column: 1,
+      projectPath: '/project',
+      fs: createFs({ '/project/src/lib.rs': '' }),
+      spawn,
+      languageServers: manager,
+    })
+
+    expect(result).toMatchObject({
+      value: {
+        errorMessage: expect.stringContaining('Make sure it is installed'),
+      },
+    })
+  })
+})
@@ -0,0 +1,133 @@
+import { describe, expect, it } from 'bun:test'
+
+import { getRegisteredLanguageNames } from '@levelcode/code-map/languages'
+
+import {
+  languagesFileSchema,
+  loadLanguageConfig,
+  registerLanguages,
+} from '../agents/load-language-config'
+
+import type { LevelCodeFileSystem } from '@levelcode/common/types/filesystem'
+
+function createFs(files: Record<string, string>): LevelCodeFileSystem {
+  return {
+    readFile: async (filePath: string) => {
+      if (filePath in files) {
+        return files[filePath]
+      }
+      throw new Error(`File not found: ${filePath}`)
+    },
+  } as unknown as LevelCodeFileSystem
+}
+
+describe('languagesFileSchema', () => {
+  it('should require extensions or a file name pattern', () => {
+    const result = languagesFileSchema.safeParse({
+      languages: [{ name: 'zig', wasm: 'zig.wasm', query: 'zig.scm' }],
+    })
+    expect(result.success).toBe(false)
+  })
+
+  it('should reject invalid file name patterns', () => {
+    const result = languagesFileSchema.safeParse({
+      languages: [
+        {
+          name: 'starlark',
+          fileNamePattern: '^BUILD(',
+          wasm: 'starlark.wasm',
+          query: 'starlark.scm',
+        },
+      ],
+    })
+    expect(result.success).toBe(false)
+  })
+})
+
+describe('loadLanguageConfig', () => {
+  it('should resolve paths against the project root', async () => {
+    const fs = createFs({
+      '/project/.agents/languages.json': JSON.stringify({
+        languages: [
+          {
+            name: 'wgsl',
+            extensions: ['.wgsl'],
+            wasm: 'grammars/tree-sitter-wgsl.wasm',
+            query: 'grammars/wgsl-tags.scm',
+          },
+          {
+            name: 'starlark',
+            fileNamePattern: '^(BUILD|WORKSPACE)$',
+            wasm: 'grammars/tree-sitter-starlark.wasm',
+            query: '(function_definition name: (identifier) @identifier)',
+          },
+        ],
+      }),
+    })
+
+    const [wgsl, starlark] = await loadLanguageConfig({
+      cwd: '/project',
+      fs,
+    })
+
+    expect(wgsl.projectRoot).toBe('/project')
+    expect(wgsl.wasmPath).toBe('/project/grammars/tree-sitter-wgsl.wasm')
+    expect(wgsl.query).toBe('/project/grammars/wgsl-tags.scm')
+    expect(wgsl.matchFileName).toBeUndefined()
+
+    expect(starlark.query).toBe(
+      '(function_definition name: (identifier) @identifier)',
+    )
+    expect(starlark.matchFileName?.('WORKSPACE')).toBe(true)
+    expect(starlark.matchFileName?.('BUILD.md')).toBe(false)
+  })
+
+  it('should return no languages without a config file', async () => {
+    const languages = await loadLanguageConfig({
+      cwd: '/project',
+      fs: createFs({}),
+    })
+    expect(languages).toEqual([])
+  })
+
+  it('should ignore invalid config files', async () => {
+    const languages = await loadLanguageConfig({
+      cwd: '/project',
+      fs: createFs({ '/project/.agents/languages.json': '{ not json' }),
+    })
+    expect(languages).toEqual([])
+  })
+})
+
+describe('registerLanguages', () => {
+  it('should register languages for the project and drop removed ones', async () => {
+    const languagesFile = JSON.stringify({
+      languages: [
+        {
+          name: 'wgsl',
+          extensions: ['.wgsl'],
+          wasm: 'tree-sitter-wgsl.wasm',
+          query: 'wgsl-tags.scm',
+        },
+      ],
+    })
+    await registerLanguages({
+      cwd: '/project',
+      languages: [
+        {
+          name: 'proto',
+          extensions: ['.proto'],
+          wasmPath: '/grammars/tree-sitter-proto.wasm',
+          query: '(message (message_name) @identifier)',
+        },
+      ],
+      fs: createFs({ '/project/.agents/languages.json': languagesFile }),
+    })
+
+    expect(getRegisteredLanguageNames('/project')).toEqual(['wgsl', 'proto'])
+    expect(getRegisteredLanguageNames()).not.toContain('proto')
+
+    await registerLanguages({ cwd: '/project', fs: createFs({}) })
+    expect(getRegisteredLanguageNames('/project')).toEqual([])
+  })
+})
@@ -0,0 +1,185 @@
+import { describe, expect, it } from 'bun:test'
+
+import { readDocs } from '../tools/read-docs'
+
+import type { LevelCodeFileSystem } from '@levelcode/common/types/filesystem'
+
+function createFs(files: Record<string, string>): LevelCodeFileSystem {
+  const isDirectory = (dirPath: string) =>
+    Object.keys(files).some((filePath) => filePath.startsWith(`${dirPath}/`))
+  return {
+    readFile: async (filePath: string) => {
+      if (filePath in files) {
+        return files[filePath]
+      }
+      throw new Error(`File not found: ${filePath}`)
+    },
+    readdir: async (dirPath: string) => {
+      if (!isDirectory(dirPath)) {
+        throw new Error(`Directory not found: ${dirPath}`)
+      }
+      const names = Object.keys(files)
+        .filter((filePath) => filePath.startsWith(`${dirPath}/`))
+        .map((filePath) => filePath.slice(dirPath.length + 1).split('/')[0])
+      return [...new Set(names)]
+    },
+    stat: async (filePath: string) => {
+      if (filePath in files || isDirectory(filePath)) {
+        return {
+          isDirectory: () => isDirectory(filePath),
+          atimeMs: 0,
+        }
+      }
+      throw new Error(`File not found: ${filePath}`)
+    },
+  } as unknown as LevelCodeFileSystem
+}
+
+const registry = '/cargo/registry/src/index.crates.io-6f17d22bba15001f'
+const projectFiles = {
+  '/project/Cargo.toml': `
+[package]
+name = "app"
+version = "0.1.0"
+
+[dependencies]
+serde_json = "1"
+`,
+  '/project/Cargo.lock': `
+version = 3
+
+[[package]]
+name = "serde_json"
+version = "1.0.117"
+source = "registry+https://github.com/rust-lang/crates.io-index"
+`,
+}
+
+function rustdocJson(crateName: string, version: string): string {
+  return JSON.stringify({
+    root: 0,
+    crate_version: version,
+    format_version: 39,
+    index: {
+      0: {
+        crate_id: 0,
+        name: crateName,
+        visibility: 'public',
+        docs: `Docs of ${crateName}.`,
+        inner: { module: { is_crate: true, items: [1] } },
+      },
+      1: {
+        crate_id: 0,
+        name: 'Value',
+        span: { filename: 'src/lib.rs', begin: [4, 0], end: [4, 20] },
+        visibility: 'public',
+        docs: 'A value.',
+        inner: {
+          struct: {
+            kind: 'unit',
+            generics: { params: [], where_predicates: [] },
+            impls: [],
+          },
+        },
+      },
+    },
+    paths: {
+      0: { crate_id: 0, path: [crateName], kind: 'module' },
+      1: { crate_id: 0, path: [crateName, 'Value'], kind: 'struct' },
+    },
+  })
+}
+
+describe('readDocs', () => {
+  const params = {
+    topic: 'Value',
+    projectPath: '/project',
+    env: { CARGO_HOME: '/cargo', CARGO_TARGET_DIR: 'target' },
+  }
+
+  it('should document locked crates from the registry sources', async () => {
+    const [result] = await readDocs({
+      ...params,
+      libraryTitle: 'serde-json',
+      fs: createFs({
+        ...projectFiles,
+        [`${registry}/serde_json-1.0.110/Cargo.toml`]: '',
+        [`${registry}/serde_json-1.0.117/Cargo.toml`]:
+          '[package]\nname = "serde_json"\nversion = "1.0.117"\n',
+        [`${registry}/serde_json-1.0.117/src/lib.rs`]: '',
+      }),
+    })
+
+    expect(result.value.errorMessage).toBeUndefined()
+    expect(result.value.documentation).toStartWith(
+      `# serde_json 1.0.117 (from ${registry}/serde_json-1.0.117)`,
+    )
+  })
+
+  it('should prefer rustdoc JSON of the locked version', async () => {
+    const [workspaceCrate] = await readDocs({
+      ...params,
+      libraryTitle: 'app',
+      fs: createFs({
+        ...projectFiles,
+        '/project/target/doc/app.json': rustdocJson('app', '0.1.0'),
+      }),
+    })
+    const [staleDependency] = await readDocs({
+      ...params,
+      libraryTitle: 'serde_json',
+      fs: createFs({
+        ...projectFiles,
+        '/project/target/doc/serde_json.json': rustdocJson(
+          'serde_json',
+          '1.0.100',
+        ),
+      }),
+    })
+
+    expect(workspaceCrate.value.documentation).toStartWith(
+      '# app 0.1.0 (from /project/target/doc/app.json)',
+    )
+    expect(workspaceCrate.value.documentation).toContain(
+      '## `app::Value` (struct, src/lib.rs:4)\n\n```rust\npub struct Value;\n```',
+    )
+    expect(staleDependency.value.errorMessage).toContain(
+      'The sources of serde_json 1.0.117 are not in /cargo/registry/src',
+    )
+  })
+
+  it('should report crates whose sources were not downloaded', async () => {
+    const [result] = await readDocs({
+      ...params,
+      libraryTitle: 'serde_json',
+      fs: createFs({
+        ...projectFiles,
+        [`${registry}/serde_json-1.0.110/Cargo.toml`]: '',
+      }),
+    })
+
+    expect(result.value.errorMessage).toContain(
+      'The sources of serde_json 1.0.117 are not in /cargo/registry/src',
+    )
+  })
+
+  it('should leave other libraries to the docs search', async () => {
+    const [unlocked] = await readDocs({
+      ...params,
+      libraryTitle: 'React',
+      fs: createFs(projectFiles),
+    })
+    const [noLockfile] = await readDocs({
+      ...params,
+      libraryTitle: 'serde_json',
+      fs: createFs({ '/project/package.json': '{}' }),
+    })
+
+    expect(unlocked.value.errorMessage).toBe(
+      '"React" is not a registry package in Cargo.lock',
+    )
+    expect(noLockfile.value.errorMessage).toBe(
+      'No Cargo.lock in the project root',
+    )
+  })
+})
@@ -0,0 +1,287 @@
+import { describe, expect, it } from 'bun:test'
+import fs from 'fs'
+import os from 'os'
+import path from 'path'
+
+import {
+  addSandboxViolations,
+  createSandboxedSpawn,
+  filterSandboxEnv,
+  getBubblewrapArgs,
+  resolveSandbox,
+} from '../shell/sandbox'
+
+import type { LevelCodeSpawn } from '@levelcode/common/types/spawn'
+
+describe('sandbox', () => {
+  const projectPath = os.tmpdir()
+  const sandbox = resolveSandbox({
+    config: { writableDirs: ['~/.cargo', 'target'], network: false },
+    projectPath,
+    env: { CARGO_TARGET_DIR: '/nonexistent/target' },
+  })
+
+  it('should resolve writable directories', () => {
+    expect(sandbox).toEqual({
+      writableDirs: [
+        projectPath,
+        '/nonexistent/target',
+        path.join(os.homedir(), '.cargo'),
+        path.join(projectPath, 'target'),
+      ],
+      readOnlyPaths: [path.join(projectPath, '.git')],
+      network: false,
+      allowEnv: [],
+      denyEnv: [],
+      required: true,
+    })
+  })
+
+  it('should bind only existing writable directories', () => {
+    const args = getBubblewrapArgs({
+      sandbox,
+      command: 'bash',
+      args: ['-c', 'cargo build'],
+      cwd: projectPath,
+    })
+
+    expect(args.slice(0, 9)).toEqual([
+      '--ro-bind',
+      '/',
+      '/',
+      '--dev',
+      '/dev',
+      '--proc',
+      '/proc',
+      '--tmpfs',
+      '/tmp',
+    ])
+    expect(args).toContain('--unshare-net')
+    expect(args.join(' ')).toContain(`--bind ${projectPath} ${projectPath}`)
+    expect(args).not.toContain('/nonexistent/target')
+    expect(args.slice(-6)).toEqual([
+      '--chdir',
+      projectPath,
+      '--',
+      'bash',
+      '-c',
+      'cargo build',
+    ])
+  })
+
+  it('should remove secrets from the environment', () => {
+    const env = filterSandboxEnv(
+      {
+        PATH: '/usr/bin',
+        GITHUB_TOKEN: 'ghp_x',
+        AWS_SECRET_ACCESS_KEY: 'x',
+        OPENAI_API_KEY: 'x',
+        CARGO_REGISTRY_TOKEN: 'x',
+        TOKENIZERS_PARALLELISM: 'false',
+        DEPLOY_HOST: 'x',
+      },
+      { allowEnv: ['CARGO_REGISTRY_TOKEN'], denyEnv: ['DEPLOY_HOST'] },
+    )
+
+    expect(env).toEqual({
+      PATH: '/usr/bin',
+      CARGO_REGISTRY_TOKEN: 'x',
+      TOKENIZERS_PARALLELISM: 'false',
+    })
+  })
+
+  it('should default to the download caches of cargo', () => {
+    const { writableDirs } = resolveSandbox({ config: {}, projectPath })
+
+    expect(writableDirs).toContain(
+      path.join(os.homedir(), '.cargo', 'registry'),
+    )
+    expect(writableDirs).not.toContain(path.join(os.homedir(), '.cargo'))
+  })
+
+  it('should keep the git directory of the project read-only', () => {
+    const project = fs.mkdtempSync(path.join(os.tmpdir(), 'project-'))
+    fs.mkdirSync(path.join(project, '.git'))
+    try {
+      const args = getBubblewrapArgs({
+        sandbox: resolveSandbox({ config: {}, projectPath: project }),
+        command: 'git',
+        args: ['status'],
+      }).join(' ')
+
+      const gitDir = path.join(project, '.git')
+      expect(args).toContain(`--ro-bind ${gitDir} ${gitDir}`)
+      expect(args.indexOf(`--ro-bind ${gitDir}`)).toBeGreaterThan(
+        args.indexOf(`--bind ${project} ${project}`),
+      )
+    } finally {
+      fs.rmSync(project, { recursive: true, force: true })
+    }
+  })
+
+  it('should start a new session unless told otherwise', () => {
+    const params = { sandbox, command: 'sleep', args: ['60'] }
+
+    expect(getBubblewrapArgs(params)).toContain('--new-session')
+    expect(getBubblewrapArgs({ ...params, newSession: false })).not.toContain(
+      '--new-session',
+    )
+  })
+
+  it('should keep detached commands in the process group of bubblewrap', () => {
+    if (os.platform() !== 'linux') {
+      console.log('⚠️  Skipping test - sandboxing is only supported on Linux')
+      return
+    }
+    const binDir = fs.mkdtempSync(path.join(os.tmpdir(), 'bwrap-'))
+    fs.writeFileSync(path.join(binDir, 'bwrap'), '')
+    const calls: { command: string; args: readonly string[] }[] = []
+    const spawn = createSandboxedSpawn({
+      spawn: ((command: string, args: readonly string[]) => {
+        calls.push({ command, args })
+        return {}
+      }) as LevelCodeSpawn,
+      sandbox,
+      env: { PATH: binDir },
+    })
+
+    try {
+      spawn('sleep', ['60'], { detached: true })
+      spawn('sleep', ['60'])
+    } finally {
+      fs.rmSync(binDir, { recursive: true, force: true })
+    }
+
+    expect(calls.map(({ command }) => command)).toEqual([
+      path.join(binDir, 'bwrap'),
+      path.join(binDir, 'bwrap'),
+    ])
+    expect(calls[0].args).not.toContain('--new-session')
+    expect(calls[1].args).toContain('--new-session')
+  })
+
+  it('should report writes and lookups the sandbox blocked', () => {
+    const [output] = addSandboxViolations(
+      [
+        {
+          type: 'json',
+          value: {
+            command: 'cargo build',
+            stderr: [
+              '   Compiling foo v0.1.0',
+              "error: failed to write '/etc/foo.conf': Read-only file system (os error 30)",
+              'warning: spurious network error: Could not resolve host: index.crates.io',
+            ].join('\n'),
+            exitCode: 101,
+          },
+        },
+      ],
+      sandbox,
+    )
+
+    expect(output).toMatchObject({
+      type: 'json',
+      value: {
+        exitCode: 101,
+        sandboxViolations: [
+          { kind: 'write', path: '/etc/foo.conf' },
+          {
+            kind: 'network',
+            line: 'warning: spurious network error: Could not resolve host: index.crates.io',
+          },
+        ],
+      },
+    })
+  })
+
+  it('should report violations of each file change hook', () => {
+    const [output] = addSandboxViolations(
+      [
+        {
+          type: 'json',
+          value: [
+            { command: 'cargo fmt', exitCode: 0 },
+            {
+              command: 'touch /etc/hook',
+              stderr: "touch: cannot touch '/etc/hook': Read-only file system",
+              exitCode: 1,
+            },
+            { errorMessage: 'Hook timed out' },
+          ],
+        },
+      ],
+      sandbox,
+    )
+
+    expect(output).toMatchObject({
+      type: 'json',
+      value: [
+        { command: 'cargo fmt' },
+        {
+          command: 'touch /etc/hook',
+          sandboxViolations: [{ kind: 'write', path: '/etc/hook' }],
+        },
+        { errorMessage: 'Hook timed out' },
+      ],
+    })
+    const [fmt, , timedOut] = output.value as object[]
+    expect(fmt).not.toHaveProperty('sandboxViolations')
+    expect(timedOut).not.toHaveProperty('sandboxViolations')
+  })
+
+  it('should only report errors of failed commands', () => {
+    const output = [
+      {
+        type: 'json' as const,
+        value: {
+          command: 'grep -r "Read-only file system" logs',
+          stdout: "logs/a.log: failed to write '/etc/x': Read-only file system",
+          exitCode: 0,
+        },
+      },
+      {
+        type: 'json' as const,
+        value: {
+          command: 'cargo test',
+          stdout: "test fixture: cannot write '/etc/x': Read-only file system",
+          stderr: 'error: test failed',
+          exitCode: 101,
+        },
+      },
+      {
+        type: 'json' as const,
+        value: {
+          command: 'cat build.log',
+          stderr: "error: failed to write '/etc/x': Read-only file system",
+          exitCode: 0,
+        },
+      },
+    ]
+
+    expect(addSandboxViolations(output, sandbox)).toEqual(output)
+  })
+
+  it('should leave output without violations unchanged', () => {
+    const output = [
+      {
+        type: 'json' as const,
+        value: { command: 'curl x.com', stdout: 'Network is unreachable' },
+      },
+    ]
+    const networkSandbox = { ...sandbox, network: true }
+
+    expect(addSandboxViolations(output, networkSandbox)).toEqual(output)
+  })
+
+  it('should fail commands when the sandbox is required but missing', () => {
+    const spawn = createSandboxedSpawn({
+      spawn: (() => {
+        throw new Error('should not spawn')
+      }) as LevelCodeSpawn,
+      sandbox,
+      env: { PATH: '/nonexistent' },
+    })
+
+    expect(() => spawn('cargo', ['build'])).toThrow(/bubblewrap|Linux/)
+  })
+})
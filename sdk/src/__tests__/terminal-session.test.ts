@@ -0,0 +1,92 @@
+import { afterEach, describe, expect, it } from 'bun:test'
+import { spawn } from 'child_process'
+import os from 'os'
+
+import { ShellSessionManager } from '../shell/manager'
+import {
+  readTerminalSession,
+  runTerminalSessionCommand,
+} from '../tools/terminal-session'
+
+import type { LevelCodeSpawn } from '@levelcode/common/types/spawn'
+
+describe('terminal sessions', () => {
+  const shellSessions = new ShellSessionManager()
+  const params = {
+    session: 'dev',
+    cwd: os.tmpdir(),
+    spawn: spawn as unknown as LevelCodeSpawn,
+    shellSessions,
+  }
+
+  afterEach(() => {
+    shellSessions.shutdown()
+  })
+
+  it('should keep the directory and environment between commands', async () => {
+    await runTerminalSessionCommand({ ...params, command: 'cd /' })
+    await runTerminalSessionCommand({
+      ...params,
+      command: 'export GREETING=hi',
+    })
+    const [result] = await runTerminalSessionCommand({
+      ...params,
+      command: 'echo "$GREETING from $PWD"; echo oops >&2; false',
+    })
+
+    expect(result.value).toEqual({
+      command: 'echo "$GREETING from $PWD"; echo oops >&2; false',
+      session: 'dev',
+      stdout: 'hi from /\n',
+      stderr: 'oops\n',
+      exitCode: 1,
+    })
+  })
+
+  it('should leave slow commands running and send them input', async () => {
+    const [started] = await runTerminalSessionCommand({
+      ...params,
+      command: 'echo "name?"; read name; echo "hello $name"',
+      timeout_seconds: 0.2,
+    })
+    const [finished] = await readTerminalSession({
+      session: 'dev',
+      input: 'ferris\n',
+      shellSessions,
+    })
+
+    expect(started.value).toMatchObject({
+      stdout: 'name?\n',
+      message: expect.stringContaining('is still running'),
+    })
+    expect(finished.value).toEqual({
+      session: 'dev',
+      status: 'idle',
+      stdout: 'hello ferris\n',
+      exitCode: 0,
+    })
+  })
+
+  it('should report unknown and closed sessions', async () => {
+    await runTerminalSessionCommand({
+      ...params,
+      command: 'sleep 30',
+      timeout_seconds: 0,
+    })
+    const [closed] = await readTerminalSession({
+      session: 'dev',
+      close: true,
+      shellSessions,
+    })
+    const [unknown] = await readTerminalSession({
+      session: 'dev',
+      shellSessions,
+    })
+
+    expect(closed.value).toMatchObject({ session: 'dev', status: 'exited' })
+    expect(unknown.value).toEqual({
+      errorMessage:
+        'No terminal session "dev". Start one by passing session to run_terminal_command.',
+    })
+  })
+})
@@ -0,0 +1,94 @@
+import path from 'path'
+
+import { getErrorObject } from '@levelcode/common/util/error'
+import { z } from 'zod/v4'
+
+import type { Logger } from '@levelcode/common/types/contracts/logger'
+import type { LevelCodeFileSystem } from '@levelcode/common/types/filesystem'
+
+const HOOKS_CONFIG_FILE_NAME = 'hooks.json'
+
+export const fileChangeHookSchema = z.object({
+  name: z.string(),
+  /** Glob matched against project-relative paths; `*.rs` matches any depth */
+  filePattern: z.string(),
+  /**
+   * Shell command to run. `{file}` runs it once per changed file, `{crate}`
+   * once per Cargo package containing a changed file and `{files}` once with
+   * every matching file.
+   */
+  command: z.string(),
+  /** Whether a failure of the hook fails the edit that triggered it */
+  required: z.boolean().optional(),
+  timeoutSeconds: z.number().positive().optional(),
+  /** Directory to run the command in, relative to the project root */
+  cwd: z.string().optional(),
+})
+
+export type FileChangeHook = z.infer<typeof fileChangeHookSchema>
+
+/** Schema for the hooks.json file format */
+export const hooksFileSchema = z.object({
+  fileChangeHooks: z.array(fileChangeHookSchema).default(() => []),
+})
+
+export type HooksFileConfig = z.infer<typeof hooksFileSchema>
+
+/**
+ * Load file change hooks from `{cwd}/.agents/hooks.json`. They run after
+ * every file edit made in the SDK and through the run_file_change_hooks tool.
+ *
+ * @example
+ * ```json
+ * {
+ *   "fileChangeHooks": [
+ *     {
+ *       "name": "rustfmt",
+ *       "filePattern": "*.rs",
+ *       "command": "cargo fmt -- {file}",
+ *       "required": true
+ *     },
+ *     {
+ *       "name": "cargo-check",
+ *       "filePattern": "*.rs",
+ *       "command": "cargo check -p {crate}",
+ *       "required": true,
+ *       "timeoutSeconds": 600
+ *     }
+ *   ]
+ * }
+ * ```
+ */
+export async function loadFileChangeHooks(params: {
+  cwd: string
+  fs: LevelCodeFileSystem
+  logger?: Logger
+}): Promise<FileChangeHook[]> {
+  const { cwd, fs, logger } = params
+  const configPath = path.join(cwd, '.agents', HOOKS_CONFIG_FILE_NAME)
+
+  let content: string
+  try {
+    content = await fs.readFile(configPath, 'utf8')
+  } catch {
+    return []
+  }
+
+  try {
+    const parseResult = hooksFileSchema.safeParse(JSON.parse(content))
+    if (!parseResult.success) {
+      logger?.warn(
+        { configPath, error: parseResult.error.message },
+        'Invalid hooks.json',
+      )
+      return []
+    }
+    return parseResult.data.fileChangeHooks
+  } catch (error) {
+    logger?.warn(
+      { configPath, error: getErrorObject(error) },
+      'Failed to parse hooks.json',
+    )
+    return []
+  }
+}
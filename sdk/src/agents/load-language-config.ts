@@ -0,0 +1,160 @@
+import path from 'path'
+
+import {
+  getRegisteredLanguageNames,
+  registerLanguage,
+  unregisterLanguage,
+} from '@levelcode/code-map/languages'
+import { getErrorObject } from '@levelcode/common/util/error'
+import { z } from 'zod/v4'
+
+import type { LanguageRegistration } from '@levelcode/code-map/languages'
+import type { Logger } from '@levelcode/common/types/contracts/logger'
+import type { LevelCodeFileSystem } from '@levelcode/common/types/filesystem'
+
+const LANGUAGE_CONFIG_FILE_NAME = 'languages.json'
+
+function isValidRegExp(pattern: string): boolean {
+  try {
+    new RegExp(pattern)
+    return true
+  } catch {
+    return false
+  }
+}
+
+/**
+ * Schema for the languages.json file format. Paths are relative to the
+ * project root.
+ */
+export const languagesFileSchema = z.object({
+  languages: z
+    .array(
+      z
+        .object({
+          name: z.string(),
+          extensions: z.array(z.string()).optional(),
+          /** Regular expression matched against file names */
+          fileNamePattern: z
+            .string()
+            .refine(isValidRegExp, { message: 'Invalid regular expression' })
+            .optional(),
+          /** Path to the grammar's WASM file */
+          wasm: z.string(),
+          /** Path to a `.scm` tags query, or the query itself */
+          query: z.string(),
+        })
+        .refine(
+          (language) =>
+            (language.extensions?.length ?? 0) > 0 ||
+            language.fileNamePattern !== undefined,
+          { message: 'Each language needs extensions or a fileNamePattern' },
+        ),
+    )
+    .default(() => []),
+})
+
+export type LanguagesFileConfig = z.infer<typeof languagesFileSchema>
+
+/**
+ * Load additional tree-sitter languages from `{cwd}/.agents/languages.json`.
+ * They only apply to files of that project.
+ *
+ * @example
+ * ```json
+ * {
+ *   "languages": [
+ *     {
+ *       "name": "protobuf",
+ *       "extensions": [".proto"],
+ *       "wasm": "tools/grammars/tree-sitter-proto.wasm",
+ *       "query": "tools/grammars/proto-tags.scm"
+ *     }
+ *   ]
+ * }
+ * ```
+ */
+export async function loadLanguageConfig(params: {
+  cwd: string
+  fs: LevelCodeFileSystem
+  logger?: Logger
+}): Promise<LanguageRegistration[]> {
+  const { cwd, fs, logger } = params
+  const configPath = path.join(cwd, '.agents', LANGUAGE_CONFIG_FILE_NAME)
+
+  let content: string
+  try {
+    content = await fs.readFile(configPath, 'utf8')
+  } catch {
+    return []
+  }
+
+  let config: LanguagesFileConfig
+  try {
+    const parseResult = languagesFileSchema.safeParse(JSON.parse(content))
+    if (!parseResult.success) {
+      logger?.warn(
+        { configPath, error: parseResult.error.message },
+        'Invalid languages.json',
+      )
+      return []
+    }
+    config = parseResult.data
+  } catch (error) {
+    logger?.warn(
+      { configPath, error: getErrorObject(error) },
+      'Failed to parse languages.json',
+    )
+    return []
+  }
+
+  return config.languages.map(
+    ({ name, extensions, fileNamePattern, wasm, query }) => {
+      const pattern = fileNamePattern && new RegExp(fileNamePattern)
+      const isQueryFile = !query.includes('\n') && query.endsWith('.scm')
+      return {
+        name,
+        projectRoot: cwd,
+        extensions,
+        matchFileName: pattern
+          ? (fileName: string) => pattern.test(fileName)
+          : undefined,
+        wasmPath: path.resolve(cwd, wasm),
+        query: isQueryFile ? path.resolve(cwd, query) : query,
+      }
+    },
+  )
+}
+
+/**
+ * Registers the languages from the project's languages.json, then the given
+ * ones, so languages passed in code take precedence. With a `cwd`, all of
+ * them only apply to that project, and languages the project registered
+ * earlier but no longer has are dropped.
+ */
+export async function registerLanguages(params: {
+  cwd?: string
+  languages?: LanguageRegistration[]
+  fs: LevelCodeFileSystem
+  logger?: Logger
+}): Promise<void> {
+  const { cwd, languages = [], fs, logger } = params
+  const projectLanguages = cwd
+    ? await loadLanguageConfig({ cwd, fs, logger })
+    : []
+  const registrations = [
+    ...projectLanguages,
+    ...languages.map((language) => ({ projectRoot: cwd, ...language })),
+  ]
+  for (const language of registrations) {
+    registerLanguage(language)
+  }
+  if (cwd) {
+    const names = new Set(registrations.map(({ name }) => name))
+    for (const name of getRegisteredLanguageNames(cwd)) {
+      if (!names.has(name)) {
+        unregisterLanguage(name, cwd)
+      }
+    }
+  }
+}
@@ -2,7 +2,9 @@ import { API_KEY_ENV_VAR } from '@levelcode/common/constants/paths'
 
 import { WEBSITE_URL } from './constants'
 import { getLevelCodeApiKeyFromEnv, isStandaloneMode } from './env'
+import { LanguageServerManager } from './lsp/manager'
 import { run } from './run'
+import { BackgroundProcessManager } from './shell/background-process-manager'
 import {
   sdkCreateTeam,
   sdkDeleteTeam,
@@ -12,6 +14,7 @@ import {
 
 import type { RunOptions, LevelCodeClientOptions } from './run'
 import type { RunState } from './run-state'
+import type { BackgroundProcessInfo } from './shell/background-process'
 import type { CreateTeamOptions, TeamStatus, RunWithTeamOptions } from './team'
 import type { TeamConfig } from '@levelcode/common/types/team-config'
 import type { TeamSummary } from '@levelcode/common/utils/team-discovery'
@@ -21,6 +24,8 @@ export class LevelCodeClient {
     apiKey: string
     fingerprintId: string
   }
+  private readonly languageServerManager: LanguageServerManager
+  private readonly backgroundProcessManager: BackgroundProcessManager
 
   constructor(options: LevelCodeClientOptions) {
     const foundApiKey = options.apiKey ?? getLevelCodeApiKeyFromEnv() ?? (isStandaloneMode() ? 'standalone-mode' : undefined)
@@ -42,6 +47,13 @@ export class LevelCodeClient {
       fingerprintId: `levelcode-sdk-${Math.random().toString(36).substring(2, 15)}`,
       ...options,
     }
+    this.languageServerManager = new LanguageServerManager({
+      servers: options.languageServers,
+      logger: options.logger,
+    })
+    this.backgroundProcessManager = new BackgroundProcessManager({
+      logger: options.logger,
+    })
   }
 
   /**
@@ -57,6 +69,10 @@ export class LevelCodeClient {
    * @param agentDefinitions - (Optional) Array of custom agent definitions. Each object should satisfy the AgentDefinition type. You can input the agent's id field into the agent parameter to run that agent.
    * @param customToolDefinitions - (Optional) Array of custom tool definitions that extend the agent's capabilities. Each tool definition includes a name, Zod schema for input validation, and a handler function. These tools can be called by the agent during execution.
    * @param maxAgentSteps - (Optional) Maximum number of steps the agent can take before stopping. Use this as a safety measure in case your agent starts going off the rails. A reasonable number is around 20.
+   * @param languages - (Optional) Additional tree-sitter grammars used to index project files, each with a WASM path, a tags query, and the extensions or file name matcher of the files it covers. Languages can also be listed in `.agents/languages.json` in the project. Both only apply to files of the run's project (`cwd`).
+   * @param fileChangeHooks - (Optional) Commands run after every file edit, each with a `name`, a `filePattern` glob, a `command` (with `{file}`, `{files}` or `{crate}` placeholders), whether it is `required`, and an optional `timeoutSeconds`. When a required hook fails, the edit's result is an error with the hook output. Hooks can also be listed in `.agents/hooks.json` in the project.
+   * @param languageServers - (Optional) Language servers for the lsp_* tools (hover, definition, references, rename, workspace symbols and diagnostics), in addition to rust-analyzer, typescript-language-server and pyright. Each has an `id`, a `command` with `args`, and `languageIds` mapping file extensions to LSP language identifiers; a server with the id of a default replaces it. Servers start on first use and keep running across runs until shutdownLanguageServers() is called. Set in the constructor.
+   * @param sandbox - (Optional) Runs the agent's commands (run_terminal_command, cargo_diagnostics, file change hooks and language servers, including cargo build scripts) in a bubblewrap sandbox on Linux. They can only write to the project, CARGO_TARGET_DIR and `writableDirs` (default `~/.cargo/registry`, `~/.cargo/git` and `~/.cache`), but never to the project's `.git`; `network: false` disables their network access; and variables that look like secrets (e.g. `GITHUB_TOKEN`) are removed from their environment unless listed in `allowEnv`. Errors caused by the sandbox are reported in tool results as `sandboxViolations`. Without bubblewrap, commands fail unless `required` is false. Defaults to the LEVELCODE_SANDBOX environment variable: `1` enables the sandbox and `offline` also disables the network.
    * @param env - (Optional) Environment variables to pass to terminal commands executed by the agent. These will be merged with the current process environment, with the custom values taking precedence. Can also be provided in individual run() calls to override.
    *
    * @returns A Promise that resolves to a RunState JSON object which you can pass to a subsequent run() call to continue the run. Use result.output to get the agent's output.
@@ -64,7 +80,51 @@ export class LevelCodeClient {
   public async run(
     options: RunOptions & LevelCodeClientOptions,
   ): Promise<RunState> {
-    return run({ ...this.options, ...options })
+    return run({
+      ...this.options,
+      ...options,
+      languageServerManager: this.languageServerManager,
+      backgroundProcessManager: this.backgroundProcessManager,
+    })
+  }
+
+  /**
+   * Stop the language servers started by the lsp_* tools. Call this when
+   * done with the client; servers started again by later runs are stopped
+   * by the next call.
+   */
+  public async shutdownLanguageServers(): Promise<void> {
+    await this.languageServerManager.shutdown()
+  }
+
+  /**
+   * List the processes started by run_terminal_command with process_type
+   * BACKGROUND, including the 20 that exited most recently, until they are
+   * shut down.
+   */
+  public listBackgroundProcesses(): BackgroundProcessInfo[] {
+    return this.backgroundProcessManager.list()
+  }
+
+  /**
+   * Subscribe to background processes starting and exiting, e.g. to show
+   * them in a status bar.
+   *
+   * @returns A function that removes the listener.
+   */
+  public onBackgroundProcessesChange(
+    listener: (processes: BackgroundProcessInfo[]) => void,
+  ): () => void {
+    return this.backgroundProcessManager.subscribe(listener)
+  }
+
+  /**
+   * Stop the background processes started by the agent, with SIGTERM and
+   * then SIGKILL. Processes still running when this Node process exits or
+   * gets SIGINT or SIGTERM are killed even if this is never called.
+   */
+  public async shutdownBackgroundProcesses(): Promise<void> {
+    await this.backgroundProcessManager.shutdown()
   }
 
   /**
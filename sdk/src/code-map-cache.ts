@@ -0,0 +1,70 @@
+import { createHash } from 'crypto'
+import path from 'path'
+
+import { ParseCache } from '@levelcode/code-map/parse-cache'
+import { getErrorObject } from '@levelcode/common/util/error'
+
+import { getConfigDir } from './credentials'
+
+import type { Logger } from '@levelcode/common/types/contracts/logger'
+import type { LevelCodeFileSystem } from '@levelcode/common/types/filesystem'
+
+/** Caches loaded in this process, which also hold syntax trees */
+const parseCaches = new Map<string, Promise<ParseCache>>()
+
+/** File the parse cache of a project is stored in */
+export function getParseCachePath(projectRoot: string): string {
+  const key = createHash('sha1').update(projectRoot).digest('hex').slice(0, 16)
+  return path.join(getConfigDir(), 'code-map', `${key}.json`)
+}
+
+/**
+ * Returns the parse cache of a project, loading it from disk the first time.
+ * A missing or unreadable cache file yields an empty cache.
+ */
+export function loadParseCache(params: {
+  projectRoot: string
+  fs: LevelCodeFileSystem
+  logger?: Logger
+}): Promise<ParseCache> {
+  const { projectRoot, fs, logger } = params
+  let cache = parseCaches.get(projectRoot)
+  if (!cache) {
+    cache = fs
+      .readFile(getParseCachePath(projectRoot), 'utf8')
+      .then((content) => ParseCache.fromJSON(JSON.parse(content)))
+      .catch((error) => {
+        logger?.debug?.(
+          { projectRoot, error: getErrorObject(error) },
+          'No usable code map cache, parsing all files',
+        )
+        return new ParseCache()
+      })
+    parseCaches.set(projectRoot, cache)
+  }
+  return cache
+}
+
+/** Writes the parse cache of a project to disk if it changed */
+export async function saveParseCache(params: {
+  projectRoot: string
+  cache: ParseCache
+  fs: LevelCodeFileSystem
+  logger?: Logger
+}): Promise<void> {
+  const { projectRoot, cache, fs, logger } = params
+  if (!cache.isChanged) {
+    return
+  }
+  const cachePath = getParseCachePath(projectRoot)
+  try {
+    await fs.mkdir(path.dirname(cachePath), { recursive: true })
+    await fs.writeFile(cachePath, JSON.stringify(cache))
+    cache.markSaved()
+  } catch (error) {
+    logger?.warn(
+      { cachePath, error: getErrorObject(error) },
+      'Failed to save code map cache',
+    )
+  }
+}
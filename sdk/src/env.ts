@@ -10,6 +10,7 @@ import { CLAUDE_OAUTH_TOKEN_ENV_VAR } from '@levelcode/common/constants/claude-o
 import { API_KEY_ENV_VAR } from '@levelcode/common/constants/paths'
 import { getBaseEnv } from '@levelcode/common/env-process'
 
+import type { SandboxConfig } from './shell/sandbox'
 import type { SdkEnv } from './types/env'
 
 /**
@@ -38,6 +39,18 @@ export const getSystemProcessEnv = (): NodeJS.ProcessEnv => {
   return process.env
 }
 
+/**
+ * Sandbox for agent commands from LEVELCODE_SANDBOX: `1` runs them in the
+ * sandbox, `offline` also disables their network access.
+ */
+export const getSandboxConfigFromEnv = (): SandboxConfig | undefined => {
+  const value = process.env.LEVELCODE_SANDBOX
+  if (!value || value === '0' || value === 'false') {
+    return undefined
+  }
+  return value === 'offline' ? { network: false } : {}
+}
+
 export const getByokOpenrouterApiKeyFromEnv = (): string | undefined => {
   return process.env[BYOK_OPENROUTER_ENV_VAR]
 }
@@ -38,6 +38,7 @@ export { isStandaloneMode, getOpenRouterApiKeyFromEnv, getAnthropicApiKeyFromEnv
 export * from './credentials'
 export { loadLocalAgents } from './agents/load-agents'
 export { loadMCPConfig, loadMCPConfigSync } from './agents/load-mcp-config'
+export { loadFileChangeHooks } from './agents/load-file-change-hooks'
 export { loadSkills } from './skills/load-skills'
 export { formatAvailableSkillsXml } from '@levelcode/common/util/skills'
 export type { LoadSkillsOptions } from './skills/load-skills'
@@ -52,6 +53,17 @@ export type {
   MCPFileConfig,
   LoadedMCPConfig,
 } from './agents/load-mcp-config'
+export type { FileChangeHook } from './agents/load-file-change-hooks'
+export { LanguageServerManager } from './lsp/manager'
+export { BackgroundProcessManager } from './shell/background-process-manager'
+export type {
+  BackgroundProcessInfo,
+  BackgroundProcessStatus,
+} from './shell/background-process'
+export { DEFAULT_SANDBOX_WRITABLE_DIRS } from './shell/sandbox'
+export type { SandboxConfig } from './shell/sandbox'
+export { DEFAULT_LANGUAGE_SERVERS } from './lsp/servers'
+export type { LanguageServerConfig } from './lsp/servers'
 
 export { validateAgents } from './validate-agents'
 export type { ValidationResult, ValidateAgentsOptions } from './validate-agents'
@@ -83,8 +95,42 @@ export {
 export type { LevelCodeFileSystem } from '@levelcode/common/types/filesystem'
 
 // Tree-sitter / code-map exports
-export { getFileTokenScores, setWasmDir } from '@levelcode/code-map'
-export type { FileTokenData, TokenCallerMap } from '@levelcode/code-map'
+export {
+  buildRustTestIndex,
+  buildSymbolIndex,
+  diffRustPublicApi,
+  findAffectedRustTests,
+  findRepoMapSeeds,
+  getFileTokenScores,
+  getRustPublicApi,
+  getRustTestCommands,
+  getRustTestIndex,
+  getSymbolIndex,
+  ParseCache,
+  rankSymbols,
+  registerLanguage,
+  renderRepoMap,
+  setWasmDir,
+  unregisterLanguage,
+} from '@levelcode/code-map'
+export type {
+  FileTokenData,
+  LanguageRegistration,
+  RankedSymbol,
+  RepoMapSeeds,
+  RustCrateApi,
+  RustPublicApiDiff,
+  RustPublicItem,
+  RustTest,
+  RustTestIndex,
+  SourceRange,
+  SymbolGraph,
+  SymbolIndex,
+  SymbolLocation,
+  SymbolMatch,
+  TokenCallerMap,
+  TokenLocation,
+} from '@levelcode/code-map'
 
 export { runTerminalCommand } from './tools/run-terminal-command'
 export {
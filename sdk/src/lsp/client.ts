@@ -0,0 +1,430 @@
+import path from 'path'
+
+import { getErrorObject } from '@levelcode/common/util/error'
+
+import { JsonRpcConnection, JsonRpcError } from './json-rpc'
+import { pathToUri, toLocations } from './protocol'
+
+import type {
+  Diagnostic,
+  Hover,
+  Location,
+  LocationLink,
+  Position,
+  SymbolInformation,
+  WorkspaceEdit,
+} from './protocol'
+import type { LanguageServerConfig } from './servers'
+import type { Logger } from '@levelcode/common/types/contracts/logger'
+import type { LevelCodeSpawn } from '@levelcode/common/types/spawn'
+import type { ChildProcess } from 'child_process'
+
+const INITIALIZE_TIMEOUT_MS = 60_000
+const REQUEST_TIMEOUT_MS = 60_000
+/** Indexing a large Cargo workspace takes a while after startup */
+const READY_TIMEOUT_MS = 120_000
+/** How long to wait for pushed diagnostics after a document changed */
+const PUBLISHED_DIAGNOSTICS_TIMEOUT_MS = 10_000
+const SHUTDOWN_TIMEOUT_MS = 5_000
+/** Sent by servers when a document changed while computing a result */
+const CONTENT_MODIFIED = -32801
+const MAX_CONTENT_MODIFIED_RETRIES = 3
+
+export type DefinitionKind = 'definition' | 'type_definition' | 'implementation'
+
+const DEFINITION_METHODS: Record<DefinitionKind, string> = {
+  definition: 'textDocument/definition',
+  type_definition: 'textDocument/typeDefinition',
+  implementation: 'textDocument/implementation',
+}
+
+type ServerCapabilities = {
+  diagnosticProvider?: unknown
+  renameProvider?: boolean | { prepareProvider?: boolean }
+  [key: string]: unknown
+}
+
+type ProgressParams = {
+  token: string | number
+  value?: { kind?: 'begin' | 'report' | 'end' }
+}
+
+/**
+ * A running language server for one workspace root. Documents are synced
+ * from disk before each request, so edits made by other tools are seen.
+ */
+export class LanguageServerClient {
+  private readonly openDocuments = new Map<
+    string,
+    { version: number; content: string }
+  >()
+  private readonly publishedDiagnostics = new Map<
+    string,
+    { version?: number; diagnostics: Diagnostic[] }
+  >()
+  private readonly activeProgress = new Set<string | number>()
+  /** Undefined until the server reports its status, if it does at all */
+  private quiescent: boolean | undefined
+  private readonly listeners = new Set<() => void>()
+  private exited = false
+  private capabilities: ServerCapabilities = {}
+
+  private constructor(
+    readonly config: LanguageServerConfig,
+    readonly rootPath: string,
+    private readonly child: ChildProcess,
+    private readonly connection: JsonRpcConnection,
+    private readonly logger?: Logger,
+  ) {}
+
+  static async start(params: {
+    config: LanguageServerConfig
+    rootPath: string
+    spawn: LevelCodeSpawn
+    env?: Record<string, string>
+    logger?: Logger
+  }): Promise<LanguageServerClient> {
+    const { config, rootPath, spawn, env, logger } = params
+    const child = spawn(config.command, config.args ?? [], {
+      cwd: rootPath,
+      env: { ...process.env, ...env, ...config.env },
+      stdio: 'pipe',
+    })
+    if (!child.stdout || !child.stdin) {
+      throw new Error(`Failed to start ${config.id}: no stdio`)
+    }
+    // Drain stderr so a chatty server does not block on a full pipe
+    child.stderr?.resume()
+
+    const connection = new JsonRpcConnection(child.stdout, child.stdin)
+    const client = new LanguageServerClient(
+      config,
+      rootPath,
+      child,
+      connection,
+      logger,
+    )
+    child.on('error', (error) => {
+      client.onExit(`Failed to start ${config.id}: ${error.message}`)
+    })
+    child.on('exit', (code, signal) => {
+      client.onExit(`${config.id} exited (${signal ?? `code ${code}`})`)
+    })
+    child.stdin.on('error', () => {
+      // Reported by the exit handler
+    })
+
+    try {
+      await client.initialize()
+    } catch (error) {
+      client.kill()
+      throw new Error(
+        `Failed to start the ${config.id} language server ("${config.command}"). Make sure it is installed and on the PATH: ${error instanceof Error ? error.message : String(error)}`,
+      )
+    }
+    return client
+  }
+
+  get isRunning(): boolean {
+    return !this.exited
+  }
+
+  private onExit(reason: string): void {
+    if (this.exited) {
+      return
+    }
+    this.exited = true
+    this.connection.close(reason)
+    this.notifyListeners()
+    this.logger?.debug({ server: this.config.id }, reason)
+  }
+
+  private notifyListeners(): void {
+    for (const listener of [...this.listeners]) {
+      listener()
+    }
+  }
+
+  /** Resolves once the condition holds, checking it after every change */
+  private waitFor(condition: () => boolean, timeoutMs: number): Promise<void> {
+    if (condition() || this.exited) {
+      return Promise.resolve()
+    }
+    return new Promise((resolve) => {
+      const done = () => {
+        clearTimeout(timer)
+        this.listeners.delete(listener)
+        resolve()
+      }
+      const listener = () => {
+        if (condition() || this.exited) {
+          done()
+        }
+      }
+      const timer = setTimeout(done, timeoutMs)
+      this.listeners.add(listener)
+    })
+  }
+
+  private async initialize(): Promise<void> {
+    const { connection } = this
+    connection.onRequest('workspace/configuration', (params) =>
+      ((params as { items?: unknown[] }).items ?? []).map(() => null),
+    )
+    connection.onRequest('window/workDoneProgress/create', () => null)
+    connection.onRequest('client/registerCapability', () => null)
+    connection.onRequest('client/unregisterCapability', () => null)
+    connection.onRequest('workspace/applyEdit', () => ({
+      applied: false,
+      failureReason: 'Edits are applied by the client',
+    }))
+    connection.onNotification('$/progress', (params) => {
+      const { token, value } = params as ProgressParams
+      if (value?.kind === 'begin') {
+        this.activeProgress.add(token)
+      } else if (value?.kind === 'end') {
+        this.activeProgress.delete(token)
+      }
+      this.notifyListeners()
+    })
+    connection.onNotification('experimental/serverStatus', (params) => {
+      this.quiescent = (params as { quiescent?: boolean }).quiescent ?? true
+      this.notifyListeners()
+    })
+    connection.onNotification('textDocument/publishDiagnostics', (params) => {
+      const { uri, version, diagnostics } = params as {
+        uri: string
+        version?: number
+        diagnostics: Diagnostic[]
+      }
+      this.publishedDiagnostics.set(uri, { version, diagnostics })
+      this.notifyListeners()
+    })
+
+    const rootUri = pathToUri(this.rootPath)
+    const result = await connection.request<{
+      capabilities: ServerCapabilities
+    }>(
+      'initialize',
+      {
+        processId: process.pid,
+        clientInfo: { name: 'levelcode' },
+        rootPath: this.rootPath,
+        rootUri,
+        workspaceFolders: [
+          { uri: rootUri, name: path.basename(this.rootPath) },
+        ],
+        initializationOptions: this.config.initializationOptions,
+        capabilities: {
+          general: { positionEncodings: ['utf-16'] },
+          window: { workDoneProgress: true },
+          workspace: {
+            configuration: true,
+            workspaceFolders: true,
+            symbol: {},
+            diagnostics: {},
+          },
+          textDocument: {
+            synchronization: { didSave: true },
+            hover: { contentFormat: ['markdown', 'plaintext'] },
+            definition: { linkSupport: true },
+            typeDefinition: { linkSupport: true },
+            implementation: { linkSupport: true },
+            references: {},
+            rename: { prepareSupport: true },
+            publishDiagnostics: { versionSupport: true },
+            diagnostic: {},
+          },
+          experimental: { serverStatusNotification: true },
+        },
+      },
+      INITIALIZE_TIMEOUT_MS,
+    )
+    this.capabilities = result?.capabilities ?? {}
+    connection.notify('initialized', {})
+  }
+
+  /**
+   * Waits until the server has finished indexing, as far as it reports it,
+   * so results do not miss items that are not indexed yet.
+   */
+  private waitUntilReady(): Promise<void> {
+    return this.waitFor(
+      () => this.quiescent !== false && this.activeProgress.size === 0,
+      READY_TIMEOUT_MS,
+    )
+  }
+
+  private async request<T>(method: string, params: unknown): Promise<T> {
+    await this.waitUntilReady()
+    for (let attempt = 0; ; attempt++) {
+      try {
+        return await this.connection.request<T>(
+          method,
+          params,
+          REQUEST_TIMEOUT_MS,
+        )
+      } catch (error) {
+        const retry =
+          error instanceof JsonRpcError &&
+          error.code === CONTENT_MODIFIED &&
+          attempt < MAX_CONTENT_MODIFIED_RETRIES
+        if (!retry) {
+          throw error
+        }
+      }
+    }
+  }
+
+  /** Opens the document, or sends its new content if it changed */
+  syncDocument(filePath: string, content: string): string {
+    const uri = pathToUri(filePath)
+    const open = this.openDocuments.get(uri)
+    if (!open) {
+      const extension = path.extname(filePath).toLowerCase()
+      this.connection.notify('textDocument/didOpen', {
+        textDocument: {
+          uri,
+          languageId: this.config.languageIds[extension] ?? 'plaintext',
+          version: 1,
+          text: content,
+        },
+      })
+      this.openDocuments.set(uri, { version: 1, content })
+    } else if (open.content !== content) {
+      const version = open.version + 1
+      this.connection.notify('textDocument/didChange', {
+        textDocument: { uri, version },
+        contentChanges: [{ text: content }],
+      })
+      // The content is on disk already, which lets servers that only check
+      // saved files, like rust-analyzer's cargo check, report on it
+      this.connection.notify('textDocument/didSave', { textDocument: { uri } })
+      this.openDocuments.set(uri, { version, content })
+    }
+    return uri
+  }
+
+  async hover(uri: string, position: Position): Promise<Hover | null> {
+    return this.request<Hover | null>('textDocument/hover', {
+      textDocument: { uri },
+      position,
+    })
+  }
+
+  async definition(
+    uri: string,
+    position: Position,
+    kind: DefinitionKind,
+  ): Promise<Location[]> {
+    const result = await this.request<
+      Location | Location[] | LocationLink[] | null
+    >(DEFINITION_METHODS[kind], { textDocument: { uri }, position })
+    return toLocations(result)
+  }
+
+  async references(
+    uri: string,
+    position: Position,
+    includeDeclaration: boolean,
+  ): Promise<Location[]> {
+    const result = await this.request<Location[] | null>(
+      'textDocument/references',
+      {
+        textDocument: { uri },
+        position,
+        context: { includeDeclaration },
+      },
+    )
+    return result ?? []
+  }
+
+  async rename(
+    uri: string,
+    position: Position,
+    newName: string,
+  ): Promise<WorkspaceEdit | null> {
+    const { renameProvider } = this.capabilities
+    if (typeof renameProvider === 'object' && renameProvider.prepareProvider) {
+      const prepared = await this.request<unknown>(
+        'textDocument/prepareRename',
+        { textDocument: { uri }, position },
+      )
+      if (!prepared) {
+        throw new Error('The symbol at this position cannot be renamed')
+      }
+    }
+    return this.request<WorkspaceEdit | null>('textDocument/rename', {
+      textDocument: { uri },
+      position,
+      newName,
+    })
+  }
+
+  async workspaceSymbols(query: string): Promise<SymbolInformation[]> {
+    const result = await this.request<SymbolInformation[] | null>(
+      'workspace/symbol',
+      { query },
+    )
+    return result ?? []
+  }
+
+  /**
+   * Returns the diagnostics of a synced document: those the server computes
+   * on request, if it supports pull diagnostics, and those it published.
+   */
+  async diagnostics(uri: string): Promise<Diagnostic[]> {
+    const version = this.openDocuments.get(uri)?.version
+    let pulled: Diagnostic[] = []
+    if (this.capabilities.diagnosticProvider) {
+      const report = await this.request<{
+        kind: 'full' | 'unchanged'
+        items?: Diagnostic[]
+      }>('textDocument/diagnostic', { textDocument: { uri } })
+      pulled = report?.items ?? []
+    } else {
+      await this.waitUntilReady()
+      await this.waitFor(() => {
+        const published = this.publishedDiagnostics.get(uri)
+        return (
+          published !== undefined &&
+          (published.version === undefined || published.version === version)
+        )
+      }, PUBLISHED_DIAGNOSTICS_TIMEOUT_MS)
+    }
+
+    const published = this.publishedDiagnostics.get(uri)?.diagnostics ?? []
+    const seen = new Set<string>()
+    return [...pulled, ...published].filter((diagnostic) => {
+      const key = JSON.stringify([diagnostic.range, diagnostic.message])
+      if (seen.has(key)) {
+        return false
+      }
+      seen.add(key)
+      return true
+    })
+  }
+
+  private kill(): void {
+    if (!this.exited) {
+      this.child.kill()
+    }
+  }
+
+  async shutdown(): Promise<void> {
+    if (this.exited) {
+      return
+    }
+    try {
+      await this.connection.request('shutdown', undefined, SHUTDOWN_TIMEOUT_MS)
+      this.connection.notify('exit')
+      await this.waitFor(() => this.exited, SHUTDOWN_TIMEOUT_MS)
+    } catch (error) {
+      this.logger?.debug(
+        { server: this.config.id, error: getErrorObject(error) },
+        'Language server did not shut down cleanly',
+      )
+    }
+    this.kill()
+  }
+}
@@ -0,0 +1,205 @@
+import type { Readable, Writable } from 'stream'
+
+type RequestId = number | string
+
+type JsonRpcMessage = {
+  jsonrpc: '2.0'
+  id?: RequestId | null
+  method?: string
+  params?: unknown
+  result?: unknown
+  error?: { code: number; message: string; data?: unknown }
+}
+
+type PendingRequest = {
+  resolve: (result: unknown) => void
+  reject: (error: Error) => void
+  timer?: ReturnType<typeof setTimeout>
+}
+
+/** Returned to the server for requests without a handler */
+const METHOD_NOT_FOUND = -32601
+const INTERNAL_ERROR = -32603
+
+const HEADER_SEPARATOR = '\r\n\r\n'
+
+export class JsonRpcError extends Error {
+  constructor(
+    message: string,
+    public readonly code: number,
+  ) {
+    super(message)
+    this.name = 'JsonRpcError'
+  }
+}
+
+/**
+ * A JSON-RPC 2.0 connection using the `Content-Length` framing of the
+ * Language Server Protocol, e.g. over the stdio of a language server.
+ */
+export class JsonRpcConnection {
+  private nextId = 1
+  private buffer = Buffer.alloc(0)
+  private closed = false
+  private readonly pending = new Map<RequestId, PendingRequest>()
+  private readonly notificationHandlers = new Map<
+    string,
+    (params: unknown) => void
+  >()
+  private readonly requestHandlers = new Map<
+    string,
+    (params: unknown) => unknown
+  >()
+
+  constructor(
+    private readonly input: Readable,
+    private readonly output: Writable,
+  ) {
+    input.on('data', (chunk: Buffer) => this.onData(chunk))
+    input.on('close', () => this.close('The connection was closed'))
+  }
+
+  onNotification(method: string, handler: (params: unknown) => void): void {
+    this.notificationHandlers.set(method, handler)
+  }
+
+  /** Handles requests sent by the other side, e.g. `workspace/configuration` */
+  onRequest(method: string, handler: (params: unknown) => unknown): void {
+    this.requestHandlers.set(method, handler)
+  }
+
+  request<T>(method: string, params?: unknown, timeoutMs?: number): Promise<T> {
+    if (this.closed) {
+      return Promise.reject(new Error(`Cannot send ${method}: closed`))
+    }
+    const id = this.nextId++
+    return new Promise<T>((resolve, reject) => {
+      const pending: PendingRequest = {
+        resolve: resolve as (result: unknown) => void,
+        reject,
+      }
+      if (timeoutMs !== undefined) {
+        pending.timer = setTimeout(() => {
+          this.pending.delete(id)
+          this.notify('$/cancelRequest', { id })
+          reject(new Error(`${method} timed out after ${timeoutMs / 1000}s`))
+        }, timeoutMs)
+      }
+      this.pending.set(id, pending)
+      this.send({ jsonrpc: '2.0', id, method, params })
+    })
+  }
+
+  notify(method: string, params?: unknown): void {
+    this.send({ jsonrpc: '2.0', method, params })
+  }
+
+  /** Rejects the pending requests; later requests fail immediately */
+  close(reason: string): void {
+    if (this.closed) {
+      return
+    }
+    this.closed = true
+    for (const { reject, timer } of this.pending.values()) {
+      clearTimeout(timer)
+      reject(new Error(reason))
+    }
+    this.pending.clear()
+  }
+
+  private send(message: JsonRpcMessage): void {
+    if (this.closed) {
+      return
+    }
+    const body = Buffer.from(JSON.stringify(message), 'utf8')
+    this.output.write(`Content-Length: ${body.length}${HEADER_SEPARATOR}`)
+    this.output.write(body)
+  }
+
+  private onData(chunk: Buffer): void {
+    this.buffer = Buffer.concat([this.buffer, chunk])
+    while (true) {
+      const headerEnd = this.buffer.indexOf(HEADER_SEPARATOR)
+      if (headerEnd === -1) {
+        return
+      }
+      const header = this.buffer.subarray(0, headerEnd).toString('ascii')
+      const match = /Content-Length: *(\d+)/i.exec(header)
+      const bodyStart = headerEnd + HEADER_SEPARATOR.length
+      if (!match) {
+        // Skip a malformed header rather than stalling the connection
+        this.buffer = this.buffer.subarray(bodyStart)
+        continue
+      }
+      const bodyEnd = bodyStart + Number(match[1])
+      if (this.buffer.length < bodyEnd) {
+        return
+      }
+      const body = this.buffer.subarray(bodyStart, bodyEnd).toString('utf8')
+      this.buffer = this.buffer.subarray(bodyEnd)
+
+      let message: JsonRpcMessage
+      try {
+        message = JSON.parse(body)
+      } catch {
+        continue
+      }
+      this.dispatch(message)
+    }
+  }
+
+  private dispatch(message: JsonRpcMessage): void {
+    if (message.method === undefined) {
+      const pending =
+        message.id === undefined || message.id === null
+          ? undefined
+          : this.pending.get(message.id)
+      if (!pending) {
+        return
+      }
+      this.pending.delete(message.id!)
+      clearTimeout(pending.timer)
+      if (message.error) {
+        pending.reject(
+          new JsonRpcError(message.error.message, message.error.code),
+        )
+      } else {
+        pending.resolve(message.result ?? null)
+      }
+      return
+    }
+
+    if (message.id === undefined || message.id === null) {
+      this.notificationHandlers.get(message.method)?.(message.params)
+      return
+    }
+
+    const id = message.id
+    const handler = this.requestHandlers.get(message.method)
+    if (!handler) {
+      this.send({
+        jsonrpc: '2.0',
+        id,
+        error: {
+          code: METHOD_NOT_FOUND,
+          message: `Unhandled method ${message.method}`,
+        },
+      })
+      return
+    }
+    Promise.resolve()
+      .then(() => handler(message.params))
+      .then(
+        (result) => this.send({ jsonrpc: '2.0', id, result: result ?? null }),
+        (error) =>
+          this.send({
+            jsonrpc: '2.0',
+            id,
+            error: {
+              code: INTERNAL_ERROR,
+              message: error instanceof Error ? error.message : String(error),
+            },
+          }),
+      )
+  }
+}
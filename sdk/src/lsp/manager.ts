@@ -0,0 +1,109 @@
+import path from 'path'
+
+import { getErrorObject } from '@levelcode/common/util/error'
+
+import { LanguageServerClient } from './client'
+import {
+  findLanguageServerConfig,
+  mergeLanguageServerConfigs,
+} from './servers'
+
+import type { LanguageServerConfig } from './servers'
+import type { Logger } from '@levelcode/common/types/contracts/logger'
+import type { LevelCodeFileSystem } from '@levelcode/common/types/filesystem'
+import type { LevelCodeSpawn } from '@levelcode/common/types/spawn'
+
+/**
+ * Starts language servers on first use, one per server and workspace root,
+ * and keeps them running until `shutdown`. A `LevelCodeClient` owns one
+ * manager, so servers are shared by all of its runs.
+ */
+export class LanguageServerManager {
+  readonly configs: LanguageServerConfig[]
+  private readonly clients = new Map<string, Promise<LanguageServerClient>>()
+  private readonly logger?: Logger
+
+  constructor(params: { servers?: LanguageServerConfig[]; logger?: Logger }) {
+    this.configs = mergeLanguageServerConfigs(params.servers)
+    this.logger = params.logger
+  }
+
+  findConfig(filePath: string): LanguageServerConfig | undefined {
+    return findLanguageServerConfig(this.configs, filePath)
+  }
+
+  /** Configs of the languages whose root markers are in the project root */
+  async findProjectConfigs(params: {
+    projectPath: string
+    fs: LevelCodeFileSystem
+  }): Promise<LanguageServerConfig[]> {
+    const { projectPath, fs } = params
+    const entries = new Set(await fs.readdir(projectPath))
+    return this.configs.filter(({ rootMarkers = [] }) =>
+      rootMarkers.some((marker) => entries.has(marker)),
+    )
+  }
+
+  /** Returns the running server, starting it or restarting it if it exited */
+  getClient(params: {
+    config: LanguageServerConfig
+    projectPath: string
+    spawn: LevelCodeSpawn
+    env?: Record<string, string>
+  }): Promise<LanguageServerClient> {
+    const key = `${params.config.id}\0${path.resolve(params.projectPath)}`
+
+    const existing = this.clients.get(key)
+    if (existing) {
+      return existing.then((client) =>
+        client.isRunning ? client : this.startClient(key, params),
+      )
+    }
+    return this.startClient(key, params)
+  }
+
+  private startClient(
+    key: string,
+    params: {
+      config: LanguageServerConfig
+      projectPath: string
+      spawn: LevelCodeSpawn
+      env?: Record<string, string>
+    },
+  ): Promise<LanguageServerClient> {
+    const { config, projectPath, spawn, env } = params
+    const client = LanguageServerClient.start({
+      config,
+      rootPath: path.resolve(projectPath),
+      spawn,
+      env,
+      logger: this.logger,
+    })
+    this.clients.set(key, client)
+    // Retry a server that failed to start on the next request, e.g. after
+    // it was installed
+    client.catch(() => {
+      if (this.clients.get(key) === client) {
+        this.clients.delete(key)
+      }
+    })
+    return client
+  }
+
+  async shutdown(): Promise<void> {
+    const clients = [...this.clients.values()]
+    this.clients.clear()
+    await Promise.all(
+      clients.map(async (clientPromise) => {
+        try {
+          await (await clientPromise).shutdown()
+        } catch (error) {
+          this.logger?.debug(
+            { error: getErrorObject(error) },
+            'Failed to shut down a language server',
+          )
+        }
+      }),
+    )
+  }
+}
@@ -0,0 +1,212 @@
+import path from 'path'
+import { fileURLToPath, pathToFileURL } from 'url'
+
+import type { SourceRange } from '@levelcode/code-map/source-range'
+
+// The subset of the Language Server Protocol types used by the SDK.
+// Lines and characters are 0-based; characters count UTF-16 code units, like
+// JavaScript string indices.
+
+export type Position = { line: number; character: number }
+export type Range = { start: Position; end: Position }
+export type Location = { uri: string; range: Range }
+export type LocationLink = {
+  targetUri: string
+  targetRange: Range
+  targetSelectionRange: Range
+}
+export type TextEdit = { range: Range; newText: string }
+
+export type MarkupContent = { kind: 'plaintext' | 'markdown'; value: string }
+export type MarkedString = string | { language: string; value: string }
+export type Hover = {
+  contents: MarkupContent | MarkedString | MarkedString[]
+  range?: Range
+}
+
+export type Diagnostic = {
+  range: Range
+  /** 1 error, 2 warning, 3 information, 4 hint */
+  severity?: number
+  code?: number | string
+  source?: string
+  message: string
+}
+
+export type SymbolInformation = {
+  name: string
+  kind: number
+  containerName?: string
+  location: Location | { uri: string }
+}
+
+export type WorkspaceEdit = {
+  changes?: Record<string, TextEdit[]>
+  documentChanges?: (
+    | { textDocument: { uri: string }; edits: TextEdit[] }
+    | { kind: 'create' | 'rename' | 'delete' }
+  )[]
+}
+
+export const DIAGNOSTIC_SEVERITIES = [
+  'error',
+  'warning',
+  'information',
+  'hint',
+] as const
+
+/** Names of the LSP `SymbolKind` values, which start at 1 */
+const SYMBOL_KINDS = [
+  'file',
+  'module',
+  'namespace',
+  'package',
+  'class',
+  'method',
+  'property',
+  'field',
+  'constructor',
+  'enum',
+  'interface',
+  'function',
+  'variable',
+  'constant',
+  'string',
+  'number',
+  'boolean',
+  'array',
+  'object',
+  'key',
+  'null',
+  'enum_member',
+  'struct',
+  'event',
+  'operator',
+  'type_parameter',
+]
+
+export function symbolKindName(kind: number): string {
+  return SYMBOL_KINDS[kind - 1] ?? 'unknown'
+}
+
+export function pathToUri(filePath: string): string {
+  return pathToFileURL(filePath).href
+}
+
+/** Returns the path of a `file:` URI, or undefined for other schemes */
+export function uriToPath(uri: string): string | undefined {
+  return uri.startsWith('file:') ? fileURLToPath(uri) : undefined
+}
+
+/** Converts a 1-based line and column, as shown by read_files */
+export function toPosition(line: number, column: number): Position {
+  return { line: line - 1, character: column - 1 }
+}
+
+export function toSourceRange(range: Range): SourceRange {
+  return {
+    startLine: range.start.line + 1,
+    startColumn: range.start.character + 1,
+    endLine: range.end.line + 1,
+    endColumn: range.end.character + 1,
+  }
+}
+
+/** Path relative to the project root, or absolute if outside of it */
+export function toProjectRelativePath(
+  projectPath: string,
+  filePath: string,
+): string {
+  const relative = path.relative(projectPath, filePath)
+  return relative.startsWith('..') || path.isAbsolute(relative)
+    ? filePath
+    : relative.replaceAll(path.sep, '/')
+}
+
+export function getHoverText(hover: Hover): string {
+  const toText = (content: MarkupContent | MarkedString): string =>
+    typeof content === 'string'
+      ? content
+      : 'language' in content
+        ? `\`\`\`${content.language}\n${content.value}\n\`\`\``
+        : content.value
+  const contents = Array.isArray(hover.contents)
+    ? hover.contents
+    : [hover.contents]
+  return contents.map(toText).join('\n\n').trim()
+}
+
+/** Normalizes the results of definition-like requests to locations */
+export function toLocations(
+  result: Location | Location[] | LocationLink[] | null,
+): Location[] {
+  if (!result) {
+    return []
+  }
+  return (Array.isArray(result) ? result : [result]).map((location) =>
+    'targetUri' in location
+      ? { uri: location.targetUri, range: location.targetSelectionRange }
+      : location,
+  )
+}
+
+/** Groups the text edits of a workspace edit by document URI */
+export function getWorkspaceEditChanges(
+  edit: WorkspaceEdit,
+): Map<string, TextEdit[]> {
+  const changes = new Map<string, TextEdit[]>()
+  const add = (uri: string, edits: TextEdit[]) =>
+    changes.set(uri, [...(changes.get(uri) ?? []), ...edits])
+
+  if (edit.documentChanges) {
+    for (const change of edit.documentChanges) {
+      if ('kind' in change) {
+        throw new Error(
+          `Workspace edits that ${change.kind} files are not supported`,
+        )
+      }
+      add(change.textDocument.uri, change.edits)
+    }
+  } else {
+    for (const [uri, edits] of Object.entries(edit.changes ?? {})) {
+      add(uri, edits)
+    }
+  }
+  return changes
+}
+
+function getOffset(lineStarts: number[], position: Position): number {
+  const end = lineStarts[lineStarts.length - 1]
+  const lineStart = lineStarts[position.line]
+  return lineStart === undefined
+    ? end
+    : Math.min(lineStart + position.character, end)
+}
+
+/** Applies non-overlapping text edits, as returned by a language server */
+export function applyTextEdits(content: string, edits: TextEdit[]): string {
+  const lineStarts = [0]
+  for (let i = 0; i < content.length; i++) {
+    if (content[i] === '\n') {
+      lineStarts.push(i + 1)
+    }
+  }
+  lineStarts.push(content.length)
+
+  // Apply from the end so earlier offsets stay valid. Insertions at the same
+  // position keep their order.
+  const sorted = edits
+    .map((edit, index) => ({
+      start: getOffset(lineStarts, edit.range.start),
+      end: getOffset(lineStarts, edit.range.end),
+      newText: edit.newText,
+      index,
+    }))
+    .sort((a, b) => b.start - a.start || b.index - a.index)
+
+  let result = content
+  for (const { start, end, newText } of sorted) {
+    result = result.slice(0, start) + newText + result.slice(end)
+  }
+  return result
+}
@@ -0,0 +1,76 @@
+import path from 'path'
+
+export type LanguageServerConfig = {
+  /** Unique name of the server; a config with a default's id replaces it */
+  id: string
+  command: string
+  args?: string[]
+  /**
+   * Extensions of the files the server handles, with the leading dot, and
+   * their LSP language identifiers, e.g. `{ ".tsx": "typescriptreact" }`
+   */
+  languageIds: Record<string, string>
+  /**
+   * Files marking a project of the server's language, used to pick servers
+   * for requests that are not about a file, e.g. workspace symbols
+   */
+  rootMarkers?: string[]
+  initializationOptions?: unknown
+  env?: Record<string, string>
+}
+
+export const DEFAULT_LANGUAGE_SERVERS: LanguageServerConfig[] = [
+  {
+    id: 'rust-analyzer',
+    command: 'rust-analyzer',
+    languageIds: { '.rs': 'rust' },
+    rootMarkers: ['Cargo.toml'],
+  },
+  {
+    id: 'typescript-language-server',
+    command: 'typescript-language-server',
+    args: ['--stdio'],
+    languageIds: {
+      '.ts': 'typescript',
+      '.tsx': 'typescriptreact',
+      '.mts': 'typescript',
+      '.cts': 'typescript',
+      '.js': 'javascript',
+      '.jsx': 'javascriptreact',
+      '.mjs': 'javascript',
+      '.cjs': 'javascript',
+    },
+    rootMarkers: ['tsconfig.json', 'jsconfig.json', 'package.json'],
+  },
+  {
+    id: 'pyright',
+    command: 'pyright-langserver',
+    args: ['--stdio'],
+    languageIds: { '.py': 'python', '.pyi': 'python' },
+    rootMarkers: [
+      'pyproject.toml',
+      'pyrightconfig.json',
+      'setup.py',
+      'requirements.txt',
+    ],
+  },
+]
+
+/** The defaults, with configs of the same id replaced by the custom ones */
+export function mergeLanguageServerConfigs(
+  custom: LanguageServerConfig[] = [],
+): LanguageServerConfig[] {
+  const customIds = new Set(custom.map(({ id }) => id))
+  return [
+    ...custom,
+    ...DEFAULT_LANGUAGE_SERVERS.filter(({ id }) => !customIds.has(id)),
+  ]
+}
+
+export function findLanguageServerConfig(
+  configs: LanguageServerConfig[],
+  filePath: string,
+): LanguageServerConfig | undefined {
+  const extension = path.extname(filePath).toLowerCase()
+  return configs.find(({ languageIds }) => extension in languageIds)
+}
@@ -0,0 +1,101 @@
+import path from 'path'
+
+import { findLanguageConfigByExtension } from '@levelcode/code-map/languages'
+import { loadCargoWorkspace } from '@levelcode/code-map/rust/cargo'
+import {
+  flattenTree,
+  getProjectFileTree,
+} from '@levelcode/common/project-file-tree'
+
+import { loadParseCache, saveParseCache } from './code-map-cache'
+
+import type { ParseCache } from '@levelcode/code-map/parse-cache'
+import type { CargoWorkspace } from '@levelcode/code-map/rust/cargo'
+import type { Logger } from '@levelcode/common/types/contracts/logger'
+import type { LevelCodeFileSystem } from '@levelcode/common/types/filesystem'
+
+/** Source files of a project, read for building a code map index */
+export interface ProjectSources {
+  /** Files accepted by the filter, relative to the project root */
+  filePaths: string[]
+  readFile: (filePath: string) => string | null
+  cargoWorkspace: CargoWorkspace | undefined
+  /** The project's persisted parse cache */
+  cache: ParseCache
+}
+
+export function isCargoFile(filePath: string): boolean {
+  const fileName = path.basename(filePath)
+  return fileName === 'Cargo.toml' || fileName === 'Cargo.lock'
+}
+
+/** Reads the files of the project tree that `filter` accepts */
+export async function readProjectFiles(params: {
+  projectRoot: string
+  fs: LevelCodeFileSystem
+  filter: (filePath: string) => boolean
+  maxFiles?: number
+}): Promise<Record<string, string>> {
+  const { projectRoot, fs, filter, maxFiles } = params
+  const fileTree = await getProjectFileTree({
+    projectRoot,
+    fs,
+    ...(maxFiles && { maxFiles }),
+  })
+  const filePaths = flattenTree(fileTree)
+    .filter((node) => node.type === 'file' && filter(node.filePath))
+    .map((node) => node.filePath)
+
+  const contents: Record<string, string> = {}
+  await Promise.all(
+    filePaths.map(async (filePath) => {
+      try {
+        contents[filePath] = await fs.readFile(
+          path.join(projectRoot, filePath),
+          'utf8',
+        )
+      } catch {
+        // Skip unreadable files
+      }
+    }),
+  )
+  return contents
+}
+
+/**
+ * Reads the project's source files (those a language is configured for, or
+ * those `filter` accepts, given paths relative to the project root) and its
+ * Cargo workspace, and passes them to `use` along with the persisted parse
+ * cache. The cache is saved afterwards, so only files that changed since they
+ * were last parsed are parsed again.
+ */
+export async function withProjectSources<T>(params: {
+  projectRoot: string
+  fs: LevelCodeFileSystem
+  logger?: Logger
+  filter?: (filePath: string) => boolean
+  use: (sources: ProjectSources) => Promise<T>
+}): Promise<T> {
+  const { projectRoot, fs, logger, use } = params
+  const filter =
+    params.filter ??
+    ((filePath: string) =>
+      !!findLanguageConfigByExtension(path.join(projectRoot, filePath)))
+
+  const contents = await readProjectFiles({
+    projectRoot,
+    fs,
+    filter: (filePath) => isCargoFile(filePath) || filter(filePath),
+  })
+  const cache = await loadParseCache({ projectRoot, fs, logger })
+  const result = await use({
+    filePaths: Object.keys(contents).filter(
+      (filePath) => !isCargoFile(filePath) && filter(filePath),
+    ),
+    readFile: (filePath) => contents[filePath] ?? null,
+    cargoWorkspace: loadCargoWorkspace(contents),
+    cache,
+  })
+  await saveParseCache({ projectRoot, cache, fs, logger })
+  return result
+}
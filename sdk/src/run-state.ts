@@ -1,7 +1,11 @@
 import * as os from 'os'
 import path from 'path'
 
-import { getFileTokenScores } from '@levelcode/code-map/parse'
+import {
+  parseProjectFiles,
+  scoreParsedFiles,
+} from '@levelcode/code-map/parse'
+import { loadCargoWorkspace } from '@levelcode/code-map/rust/cargo'
 import {
   KNOWLEDGE_FILE_NAMES_LOWERCASE,
   isKnowledgeFile,
@@ -12,10 +16,13 @@ import {
 } from '@levelcode/common/project-file-tree'
 import { getInitialSessionState } from '@levelcode/common/types/session-state'
 import { getErrorObject } from '@levelcode/common/util/error'
+import { LRUCache } from '@levelcode/common/util/lru-cache'
 import { cloneDeep } from 'lodash'
 import z from 'zod/v4'
 
 import { loadLocalAgents } from './agents/load-agents'
+import { loadParseCache, saveParseCache } from './code-map-cache'
+import { isCargoFile } from './project-sources'
 import { loadSkills } from './skills/load-skills'
 
 // Re-export for SDK consumers
@@ -26,7 +33,9 @@ export {
 } from '@levelcode/common/constants/knowledge'
 
 import type { CustomToolDefinition } from './custom-tool'
+import type { ParsedTokens } from '@levelcode/code-map/parse'
 import type { AgentDefinition } from '@levelcode/common/templates/initial-agents-dir/types/agent-definition'
+import type { CargoWorkspace } from '@levelcode/common/types/cargo-workspace'
 import type { Logger } from '@levelcode/common/types/contracts/logger'
 import type { LevelCodeFileSystem } from '@levelcode/common/types/filesystem'
 import type { Message } from '@levelcode/common/types/messages/levelcode-message'
@@ -35,6 +44,7 @@ import type {
   SessionState,
 } from '@levelcode/common/types/session-state'
 import type { LevelCodeSpawn } from '@levelcode/common/types/spawn'
+import type { SymbolGraph } from '@levelcode/common/types/symbol-graph'
 import type {
   CustomToolDefinitions,
   FileTreeNode,
@@ -130,38 +140,212 @@ function processCustomToolDefinitions(
   )
 }
 
+/** A project indexed in this process, kept to refresh the index after edits */
+interface IndexedProject {
+  filePaths: Set<string>
+  /** Contents of the Cargo manifests and lockfiles, to reload the workspace */
+  cargoFiles: Record<string, string>
+  /** Parse results of the files a language is configured for */
+  parsedFiles: Map<string, ParsedTokens>
+  cargoWorkspace: CargoWorkspace | undefined
+}
+
+/** Projects kept at most; runs in evicted projects skip index refreshes */
+const MAX_INDEXED_PROJECTS = 4
+const indexedProjects = new LRUCache<string, IndexedProject>(
+  MAX_INDEXED_PROJECTS,
+)
+
+/** The project's Cargo files with their contents, and its other file paths */
+function getWorkspaceFiles(project: IndexedProject): Record<string, string> {
+  const files: Record<string, string> = {}
+  for (const filePath of project.filePaths) {
+    files[filePath] = project.cargoFiles[filePath] ?? ''
+  }
+  return files
+}
+
+function tryLoadCargoWorkspace(
+  projectFiles: Record<string, string>,
+  logger?: Logger,
+): CargoWorkspace | undefined {
+  try {
+    return loadCargoWorkspace(projectFiles)
+  } catch (error) {
+    logger?.warn(
+      { error: getErrorObject(error) },
+      'Failed to load Cargo workspace',
+    )
+    return undefined
+  }
+}
+
 /**
- * Computes project file indexes (file tree and token scores)
+ * Computes project file indexes (file tree, token scores, symbol graph and
+ * Cargo workspace).
+ * Parse results are cached on disk, so only files that changed since the last
+ * session are parsed.
  */
 async function computeProjectIndex(
   cwd: string,
   projectFiles: Record<string, string>,
+  fs: LevelCodeFileSystem = (require('fs') as typeof fsType).promises,
+  logger?: Logger,
 ): Promise<{
   fileTree: FileTreeNode[]
   fileTokenScores: Record<string, any>
   tokenCallers: Record<string, any>
+  symbolGraph: SymbolGraph | undefined
+  cargoWorkspace: CargoWorkspace | undefined
 }> {
   const filePaths = Object.keys(projectFiles).sort()
   const fileTree = buildFileTree(filePaths)
   let fileTokenScores = {}
   let tokenCallers = {}
+  let symbolGraph: SymbolGraph | undefined
+
+  const cargoWorkspace = tryLoadCargoWorkspace(projectFiles, logger)
+  const project: IndexedProject = {
+    filePaths: new Set(filePaths),
+    cargoFiles: Object.fromEntries(
+      filePaths
+        .filter(isCargoFile)
+        .map((filePath) => [filePath, projectFiles[filePath]]),
+    ),
+    parsedFiles: new Map(),
+    cargoWorkspace,
+  }
+  indexedProjects.set(cwd, project)
 
   if (filePaths.length > 0) {
     try {
-      const tokenData = await getFileTokenScores(
+      const cache = await loadParseCache({ projectRoot: cwd, fs, logger })
+      project.parsedFiles = await parseProjectFiles(
         cwd,
         filePaths,
         (filePath: string) => projectFiles[filePath] || null,
+        cache,
+      )
+      const tokenData = scoreParsedFiles(
+        cwd,
+        project.parsedFiles,
+        cargoWorkspace,
       )
       fileTokenScores = tokenData.tokenScores
       tokenCallers = tokenData.tokenCallers
+      symbolGraph = tokenData.symbolGraph
+      cache.retain(filePaths)
+      await saveParseCache({ projectRoot: cwd, cache, fs, logger })
     } catch (error) {
       // If token scoring fails, continue with empty scores
       console.warn('Failed to generate parsed symbol scores:', error)
     }
   }
 
-  return { fileTree, fileTokenScores, tokenCallers }
+  return {
+    fileTree,
+    fileTokenScores,
+    tokenCallers,
+    symbolGraph,
+    cargoWorkspace,
+  }
+}
+
+/**
+ * Updates the project index of a running session after files were written,
+ * so token scores reflect the agent's own edits. Only the changed files are
+ * re-parsed, incrementally when their previous syntax tree is still cached,
+ * and the scores are recomputed from the parse results kept in memory. The
+ * Cargo workspace is reloaded only when a manifest or lockfile changed.
+ * Does nothing if the project was not indexed in this process, or was evicted
+ * by more recently indexed projects.
+ *
+ * The parse cache is not written to disk; see `saveProjectIndexCache`.
+ */
+export async function refreshProjectIndex(params: {
+  cwd: string
+  fileContext: SessionState['fileContext']
+  changedFiles: string[]
+  fs: LevelCodeFileSystem
+  logger?: Logger
+}): Promise<void> {
+  const { cwd, fileContext, changedFiles, fs, logger } = params
+  const project = indexedProjects.get(cwd)
+  if (!project) {
+    return
+  }
+  const { filePaths, cargoFiles, parsedFiles } = project
+  const contents: Record<string, string> = {}
+  let isFileListChanged = false
+  for (const filePath of new Set(changedFiles)) {
+    try {
+      const content = await fs.readFile(path.join(cwd, filePath), 'utf8')
+      isFileListChanged ||= !filePaths.has(filePath)
+      filePaths.add(filePath)
+      contents[filePath] = content
+      if (isCargoFile(filePath)) {
+        cargoFiles[filePath] = content
+      }
+    } catch (error) {
+      // Deleted, e.g. by a rename
+      logger?.debug?.(
+        { filePath, error: getErrorObject(error) },
+        'Failed to read changed file for the project index',
+      )
+      isFileListChanged ||= filePaths.has(filePath)
+      filePaths.delete(filePath)
+      delete cargoFiles[filePath]
+      parsedFiles.delete(filePath)
+    }
+  }
+
+  const cache = await loadParseCache({ projectRoot: cwd, fs, logger })
+  const reparsed = await parseProjectFiles(
+    cwd,
+    Object.keys(contents),
+    (filePath: string) => contents[filePath] ?? null,
+    cache,
+  )
+  for (const [filePath, parsed] of reparsed) {
+    parsedFiles.set(filePath, parsed)
+  }
+  if (isFileListChanged) {
+    cache.retain(filePaths)
+    fileContext.fileTree = buildFileTree([...filePaths].sort())
+  }
+  if (changedFiles.some(isCargoFile)) {
+    project.cargoWorkspace = tryLoadCargoWorkspace(
+      getWorkspaceFiles(project),
+      logger,
+    )
+    fileContext.cargoWorkspace = project.cargoWorkspace
+  }
+
+  const { tokenScores, tokenCallers, symbolGraph } = scoreParsedFiles(
+    cwd,
+    parsedFiles,
+    project.cargoWorkspace,
+  )
+  fileContext.fileTokenScores = tokenScores
+  fileContext.tokenCallers = tokenCallers
+  fileContext.symbolGraph = symbolGraph
+}
+
+/**
+ * Writes the parse cache of a project indexed in this process to disk, e.g.
+ * at the end of a run that refreshed the index after edits
+ */
+export async function saveProjectIndexCache(params: {
+  cwd: string
+  fs: LevelCodeFileSystem
+  logger?: Logger
+}): Promise<void> {
+  const { cwd, fs, logger } = params
+  if (!indexedProjects.get(cwd)) {
+    return
+  }
+  const cache = await loadParseCache({ projectRoot: cwd, fs, logger })
+  await saveParseCache({ projectRoot: cwd, cache, fs, logger })
 }
 
 /**
@@ -462,12 +646,16 @@ export async function initialSessionState(
   let fileTree: FileTreeNode[] = []
   let fileTokenScores: Record<string, any> = {}
   let tokenCallers: Record<string, any> = {}
+  let symbolGraph: SymbolGraph | undefined
+  let cargoWorkspace: CargoWorkspace | undefined
 
   if (cwd && projectFiles) {
-    const result = await computeProjectIndex(cwd, projectFiles)
+    const result = await computeProjectIndex(cwd, projectFiles, fs, logger)
     fileTree = result.fileTree
     fileTokenScores = result.fileTokenScores
     tokenCallers = result.tokenCallers
+    symbolGraph = result.symbolGraph
+    cargoWorkspace = result.cargoWorkspace
   }
 
   // Gather git changes if cwd is available
@@ -496,6 +684,8 @@ export async function initialSessionState(
     fileTree,
     fileTokenScores,
     tokenCallers,
+    symbolGraph,
+    cargoWorkspace,
     knowledgeFiles,
     userKnowledgeFiles,
     agentTemplates: processedAgentTemplates,
@@ -604,6 +794,9 @@ export async function applyOverridesToSessionState(
     agentDefinitions?: AgentDefinition[]
     customToolDefinitions?: CustomToolDefinition[]
     maxAgentSteps?: number
+    /** Used to index projectFiles; defaults to the real file system */
+    fs?: LevelCodeFileSystem
+    logger?: Logger
   },
 ): Promise<SessionState> {
   // Deep clone to avoid mutating the original session state
@@ -619,16 +812,30 @@ export async function applyOverridesToSessionState(
   // Apply projectFiles override (recomputes file tree and token scores)
   if (overrides.projectFiles !== undefined) {
     if (cwd) {
-      const { fileTree, fileTokenScores, tokenCallers } =
-        await computeProjectIndex(cwd, overrides.projectFiles)
+      const {
+        fileTree,
+        fileTokenScores,
+        tokenCallers,
+        symbolGraph,
+        cargoWorkspace,
+      } = await computeProjectIndex(
+        cwd,
+        overrides.projectFiles,
+        overrides.fs,
+        overrides.logger,
+      )
       sessionState.fileContext.fileTree = fileTree
       sessionState.fileContext.fileTokenScores = fileTokenScores
       sessionState.fileContext.tokenCallers = tokenCallers
+      sessionState.fileContext.symbolGraph = symbolGraph
+      sessionState.fileContext.cargoWorkspace = cargoWorkspace
     } else {
       // If projectFiles are provided but no cwd, reset file context fields
       sessionState.fileContext.fileTree = []
       sessionState.fileContext.fileTokenScores = {}
       sessionState.fileContext.tokenCallers = {}
+      sessionState.fileContext.symbolGraph = undefined
+      sessionState.fileContext.cargoWorkspace = undefined
     }
 
     // Auto-derive knowledgeFiles if not explicitly provided
@@ -11,23 +11,70 @@ import { getMCPClient, listMCPTools, callMCPTool } from '@levelcode/common/mcp/c
 import { toolNames } from '@levelcode/common/tools/constants'
 import { clientToolCallSchema } from '@levelcode/common/tools/list'
 import { AgentOutputSchema } from '@levelcode/common/types/session-state'
+import { getErrorObject } from '@levelcode/common/util/error'
 import { cloneDeep } from 'lodash'
 
+import { loadFileChangeHooks } from './agents/load-file-change-hooks'
+import { registerLanguages } from './agents/load-language-config'
+import { getSandboxConfigFromEnv } from './env'
 import { getErrorStatusCode } from './error-utils'
 import { getAgentRuntimeImpl } from './impl/agent-runtime'
 import { getUserInfoFromApiKey } from './impl/database'
-import { initialSessionState, applyOverridesToSessionState } from './run-state'
+import { LanguageServerManager } from './lsp/manager'
+import {
+  initialSessionState,
+  applyOverridesToSessionState,
+  refreshProjectIndex,
+  saveProjectIndexCache,
+} from './run-state'
+import { BackgroundProcessManager } from './shell/background-process-manager'
+import { ShellSessionManager } from './shell/manager'
+import {
+  addSandboxViolations,
+  createSandboxedSpawn,
+  resolveSandbox,
+} from './shell/sandbox'
+import {
+  readBackgroundProcess,
+  runBackgroundCommand,
+  signalBackgroundProcess,
+} from './tools/background-process'
 import { changeFile } from './tools/change-file'
+import { cargoDiagnostics } from './tools/cargo-diagnostics'
 import { codeSearch } from './tools/code-search'
+import { findAffectedTests } from './tools/find-affected-tests'
+import { findTraitImpls } from './tools/find-trait-impls'
 import { glob } from './tools/glob'
+import {
+  lspDefinition,
+  lspDiagnostics,
+  lspHover,
+  lspReferences,
+  lspRename,
+  lspWorkspaceSymbols,
+} from './tools/language-server'
 import { listDirectory } from './tools/list-directory'
+import { findDefinition, findReferences } from './tools/navigate-symbols'
+import { readDocs } from './tools/read-docs'
 import { getFiles } from './tools/read-files'
+import {
+  runFileChangeHooks,
+  runFileChangeHooksAfterEdit,
+} from './tools/run-file-change-hooks'
 import { runTerminalCommand } from './tools/run-terminal-command'
+import {
+  readTerminalSession,
+  runTerminalSessionCommand,
+} from './tools/terminal-session'
 
 
+import type { FileChangeHook } from './agents/load-file-change-hooks'
 import type { CustomToolDefinition } from './custom-tool'
+import type { LanguageServerConfig } from './lsp/servers'
 import type { RunState } from './run-state'
+import type { Sandbox, SandboxConfig } from './shell/sandbox'
 import type { FileFilter } from './tools/read-files'
+import type { LanguageRegistration } from '@levelcode/code-map/languages'
 import type { ServerAction } from '@levelcode/common/actions'
 import type { AgentDefinition } from '@levelcode/common/templates/initial-agents-dir/types/agent-definition'
 import type {
@@ -53,6 +100,23 @@ import type { SessionState } from '@levelcode/common/types/session-state'
 import type { Source } from '@levelcode/common/types/source'
 import type { LevelCodeSpawn } from '@levelcode/common/types/spawn'
 
+/** Tools that write a file, after which the project index is refreshed */
+const FILE_EDIT_TOOL_NAMES: string[] = [
+  'write_file',
+  'str_replace',
+  'edit_symbol',
+  'apply_suggestions',
+]
+
+/** Tools whose output can show that the sandbox blocked a command */
+const COMMAND_TOOL_NAMES: string[] = [
+  'run_terminal_command',
+  'read_terminal_session',
+  'read_background_process',
+  'cargo_diagnostics',
+  'run_file_change_hooks',
+]
+
 /**
  * Wraps content for user messages, ensuring text is wrapped in <user_message> tags.
  * Uses buildUserMessageContent from agent-runtime for consistency.
@@ -111,6 +175,25 @@ export type LevelCodeClientOptions = {
     }
   >
   customToolDefinitions?: CustomToolDefinition[]
+  /** Additional tree-sitter grammars used to index project files */
+  languages?: LanguageRegistration[]
+  /**
+   * Commands run after each file edit, in addition to those in the project's
+   * `.agents/hooks.json`. A failing required hook fails the edit.
+   */
+  fileChangeHooks?: FileChangeHook[]
+  /**
+   * Language servers for the lsp_* tools, in addition to the defaults
+   * (rust-analyzer, typescript-language-server and pyright). A server with
+   * the id of a default replaces it.
+   */
+  languageServers?: LanguageServerConfig[]
+  /**
+   * Runs the commands of run_terminal_command, cargo_diagnostics, file change
+   * hooks and language servers in a bubblewrap sandbox on Linux. Default:
+   * from LEVELCODE_SANDBOX, otherwise no sandbox
+   */
+  sandbox?: SandboxConfig
 
   fsSource?: Source<LevelCodeFileSystem>
   spawnSource?: Source<LevelCodeSpawn>
@@ -155,6 +238,10 @@ type RunExecutionOptions = RunOptions &
   LevelCodeClientOptions & {
     apiKey: string
     fingerprintId: string
+    /** Language servers shared across runs, owned by a `LevelCodeClient` */
+    languageServerManager?: LanguageServerManager
+    /** Background processes shared across runs, owned by a `LevelCodeClient` */
+    backgroundProcessManager?: BackgroundProcessManager
   }
 type RunReturnType = RunState
 
@@ -172,7 +259,35 @@ export async function run(options: RunExecutionOptions): Promise<RunState> {
     }
   }
 
-  return runOnce(options)
+  // Without a client to own them, language servers and background processes
+  // only live for this run
+  const languageServerManager =
+    options.languageServerManager ??
+    new LanguageServerManager({
+      servers: options.languageServers,
+      logger: options.logger,
+    })
+  const backgroundProcessManager =
+    options.backgroundProcessManager ??
+    new BackgroundProcessManager({ logger: options.logger })
+  // Terminal sessions never outlive the run that opened them
+  const shellSessions = new ShellSessionManager({ logger: options.logger })
+  try {
+    return await runOnce({
+      ...options,
+      languageServerManager,
+      backgroundProcessManager,
+      shellSessions,
+    })
+  } finally {
+    shellSessions.shutdown()
+    if (!options.languageServerManager) {
+      await languageServerManager.shutdown()
+    }
+    if (!options.backgroundProcessManager) {
+      await backgroundProcessManager.shutdown()
+    }
+  }
 }
 
 async function runOnce({
@@ -192,6 +307,12 @@ async function runOnce({
   fileFilter,
   overrideTools,
   customToolDefinitions,
+  languages,
+  fileChangeHooks: clientFileChangeHooks = [],
+  languageServerManager,
+  backgroundProcessManager,
+  shellSessions,
+  sandbox: sandboxConfig = getSandboxConfigFromEnv(),
 
   fsSource = () => require('fs').promises,
   spawnSource,
@@ -205,7 +326,11 @@ async function runOnce({
   extraToolResults,
   signal,
   costMode,
-}: RunExecutionOptions): Promise<RunState> {
+}: RunExecutionOptions & {
+  languageServerManager: LanguageServerManager
+  backgroundProcessManager: BackgroundProcessManager
+  shellSessions: ShellSessionManager
+}): Promise<RunState> {
   const fsSourceValue = typeof fsSource === 'function' ? fsSource() : fsSource
   const fs = await fsSourceValue
   let spawn: LevelCodeSpawn
@@ -215,8 +340,26 @@ async function runOnce({
   } else {
     spawn = require('child_process').spawn as LevelCodeSpawn
   }
+  // Only the agent's commands run in the sandbox, not e.g. `git status` for
+  // the initial session state
+  const sandbox =
+    sandboxConfig && cwd
+      ? resolveSandbox({ config: sandboxConfig, projectPath: cwd, env })
+      : undefined
+  const commandSpawn = sandbox
+    ? createSandboxedSpawn({ spawn, sandbox, env, logger })
+    : spawn
   const preparedContent = wrapContentForUserMessage(content)
 
+  // Register additional languages before the project is indexed
+  await registerLanguages({ cwd, languages, fs, logger })
+
+  // Project hooks run before the ones passed in code
+  const fileChangeHooks = [
+    ...(cwd ? await loadFileChangeHooks({ cwd, fs, logger }) : []),
+    ...clientFileChangeHooks,
+  ]
+
   // Init session state
   let agentId
   if (typeof agent !== 'string') {
@@ -238,6 +381,8 @@ async function runOnce({
         customToolDefinitions,
         projectFiles,
         maxAgentSteps,
+        fs,
+        logger,
       },
     )
   } else {
@@ -360,7 +505,7 @@ async function runOnce({
       // Does nothing for now
     },
     requestToolCall: async ({ userInputId, toolName, input, mcpConfig }) => {
-      return handleToolCall({
+      let result = await handleToolCall({
         action: {
           type: 'tool-call-request',
           requestId: crypto.randomUUID(),
@@ -378,8 +523,55 @@ async function runOnce({
           : {},
         cwd,
         fs,
+        spawn: commandSpawn,
         env,
+        sandbox,
+        fileChangeHooks,
+        languageServers: languageServerManager,
+        backgroundProcesses: backgroundProcessManager,
+        shellSessions,
       })
+      const refreshIndex = (projectPath: string, changedFiles: string[]) =>
+        refreshProjectIndex({
+          cwd: projectPath,
+          fileContext: sessionState.fileContext,
+          changedFiles,
+          fs,
+          logger,
+        }).catch((error) => {
+          logger?.warn(
+            { error: getErrorObject(error) },
+            'Failed to refresh project index',
+          )
+        })
+      if (
+        cwd &&
+        !mcpConfig &&
+        !overrideTools?.write_file &&
+        FILE_EDIT_TOOL_NAMES.includes(toolName)
+      ) {
+        const changedFile = (input as { path: string }).path
+        // Hooks such as formatters may change the file again, so run them
+        // before refreshing the index
+        if (fileChangeHooks.length > 0) {
+          result = {
+            output: await runFileChangeHooksAfterEdit({
+              editResult: result.output,
+              file: changedFile,
+              hooks: fileChangeHooks,
+              projectPath: cwd,
+              fs,
+              env,
+              spawn: commandSpawn,
+            }),
+          }
+        }
+        await refreshIndex(cwd, [changedFile])
+      }
+      if (cwd && !mcpConfig && toolName === 'lsp_rename') {
+        await refreshIndex(cwd, getRenamedFiles(result.output))
+      }
+      return result
     },
     requestMcpToolData: async ({ mcpConfig, toolNames }) => {
       const mcpClientId = await getMCPClient(mcpConfig)
@@ -525,7 +717,14 @@ async function runOnce({
     })
   })
 
-  return promise
+  try {
+    return await promise
+  } finally {
+    // Index refreshes after edits only update the parse cache in memory
+    if (cwd) {
+      await saveProjectIndexCache({ cwd, fs, logger })
+    }
+  }
 }
 
 function requireCwd(cwd: string | undefined, toolName: string): string {
@@ -537,6 +736,15 @@ function requireCwd(cwd: string | undefined, toolName: string): string {
   return cwd
 }
 
+/** Files changed by an lsp_rename call, relative to the project root */
+function getRenamedFiles(output: ToolResultOutput[]): string[] {
+  const [first] = output as LevelCodeToolOutput<'lsp_rename'>
+  if (first?.type !== 'json' || !('files' in first.value)) {
+    return []
+  }
+  return first.value.files.map(({ file }) => file)
+}
+
 async function readFiles({
   filePaths,
   override,
@@ -564,14 +772,26 @@ async function handleToolCall({
   customToolDefinitions,
   cwd,
   fs,
+  spawn,
   env,
+  sandbox,
+  fileChangeHooks,
+  languageServers,
+  backgroundProcesses,
+  shellSessions,
 }: {
   action: ServerAction<'tool-call-request'>
   overrides: NonNullable<LevelCodeClientOptions['overrideTools']>
   customToolDefinitions: Record<string, CustomToolDefinition>
   cwd?: string
   fs: LevelCodeFileSystem
+  spawn: LevelCodeSpawn
   env?: Record<string, string>
+  sandbox?: Sandbox
+  fileChangeHooks: FileChangeHook[]
+  languageServers: LanguageServerManager
+  backgroundProcesses: BackgroundProcessManager
+  shellSessions: ShellSessionManager
 }): Promise<{ output: ToolResultOutput[] }> {
   const toolName = action.toolName
   const input = action.input
@@ -618,8 +838,13 @@ async function handleToolCall({
 
   try {
     let override = overrides[toolName as PublishedClientToolName]
-    if (!override && toolName === 'str_replace') {
-      // Note: write_file and str_replace have the same implementation, so reuse their write_file override.
+    if (
+      !override &&
+      (toolName === 'str_replace' ||
+        toolName === 'edit_symbol' ||
+        toolName === 'apply_suggestions')
+    ) {
+      // Note: write_file, str_replace, edit_symbol and apply_suggestions have the same implementation, so reuse their write_file override.
       override = overrides['write_file']
     }
     if (override) {
@@ -630,19 +855,61 @@ async function handleToolCall({
       result = await override(input as any)
     } else if (toolName === 'end_turn') {
       result = [{ type: 'json', value: { message: 'Turn ended.' } }]
-    } else if (toolName === 'write_file' || toolName === 'str_replace') {
+    } else if (
+      toolName === 'write_file' ||
+      toolName === 'str_replace' ||
+      toolName === 'edit_symbol' ||
+      toolName === 'apply_suggestions'
+    ) {
       result = await changeFile({
         parameters: input,
         cwd: requireCwd(cwd, toolName),
         fs,
       })
+    } else if (
+      toolName === 'run_terminal_command' &&
+      input.process_type === 'BACKGROUND'
+    ) {
+      const resolvedCwd = requireCwd(cwd, 'run_terminal_command')
+      result = await runBackgroundCommand({
+        command: input.command,
+        cwd: path.resolve(resolvedCwd, input.cwd ?? '.'),
+        spawn,
+        env,
+        backgroundProcesses,
+      })
+    } else if (toolName === 'run_terminal_command' && input.session) {
+      const resolvedCwd = requireCwd(cwd, 'run_terminal_command')
+      result = await runTerminalSessionCommand({
+        ...input,
+        cwd: path.resolve(resolvedCwd, input.cwd ?? '.'),
+        spawn,
+        env,
+        shellSessions,
+      } as Parameters<typeof runTerminalSessionCommand>[0])
     } else if (toolName === 'run_terminal_command') {
       const resolvedCwd = requireCwd(cwd, 'run_terminal_command')
       result = await runTerminalCommand({
         ...input,
         cwd: path.resolve(resolvedCwd, input.cwd ?? '.'),
         env,
+        spawn,
       } as Parameters<typeof runTerminalCommand>[0])
+    } else if (toolName === 'read_background_process') {
+      result = await readBackgroundProcess({
+        ...input,
+        backgroundProcesses,
+      } as Parameters<typeof readBackgroundProcess>[0])
+    } else if (toolName === 'signal_background_process') {
+      result = await signalBackgroundProcess({
+        ...input,
+        backgroundProcesses,
+      } as Parameters<typeof signalBackgroundProcess>[0])
+    } else if (toolName === 'read_terminal_session') {
+      result = await readTerminalSession({
+        ...input,
+        shellSessions,
+      } as Parameters<typeof readTerminalSession>[0])
     } else if (toolName === 'code_search') {
       result = await codeSearch({
         projectPath: requireCwd(cwd, 'code_search'),
@@ -661,16 +928,128 @@ async function handleToolCall({
         cwd: (input as { pattern: string; cwd?: string }).cwd,
         fs,
       })
+    } else if (toolName === 'find_trait_impls') {
+      const { trait, type, cwd: searchCwd } = input as {
+        trait?: string
+        type?: string
+        cwd?: string
+      }
+      result = await findTraitImpls({
+        trait,
+        type,
+        projectPath: requireCwd(cwd, 'find_trait_impls'),
+        cwd: searchCwd,
+        fs,
+        env,
+      })
+    } else if (toolName === 'find_definition') {
+      const { path: filePath, line, column } = input as {
+        path: string
+        line: number
+        column: number
+      }
+      result = await findDefinition({
+        path: filePath,
+        line,
+        column,
+        projectPath: requireCwd(cwd, 'find_definition'),
+        fs,
+        env,
+      })
+    } else if (toolName === 'find_references') {
+      const { symbol, path: filePath } = input as {
+        symbol: string
+        path?: string
+      }
+      result = await findReferences({
+        symbol,
+        path: filePath,
+        projectPath: requireCwd(cwd, 'find_references'),
+        fs,
+      })
+    } else if (toolName === 'find_affected_tests') {
+      result = await findAffectedTests({
+        paths: (input as { paths: string[] }).paths,
+        projectPath: requireCwd(cwd, 'find_affected_tests'),
+        fs,
+      })
+    } else if (toolName === 'cargo_diagnostics') {
+      result = await cargoDiagnostics({
+        ...input,
+        projectPath: requireCwd(cwd, 'cargo_diagnostics'),
+        spawn,
+        env,
+      } as Parameters<typeof cargoDiagnostics>[0])
     } else if (toolName === 'run_file_change_hooks') {
-      // No-op: SDK doesn't run file change hooks
-      result = [
-        {
-          type: 'json',
-          value: {
-            message: 'File change hooks are not supported in SDK mode',
-          },
-        },
-      ]
+      result = await runFileChangeHooks({
+        files: (input as { files: string[] }).files,
+        hooks: fileChangeHooks,
+        projectPath: requireCwd(cwd, 'run_file_change_hooks'),
+        fs,
+        env,
+        spawn,
+      })
+    } else if (toolName === 'lsp_hover') {
+      result = await lspHover({
+        ...input,
+        projectPath: requireCwd(cwd, 'lsp_hover'),
+        fs,
+        spawn,
+        env,
+        languageServers,
+      } as Parameters<typeof lspHover>[0])
+    } else if (toolName === 'lsp_definition') {
+      result = await lspDefinition({
+        ...input,
+        projectPath: requireCwd(cwd, 'lsp_definition'),
+        fs,
+        spawn,
+        env,
+        languageServers,
+      } as Parameters<typeof lspDefinition>[0])
+    } else if (toolName === 'lsp_references') {
+      result = await lspReferences({
+        ...input,
+        projectPath: requireCwd(cwd, 'lsp_references'),
+        fs,
+        spawn,
+        env,
+        languageServers,
+      } as Parameters<typeof lspReferences>[0])
+    } else if (toolName === 'lsp_rename') {
+      result = await lspRename({
+        ...input,
+        projectPath: requireCwd(cwd, 'lsp_rename'),
+        fs,
+        spawn,
+        env,
+        languageServers,
+      } as Parameters<typeof lspRename>[0])
+    } else if (toolName === 'lsp_workspace_symbols') {
+      result = await lspWorkspaceSymbols({
+        ...input,
+        projectPath: requireCwd(cwd, 'lsp_workspace_symbols'),
+        fs,
+        spawn,
+        env,
+        languageServers,
+      } as Parameters<typeof lspWorkspaceSymbols>[0])
+    } else if (toolName === 'lsp_diagnostics') {
+      result = await lspDiagnostics({
+        ...input,
+        projectPath: requireCwd(cwd, 'lsp_diagnostics'),
+        fs,
+        spawn,
+        env,
+        languageServers,
+      } as Parameters<typeof lspDiagnostics>[0])
+    } else if (toolName === 'read_docs') {
+      result = await readDocs({
+        ...input,
+        projectPath: requireCwd(cwd, 'read_docs'),
+        fs,
+        env,
+      } as Parameters<typeof readDocs>[0])
     } else {
       throw new Error(
         `Tool not implemented in SDK. Please provide an override or modify your agent to not use this tool: ${toolName}`,
@@ -694,6 +1073,9 @@ async function handleToolCall({
       },
     ]
   }
+  if (sandbox && COMMAND_TOOL_NAMES.includes(toolName)) {
+    result = addSandboxViolations(result, sandbox)
+  }
   return {
     output: result,
   }
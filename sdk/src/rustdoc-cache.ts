@@ -0,0 +1,89 @@
+import path from 'path'
+
+import { parseRustdocJson } from '@levelcode/code-map/rust/rustdoc'
+import { getErrorObject } from '@levelcode/common/util/error'
+
+import { getSystemProcessEnv } from './env'
+
+import type { RustdocIndex } from '@levelcode/code-map/rust/rustdoc'
+import type { Logger } from '@levelcode/common/types/contracts/logger'
+import type { LevelCodeFileSystem } from '@levelcode/common/types/filesystem'
+
+export interface RustdocFile {
+  /** Absolute path of the JSON file */
+  filePath: string
+  index: RustdocIndex
+}
+
+/** Parsed files by path, reparsed when their modification time changes */
+const rustdocFiles = new Map<
+  string,
+  { mtimeMs: number | undefined; index: Promise<RustdocIndex | null> }
+>()
+
+/** Directory cargo writes docs to: `target/doc`, or under CARGO_TARGET_DIR */
+export function getRustdocDir(
+  projectPath: string,
+  env?: Record<string, string>,
+): string {
+  const targetDir =
+    env?.CARGO_TARGET_DIR ?? getSystemProcessEnv().CARGO_TARGET_DIR ?? 'target'
+  return path.resolve(projectPath, targetDir, 'doc')
+}
+
+/**
+ * Loads the rustdoc JSON in the project's doc directory, one file per crate
+ * documented with `cargo +nightly rustdoc -- --output-format json`. Files
+ * that are not rustdoc JSON are skipped, so a missing or HTML-only doc
+ * directory yields no indexes.
+ */
+export async function loadRustdocFiles(params: {
+  projectPath: string
+  fs: LevelCodeFileSystem
+  env?: Record<string, string>
+  logger?: Logger
+}): Promise<RustdocFile[]> {
+  const { projectPath, fs, env, logger } = params
+  const docDir = getRustdocDir(projectPath, env)
+  let fileNames: string[]
+  try {
+    fileNames = await fs.readdir(docDir)
+  } catch {
+    return []
+  }
+
+  const files = await Promise.all(
+    fileNames
+      .filter((fileName) => fileName.endsWith('.json'))
+      .sort()
+      .map(async (fileName): Promise<RustdocFile | null> => {
+        const filePath = path.join(docDir, fileName)
+        let mtimeMs: number | undefined
+        try {
+          mtimeMs = (await fs.stat(filePath)).mtimeMs
+        } catch {
+          return null
+        }
+        let cached = rustdocFiles.get(filePath)
+        if (!cached || cached.mtimeMs !== mtimeMs) {
+          cached = {
+            mtimeMs,
+            index: fs
+              .readFile(filePath, 'utf8')
+              .then(parseRustdocJson)
+              .catch((error) => {
+                logger?.debug?.(
+                  { filePath, error: getErrorObject(error) },
+                  'Skipping unreadable rustdoc JSON',
+                )
+                return null
+              }),
+          }
+          rustdocFiles.set(filePath, cached)
+        }
+        const index = await cached.index
+        return index && { filePath, index }
+      }),
+  )
+  return files.filter((file): file is RustdocFile => file !== null)
+}
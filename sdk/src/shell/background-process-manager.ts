@@ -0,0 +1,154 @@
+import { BackgroundProcess } from './background-process'
+
+import type { BackgroundProcessInfo } from './background-process'
+import type { Logger } from '@levelcode/common/types/contracts/logger'
+import type { LevelCodeSpawn } from '@levelcode/common/types/spawn'
+
+/** Processes running at once, so a looping agent cannot fork servers forever */
+const MAX_RUNNING_PROCESSES = 10
+/** Exited processes kept with their output; older ones are dropped */
+const MAX_EXITED_PROCESSES = 20
+/** Signals that end this Node process, after which children are killed */
+const EXIT_SIGNALS = ['SIGINT', 'SIGTERM'] as const
+/** How long `shutdown` waits after SIGTERM before killing what is left */
+const SHUTDOWN_GRACE_MS = 3000
+
+/**
+ * Keeps the background processes started by run_terminal_command. A
+ * `LevelCodeClient` owns one manager, so a dev server started in one run can
+ * be polled in the next. Processes still running when `shutdown` is called,
+ * or when this Node process exits or gets SIGINT or SIGTERM, are killed with
+ * their process groups.
+ */
+export class BackgroundProcessManager {
+  private readonly processes = new Map<number, BackgroundProcess>()
+  private nextId = 1
+  private readonly listeners = new Set<
+    (processes: BackgroundProcessInfo[]) => void
+  >()
+  private readonly logger?: Logger
+
+  constructor(params: { logger?: Logger } = {}) {
+    this.logger = params.logger
+  }
+
+  start(params: {
+    command: string
+    cwd: string
+    spawn: LevelCodeSpawn
+    env?: Record<string, string>
+  }): BackgroundProcess {
+    const running = this.getRunning()
+    if (running.length >= MAX_RUNNING_PROCESSES) {
+      throw new Error(
+        `At most ${MAX_RUNNING_PROCESSES} background processes can run at once. Stop one with signal_background_process first: ${running.map(({ id, command }) => `${id} (\`${command}\`)`).join(', ')}`,
+      )
+    }
+    const backgroundProcess = BackgroundProcess.start({
+      ...params,
+      id: this.nextId++,
+      onExit: () => this.onChange(),
+      logger: this.logger,
+    })
+    this.processes.set(backgroundProcess.id, backgroundProcess)
+    if (running.length === 0) {
+      process.on('exit', this.killAll)
+      for (const signal of EXIT_SIGNALS) {
+        process.on(signal, this.onExitSignal)
+      }
+    }
+    this.onChange()
+    return backgroundProcess
+  }
+
+  get(id: number): BackgroundProcess | undefined {
+    return this.processes.get(id)
+  }
+
+  list(): BackgroundProcessInfo[] {
+    return [...this.processes.values()].map(({ info }) => info)
+  }
+
+  /** Calls the listener with all processes whenever one starts or exits */
+  subscribe(
+    listener: (processes: BackgroundProcessInfo[]) => void,
+  ): () => void {
+    this.listeners.add(listener)
+    return () => {
+      this.listeners.delete(listener)
+    }
+  }
+
+  /** Stops all processes: SIGTERM first, then SIGKILL after a grace period */
+  async shutdown(): Promise<void> {
+    const running = this.getRunning()
+    for (const backgroundProcess of running) {
+      backgroundProcess.signal('SIGTERM')
+    }
+    await Promise.all(
+      running.map((backgroundProcess) =>
+        backgroundProcess.waitFor({
+          offset: backgroundProcess.endOffset,
+          timeoutMs: SHUTDOWN_GRACE_MS,
+        }),
+      ),
+    )
+    this.killAll()
+    this.processes.clear()
+    this.onChange()
+  }
+
+  /** Synchronous, so it can run in the 'exit' handler of this process */
+  private readonly killAll = () => {
+    for (const backgroundProcess of this.getRunning()) {
+      backgroundProcess.signal('SIGKILL')
+    }
+  }
+
+  /**
+   * Kills the processes before this process ends. Listening for a signal
+   * disables Node's default of exiting on it, so the signal is raised again
+   * once no other listener handles it.
+   */
+  private readonly onExitSignal = (signal: NodeJS.Signals) => {
+    this.killAll()
+    this.stopWatchingExit()
+    if (process.listenerCount(signal) === 0) {
+      process.kill(process.pid, signal)
+    }
+  }
+
+  private stopWatchingExit(): void {
+    process.off('exit', this.killAll)
+    for (const signal of EXIT_SIGNALS) {
+      process.off(signal, this.onExitSignal)
+    }
+  }
+
+  /** Drops the oldest exited processes beyond `MAX_EXITED_PROCESSES` */
+  private pruneExited(): void {
+    const exited = [...this.processes.values()].filter(
+      ({ status }) => status !== 'running',
+    )
+    for (const { id } of exited.slice(0, -MAX_EXITED_PROCESSES)) {
+      this.processes.delete(id)
+    }
+  }
+
+  private getRunning(): BackgroundProcess[] {
+    return [...this.processes.values()].filter(
+      ({ status }) => status === 'running',
+    )
+  }
+
+  private onChange(): void {
+    if (this.getRunning().length === 0) {
+      this.stopWatchingExit()
+    }
+    this.pruneExited()
+    const processes = this.list()
+    for (const listener of this.listeners) {
+      listener(processes)
+    }
+  }
+}
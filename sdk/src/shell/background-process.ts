@@ -0,0 +1,248 @@
+import os from 'os'
+
+import { getErrorObject } from '@levelcode/common/util/error'
+
+import { getSystemProcessEnv } from '../env'
+import { findWindowsBash } from '../tools/run-terminal-command'
+
+import type { Logger } from '@levelcode/common/types/contracts/logger'
+import type { LevelCodeSpawn } from '@levelcode/common/types/spawn'
+import type { ChildProcess } from 'child_process'
+
+/** Output kept per process; older output is dropped and reported as skipped */
+const MAX_LOG_LENGTH = 1_000_000
+
+export type BackgroundProcessStatus = 'running' | 'completed' | 'error'
+
+/** Snapshot of a background process, as listed by `LevelCodeClient` */
+export interface BackgroundProcessInfo {
+  /** Id given by the manager, which unlike a pid is never reused */
+  id: number
+  /** Process id of the shell running the command */
+  pid: number
+  command: string
+  cwd: string
+  status: BackgroundProcessStatus
+  exitCode?: number
+  signal?: string
+  /** Milliseconds since the epoch */
+  startedAt: number
+}
+
+export interface BackgroundProcessOutput {
+  /** Combined stdout and stderr, from `offset` to `nextOffset` */
+  output: string
+  offset: number
+  nextOffset: number
+  /** Characters between the requested offset and `offset` that were dropped */
+  skipped: number
+}
+
+/**
+ * A command running detached from the tool call that started it, in its own
+ * process group so it can be signalled together with everything it spawned.
+ * Its stdout and stderr go to one log that is read by offset, so callers can
+ * poll for new output without missing or repeating any.
+ */
+export class BackgroundProcess {
+  private log = ''
+  /** Offset of the first character still in `log` */
+  private logStart = 0
+  private exit: { exitCode?: number; signal?: string } | undefined
+  private failed = false
+  private readonly listeners = new Set<() => void>()
+  /** Where the previous read ended, for reads without an offset */
+  readOffset = 0
+  readonly startedAt = Date.now()
+
+  private constructor(
+    readonly id: number,
+    readonly pid: number,
+    readonly command: string,
+    readonly cwd: string,
+    private readonly child: ChildProcess,
+    private readonly onExit?: (backgroundProcess: BackgroundProcess) => void,
+    private readonly logger?: Logger,
+  ) {}
+
+  static start(params: {
+    id: number
+    command: string
+    cwd: string
+    spawn: LevelCodeSpawn
+    env?: Record<string, string>
+    /** Called once the process exited or failed to start */
+    onExit?: (backgroundProcess: BackgroundProcess) => void
+    logger?: Logger
+  }): BackgroundProcess {
+    const { id, command, cwd, spawn, env, onExit, logger } = params
+    const processEnv = { ...getSystemProcessEnv(), ...env }
+    const isWindows = os.platform() === 'win32'
+    const shell = isWindows ? findWindowsBash(processEnv) : 'bash'
+    if (!shell) {
+      throw new Error(
+        'Bash is required for background processes but was not found',
+      )
+    }
+    const child = spawn(shell, ['-c', command], {
+      cwd,
+      env: processEnv,
+      stdio: ['ignore', 'pipe', 'pipe'],
+      detached: !isWindows,
+    })
+    if (child.pid === undefined || !child.stdout || !child.stderr) {
+      child.kill()
+      throw new Error(`Failed to start \`${command}\``)
+    }
+
+    const backgroundProcess = new BackgroundProcess(
+      id,
+      child.pid,
+      command,
+      cwd,
+      child,
+      onExit,
+      logger,
+    )
+    const append = (data: Buffer) => backgroundProcess.append(data.toString())
+    child.stdout.on('data', append)
+    child.stderr.on('data', append)
+    child.on('error', (error) => {
+      backgroundProcess.append(`\n${error.message}\n`)
+      backgroundProcess.failed = true
+      backgroundProcess.handleExit({})
+    })
+    // After the output is flushed, unlike 'exit'
+    child.on('close', (code, signal) => {
+      backgroundProcess.handleExit({
+        ...(code !== null ? { exitCode: code } : {}),
+        ...(signal ? { signal } : {}),
+      })
+    })
+    return backgroundProcess
+  }
+
+  get status(): BackgroundProcessStatus {
+    if (!this.exit) {
+      return 'running'
+    }
+    return this.failed || this.exit.exitCode !== 0 ? 'error' : 'completed'
+  }
+
+  /** Offset just past the last output */
+  get endOffset(): number {
+    return this.logStart + this.log.length
+  }
+
+  get info(): BackgroundProcessInfo {
+    return {
+      id: this.id,
+      pid: this.pid,
+      command: this.command,
+      cwd: this.cwd,
+      status: this.status,
+      ...this.exit,
+      startedAt: this.startedAt,
+    }
+  }
+
+  /** Output from the offset on, at most `maxLength` characters of it */
+  read(offset: number, maxLength: number): BackgroundProcessOutput {
+    const start = Math.max(offset, this.logStart)
+    const output = this.log.slice(
+      start - this.logStart,
+      start - this.logStart + maxLength,
+    )
+    return {
+      output,
+      offset: start,
+      nextOffset: start + output.length,
+      skipped: start - offset,
+    }
+  }
+
+  /**
+   * Resolves to whether the pattern matched the output from the offset on,
+   * once it does, the process exits, or the timeout passes
+   */
+  waitFor(params: {
+    pattern?: RegExp
+    offset: number
+    timeoutMs: number
+  }): Promise<boolean> {
+    const { pattern, offset, timeoutMs } = params
+    const matches = () =>
+      !!pattern &&
+      pattern.test(this.log.slice(Math.max(offset - this.logStart, 0)))
+
+    return new Promise((resolve) => {
+      if (matches() || this.exit || timeoutMs <= 0) {
+        resolve(matches())
+        return
+      }
+      const done = (matched: boolean) => {
+        if (timer) {
+          clearTimeout(timer)
+        }
+        this.listeners.delete(listener)
+        resolve(matched)
+      }
+      const listener = () => {
+        if (matches()) {
+          done(true)
+        } else if (this.exit) {
+          done(false)
+        }
+      }
+      const timer = Number.isFinite(timeoutMs)
+        ? setTimeout(() => done(false), timeoutMs)
+        : undefined
+      this.listeners.add(listener)
+    })
+  }
+
+  /** Sends the signal to the whole process group */
+  signal(signal: NodeJS.Signals): void {
+    if (this.exit) {
+      return
+    }
+    try {
+      if (os.platform() !== 'win32') {
+        process.kill(-this.pid, signal)
+      } else {
+        this.child.kill(signal)
+      }
+    } catch (error) {
+      this.logger?.debug(
+        { pid: this.pid, signal, error: getErrorObject(error) },
+        'Failed to signal background process',
+      )
+      this.child.kill(signal)
+    }
+  }
+
+  private handleExit(exit: { exitCode?: number; signal?: string }): void {
+    if (this.exit) {
+      return
+    }
+    this.exit = exit
+    this.notifyListeners()
+    this.onExit?.(this)
+  }
+
+  private append(text: string): void {
+    this.log += text
+    if (this.log.length > MAX_LOG_LENGTH) {
+      const dropped = this.log.length - MAX_LOG_LENGTH
+      this.log = this.log.slice(dropped)
+      this.logStart += dropped
+    }
+    this.notifyListeners()
+  }
+
+  private notifyListeners(): void {
+    for (const listener of [...this.listeners]) {
+      listener()
+    }
+  }
+}
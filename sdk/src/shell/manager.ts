@@ -0,0 +1,64 @@
+import { ShellSession } from './session'
+
+import type { Logger } from '@levelcode/common/types/contracts/logger'
+import type { LevelCodeSpawn } from '@levelcode/common/types/spawn'
+
+/** Sessions open at once, so a looping agent cannot fork shells forever */
+const MAX_SESSIONS = 8
+
+/**
+ * Keeps the named terminal sessions of a run. Sessions start on their first
+ * command and are all killed by `shutdown` when the run ends.
+ */
+export class ShellSessionManager {
+  private readonly sessions = new Map<string, ShellSession>()
+  private readonly logger?: Logger
+
+  constructor(params: { logger?: Logger } = {}) {
+    this.logger = params.logger
+  }
+
+  get(name: string): ShellSession | undefined {
+    return this.sessions.get(name)
+  }
+
+  /** Returns the session, starting it or restarting it if its shell exited */
+  open(params: {
+    name: string
+    cwd: string
+    spawn: LevelCodeSpawn
+    env?: Record<string, string>
+  }): ShellSession {
+    const { name } = params
+    const existing = this.sessions.get(name)
+    if (existing && existing.status !== 'exited') {
+      return existing
+    }
+    this.sessions.delete(name)
+    if (this.sessions.size >= MAX_SESSIONS) {
+      throw new Error(
+        `At most ${MAX_SESSIONS} terminal sessions can be open. Close one with read_terminal_session first: ${[...this.sessions.keys()].join(', ')}`,
+      )
+    }
+    const session = ShellSession.start({ ...params, logger: this.logger })
+    this.sessions.set(name, session)
+    return session
+  }
+
+  close(name: string): boolean {
+    const session = this.sessions.get(name)
+    if (!session) {
+      return false
+    }
+    this.sessions.delete(name)
+    session.close()
+    return true
+  }
+
+  shutdown(): void {
+    for (const session of this.sessions.values()) {
+      session.close()
+    }
+    this.sessions.clear()
+  }
+}
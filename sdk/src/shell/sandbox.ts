@@ -0,0 +1,311 @@
+import fs from 'fs'
+import os from 'os'
+import path from 'path'
+
+import { getSystemProcessEnv } from '../env'
+
+import type { SandboxViolation } from '@levelcode/common/tools/params/tool/run-terminal-command'
+import type { Logger } from '@levelcode/common/types/contracts/logger'
+import type { ToolResultOutput } from '@levelcode/common/types/messages/content-part'
+import type { LevelCodeSpawn } from '@levelcode/common/types/spawn'
+
+/**
+ * Directories commands may write to besides the project root. Only the
+ * download caches of cargo: its binaries and config run outside the sandbox.
+ */
+export const DEFAULT_SANDBOX_WRITABLE_DIRS = [
+  '~/.cargo/registry',
+  '~/.cargo/git',
+  '~/.cache',
+]
+
+/** Name parts of secret variables, e.g. GITHUB_TOKEN or AWS_SECRET_KEY */
+const SECRET_ENV_PATTERN =
+  /(^|_)(TOKEN|SECRET|PASSWORD|PASSWD|API_?KEY|PRIVATE_?KEY|ACCESS_?KEY|CREDENTIALS?|AUTH|COOKIE)(_|$)/i
+
+const READ_ONLY_PATTERN = /Read-only file system|\(os error 30\)|EROFS/
+const NETWORK_PATTERN =
+  /Could not resolve host|Temporary failure in name resolution|Network is unreachable|getaddrinfo (?:EAI_AGAIN|ENOTFOUND)|failed to lookup address information|Name or service not known/
+/** A quoted absolute or home-relative path in an error message */
+const QUOTED_PATH_PATTERN = /['‘`"]((?:\/|~\/)[^'’`"]*)['’`"]/
+
+export interface SandboxConfig {
+  /**
+   * Directories commands may write to besides the project root and
+   * CARGO_TARGET_DIR. `~` is the home directory. Default:
+   * `~/.cargo/registry`, `~/.cargo/git` and `~/.cache`
+   */
+  writableDirs?: string[]
+  /** Whether commands can use the network. Default true */
+  network?: boolean
+  /** Variables passed to commands even though their names look secret */
+  allowEnv?: string[]
+  /** Variables removed from the environment of commands besides secrets */
+  denyEnv?: string[]
+  /**
+   * Whether commands fail when the sandbox is not available, i.e. not on
+   * Linux or without bubblewrap. Otherwise they run unsandboxed. Default true
+   */
+  required?: boolean
+}
+
+/** A sandbox config with defaults applied and directories made absolute */
+export interface Sandbox {
+  writableDirs: string[]
+  /**
+   * Paths inside the writable directories that stay read-only, like the
+   * project's .git with its hooks, which run outside the sandbox
+   */
+  readOnlyPaths: string[]
+  network: boolean
+  allowEnv: string[]
+  denyEnv: string[]
+  required: boolean
+}
+
+function expandHome(dir: string): string {
+  return dir === '~' || dir.startsWith('~/')
+    ? path.join(os.homedir(), dir.slice(1))
+    : dir
+}
+
+/** Resolves the directories a sandbox started in the project may write to */
+export function resolveSandbox(params: {
+  config: SandboxConfig
+  projectPath: string
+  env?: Record<string, string>
+}): Sandbox {
+  const { config, projectPath, env } = params
+  const cargoTargetDir =
+    env?.CARGO_TARGET_DIR ?? getSystemProcessEnv().CARGO_TARGET_DIR
+  const writableDirs = [
+    projectPath,
+    ...(cargoTargetDir ? [cargoTargetDir] : []),
+    ...(config.writableDirs ?? DEFAULT_SANDBOX_WRITABLE_DIRS),
+  ].map((dir) => path.resolve(projectPath, expandHome(dir)))
+  return {
+    writableDirs: [...new Set(writableDirs)],
+    readOnlyPaths: [path.join(path.resolve(projectPath), '.git')],
+    network: config.network ?? true,
+    allowEnv: config.allowEnv ?? [],
+    denyEnv: config.denyEnv ?? [],
+    required: config.required ?? true,
+  }
+}
+
+/** Drops variables that look like secrets, except the allowed ones */
+export function filterSandboxEnv(
+  env: NodeJS.ProcessEnv,
+  sandbox: Pick<Sandbox, 'allowEnv' | 'denyEnv'>,
+): NodeJS.ProcessEnv {
+  const allowed = new Set(sandbox.allowEnv)
+  const denied = new Set(sandbox.denyEnv)
+  return Object.fromEntries(
+    Object.entries(env).filter(
+      ([name]) =>
+        allowed.has(name) ||
+        (!denied.has(name) && !SECRET_ENV_PATTERN.test(name)),
+    ),
+  )
+}
+
+/**
+ * Arguments for bubblewrap: the whole file system read-only except the
+ * writable directories minus the read-only paths, a private /tmp, and new
+ * PID, IPC and optionally network namespaces. Missing directories are
+ * skipped, as bubblewrap cannot bind them.
+ *
+ * `newSession` detaches the command from the terminal, so it cannot inject
+ * input into it. It also moves the command out of bubblewrap's process
+ * group, so leave it off for commands that are signaled through their
+ * process group.
+ */
+export function getBubblewrapArgs(params: {
+  sandbox: Sandbox
+  command: string
+  args: readonly string[]
+  cwd?: string
+  newSession?: boolean
+}): string[] {
+  const { sandbox, command, args, cwd, newSession = true } = params
+  return [
+    '--ro-bind',
+    '/',
+    '/',
+    '--dev',
+    '/dev',
+    '--proc',
+    '/proc',
+    '--tmpfs',
+    '/tmp',
+    ...sandbox.writableDirs
+      .filter((dir) => fs.existsSync(dir))
+      .flatMap((dir) => ['--bind', dir, dir]),
+    ...sandbox.readOnlyPaths
+      .filter((readOnlyPath) => fs.existsSync(readOnlyPath))
+      .flatMap((readOnlyPath) => ['--ro-bind', readOnlyPath, readOnlyPath]),
+    '--unshare-pid',
+    '--unshare-ipc',
+    '--unshare-uts',
+    '--unshare-cgroup-try',
+    ...(sandbox.network ? [] : ['--unshare-net']),
+    '--die-with-parent',
+    ...(newSession ? ['--new-session'] : []),
+    ...(cwd ? ['--chdir', path.resolve(cwd)] : []),
+    '--',
+    command,
+    ...args,
+  ]
+}
+
+function findBubblewrap(env: NodeJS.ProcessEnv): string | null {
+  for (const dir of (env.PATH ?? '').split(path.delimiter)) {
+    const bwrapPath = path.join(dir, 'bwrap')
+    if (dir && fs.existsSync(bwrapPath)) {
+      return bwrapPath
+    }
+  }
+  return null
+}
+
+/**
+ * Wraps a spawn function so every command runs in a bubblewrap sandbox and
+ * gets an environment without secrets. When the sandbox is not available,
+ * the returned function throws, or spawns unsandboxed if the sandbox is
+ * not required.
+ */
+export function createSandboxedSpawn(params: {
+  spawn: LevelCodeSpawn
+  sandbox: Sandbox
+  env?: Record<string, string>
+  logger?: Logger
+}): LevelCodeSpawn {
+  const { spawn, sandbox, env, logger } = params
+  const bwrapPath =
+    os.platform() === 'linux'
+      ? findBubblewrap({ ...getSystemProcessEnv(), ...env })
+      : null
+
+  if (!bwrapPath) {
+    const reason =
+      os.platform() === 'linux'
+        ? 'bubblewrap (bwrap) is not installed'
+        : `sandboxing is only supported on Linux, not ${os.platform()}`
+    if (sandbox.required) {
+      return () => {
+        throw new Error(
+          `Commands must run in the sandbox, but ${reason}. Install bubblewrap, or ask the user to turn off the sandbox.`,
+        )
+      }
+    }
+    logger?.warn({ reason }, 'Running commands without the sandbox')
+    return spawn
+  }
+
+  return (command, args = [], options = {}) => {
+    const cwd = options.cwd?.toString()
+    // Detached processes, e.g. background processes, already get a session
+    // without a terminal and are signaled through their process group
+    const newSession = !options.detached
+    return spawn(
+      bwrapPath,
+      getBubblewrapArgs({ sandbox, command, args, cwd, newSession }),
+      {
+        ...options,
+        env: filterSandboxEnv(options.env ?? getSystemProcessEnv(), sandbox),
+      },
+    )
+  }
+}
+
+/**
+ * Finds the errors in command output that the sandbox caused: writes outside
+ * the writable directories and, with the network disabled, failed lookups
+ */
+export function findSandboxViolations(
+  output: string,
+  sandbox: Sandbox,
+): SandboxViolation[] {
+  return output.split('\n').flatMap((line): SandboxViolation[] => {
+    if (READ_ONLY_PATTERN.test(line)) {
+      const blockedPath = QUOTED_PATH_PATTERN.exec(line)?.[1]
+      return [
+        {
+          kind: 'write',
+          ...(blockedPath ? { path: expandHome(blockedPath) } : {}),
+          line: line.trim(),
+          message: `The sandbox blocked writing to ${blockedPath ?? 'a directory outside the writable ones'}. Commands can only write to ${sandbox.writableDirs.join(', ')} and /tmp, except ${sandbox.readOnlyPaths.join(', ')}.`,
+        },
+      ]
+    }
+    if (!sandbox.network && NETWORK_PATTERN.test(line)) {
+      return [
+        {
+          kind: 'network',
+          line: line.trim(),
+          message:
+            'The sandbox has no network access. Use what is already downloaded, e.g. with `cargo --offline`, or ask the user to allow network access.',
+        },
+      ]
+    }
+    return []
+  })
+}
+
+/**
+ * Fields of command results holding error output. Background processes mix
+ * their stderr into `output`.
+ */
+const ERROR_OUTPUT_FIELDS = ['stderr', 'errorMessage', 'output']
+
+function hasFailed(result: Record<string, unknown>): boolean {
+  return (
+    'errorMessage' in result ||
+    result.success === false ||
+    result.passed === false ||
+    result.status === 'error' ||
+    (typeof result.exitCode === 'number' && result.exitCode !== 0)
+  )
+}
+
+function addViolationsToResult<T>(result: T, sandbox: Sandbox): T {
+  if (
+    !result ||
+    typeof result !== 'object' ||
+    Array.isArray(result) ||
+    !hasFailed(result as Record<string, unknown>)
+  ) {
+    return result
+  }
+  const errorOutput = ERROR_OUTPUT_FIELDS.map(
+    (field) => (result as Record<string, unknown>)[field],
+  ).filter((value): value is string => typeof value === 'string')
+  const sandboxViolations = findSandboxViolations(
+    errorOutput.join('\n'),
+    sandbox,
+  )
+  return sandboxViolations.length > 0
+    ? { ...result, sandboxViolations }
+    : result
+}
+
+/**
+ * Adds the sandbox violations found in the error output of failed commands
+ * to a command tool's output as `sandboxViolations`, so the agent learns why
+ * the command failed. Outputs listing several commands, like file change
+ * hooks, get them per command.
+ */
+export function addSandboxViolations(
+  output: ToolResultOutput[],
+  sandbox: Sandbox,
+): ToolResultOutput[] {
+  return output.map((part) => {
+    if (part.type !== 'json') {
+      return part
+    }
+    const value = Array.isArray(part.value)
+      ? part.value.map((result) => addViolationsToResult(result, sandbox))
+      : addViolationsToResult(part.value, sandbox)
+    return { ...part, value }
+  })
+}
@@ -0,0 +1,303 @@
+import { randomUUID } from 'crypto'
+import os from 'os'
+
+import { getErrorObject } from '@levelcode/common/util/error'
+
+import { getSystemProcessEnv } from '../env'
+import { findWindowsBash } from '../tools/run-terminal-command'
+
+import type { Logger } from '@levelcode/common/types/contracts/logger'
+import type { LevelCodeSpawn } from '@levelcode/common/types/spawn'
+import type { ChildProcess } from 'child_process'
+
+/** Output kept while nobody reads it; older output is dropped */
+const MAX_BUFFERED_OUTPUT = 1_000_000
+const MARKER_PREFIX = '__LEVELCODE_DONE_'
+
+export type ShellSessionStatus = 'idle' | 'running' | 'exited'
+
+export interface ShellSessionOutput {
+  status: ShellSessionStatus
+  /** Output since the previous read, without the session's markers */
+  stdout: string
+  stderr: string
+  /** Exit code of the command that just finished, or of the shell */
+  exitCode?: number
+}
+
+interface RunningCommand {
+  command: string
+  marker: string
+  exitCode?: number
+  stdoutDone: boolean
+  stderrDone: boolean
+}
+
+function keepTail(output: string): string {
+  return output.length > MAX_BUFFERED_OUTPUT
+    ? output.slice(output.length - MAX_BUFFERED_OUTPUT)
+    : output
+}
+
+/**
+ * Wraps a command so bash parses all of it, including the markers reporting
+ * its exit code, before running it. Whatever the command reads from stdin
+ * is then only what is written to the session later, and quotes or heredocs
+ * in the command cannot swallow the markers.
+ */
+function wrapCommand(command: string, marker: string): string {
+  const delimiter = `__LEVELCODE_COMMAND_${marker.slice(MARKER_PREFIX.length)}`
+  return [
+    `{ eval "$(cat <<'${delimiter}'`,
+    command,
+    delimiter,
+    `)"; printf '\\n%s %s\\n' '${marker}' "$?"; printf '\\n%s\\n' '${marker}' >&2; }`,
+    '',
+  ].join('\n')
+}
+
+/**
+ * A long-lived bash process that commands are written to one at a time, so
+ * the working directory, exported variables, sourced scripts and shell
+ * functions carry over from one command to the next. A command that runs
+ * past its timeout keeps running; its output can be read later and input
+ * can be written to it.
+ */
+export class ShellSession {
+  private stdout = ''
+  private stderr = ''
+  private running: RunningCommand | undefined
+  private lastExitCode: number | undefined
+  private exitReason: string | undefined
+  private readonly listeners = new Set<() => void>()
+
+  private constructor(
+    readonly name: string,
+    private readonly child: ChildProcess,
+    private readonly logger?: Logger,
+  ) {}
+
+  static start(params: {
+    name: string
+    cwd: string
+    spawn: LevelCodeSpawn
+    env?: Record<string, string>
+    logger?: Logger
+  }): ShellSession {
+    const { name, cwd, spawn, env, logger } = params
+    const processEnv = { ...getSystemProcessEnv(), ...env }
+    const isWindows = os.platform() === 'win32'
+    const shell = isWindows ? findWindowsBash(processEnv) : 'bash'
+    if (!shell) {
+      throw new Error(
+        'Bash is required for terminal sessions but was not found',
+      )
+    }
+    const child = spawn(shell, [], {
+      cwd,
+      env: processEnv,
+      stdio: 'pipe',
+      // Own process group, so closing the session also stops its commands
+      detached: !isWindows,
+    })
+    if (!child.stdout || !child.stderr || !child.stdin) {
+      throw new Error(`Failed to start terminal session "${name}": no stdio`)
+    }
+
+    const session = new ShellSession(name, child, logger)
+    child.stdout.on('data', (data: Buffer) => {
+      session.stdout = keepTail(session.stdout + data.toString())
+      session.checkCommandDone()
+    })
+    child.stderr.on('data', (data: Buffer) => {
+      session.stderr = keepTail(session.stderr + data.toString())
+      session.checkCommandDone()
+    })
+    child.on('error', (error) => {
+      session.onExit(`Failed to start bash: ${error.message}`)
+    })
+    child.on('exit', (code, signal) => {
+      session.lastExitCode = code ?? undefined
+      session.onExit(`Shell exited (${signal ?? `code ${code}`})`)
+    })
+    child.stdin.on('error', () => {
+      // Reported by the exit handler
+    })
+    return session
+  }
+
+  get status(): ShellSessionStatus {
+    return this.exitReason !== undefined
+      ? 'exited'
+      : this.running
+        ? 'running'
+        : 'idle'
+  }
+
+  /** Command still running after its timeout */
+  get runningCommand(): string | undefined {
+    return this.running?.command
+  }
+
+  /** Why the shell exited, once it did */
+  get exitMessage(): string | undefined {
+    return this.exitReason
+  }
+
+  /**
+   * Runs a command and waits until it finishes or the timeout passes.
+   * Output left unread from before is included.
+   */
+  async run(command: string, timeoutMs: number): Promise<ShellSessionOutput> {
+    if (this.exitReason !== undefined) {
+      throw new Error(`Terminal session "${this.name}" has exited`)
+    }
+    if (this.running) {
+      throw new Error(
+        `Terminal session "${this.name}" is still running \`${this.running.command}\`. Read its output or send it input with read_terminal_session, or use another session.`,
+      )
+    }
+    const marker = `${MARKER_PREFIX}${randomUUID().replace(/-/g, '')}`
+    this.running = { command, marker, stdoutDone: false, stderrDone: false }
+    this.lastExitCode = undefined
+    this.child.stdin!.write(wrapCommand(command, marker))
+    return this.read({ timeoutMs })
+  }
+
+  /**
+   * Optionally writes input to the running command, then waits until it
+   * finishes or the timeout passes, and returns the output since the
+   * previous read.
+   */
+  async read(params: {
+    input?: string
+    timeoutMs?: number
+  }): Promise<ShellSessionOutput> {
+    const { input, timeoutMs = 0 } = params
+    if (input !== undefined) {
+      if (!this.running) {
+        throw new Error(
+          `Nothing is running in terminal session "${this.name}" to send input to. Run commands with run_terminal_command.`,
+        )
+      }
+      this.child.stdin!.write(input)
+    }
+    await this.waitUntil(() => this.status !== 'running', timeoutMs)
+
+    const output: ShellSessionOutput = {
+      status: this.status,
+      stdout: this.stdout,
+      stderr: this.stderr,
+      ...(this.status !== 'running' && this.lastExitCode !== undefined
+        ? { exitCode: this.lastExitCode }
+        : {}),
+    }
+    this.stdout = ''
+    this.stderr = ''
+    if (this.status === 'idle') {
+      this.lastExitCode = undefined
+    }
+    return output
+  }
+
+  /** Kills the shell and everything running in it */
+  close(): void {
+    if (this.exitReason !== undefined) {
+      return
+    }
+    try {
+      if (this.child.pid !== undefined && os.platform() !== 'win32') {
+        process.kill(-this.child.pid, 'SIGKILL')
+      } else {
+        this.child.kill('SIGKILL')
+      }
+    } catch (error) {
+      this.logger?.debug(
+        { session: this.name, error: getErrorObject(error) },
+        'Failed to kill terminal session',
+      )
+      this.child.kill('SIGKILL')
+    }
+    this.onExit('Closed')
+  }
+
+  /** Strips the markers of the running command once bash printed them */
+  private checkCommandDone(): void {
+    const running = this.running
+    if (!running) {
+      return
+    }
+    if (!running.stdoutDone) {
+      const match = new RegExp(`\\n?${running.marker} (\\d+)\\n`).exec(
+        this.stdout,
+      )
+      if (match) {
+        running.stdoutDone = true
+        running.exitCode = Number(match[1])
+        this.stdout =
+          this.stdout.slice(0, match.index) +
+          this.stdout.slice(match.index + match[0].length)
+      }
+    }
+    if (!running.stderrDone) {
+      const markerLine = `\n${running.marker}\n`
+      const index = this.stderr.indexOf(markerLine)
+      if (index !== -1) {
+        running.stderrDone = true
+        this.stderr =
+          this.stderr.slice(0, index) +
+          this.stderr.slice(index + markerLine.length)
+      }
+    }
+    if (running.stdoutDone && running.stderrDone) {
+      this.running = undefined
+      this.lastExitCode = running.exitCode
+      this.notifyListeners()
+    }
+  }
+
+  private onExit(reason: string): void {
+    if (this.exitReason !== undefined) {
+      return
+    }
+    this.exitReason = reason
+    this.running = undefined
+    this.notifyListeners()
+    this.logger?.debug({ session: this.name }, reason)
+  }
+
+  private notifyListeners(): void {
+    for (const listener of [...this.listeners]) {
+      listener()
+    }
+  }
+
+  /**
+   * Resolves once the condition holds or the timeout passed, which may be
+   * `Infinity` to wait as long as it takes
+   */
+  private waitUntil(condition: () => boolean, timeoutMs: number) {
+    return new Promise<void>((resolve) => {
+      if (condition() || timeoutMs <= 0) {
+        resolve()
+        return
+      }
+      const done = () => {
+        if (timer) {
+          clearTimeout(timer)
+        }
+        this.listeners.delete(listener)
+        resolve()
+      }
+      const listener = () => {
+        if (condition()) {
+          done()
+        }
+      }
+      const timer = Number.isFinite(timeoutMs)
+        ? setTimeout(done, timeoutMs)
+        : undefined
+      this.listeners.add(listener)
+    })
+  }
+}
@@ -0,0 +1,178 @@
+import { stripColors } from '../../../common/src/util/string'
+import { COMMAND_OUTPUT_LIMIT } from './run-terminal-command'
+
+import type {
+  BackgroundProcessManager,
+} from '../shell/background-process-manager'
+import type { LevelCodeToolOutput } from '@levelcode/common/tools/list'
+import type { LevelCodeSpawn } from '@levelcode/common/types/spawn'
+
+/** How long read_background_process waits for `wait_for` by default */
+const DEFAULT_WAIT_FOR_SECONDS = 30
+const DEFAULT_SIGNAL_WAIT_SECONDS = 5
+
+type ErrorOutput = [{ type: 'json'; value: { errorMessage: string } }]
+
+function toErrorOutput(errorMessage: string): ErrorOutput {
+  return [{ type: 'json', value: { errorMessage } }]
+}
+
+function toNotFoundOutput(processId: number): ErrorOutput {
+  return toErrorOutput(
+    `No background process ${processId}. Start one with run_terminal_command and process_type BACKGROUND.`,
+  )
+}
+
+function toTimeoutMs(seconds: number): number {
+  return seconds < 0 ? Infinity : seconds * 1000
+}
+
+/** Starts the command detached and returns its id right away */
+export async function runBackgroundCommand(params: {
+  command: string
+  cwd: string
+  spawn: LevelCodeSpawn
+  env?: Record<string, string>
+  backgroundProcesses: BackgroundProcessManager
+}): Promise<LevelCodeToolOutput<'run_terminal_command'>> {
+  const { command, cwd, spawn, env, backgroundProcesses } = params
+  const backgroundProcess = backgroundProcesses.start({
+    command,
+    cwd,
+    spawn,
+    env,
+  })
+  return [
+    {
+      type: 'json',
+      value: {
+        command,
+        processId: backgroundProcess.id,
+        backgroundProcessStatus: backgroundProcess.status,
+        message: `Started in the background. Poll its output or wait until it is ready with read_background_process, and stop it with signal_background_process.`,
+      },
+    },
+  ]
+}
+
+/**
+ * Returns the process's output from `offset`, or from where the previous
+ * read ended, after waiting for `wait_for` to appear or for `wait_seconds`
+ */
+export async function readBackgroundProcess(params: {
+  process_id: number
+  offset?: number
+  wait_for?: string
+  wait_seconds?: number
+  backgroundProcesses: BackgroundProcessManager
+}): Promise<LevelCodeToolOutput<'read_background_process'>> {
+  const {
+    process_id,
+    wait_for,
+    wait_seconds = wait_for ? DEFAULT_WAIT_FOR_SECONDS : 0,
+    backgroundProcesses,
+  } = params
+  const backgroundProcess = backgroundProcesses.get(process_id)
+  if (!backgroundProcess) {
+    return toNotFoundOutput(process_id)
+  }
+  let pattern: RegExp | undefined
+  try {
+    pattern = wait_for !== undefined ? new RegExp(wait_for) : undefined
+  } catch (error) {
+    return toErrorOutput(
+      `Invalid wait_for pattern: ${error instanceof Error ? error.message : String(error)}`,
+    )
+  }
+
+  const offset = params.offset ?? backgroundProcess.readOffset
+  const matched = await backgroundProcess.waitFor({
+    pattern,
+    offset,
+    timeoutMs: toTimeoutMs(wait_seconds),
+  })
+  const read = backgroundProcess.read(offset, COMMAND_OUTPUT_LIMIT)
+  backgroundProcess.readOffset = read.nextOffset
+
+  const { status, exitCode, signal } = backgroundProcess.info
+  const messages = [
+    ...(read.skipped > 0
+      ? [`${read.skipped} characters of older output were dropped.`]
+      : []),
+    ...(read.nextOffset < backgroundProcess.endOffset
+      ? [`More output follows; read again from offset ${read.nextOffset}.`]
+      : []),
+    ...(pattern && !matched
+      ? [
+          status === 'running'
+            ? `${pattern} did not appear within ${wait_seconds} seconds.`
+            : `The process exited before ${pattern} appeared.`,
+        ]
+      : []),
+  ]
+  return [
+    {
+      type: 'json',
+      value: {
+        processId: process_id,
+        command: backgroundProcess.command,
+        status,
+        ...(exitCode !== undefined ? { exitCode } : {}),
+        ...(signal ? { signal } : {}),
+        output: stripColors(read.output),
+        offset: read.offset,
+        nextOffset: read.nextOffset,
+        ...(pattern ? { matched } : {}),
+        ...(messages.length > 0 ? { message: messages.join(' ') } : {}),
+      },
+    },
+  ]
+}
+
+/** Signals the process group and waits for the process to exit */
+export async function signalBackgroundProcess(params: {
+  process_id: number
+  signal?: NodeJS.Signals
+  wait_seconds?: number
+  backgroundProcesses: BackgroundProcessManager
+}): Promise<LevelCodeToolOutput<'signal_background_process'>> {
+  const {
+    process_id,
+    signal = 'SIGTERM',
+    wait_seconds = DEFAULT_SIGNAL_WAIT_SECONDS,
+    backgroundProcesses,
+  } = params
+  const backgroundProcess = backgroundProcesses.get(process_id)
+  if (!backgroundProcess) {
+    return toNotFoundOutput(process_id)
+  }
+
+  const wasRunning = backgroundProcess.status === 'running'
+  if (wasRunning) {
+    backgroundProcess.signal(signal)
+    await backgroundProcess.waitFor({
+      offset: backgroundProcess.endOffset,
+      timeoutMs: toTimeoutMs(wait_seconds),
+    })
+  }
+
+  const info = backgroundProcess.info
+  const message = !wasRunning
+    ? 'The process had already exited.'
+    : info.status === 'running'
+      ? `Sent ${signal}, but the process is still running after ${wait_seconds} seconds. Send SIGKILL to stop it for sure.`
+      : `Sent ${signal}; the process exited.`
+  return [
+    {
+      type: 'json',
+      value: {
+        processId: process_id,
+        command: backgroundProcess.command,
+        status: info.status,
+        ...(info.exitCode !== undefined ? { exitCode: info.exitCode } : {}),
+        ...(info.signal ? { signal: info.signal } : {}),
+        message,
+      },
+    },
+  ]
+}
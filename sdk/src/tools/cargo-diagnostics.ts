@@ -0,0 +1,147 @@
+import { spawn as nodeSpawn } from 'child_process'
+import * as path from 'path'
+
+import {
+  parseCargoDiagnostics,
+  summarizeCargoDiagnostics,
+} from '@levelcode/code-map/rust/diagnostics'
+import { DEFAULT_MAX_CARGO_DIAGNOSTICS } from '@levelcode/common/tools/params/tool/cargo-diagnostics'
+import {
+  stripColors,
+  truncateStringWithMessage,
+} from '@levelcode/common/util/string'
+
+import { getSystemProcessEnv } from '../env'
+
+import type { CargoDiagnostic } from '@levelcode/code-map/rust/diagnostics'
+import type { LevelCodeToolOutput } from '@levelcode/common/tools/list'
+import type { LevelCodeSpawn } from '@levelcode/common/types/spawn'
+
+const CARGO_TIMEOUT_MS = 10 * 60 * 1000
+/** Cargo's own stderr is only returned when there are no diagnostics */
+const STDERR_LIMIT = 5_000
+
+/**
+ * Runs cargo and parses its JSON messages line by line as they arrive, so
+ * artifact messages and other output are never buffered. Only the end of
+ * stderr is kept.
+ */
+function runCargo(params: {
+  args: string[]
+  cwd: string
+  spawn: LevelCodeSpawn
+  env?: Record<string, string>
+}): Promise<{
+  diagnostics: CargoDiagnostic[]
+  stderr: string
+  exitCode: number | null
+}> {
+  const { args, cwd, spawn, env } = params
+  return new Promise((resolve, reject) => {
+    const child = spawn('cargo', args, {
+      cwd,
+      env: { ...getSystemProcessEnv(), ...env },
+      stdio: 'pipe',
+    })
+    const diagnostics: CargoDiagnostic[] = []
+    let partialLine = ''
+    let stderr = ''
+    const timer = setTimeout(() => {
+      child.kill('SIGTERM')
+      reject(new Error(`cargo timed out after ${CARGO_TIMEOUT_MS / 1000}s`))
+    }, CARGO_TIMEOUT_MS)
+
+    child.stdout?.setEncoding('utf8')
+    child.stdout?.on('data', (data: string) => {
+      const lines = (partialLine + data).split('\n')
+      partialLine = lines.pop() ?? ''
+      diagnostics.push(...parseCargoDiagnostics(lines.join('\n')))
+    })
+    child.stderr?.setEncoding('utf8')
+    child.stderr?.on('data', (data: string) => {
+      // Twice the limit, so truncation still notes what was removed
+      stderr = (stderr + data).slice(-2 * STDERR_LIMIT)
+    })
+    child.on('close', (exitCode) => {
+      clearTimeout(timer)
+      diagnostics.push(...parseCargoDiagnostics(partialLine))
+      resolve({ diagnostics, stderr, exitCode })
+    })
+    child.on('error', (error) => {
+      clearTimeout(timer)
+      reject(new Error(`Failed to run cargo: ${error.message}`))
+    })
+  })
+}
+
+export async function cargoDiagnostics(params: {
+  command?: 'check' | 'clippy' | 'build'
+  package?: string
+  args?: string[]
+  cwd?: string
+  max_diagnostics?: number
+  projectPath: string
+  spawn?: LevelCodeSpawn
+  env?: Record<string, string>
+}): Promise<LevelCodeToolOutput<'cargo_diagnostics'>> {
+  const {
+    command = 'check',
+    package: packageName,
+    args = [],
+    cwd,
+    max_diagnostics = DEFAULT_MAX_CARGO_DIAGNOSTICS,
+    projectPath,
+    spawn = nodeSpawn as LevelCodeSpawn,
+    env,
+  } = params
+  const cargoArgs = [
+    command,
+    '--message-format=json',
+    ...(packageName ? ['-p', packageName] : []),
+    ...args,
+  ]
+  const commandLine = ['cargo', ...cargoArgs].join(' ')
+
+  try {
+    const { diagnostics, stderr, exitCode } = await runCargo({
+      args: cargoArgs,
+      cwd: path.resolve(projectPath, cwd ?? '.'),
+      spawn,
+      env,
+    })
+    const summary = summarizeCargoDiagnostics(diagnostics, max_diagnostics)
+    const success = exitCode === 0
+    const omitted =
+      summary.omitted > 0 ? ` (${summary.omitted} more not shown)` : ''
+
+    return [
+      {
+        type: 'json',
+        value: {
+          command: commandLine,
+          success,
+          ...(exitCode !== null && { exitCode }),
+          ...summary,
+          // Without diagnostics, cargo's own output explains the failure
+          ...(!success &&
+            diagnostics.length === 0 && {
+              stderr: truncateStringWithMessage({
+                str: stripColors(stderr),
+                maxLength: STDERR_LIMIT,
+                remove: 'START',
+              }),
+            }),
+          message: `cargo ${command} ${success ? 'succeeded' : 'failed'} with ${summary.errorCount} error(s) and ${summary.warningCount} warning(s)${omitted}`,
+        },
+      },
+    ]
+  } catch (error) {
+    const errorMessage = error instanceof Error ? error.message : String(error)
+    return [
+      {
+        type: 'json',
+        value: { errorMessage: `${commandLine}: ${errorMessage}` },
+      },
+    ]
+  }
+}
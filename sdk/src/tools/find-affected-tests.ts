@@ -0,0 +1,68 @@
+import path from 'path'
+
+import { isRustSourceFile } from '@levelcode/code-map/languages'
+import {
+  findAffectedRustTests,
+  getRustTestCommands,
+  getRustTestIndex,
+} from '@levelcode/code-map/rust/test-index'
+
+import { withProjectSources } from '../project-sources'
+
+import type { LevelCodeToolOutput } from '@levelcode/common/tools/list'
+import type { LevelCodeFileSystem } from '@levelcode/common/types/filesystem'
+
+export async function findAffectedTests(params: {
+  paths: string[]
+  projectPath: string
+  fs: LevelCodeFileSystem
+}): Promise<LevelCodeToolOutput<'find_affected_tests'>> {
+  const { projectPath, fs } = params
+  const changedFiles = params.paths.map((filePath) =>
+    path.isAbsolute(filePath)
+      ? path.relative(projectPath, filePath)
+      : path.normalize(filePath),
+  )
+
+  try {
+    const index = await withProjectSources({
+      projectRoot: projectPath,
+      fs,
+      filter: (filePath) => isRustSourceFile(path.join(projectPath, filePath)),
+      use: ({ filePaths, readFile, cargoWorkspace, cache }) =>
+        getRustTestIndex(
+          projectPath,
+          filePaths,
+          readFile,
+          cargoWorkspace,
+          cache,
+        ),
+    })
+    const affected = findAffectedRustTests(index, changedFiles)
+    const commands = getRustTestCommands(affected)
+
+    return [
+      {
+        type: 'json',
+        value: {
+          tests: affected.map(({ references: _, ...test }) => test),
+          commands,
+          message:
+            affected.length === 0
+              ? `No tests found for the changed files among ${index.tests.length} Rust test(s)`
+              : `Found ${affected.length} affected test(s) among ${index.tests.length}; run them with ${commands.length} command(s)`,
+        },
+      },
+    ]
+  } catch (error) {
+    const errorMessage = error instanceof Error ? error.message : String(error)
+    return [
+      {
+        type: 'json',
+        value: {
+          errorMessage: `Failed to find affected tests: ${errorMessage}`,
+        },
+      },
+    ]
+  }
+}
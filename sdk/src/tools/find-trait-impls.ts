@@ -0,0 +1,154 @@
+import path from 'path'
+
+import { isRustSourceFile } from '@levelcode/code-map/languages'
+import { getRustTraitIndex } from '@levelcode/code-map/rust/trait-index'
+
+import { withProjectSources } from '../project-sources'
+import { loadRustdocFiles } from '../rustdoc-cache'
+
+import type { RustTraitIndex } from '@levelcode/code-map/rust/trait-index'
+import type { LevelCodeToolOutput } from '@levelcode/common/tools/list'
+import type { LevelCodeFileSystem } from '@levelcode/common/types/filesystem'
+
+function isOutsideProject(projectPath: string, filePath: string): boolean {
+  return (
+    path.isAbsolute(filePath) &&
+    path.relative(projectPath, filePath).startsWith('..')
+  )
+}
+
+/**
+ * Adds the traits and trait impls of dependencies documented as rustdoc
+ * JSON. Those of workspace crates are skipped, since the syntax trees of the
+ * project already cover them.
+ */
+async function addDependencyImpls(params: {
+  index: RustTraitIndex
+  projectPath: string
+  fs: LevelCodeFileSystem
+  env?: Record<string, string>
+}): Promise<number> {
+  const { index, projectPath, fs, env } = params
+  let count = 0
+  const rustdocFiles = await loadRustdocFiles({ projectPath, fs, env })
+  for (const { index: rustdoc } of rustdocFiles) {
+    for (const item of rustdoc.items) {
+      if (item.kind === 'trait' && isOutsideProject(projectPath, item.file)) {
+        const traitName = item.path.split('::').pop()!
+        index.traits[traitName] = [
+          ...(index.traits[traitName] ?? []),
+          { file: item.file, line: item.line },
+        ]
+      }
+    }
+    for (const impl of rustdoc.traitImpls) {
+      if (!isOutsideProject(projectPath, impl.file)) {
+        continue
+      }
+      index.implementors[impl.traitName] = [
+        ...(index.implementors[impl.traitName] ?? []),
+        impl,
+      ]
+      if (!impl.isBlanket) {
+        index.implementedTraits[impl.typeName] = [
+          ...(index.implementedTraits[impl.typeName] ?? []),
+          impl,
+        ]
+      }
+      count++
+    }
+  }
+  return count
+}
+
+export async function findTraitImpls(params: {
+  trait?: string
+  type?: string
+  projectPath: string
+  cwd?: string
+  fs: LevelCodeFileSystem
+  env?: Record<string, string>
+}): Promise<LevelCodeToolOutput<'find_trait_impls'>> {
+  const { trait, type, projectPath, cwd, fs, env } = params
+
+  if (!trait && !type) {
+    return [
+      {
+        type: 'json',
+        value: { errorMessage: 'Provide a trait, a type, or both.' },
+      },
+    ]
+  }
+
+  try {
+    const cwdPrefix = cwd && (cwd.endsWith('/') ? cwd : `${cwd}/`)
+    const { index, fileCount } = await withProjectSources({
+      projectRoot: projectPath,
+      fs,
+      filter: (filePath) =>
+        isRustSourceFile(path.join(projectPath, filePath)) &&
+        (!cwdPrefix || filePath.startsWith(cwdPrefix)),
+      use: async ({ filePaths, readFile, cargoWorkspace, cache }) => ({
+        index: await getRustTraitIndex(
+          projectPath,
+          filePaths,
+          readFile,
+          cargoWorkspace,
+          cache,
+        ),
+        fileCount: filePaths.length,
+      }),
+    })
+    const dependencyImplCount = await addDependencyImpls({
+      index,
+      projectPath,
+      fs,
+      env,
+    })
+
+    const value: Extract<
+      LevelCodeToolOutput<'find_trait_impls'>[0]['value'],
+      { message: string }
+    > = { message: '' }
+    const summary: string[] = []
+
+    if (trait) {
+      value.traitDefinitions = index.traits[trait] ?? []
+      value.implementors = index.implementors[trait] ?? []
+      summary.push(
+        `Found ${value.implementors.length} impl(s) of trait "${trait}"`,
+      )
+    }
+    if (type) {
+      value.implementedTraits = (index.implementedTraits[type] ?? []).filter(
+        (impl) => !trait || impl.traitName === trait,
+      )
+      // Blanket impls may apply to any type, depending on their bounds
+      value.blanketImpls = Object.values(index.implementors)
+        .flat()
+        .filter(
+          (impl) => impl.isBlanket && (!trait || impl.traitName === trait),
+        )
+      summary.push(
+        `Found ${value.implementedTraits.length} trait(s) implemented for type "${type}" and ${value.blanketImpls.length} blanket impl(s) that may apply`,
+      )
+    }
+
+    const dependencies =
+      dependencyImplCount > 0
+        ? `, plus ${dependencyImplCount} impl(s) in dependencies from rustdoc JSON`
+        : ''
+    value.message = `${summary.join('; ')} across ${fileCount} Rust file(s)${cwd ? ` in directory "${cwd}"` : ''}${dependencies}`
+    return [{ type: 'json', value }]
+  } catch (error) {
+    const errorMessage = error instanceof Error ? error.message : String(error)
+    return [
+      {
+        type: 'json',
+        value: {
+          errorMessage: `Failed to index trait implementations: ${errorMessage}`,
+        },
+      },
+    ]
+  }
+}
@@ -1,16 +1,55 @@
 // Tool handlers for the LevelCode SDK
+import {
+  readBackgroundProcess,
+  runBackgroundCommand,
+  signalBackgroundProcess,
+} from './background-process'
 import { changeFile } from './change-file'
+import { cargoDiagnostics } from './cargo-diagnostics'
 import { codeSearch } from './code-search'
+import { findAffectedTests } from './find-affected-tests'
+import { findTraitImpls } from './find-trait-impls'
 import { glob } from './glob'
+import {
+  lspDefinition,
+  lspDiagnostics,
+  lspHover,
+  lspReferences,
+  lspRename,
+  lspWorkspaceSymbols,
+} from './language-server'
 import { listDirectory } from './list-directory'
+import { findDefinition, findReferences } from './navigate-symbols'
+import { readDocs } from './read-docs'
 import { getFiles } from './read-files'
 import { runFileChangeHooks } from './run-file-change-hooks'
 import { runTerminalCommand } from './run-terminal-command'
+import {
+  readTerminalSession,
+  runTerminalSessionCommand,
+} from './terminal-session'
 
 // Export tools under Tools namespace
 export const ToolHelpers = {
   runTerminalCommand,
+  runTerminalSessionCommand,
+  readTerminalSession,
+  runBackgroundCommand,
+  readBackgroundProcess,
+  signalBackgroundProcess,
   codeSearch,
+  cargoDiagnostics,
+  findTraitImpls,
+  findDefinition,
+  findReferences,
+  findAffectedTests,
+  lspHover,
+  lspDefinition,
+  lspReferences,
+  lspRename,
+  lspWorkspaceSymbols,
+  lspDiagnostics,
+  readDocs,
   glob,
   listDirectory,
   getFiles,
@@ -0,0 +1,444 @@
+import path from 'path'
+
+import { createPatch } from 'diff'
+
+import {
+  DIAGNOSTIC_SEVERITIES,
+  applyTextEdits,
+  getHoverText,
+  getWorkspaceEditChanges,
+  symbolKindName,
+  toPosition,
+  toProjectRelativePath,
+  toSourceRange,
+  uriToPath,
+} from '../lsp/protocol'
+
+import type { DefinitionKind, LanguageServerClient } from '../lsp/client'
+import type { LanguageServerManager } from '../lsp/manager'
+import type { Location } from '../lsp/protocol'
+import type { LevelCodeToolOutput } from '@levelcode/common/tools/list'
+import type { LevelCodeFileSystem } from '@levelcode/common/types/filesystem'
+import type { LevelCodeSpawn } from '@levelcode/common/types/spawn'
+
+/** Locations returned at most, to keep results for common names readable */
+const MAX_LOCATIONS = 100
+const MAX_DIAGNOSTICS_PER_FILE = 50
+
+type LanguageServerToolParams = {
+  projectPath: string
+  fs: LevelCodeFileSystem
+  spawn: LevelCodeSpawn
+  env?: Record<string, string>
+  languageServers: LanguageServerManager
+}
+
+type ErrorOutput = [{ type: 'json'; value: { errorMessage: string } }]
+
+function toErrorOutput(action: string, error: unknown): ErrorOutput {
+  const errorMessage = error instanceof Error ? error.message : String(error)
+  return [
+    {
+      type: 'json',
+      value: { errorMessage: `Failed to ${action}: ${errorMessage}` },
+    },
+  ]
+}
+
+function getClient(
+  params: LanguageServerToolParams & { filePath: string },
+): Promise<LanguageServerClient> {
+  const { filePath, projectPath, spawn, env, languageServers } = params
+  const config = languageServers.findConfig(filePath)
+  if (!config) {
+    throw new Error(
+      `No language server is configured for ${path.extname(filePath) || 'extensionless'} files`,
+    )
+  }
+  return languageServers.getClient({ config, projectPath, spawn, env })
+}
+
+/** Starts the file's language server and syncs the file's content from disk */
+async function openDocument(
+  params: LanguageServerToolParams & { path: string },
+): Promise<{ client: LanguageServerClient; uri: string; filePath: string }> {
+  const { projectPath, fs } = params
+  const filePath = path.resolve(projectPath, params.path)
+  const content = await fs.readFile(filePath, 'utf8')
+  const client = await getClient({ ...params, filePath })
+  return { client, uri: client.syncDocument(filePath, content), filePath }
+}
+
+/** Converts locations to project paths, with the source line they start on */
+async function toLocationOutputs(
+  locations: Location[],
+  params: { projectPath: string; fs: LevelCodeFileSystem },
+) {
+  const { projectPath, fs } = params
+  const fileLines = new Map<string, Promise<string[] | null>>()
+  const getLines = (filePath: string) => {
+    if (!fileLines.has(filePath)) {
+      fileLines.set(
+        filePath,
+        fs.readFile(filePath, 'utf8').then(
+          (content) => content.split('\n'),
+          () => null,
+        ),
+      )
+    }
+    return fileLines.get(filePath)!
+  }
+
+  return Promise.all(
+    locations.flatMap(({ uri, range }) => {
+      const filePath = uriToPath(uri)
+      if (!filePath) {
+        return []
+      }
+      return [
+        getLines(filePath).then((lines) => ({
+          file: toProjectRelativePath(projectPath, filePath),
+          range: toSourceRange(range),
+          text: lines?.[range.start.line]?.trim(),
+        })),
+      ]
+    }),
+  )
+}
+
+export async function lspHover(
+  params: LanguageServerToolParams & {
+    path: string
+    line: number
+    column: number
+  },
+): Promise<LevelCodeToolOutput<'lsp_hover'>> {
+  const { line, column } = params
+  try {
+    const { client, uri } = await openDocument(params)
+    const hover = await client.hover(uri, toPosition(line, column))
+    const contents = hover ? getHoverText(hover) : ''
+    if (!contents) {
+      return [
+        {
+          type: 'json',
+          value: {
+            errorMessage: `No type information at ${params.path}:${line}:${column}. Check the position with read_files.`,
+          },
+        },
+      ]
+    }
+    return [
+      {
+        type: 'json',
+        value: {
+          contents,
+          ...(hover?.range && { range: toSourceRange(hover.range) }),
+          message: `Type information from ${client.config.id}`,
+        },
+      },
+    ]
+  } catch (error) {
+    return toErrorOutput('get type information', error)
+  }
+}
+
+export async function lspDefinition(
+  params: LanguageServerToolParams & {
+    path: string
+    line: number
+    column: number
+    kind?: DefinitionKind
+  },
+): Promise<LevelCodeToolOutput<'lsp_definition'>> {
+  const { line, column, kind = 'definition' } = params
+  const name = kind.replace('_', ' ')
+  try {
+    const { client, uri } = await openDocument(params)
+    const locations = await client.definition(
+      uri,
+      toPosition(line, column),
+      kind,
+    )
+    const outputs = await toLocationOutputs(
+      locations.slice(0, MAX_LOCATIONS),
+      params,
+    )
+    return [
+      {
+        type: 'json',
+        value: {
+          locations: outputs,
+          message:
+            locations.length === 0
+              ? `No ${name} found at ${params.path}:${line}:${column}`
+              : `Found ${locations.length} ${name} location(s)`,
+        },
+      },
+    ]
+  } catch (error) {
+    return toErrorOutput(`find the ${name}`, error)
+  }
+}
+
+export async function lspReferences(
+  params: LanguageServerToolParams & {
+    path: string
+    line: number
+    column: number
+    include_declaration?: boolean
+  },
+): Promise<LevelCodeToolOutput<'lsp_references'>> {
+  const { line, column, include_declaration = true } = params
+  try {
+    const { client, uri } = await openDocument(params)
+    const references = await client.references(
+      uri,
+      toPosition(line, column),
+      include_declaration,
+    )
+    const truncated =
+      references.length > MAX_LOCATIONS
+        ? ` (showing the first ${MAX_LOCATIONS})`
+        : ''
+    return [
+      {
+        type: 'json',
+        value: {
+          references: await toLocationOutputs(
+            references.slice(0, MAX_LOCATIONS),
+            params,
+          ),
+          message: `Found ${references.length} reference(s)${truncated}`,
+        },
+      },
+    ]
+  } catch (error) {
+    return toErrorOutput('find references', error)
+  }
+}
+
+/** Computes the rename with the language server and writes the edits */
+export async function lspRename(
+  params: LanguageServerToolParams & {
+    path: string
+    line: number
+    column: number
+    new_name: string
+  },
+): Promise<LevelCodeToolOutput<'lsp_rename'>> {
+  const { line, column, new_name, projectPath, fs } = params
+  try {
+    const { client, uri } = await openDocument(params)
+    const edit = await client.rename(uri, toPosition(line, column), new_name)
+    const changes = getWorkspaceEditChanges(edit ?? {})
+    if (changes.size === 0) {
+      return [
+        {
+          type: 'json',
+          value: {
+            errorMessage: `The language server found nothing to rename at ${params.path}:${line}:${column}`,
+          },
+        },
+      ]
+    }
+
+    // Compute every file's new content before writing any of them
+    const updates: {
+      filePath: string
+      file: string
+      content: string
+      edits: number
+      unifiedDiff: string
+    }[] = []
+    for (const [changedUri, edits] of changes) {
+      const filePath = uriToPath(changedUri)
+      const file = filePath && toProjectRelativePath(projectPath, filePath)
+      if (!filePath || !file || path.isAbsolute(file)) {
+        throw new Error(
+          `The rename would change ${filePath ?? changedUri}, which is outside of the project`,
+        )
+      }
+      const oldContent = await fs.readFile(filePath, 'utf8')
+      const content = applyTextEdits(oldContent, edits)
+      updates.push({
+        filePath,
+        file,
+        content,
+        edits: edits.length,
+        unifiedDiff: createPatch(file, oldContent, content),
+      })
+    }
+    for (const { filePath, content } of updates) {
+      await fs.writeFile(filePath, content)
+      client.syncDocument(filePath, content)
+    }
+
+    const editCount = updates.reduce((sum, { edits }) => sum + edits, 0)
+    return [
+      {
+        type: 'json',
+        value: {
+          files: updates.map(({ file, edits, unifiedDiff }) => ({
+            file,
+            edits,
+            unifiedDiff,
+          })),
+          message: `Renamed to "${new_name}" with ${editCount} edit(s) in ${updates.length} file(s)`,
+        },
+      },
+    ]
+  } catch (error) {
+    return toErrorOutput('rename', error)
+  }
+}
+
+export async function lspWorkspaceSymbols(
+  params: LanguageServerToolParams & { query: string; path?: string },
+): Promise<LevelCodeToolOutput<'lsp_workspace_symbols'>> {
+  const { query, projectPath, fs, spawn, env, languageServers } = params
+  try {
+    const pathConfig = params.path && languageServers.findConfig(params.path)
+    const configs = params.path
+      ? pathConfig
+        ? [pathConfig]
+        : []
+      : await languageServers.findProjectConfigs({ projectPath, fs })
+    if (configs.length === 0) {
+      return [
+        {
+          type: 'json',
+          value: {
+            errorMessage: params.path
+              ? `No language server is configured for ${params.path}`
+              : 'No language server matches the project root. Pass the path of a source file to pick one.',
+          },
+        },
+      ]
+    }
+
+    const results = await Promise.allSettled(
+      configs.map(async (config) => {
+        const client = await languageServers.getClient({
+          config,
+          projectPath,
+          spawn,
+          env,
+        })
+        return client.workspaceSymbols(query)
+      }),
+    )
+    const failures = results.filter(
+      (result): result is PromiseRejectedResult => result.status === 'rejected',
+    )
+    if (failures.length === results.length) {
+      throw failures[0].reason
+    }
+
+    const symbols = results
+      .flatMap((result) => (result.status === 'fulfilled' ? result.value : []))
+      .flatMap(({ name, kind, containerName, location }) => {
+        const filePath = uriToPath(location.uri)
+        if (!filePath) {
+          return []
+        }
+        return [
+          {
+            name,
+            kind: symbolKindName(kind),
+            ...(containerName ? { container: containerName } : {}),
+            file: toProjectRelativePath(projectPath, filePath),
+            ...('range' in location && {
+              range: toSourceRange(location.range),
+            }),
+          },
+        ]
+      })
+    const truncated =
+      symbols.length > MAX_LOCATIONS
+        ? ` (showing the first ${MAX_LOCATIONS})`
+        : ''
+    return [
+      {
+        type: 'json',
+        value: {
+          symbols: symbols.slice(0, MAX_LOCATIONS),
+          message: `Found ${symbols.length} symbol(s) matching "${query}"${truncated}`,
+        },
+      },
+    ]
+  } catch (error) {
+    return toErrorOutput('search workspace symbols', error)
+  }
+}
+
+export async function lspDiagnostics(
+  params: LanguageServerToolParams & { paths: string[] },
+): Promise<LevelCodeToolOutput<'lsp_diagnostics'>> {
+  const { projectPath } = params
+  try {
+    const files = await Promise.all(
+      params.paths.map(async (inputPath) => {
+        const file = toProjectRelativePath(
+          projectPath,
+          path.resolve(projectPath, inputPath),
+        )
+        try {
+          const { client, uri } = await openDocument({
+            ...params,
+            path: inputPath,
+          })
+          const diagnostics = (await client.diagnostics(uri))
+            .map(({ range, severity = 1, code, source, message }) => ({
+              severity: DIAGNOSTIC_SEVERITIES[severity - 1] ?? 'error',
+              ...(code !== undefined && { code: String(code) }),
+              ...(source ? { source } : {}),
+              message,
+              range: toSourceRange(range),
+            }))
+            .sort(
+              (a, b) =>
+                DIAGNOSTIC_SEVERITIES.indexOf(a.severity) -
+                  DIAGNOSTIC_SEVERITIES.indexOf(b.severity) ||
+                a.range.startLine - b.range.startLine,
+            )
+          return { file, diagnostics }
+        } catch (error) {
+          return {
+            file,
+            diagnostics: [],
+            errorMessage:
+              error instanceof Error ? error.message : String(error),
+          }
+        }
+      }),
+    )
+
+    const all = files.flatMap(({ diagnostics }) => diagnostics)
+    const count = (severity: string) =>
+      all.filter((diagnostic) => diagnostic.severity === severity).length
+    const errorCount = count('error')
+    const warningCount = count('warning')
+    const omitted = files.reduce(
+      (sum, { diagnostics }) =>
+        sum + Math.max(0, diagnostics.length - MAX_DIAGNOSTICS_PER_FILE),
+      0,
+    )
+    return [
+      {
+        type: 'json',
+        value: {
+          files: files.map(({ diagnostics, ...rest }) => ({
+            ...rest,
+            diagnostics: diagnostics.slice(0, MAX_DIAGNOSTICS_PER_FILE),
+          })),
+          errorCount,
+          warningCount,
+          message: `${errorCount} error(s) and ${warningCount} warning(s) in ${files.length} file(s)${omitted > 0 ? `, ${omitted} diagnostic(s) omitted` : ''}`,
+        },
+      },
+    ]
+  } catch (error) {
+    return toErrorOutput('get diagnostics', error)
+  }
+}
@@ -0,0 +1,187 @@
+import path from 'path'
+
+import { getSymbolIndex } from '@levelcode/code-map/navigation'
+import { matchesItemPath } from '@levelcode/code-map/rust/outline'
+
+import { withProjectSources } from '../project-sources'
+import { loadRustdocFiles } from '../rustdoc-cache'
+
+import type {
+  SymbolIndex,
+  SymbolLocation,
+} from '@levelcode/code-map/navigation'
+import type { RustDocItem } from '@levelcode/code-map/rust/crate-docs'
+import type { SourceRange } from '@levelcode/code-map/source-range'
+import type { LevelCodeToolOutput } from '@levelcode/common/tools/list'
+import type { LevelCodeFileSystem } from '@levelcode/common/types/filesystem'
+
+/** References returned at most, to keep results for common names readable */
+const MAX_REFERENCES = 100
+const MAX_RUSTDOC_ITEMS = 10
+
+/** Parses the project's source files, reusing the persisted parse cache */
+function loadSymbolIndex(params: {
+  projectPath: string
+  fs: LevelCodeFileSystem
+}): Promise<SymbolIndex> {
+  const { projectPath, fs } = params
+  return withProjectSources({
+    projectRoot: projectPath,
+    fs,
+    use: ({ filePaths, readFile, cargoWorkspace, cache }) =>
+      getSymbolIndex(projectPath, filePaths, readFile, cargoWorkspace, cache),
+  })
+}
+
+function toProjectPath(projectPath: string, filePath: string): string {
+  return path.isAbsolute(filePath)
+    ? path.relative(projectPath, filePath)
+    : path.normalize(filePath)
+}
+
+const toOutput = <T extends SymbolLocation>({ token, ...rest }: T) => ({
+  ...rest,
+  symbol: token,
+})
+
+/**
+ * Looks up a Rust symbol in the rustdoc JSON of the project's crates and
+ * dependencies, by the path written before it (e.g. `serde_json::from_str`)
+ * or else by its name alone.
+ */
+async function findRustdocItems(params: {
+  file: string
+  token: string
+  range: SourceRange
+  projectPath: string
+  fs: LevelCodeFileSystem
+  env?: Record<string, string>
+}): Promise<RustDocItem[]> {
+  const { file, token, range, projectPath, fs, env } = params
+  const rustdocFiles = await loadRustdocFiles({ projectPath, fs, env })
+  if (rustdocFiles.length === 0) {
+    return []
+  }
+  const items = rustdocFiles.flatMap(({ index }) => index.items)
+  const source = await fs
+    .readFile(path.join(projectPath, file), 'utf8')
+    .catch(() => '')
+  const lineText = source.split('\n')[range.startLine - 1] ?? ''
+  const qualifier =
+    lineText.slice(0, range.startColumn - 1).match(/(?:\w+::)+$/)?.[0] ?? ''
+  const matching = (query: string) =>
+    items.filter((item) => matchesItemPath(item.path, query))
+  const qualified = qualifier ? matching(`${qualifier}${token}`) : []
+  return qualified.length > 0 ? qualified : matching(token)
+}
+
+export async function findDefinition(params: {
+  path: string
+  line: number
+  column: number
+  projectPath: string
+  fs: LevelCodeFileSystem
+  env?: Record<string, string>
+}): Promise<LevelCodeToolOutput<'find_definition'>> {
+  const { line, column, projectPath, fs, env } = params
+  const filePath = toProjectPath(projectPath, params.path)
+
+  try {
+    const index = await loadSymbolIndex({ projectPath, fs })
+    const result = index.findDefinition(filePath, line, column)
+    if (!result) {
+      return [
+        {
+          type: 'json',
+          value: {
+            errorMessage: `No identifier at ${filePath}:${line}:${column}. Check the position with read_files, or use find_references with the symbol's name.`,
+          },
+        },
+      ]
+    }
+
+    const { symbol, definitions } = result
+    const resolved = definitions.some((definition) => definition.resolved)
+    // Items of dependencies are not in the project's syntax trees
+    const rustdocItems =
+      !resolved && filePath.endsWith('.rs')
+        ? await findRustdocItems({ ...symbol, projectPath, fs, env })
+        : []
+    const summary =
+      definitions.length === 0
+        ? `No definition found for "${symbol.token}"`
+        : `Found ${definitions.length} definition(s) of "${symbol.token}"${resolved ? '' : ' by name'}`
+    const fromRustdoc =
+      rustdocItems.length > 0
+        ? `; ${rustdocItems.length} matching item(s) in rustdoc JSON`
+        : ''
+    return [
+      {
+        type: 'json',
+        value: {
+          symbol: symbol.token,
+          kind: symbol.kind,
+          definitions: definitions.map(toOutput),
+          ...(rustdocItems.length > 0
+            ? {
+                rustdocItems: rustdocItems
+                  .slice(0, MAX_RUSTDOC_ITEMS)
+                  .map(({ docs: _, ...item }) => item),
+              }
+            : {}),
+          message: `${summary}${fromRustdoc}`,
+        },
+      },
+    ]
+  } catch (error) {
+    const errorMessage = error instanceof Error ? error.message : String(error)
+    return [
+      {
+        type: 'json',
+        value: {
+          errorMessage: `Failed to find the definition: ${errorMessage}`,
+        },
+      },
+    ]
+  }
+}
+
+export async function findReferences(params: {
+  symbol: string
+  path?: string
+  projectPath: string
+  fs: LevelCodeFileSystem
+}): Promise<LevelCodeToolOutput<'find_references'>> {
+  const { symbol, projectPath, fs } = params
+  const filePath = params.path && toProjectPath(projectPath, params.path)
+
+  try {
+    const index = await loadSymbolIndex({ projectPath, fs })
+    const { definitions, references } = index.findReferences(symbol, filePath)
+
+    const truncated =
+      references.length > MAX_REFERENCES
+        ? ` (showing the first ${MAX_REFERENCES})`
+        : ''
+    return [
+      {
+        type: 'json',
+        value: {
+          definitions: definitions.map(toOutput),
+          references: references.slice(0, MAX_REFERENCES).map(toOutput),
+          message: `Found ${definitions.length} definition(s) of "${symbol}" and ${references.length} reference(s)${truncated}`,
+        },
+      },
+    ]
+  } catch (error) {
+    const errorMessage = error instanceof Error ? error.message : String(error)
+    return [
+      {
+        type: 'json',
+        value: {
+          errorMessage: `Failed to find references: ${errorMessage}`,
+        },
+      },
+    ]
+  }
+}
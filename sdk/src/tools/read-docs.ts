@@ -0,0 +1,186 @@
+import os from 'os'
+import path from 'path'
+
+import { findLockedRegistryPackage } from '@levelcode/code-map/rust/cargo'
+import {
+  buildRustCrateDocs,
+  renderRustCrateDocs,
+} from '@levelcode/code-map/rust/crate-docs'
+import { toRustCrateDocs } from '@levelcode/code-map/rust/rustdoc'
+
+import { getSystemProcessEnv } from '../env'
+import { readProjectFiles } from '../project-sources'
+import { loadRustdocFiles } from '../rustdoc-cache'
+
+import type { RustCrateDocs } from '@levelcode/code-map/rust/crate-docs'
+import type { LevelCodeToolOutput } from '@levelcode/common/tools/list'
+import type { LevelCodeFileSystem } from '@levelcode/common/types/filesystem'
+
+const DEFAULT_MAX_TOKENS = 10_000
+const MAX_CRATE_FILES = 5_000
+/** Test and benchmark sources are not part of the documented API */
+const IGNORED_CRATE_DIRS = /^(tests|benches|target)\//
+/** Registry sources of a version never change, so each crate is read once */
+const crateDocsCache = new Map<string, Promise<RustCrateDocs>>()
+
+/** Crate names as rustdoc spells them, e.g. `serde_json` for `serde-json` */
+function toCrateName(name: string): string {
+  return name.trim().toLowerCase().replace(/[\s-]+/g, '_')
+}
+
+function toDocsOutput(
+  docs: RustCrateDocs,
+  params: { title: string; topic: string; maxTokens: number },
+): LevelCodeToolOutput<'read_docs'> {
+  return [
+    {
+      type: 'json',
+      value: { documentation: renderRustCrateDocs(docs, params) },
+    },
+  ]
+}
+
+function toErrorOutput(
+  errorMessage: string,
+): LevelCodeToolOutput<'read_docs'> {
+  return [
+    { type: 'json', value: { documentation: errorMessage, errorMessage } },
+  ]
+}
+
+/** Finds `<name>-<version>` in any registry index under `registry/src` */
+async function findRegistrySource(params: {
+  cargoHome: string
+  name: string
+  version: string
+  fs: LevelCodeFileSystem
+}): Promise<string | undefined> {
+  const { cargoHome, name, version, fs } = params
+  const registrySrc = path.join(cargoHome, 'registry', 'src')
+  let indexes: string[]
+  try {
+    indexes = await fs.readdir(registrySrc)
+  } catch {
+    return undefined
+  }
+  for (const index of indexes.sort()) {
+    const crateDir = path.join(registrySrc, index, `${name}-${version}`)
+    try {
+      await fs.stat(path.join(crateDir, 'Cargo.toml'))
+      return crateDir
+    } catch {
+      // Not downloaded from this index
+    }
+  }
+  return undefined
+}
+
+function getCrateDocs(
+  crateDir: string,
+  fs: LevelCodeFileSystem,
+): Promise<RustCrateDocs> {
+  const cached = crateDocsCache.get(crateDir)
+  if (cached) {
+    return cached
+  }
+  const docs = (async () => {
+    const files = await readProjectFiles({
+      projectRoot: crateDir,
+      fs,
+      maxFiles: MAX_CRATE_FILES,
+      filter: (filePath) =>
+        !IGNORED_CRATE_DIRS.test(filePath) &&
+        (filePath === 'Cargo.toml' ||
+          filePath.endsWith('.rs') ||
+          filePath.endsWith('.md')),
+    })
+    return buildRustCrateDocs(crateDir, files)
+  })()
+  crateDocsCache.set(crateDir, docs)
+  docs.catch(() => crateDocsCache.delete(crateDir))
+  return docs
+}
+
+/**
+ * Answers `read_docs` for the project's Rust crates without network access.
+ * Rustdoc JSON in the project's doc directory is preferred, since the
+ * compiler resolved its paths and signatures. It is used for workspace
+ * crates, and for dependencies if it matches the version locked in
+ * Cargo.lock. Otherwise dependencies are documented from the sources cargo
+ * downloaded to `$CARGO_HOME/registry/src`. Other libraries get an error, so
+ * the server falls back to its documentation search.
+ */
+export async function readDocs(params: {
+  libraryTitle: string
+  topic: string
+  max_tokens?: number
+  projectPath: string
+  fs: LevelCodeFileSystem
+  env?: Record<string, string>
+}): Promise<LevelCodeToolOutput<'read_docs'>> {
+  const { libraryTitle, topic, projectPath, fs, env } = params
+  const maxTokens = params.max_tokens ?? DEFAULT_MAX_TOKENS
+
+  try {
+    // Cargo writes Cargo.lock before building docs, so projects with rustdoc
+    // JSON have one too
+    const lockfile = await fs
+      .readFile(path.join(projectPath, 'Cargo.lock'), 'utf8')
+      .catch(() => undefined)
+    if (lockfile === undefined) {
+      return toErrorOutput('No Cargo.lock in the project root')
+    }
+    const manifests = await readProjectFiles({
+      projectRoot: projectPath,
+      fs,
+      filter: (filePath) => path.basename(filePath) === 'Cargo.toml',
+    })
+    const lockedPackage = findLockedRegistryPackage(
+      { ...manifests, 'Cargo.lock': lockfile },
+      libraryTitle,
+    )
+
+    const crateName = toCrateName(lockedPackage?.name ?? libraryTitle)
+    const rustdoc = (await loadRustdocFiles({ projectPath, fs, env })).find(
+      ({ index }) =>
+        index.crateName === crateName &&
+        (!lockedPackage || index.version === lockedPackage.version),
+    )
+    if (rustdoc) {
+      const { crateName: name, version } = rustdoc.index
+      return toDocsOutput(toRustCrateDocs(rustdoc.index), {
+        title: `${name}${version ? ` ${version}` : ''} (from ${rustdoc.filePath})`,
+        topic,
+        maxTokens,
+      })
+    }
+
+    if (!lockedPackage) {
+      return toErrorOutput(
+        `"${libraryTitle}" is not a registry package in Cargo.lock`,
+      )
+    }
+    const { name, version } = lockedPackage
+    const cargoHome =
+      env?.CARGO_HOME ??
+      getSystemProcessEnv().CARGO_HOME ??
+      path.join(os.homedir(), '.cargo')
+    const crateDir = await findRegistrySource({ cargoHome, name, version, fs })
+    if (!crateDir) {
+      return toErrorOutput(
+        `The sources of ${name} ${version} are not in ${path.join(cargoHome, 'registry', 'src')}. Run \`cargo fetch\` to download them.`,
+      )
+    }
+
+    return toDocsOutput(await getCrateDocs(crateDir, fs), {
+      title: `${name} ${version} (from ${crateDir})`,
+      topic,
+      maxTokens,
+    })
+  } catch (error) {
+    const errorMessage = error instanceof Error ? error.message : String(error)
+    return toErrorOutput(
+      `Failed to read local docs for "${libraryTitle}": ${errorMessage}`,
+    )
+  }
+}
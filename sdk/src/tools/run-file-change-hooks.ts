@@ -1,22 +1,214 @@
-import type { LevelCodeToolOutput } from '../../../common/src/tools/list'
+import path from 'path'
 
-export function runFileChangeHooks({
-  files,
-}: {
+import {
+  findCrateForFile,
+  loadCargoWorkspace,
+} from '@levelcode/code-map/rust/cargo'
+import micromatch from 'micromatch'
+
+import { readProjectFiles } from '../project-sources'
+import { runTerminalCommand } from './run-terminal-command'
+
+import type { FileChangeHook } from '../agents/load-file-change-hooks'
+import type { CargoWorkspace } from '@levelcode/code-map/rust/cargo'
+import type { fileChangeHookResultSchema } from '@levelcode/common/tools/params/tool/run-file-change-hooks'
+import type { LevelCodeToolOutput } from '@levelcode/common/tools/list'
+import type { LevelCodeFileSystem } from '@levelcode/common/types/filesystem'
+import type { ToolResultOutput } from '@levelcode/common/types/messages/content-part'
+import type { LevelCodeSpawn } from '@levelcode/common/types/spawn'
+import type z from 'zod/v4'
+
+const DEFAULT_HOOK_TIMEOUT_SECONDS = 120
+
+type FileChangeHookResult = z.infer<typeof fileChangeHookResultSchema>
+
+/** Quotes a value for bash unless it only contains safe characters */
+function quoteShellArg(value: string): string {
+  return /^[\w@%+=:,./-]+$/.test(value)
+    ? value
+    : `'${value.replace(/'/g, `'\\''`)}'`
+}
+
+/**
+ * Expands a hook's command for the changed files it matches. Commands using
+ * `{file}` or `{crate}` run once per file or crate; others run once.
+ */
+export function getHookCommands(
+  hook: FileChangeHook,
+  files: string[],
+  workspace?: CargoWorkspace,
+): string[] {
+  const matches = micromatch(files, hook.filePattern, {
+    basename: true,
+    dot: true,
+  })
+  const allFiles = matches.map(quoteShellArg).join(' ')
+  const commands = matches.flatMap((file) => {
+    let command = hook.command
+      .replaceAll('{file}', quoteShellArg(file))
+      .replaceAll('{files}', allFiles)
+    if (command.includes('{crate}')) {
+      const crate = workspace && findCrateForFile(workspace, file)
+      if (!crate) {
+        return []
+      }
+      command = command.replaceAll('{crate}', quoteShellArg(crate.name))
+    }
+    return [command]
+  })
+  return [...new Set(commands)]
+}
+
+async function loadWorkspace(
+  projectPath: string,
+  fs: LevelCodeFileSystem,
+): Promise<CargoWorkspace | undefined> {
+  const manifests = await readProjectFiles({
+    projectRoot: projectPath,
+    fs,
+    filter: (filePath) => path.basename(filePath) === 'Cargo.toml',
+  })
+  return loadCargoWorkspace(manifests)
+}
+
+/**
+ * Runs the hooks matching the changed files in order, e.g. a formatter before
+ * a type checker. A hook passes when its command exits with code 0.
+ */
+export async function runFileChangeHooks(params: {
   files: string[]
+  hooks: FileChangeHook[]
+  projectPath: string
+  fs: LevelCodeFileSystem
+  env?: Record<string, string>
+  spawn?: LevelCodeSpawn
 }): Promise<LevelCodeToolOutput<'run_file_change_hooks'>> {
-  // In the SDK, we don't have access to client-side hook configuration
-  // or the hook running infrastructure, so this is a no-op
+  const { hooks, projectPath, fs, env, spawn } = params
+  const files = params.files.map((filePath) =>
+    (path.isAbsolute(filePath)
+      ? path.relative(projectPath, filePath)
+      : path.normalize(filePath)
+    ).replaceAll(path.sep, '/'),
+  )
 
-  return Promise.resolve([
-    {
-      type: 'json',
-      value: [
-        {
+  const workspace = hooks.some((hook) => hook.command.includes('{crate}'))
+    ? await loadWorkspace(projectPath, fs)
+    : undefined
+
+  const results: FileChangeHookResult[] = []
+  for (const hook of hooks) {
+    const required = hook.required ?? false
+    const timeoutSeconds = hook.timeoutSeconds ?? DEFAULT_HOOK_TIMEOUT_SECONDS
+    for (const command of getHookCommands(hook, files, workspace)) {
+      try {
+        const [{ value }] = await runTerminalCommand({
+          command,
+          process_type: 'SYNC',
+          cwd: path.resolve(projectPath, hook.cwd ?? '.'),
+          timeout_seconds: timeoutSeconds,
+          env,
+          spawn,
+        })
+        const passed = 'exitCode' in value && value.exitCode === 0
+        results.push({ ...value, hookName: hook.name, required, passed })
+      } catch (error) {
+        results.push({
+          command,
           errorMessage:
-            'No file change hooks were triggered for the specified files. File change hooks are not supported in the SDK environment.',
+            error instanceof Error ? error.message : String(error),
+          hookName: hook.name,
+          required,
+          passed: false,
+        })
+      }
+    }
+  }
+
+  if (results.length === 0) {
+    return [
+      {
+        type: 'json',
+        value: [
+          {
+            errorMessage:
+              'No file change hooks were triggered for the specified files.',
+          },
+        ],
+      },
+    ]
+  }
+  return [{ type: 'json', value: results }]
+}
+
+async function readFileOrNull(
+  fs: LevelCodeFileSystem,
+  filePath: string,
+): Promise<string | null> {
+  try {
+    return await fs.readFile(filePath, 'utf8')
+  } catch {
+    return null
+  }
+}
+
+/**
+ * Runs the hooks for a file that was just edited and adds their results to
+ * the edit's result, noting whether they changed the file, so later edits
+ * start from its new content. The edit fails if a required hook failed, so
+ * the agent fixes the problem before moving on.
+ */
+export async function runFileChangeHooksAfterEdit(params: {
+  editResult: ToolResultOutput[]
+  file: string
+  hooks: FileChangeHook[]
+  projectPath: string
+  fs: LevelCodeFileSystem
+  env?: Record<string, string>
+  spawn?: LevelCodeSpawn
+}): Promise<ToolResultOutput[]> {
+  const { editResult, file, ...rest } = params
+  const [first] = editResult as LevelCodeToolOutput<'str_replace'>
+  if (first?.type !== 'json' || 'errorMessage' in first.value) {
+    return editResult
+  }
+
+  const filePath = path.resolve(rest.projectPath, file)
+  const contentBeforeHooks = await readFileOrNull(rest.fs, filePath)
+  const [{ value }] = await runFileChangeHooks({ ...rest, files: [file] })
+  const hookResults = value.filter(
+    (result): result is FileChangeHookResult => 'hookName' in result,
+  )
+  if (hookResults.length === 0) {
+    return editResult
+  }
+  const changedByHooks =
+    (await readFileOrNull(rest.fs, filePath)) !== contentBeforeHooks
+
+  const failed = hookResults.filter(
+    ({ required, passed }) => required && !passed,
+  )
+  if (failed.length === 0) {
+    return [
+      {
+        type: 'json',
+        value: {
+          ...first.value,
+          hooks: hookResults,
+          ...(changedByHooks && { changedByHooks }),
         },
-      ],
+      },
+    ]
+  }
+  const names = [...new Set(failed.map(({ hookName }) => hookName))]
+  return [
+    {
+      type: 'json',
+      value: {
+        file: first.value.file,
+        errorMessage: `The edit was applied, but required file change hook(s) failed: ${names.join(', ')}. Fix the problems they report before continuing.`,
+        hooks: hookResults,
+        ...(changedByHooks && { changedByHooks }),
+      },
     },
-  ])
+  ]
 }
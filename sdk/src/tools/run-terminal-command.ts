@@ -1,4 +1,4 @@
-import { spawn } from 'child_process'
+import { spawn as nodeSpawn } from 'child_process'
 import * as fs from 'fs'
 import * as os from 'os'
 import * as path from 'path'
@@ -10,8 +10,9 @@ import {
 import { getSystemProcessEnv } from '../env'
 
 import type { LevelCodeToolOutput } from '../../../common/src/tools/list'
+import type { LevelCodeSpawn } from '@levelcode/common/types/spawn'
 
-const COMMAND_OUTPUT_LIMIT = 50_000
+export const COMMAND_OUTPUT_LIMIT = 50_000
 
 // Common locations where Git Bash might be installed on Windows
 const GIT_BASH_COMMON_PATHS = [
@@ -40,7 +41,7 @@ const WSL_BASH_PATH_PATTERNS = [
  * - Quote/argument escaping issues between Windows and Linux
  * - UTF-16 encoding mismatches
  */
-function findWindowsBash(env: NodeJS.ProcessEnv): string | null {
+export function findWindowsBash(env: NodeJS.ProcessEnv): string | null {
   // Check for user-specified path via environment variable
   const customPath = env.LEVELCODE_GIT_BASH_PATH
   if (customPath && fs.existsSync(customPath)) {
@@ -122,12 +123,15 @@ export function runTerminalCommand({
   cwd,
   timeout_seconds,
   env,
+  spawn = nodeSpawn as LevelCodeSpawn,
 }: {
   command: string
   process_type: 'SYNC' | 'BACKGROUND'
   cwd: string
   timeout_seconds: number
   env?: NodeJS.ProcessEnv
+  /** e.g. one that runs the command in the sandbox */
+  spawn?: LevelCodeSpawn
 }): Promise<LevelCodeToolOutput<'run_terminal_command'>> {
   if (process_type === 'BACKGROUND') {
     throw new Error('BACKGROUND process_type not implemented')
@@ -187,12 +191,12 @@ export function runTerminalCommand({
     }
 
     // Collect stdout
-    childProcess.stdout.on('data', (data: Buffer) => {
+    childProcess.stdout?.on('data', (data: Buffer) => {
       stdout += data.toString()
     })
 
     // Collect stderr
-    childProcess.stderr.on('data', (data: Buffer) => {
+    childProcess.stderr?.on('data', (data: Buffer) => {
       stderr += data.toString()
     })
 
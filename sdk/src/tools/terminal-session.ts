@@ -0,0 +1,146 @@
+import {
+  stripColors,
+  truncateStringWithMessage,
+} from '../../../common/src/util/string'
+import { COMMAND_OUTPUT_LIMIT } from './run-terminal-command'
+
+import type { ShellSessionManager } from '../shell/manager'
+import type { ShellSession, ShellSessionOutput } from '../shell/session'
+import type { LevelCodeToolOutput } from '@levelcode/common/tools/list'
+import type { LevelCodeSpawn } from '@levelcode/common/types/spawn'
+
+/** How long read_terminal_session waits for a running command by default */
+const DEFAULT_WAIT_SECONDS = 10
+
+function truncateOutput(output: string): string {
+  return truncateStringWithMessage({
+    str: stripColors(output),
+    maxLength: COMMAND_OUTPUT_LIMIT,
+    remove: 'MIDDLE',
+  })
+}
+
+function toOutputFields(output: ShellSessionOutput) {
+  const stderr = truncateOutput(output.stderr)
+  return {
+    stdout: truncateOutput(output.stdout),
+    ...(stderr ? { stderr } : {}),
+    ...(output.exitCode !== undefined ? { exitCode: output.exitCode } : {}),
+  }
+}
+
+function getStatusMessage(
+  session: ShellSession,
+  status: ShellSessionOutput['status'],
+): string | undefined {
+  if (status === 'running') {
+    return `\`${session.runningCommand}\` is still running in terminal session "${session.name}". Read more output, send it input or wait for it with read_terminal_session.`
+  }
+  if (status === 'exited') {
+    return `The shell of terminal session "${session.name}" exited: ${session.exitMessage}. The next command in this session starts a new shell.`
+  }
+  return undefined
+}
+
+/**
+ * Runs a command in a named terminal session, starting the session in `cwd`
+ * if needed. The command sees the working directory and environment the
+ * session's previous commands left behind. A command still running at the
+ * timeout is not killed; read_terminal_session picks it up.
+ */
+export async function runTerminalSessionCommand(params: {
+  command: string
+  session: string
+  cwd: string
+  timeout_seconds?: number
+  spawn: LevelCodeSpawn
+  env?: Record<string, string>
+  shellSessions: ShellSessionManager
+}): Promise<LevelCodeToolOutput<'run_terminal_command'>> {
+  const {
+    command,
+    session: name,
+    cwd,
+    timeout_seconds = 30,
+    spawn,
+    env,
+    shellSessions,
+  } = params
+  const session = shellSessions.open({ name, cwd, spawn, env })
+  const output = await session.run(
+    command,
+    timeout_seconds < 0 ? Infinity : timeout_seconds * 1000,
+  )
+  const message = getStatusMessage(session, output.status)
+  return [
+    {
+      type: 'json',
+      value: {
+        command,
+        session: name,
+        ...toOutputFields(output),
+        ...(message ? { message } : {}),
+      },
+    },
+  ]
+}
+
+export async function readTerminalSession(params: {
+  session: string
+  input?: string
+  wait_seconds?: number
+  close?: boolean
+  shellSessions: ShellSessionManager
+}): Promise<LevelCodeToolOutput<'read_terminal_session'>> {
+  const {
+    session: name,
+    input,
+    wait_seconds = DEFAULT_WAIT_SECONDS,
+    close = false,
+    shellSessions,
+  } = params
+  const session = shellSessions.get(name)
+  if (!session) {
+    return [
+      {
+        type: 'json',
+        value: {
+          errorMessage: `No terminal session "${name}". Start one by passing session to run_terminal_command.`,
+        },
+      },
+    ]
+  }
+
+  if (close) {
+    const output = await session.read({})
+    shellSessions.close(name)
+    return [
+      {
+        type: 'json',
+        value: {
+          session: name,
+          status: 'exited',
+          ...toOutputFields(output),
+          message: `Closed terminal session "${name}"`,
+        },
+      },
+    ]
+  }
+
+  const output = await session.read({
+    input,
+    timeoutMs: wait_seconds < 0 ? Infinity : wait_seconds * 1000,
+  })
+  const message = getStatusMessage(session, output.status)
+  return [
+    {
+      type: 'json',
+      value: {
+        session: name,
+        status: output.status,
+        ...toOutputFields(output),
+        ...(message ? { message } : {}),
+      },
+    },
+  ]
+}